@@ -0,0 +1,40 @@
+use anyhow::{anyhow, Result};
+use calculator::api::{router, ApiState};
+use calculator::services::calendar::load_business_calendar;
+use calculator::settings::calendar::CalendarSettings;
+use calculator::settings::config::Settings;
+use calculator::settings::disbursements::DisbursementSettings;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1:8080";
+
+// Usage: api [--bind <address:port>]
+// Serves the previews of the disbursement runs (see calculator::api), on localhost by default.
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let mut bind_address = DEFAULT_BIND_ADDRESS.to_string();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bind" => bind_address = args.next().ok_or_else(|| anyhow!("--bind needs a value"))?,
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    let settings = Settings::from_env();
+    let state = ApiState {
+        pool: PgPool::connect(&settings.database_url).await?,
+        calendar: load_business_calendar(&CalendarSettings::from_env())?,
+        disbursement_settings: DisbursementSettings::from_env(),
+    };
+
+    let listener = TcpListener::bind(&bind_address).await?;
+    println!("Listening on {}", listener.local_addr()?);
+    axum::serve(listener, router(Arc::new(state))).await?;
+
+    Ok(())
+}
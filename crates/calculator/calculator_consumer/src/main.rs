@@ -1,5 +1,9 @@
 use calculator::settings::config::Settings;
 use calculator::events::handlers::merchant_upserted_handler::MerchantUpsertedHandlerBuilder;
+use calculator::events::handlers::order_adjusted_handler::OrderAdjustedHandlerBuilder;
+use calculator::events::handlers::order_created_handler::OrderCreatedHandlerBuilder;
+use calculator::events::handlers::order_refunded_handler::OrderRefundedHandlerBuilder;
+use calculator::events::handlers::payout_returned_handler::PayoutReturnedHandlerBuilder;
 use calculator::events::kafka::consumer::KafkaCalculatorConsumer;
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -13,18 +17,61 @@ async fn main() -> Result<()> {
     let settings = Settings::from_env();
     let pool = PgPool::connect(&settings.database_url).await?;
 
-    let handler = MerchantUpsertedHandlerBuilder.build(pool);
-    let handler_arc: Arc<dyn EventHandler + Send + Sync> = Arc::new(handler);
-
-    let consumer = KafkaCalculatorConsumer::new(
-        handler_arc,
-        &settings.kafka_brokers,
+    // one consumer (and consumer group) per topic, the same way each handler only knows about its own event
+    let merchants_consumer = build_consumer(
+        Arc::new(MerchantUpsertedHandlerBuilder.build(pool.clone())),
+        &settings,
         "calculator-merchant-group",
+        "merchant_upserted",
+    )?;
+
+    let orders_consumer = build_consumer(
+        Arc::new(OrderCreatedHandlerBuilder.build(pool.clone())),
+        &settings,
+        "calculator-order-group",
+        "order_created",
+    )?;
+
+    let refunds_consumer = build_consumer(
+        Arc::new(OrderRefundedHandlerBuilder.build(pool.clone())),
+        &settings,
+        "calculator-order-refund-group",
+        "order_refunded",
+    )?;
+
+    let corrections_consumer = build_consumer(
+        Arc::new(OrderAdjustedHandlerBuilder.build(pool.clone())),
+        &settings,
+        "calculator-order-adjustment-group",
+        "order_adjusted",
     )?;
 
-    consumer.subscribe(&["merchant_upserted"])?;
+    let returns_consumer = build_consumer(
+        Arc::new(PayoutReturnedHandlerBuilder.build(pool)),
+        &settings,
+        "calculator-payout-return-group",
+        "payout_returned",
+    )?;
 
-    consumer.run().await?;
+    tokio::try_join!(
+        merchants_consumer.run(),
+        orders_consumer.run(),
+        refunds_consumer.run(),
+        corrections_consumer.run(),
+        returns_consumer.run(),
+    )?;
 
     Ok(())
 }
+
+fn build_consumer(
+    handler: Arc<dyn EventHandler + Send + Sync>,
+    settings: &Settings,
+    group_id: &str,
+    topic: &str,
+) -> Result<KafkaCalculatorConsumer> {
+    let consumer = KafkaCalculatorConsumer::new(handler, &settings.kafka_brokers, group_id)?;
+    consumer.subscribe(&[topic])?;
+
+    Ok(consumer)
+}
@@ -0,0 +1,128 @@
+use anyhow::{anyhow, Result};
+use calculator::entities::disbursements::DisbursementStatus;
+use calculator::jobs::disbursement_batches::approve_batch_handler;
+use calculator::jobs::disbursement_status::change_disbursement_status_handler;
+use calculator::repositories::disbursement_batches::find_batches_on;
+use calculator::repositories::disbursements::{find_disbursement_status_history, find_disbursements, find_disbursements_by_reference, DisbursementFilter};
+use calculator::settings::config::Settings;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+// Usage:
+//   disbursements list [--status <STATUS>]... [--merchant <merchant_reference>] [--from YYYY-MM-DD] [--until YYYY-MM-DD] [--batch <id>]
+//   disbursements history --reference <reference>
+//   disbursements status --reference <reference> --to <STATUS> --actor <name> [--note <text>]
+//   disbursements batches --date YYYY-MM-DD
+//   disbursements approve --batch <id> --approver <name>
+// The statuses are CALCULATED, APPROVED, EXPORTED, SENT, SETTLED, RETURNED and VOIDED. APPROVED is only set by
+// approving the batch, twice by two different people.
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let mut args = std::env::args().skip(1);
+    let command = args.next().ok_or_else(|| anyhow!("Usage: disbursements list | history | status | batches | approve"))?;
+    let options = parse_options(args)?;
+
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+
+    match command.as_str() {
+        "list" => {
+            let mut conn = pool.acquire().await?;
+            let disbursements = find_disbursements(&mut conn, &options.filter).await?;
+
+            for disbursement in &disbursements {
+                println!(
+                    "{} {} {} {} {} {}",
+                    disbursement.reference,
+                    disbursement.merchant_reference,
+                    disbursement.disbursed_on,
+                    disbursement.net_amount,
+                    disbursement.currency,
+                    disbursement.status
+                );
+            }
+        }
+        "history" => {
+            let reference = options.reference.ok_or_else(|| anyhow!("--reference is required"))?;
+            let mut conn = pool.acquire().await?;
+            let disbursement = find_disbursements_by_reference(&mut conn, std::slice::from_ref(&reference))
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("Unknown disbursement {}", reference))?;
+            let history = find_disbursement_status_history(&mut conn, disbursement.id).await?;
+
+            println!("{}", serde_json::to_string_pretty(&history)?);
+        }
+        "status" => {
+            let reference = options.reference.ok_or_else(|| anyhow!("--reference is required"))?;
+            let to = options.to.ok_or_else(|| anyhow!("--to is required"))?;
+            let actor = options.actor.ok_or_else(|| anyhow!("--actor is required"))?;
+            let change = change_disbursement_status_handler(&pool, &reference, to, &actor, options.note).await?;
+
+            println!("Disbursement {} is {} (was {:?})", reference, change.to, change.from);
+        }
+        "batches" => {
+            let date = options.date.ok_or_else(|| anyhow!("--date is required"))?;
+            let mut conn = pool.acquire().await?;
+            let batches = find_batches_on(&mut conn, date).await?;
+
+            println!("{}", serde_json::to_string_pretty(&batches)?);
+        }
+        "approve" => {
+            let batch_id = options.filter.batch_id.ok_or_else(|| anyhow!("--batch is required"))?;
+            let approver = options.approver.ok_or_else(|| anyhow!("--approver is required"))?;
+            let batch = approve_batch_handler(&pool, batch_id, &approver).await?;
+
+            println!("Batch {} is {} ({} of 2 approvals)", batch.id, batch.status, batch.approvals.len());
+        }
+        other => return Err(anyhow!("Unknown command: {}", other)),
+    }
+
+    Ok(())
+}
+
+struct Options {
+    filter: DisbursementFilter,
+    reference: Option<String>,
+    to: Option<DisbursementStatus>,
+    actor: Option<String>,
+    note: Option<String>,
+    date: Option<NaiveDate>,
+    approver: Option<String>,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options> {
+    let mut options = Options {
+        filter: DisbursementFilter::default(),
+        reference: None,
+        to: None,
+        actor: None,
+        note: None,
+        date: None,
+        approver: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--status" => options.filter.statuses.push(value()?.to_uppercase().parse()?),
+            "--merchant" => options.filter.merchant_reference = Some(value()?),
+            "--from" => options.filter.disbursed_from = Some(NaiveDate::parse_from_str(&value()?, "%Y-%m-%d")?),
+            "--until" => options.filter.disbursed_to = Some(NaiveDate::parse_from_str(&value()?, "%Y-%m-%d")?),
+            "--to" => options.to = Some(value()?.to_uppercase().parse()?),
+            "--reference" => options.reference = Some(value()?),
+            "--actor" => options.actor = Some(value()?),
+            "--note" => options.note = Some(value()?),
+            "--batch" => options.filter.batch_id = Some(Uuid::parse_str(&value()?)?),
+            "--date" => options.date = Some(NaiveDate::parse_from_str(&value()?, "%Y-%m-%d")?),
+            "--approver" => options.approver = Some(value()?),
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(options)
+}
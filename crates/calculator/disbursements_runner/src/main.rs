@@ -0,0 +1,66 @@
+use anyhow::{anyhow, Result};
+use calculator::jobs::process_disbursements::{process_disbursements_handler, ProcessDisbursementsJob, RunMode, RunOutcome};
+use calculator::services::calendar::load_business_calendar;
+use calculator::settings::calendar::CalendarSettings;
+use calculator::settings::config::Settings;
+use calculator::settings::disbursements::DisbursementSettings;
+use chrono::{NaiveDate, Utc};
+use sqlx::PgPool;
+
+// Usage: disbursements_runner [--date YYYY-MM-DD] [--preview]
+// The preview is also served over HTTP by the api binary.
+// Without --date the run is for today (UTC), it disburses the orders created before that date.
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let job = parse_args(std::env::args().skip(1))?;
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+    let calendar = load_business_calendar(&CalendarSettings::from_env())?;
+
+    let disbursement_settings = DisbursementSettings::from_env();
+
+    match process_disbursements_handler(job, &pool, &calendar, &disbursement_settings).await? {
+        RunOutcome::Committed(run) => {
+            println!(
+                "Created {} disbursements and {} monthly fees, carried {} balances below the minimum payout",
+                run.disbursements.len(),
+                run.monthly_fees.len(),
+                run.carried_balances.len()
+            );
+            for receivable in run.receivables.iter().filter(|receivable| receivable.flagged_for_collection) {
+                println!(
+                    "Flagged {} for collection: owes {} {}",
+                    receivable.merchant_reference, receivable.amount, receivable.currency
+                );
+            }
+            for merchant_reference in &run.skipped_merchants {
+                println!("Skipped {}: no VAT rule for its country", merchant_reference);
+            }
+        }
+        RunOutcome::Previewed(diff) => {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<ProcessDisbursementsJob> {
+    let mut date = Utc::now().date_naive();
+    let mut mode = RunMode::Commit;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--date" => {
+                let value = args.next().ok_or_else(|| anyhow!("--date needs a value (YYYY-MM-DD)"))?;
+                date = NaiveDate::parse_from_str(&value, "%Y-%m-%d")?;
+            }
+            "--preview" => mode = RunMode::Preview,
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(ProcessDisbursementsJob { date, mode })
+}
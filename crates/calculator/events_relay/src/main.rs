@@ -0,0 +1,67 @@
+use anyhow::{anyhow, Result};
+use calculator::events::kafka::publisher::KafkaPublisherBuilder;
+use calculator::jobs::publish_events::{publish_events_handler, PublishEventsJob};
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+use std::time::Duration;
+
+const DEFAULT_LIMIT: i64 = 100;
+const DEFAULT_INTERVAL_SECONDS: u64 = 5;
+
+// Usage: events_relay [--once] [--limit <events per batch>] [--interval <seconds>]
+// Publishes the events of the outbox to Kafka, forever unless --once. A failed batch is retried on the next tick.
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let options = parse_args(std::env::args().skip(1))?;
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+    let publisher = KafkaPublisherBuilder::new(&settings.kafka_brokers).build()?;
+
+    loop {
+        match publish_events_handler(options.job.clone(), &pool, &publisher).await {
+            Ok(published) => {
+                if published > 0 {
+                    println!("Published {} events", published);
+                }
+                // a full batch means there may be more waiting
+                if published as i64 == options.job.limit {
+                    continue;
+                }
+            }
+            Err(err) => eprintln!("Error publishing events: {:#}", err),
+        }
+
+        if options.once {
+            return Ok(());
+        }
+        tokio::time::sleep(options.interval).await;
+    }
+}
+
+struct Options {
+    job: PublishEventsJob,
+    once: bool,
+    interval: Duration,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options> {
+    let mut options = Options {
+        job: PublishEventsJob { limit: DEFAULT_LIMIT },
+        once: false,
+        interval: Duration::from_secs(DEFAULT_INTERVAL_SECONDS),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--once" => options.once = true,
+            "--limit" => options.job.limit = value()?.parse()?,
+            "--interval" => options.interval = Duration::from_secs(value()?.parse()?),
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,28 @@
+use anyhow::{anyhow, Result};
+use calculator::repositories::fx_rates::upsert_fx_rates;
+use calculator::services::fx::parse_fx_rates_csv;
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+
+// Usage: fx_rates_loader --file <path>
+// The file is `rate_date;base_currency;quote_currency;rate` with a header, see tests/fixtures/fx_rates.csv
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let path = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["--file", path] => path.to_string(),
+        _ => return Err(anyhow!("Usage: fx_rates_loader --file <path>")),
+    };
+
+    let rates = parse_fx_rates_csv(&std::fs::read_to_string(&path)?)?;
+
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+    upsert_fx_rates(&pool, &rates).await?;
+
+    println!("Loaded {} fx rates from {}", rates.len(), path);
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anyhow::{anyhow, Result};
+use calculator::jobs::invoices::{
+    credit_invoice_handler, issue_invoices_handler, render_invoice_handler, InvoiceFormat, IssueInvoicesJob,
+};
+use calculator::settings::config::Settings;
+use calculator::settings::invoices::InvoiceSettings;
+use chrono::{NaiveDate, Utc};
+use sqlx::PgPool;
+
+// Usage:
+//   invoices issue --month YYYY-MM [--date YYYY-MM-DD]                        invoices the monthly fee shortfalls of the month
+//   invoices credit --number <number> [--note <text>] [--date YYYY-MM-DD]    issues a credit note reversing the invoice
+//   invoices render --number <number> [--format html|json|xml] --output <path>
+// The date is the issue date, today when it's missing.
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let mut args = std::env::args().skip(1);
+    let command = args.next().ok_or_else(|| anyhow!("Usage: invoices issue | credit | render"))?;
+    let options = parse_options(args)?;
+
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+    let issued_on = options.date.unwrap_or_else(|| Utc::now().date_naive());
+
+    match command.as_str() {
+        "issue" => {
+            let month = options.month.ok_or_else(|| anyhow!("--month is required"))?;
+            let invoices = issue_invoices_handler(IssueInvoicesJob { month, issued_on }, &pool, &InvoiceSettings::from_env()).await?;
+
+            println!("Issued {} invoices", invoices.len());
+            for invoice in &invoices {
+                println!("{} {} {} {}", invoice.number, invoice.merchant_reference, invoice.total_amount, invoice.currency);
+            }
+        }
+        "credit" => {
+            let number = options.number.ok_or_else(|| anyhow!("--number is required"))?;
+            let credit_note = credit_invoice_handler(&pool, &number, issued_on, options.note).await?;
+
+            println!("Issued credit note {} for {}", credit_note.number, number);
+        }
+        "render" => {
+            let number = options.number.ok_or_else(|| anyhow!("--number is required"))?;
+            let output_path = options.output_path.ok_or_else(|| anyhow!("--output is required"))?;
+            let content = render_invoice_handler(&pool, &number, options.format).await?;
+
+            std::fs::write(&output_path, content)?;
+            println!("Rendered {} to {}", number, output_path);
+        }
+        other => return Err(anyhow!("Unknown command: {}", other)),
+    }
+
+    Ok(())
+}
+
+struct Options {
+    month: Option<NaiveDate>,
+    date: Option<NaiveDate>,
+    number: Option<String>,
+    note: Option<String>,
+    format: InvoiceFormat,
+    output_path: Option<String>,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options> {
+    let mut options = Options {
+        month: None,
+        date: None,
+        number: None,
+        note: None,
+        format: InvoiceFormat::Html,
+        output_path: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--month" => options.month = Some(NaiveDate::parse_from_str(&format!("{}-01", value()?), "%Y-%m-%d")?),
+            "--date" => options.date = Some(NaiveDate::parse_from_str(&value()?, "%Y-%m-%d")?),
+            "--number" => options.number = Some(value()?),
+            "--note" => options.note = Some(value()?),
+            "--format" => options.format = value()?.parse()?,
+            "--output" => options.output_path = Some(value()?),
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,43 @@
+use anyhow::{anyhow, Result};
+use calculator::repositories::ledger::{ensure_ledger_balanced, find_merchant_balances};
+use calculator::repositories::receivables::find_receivables_to_collect;
+use calculator::repositories::reserves::find_open_reserves;
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+
+// Usage:
+//   ledger_report --merchant <merchant_reference>   balances of the merchant accounts
+//   ledger_report --reserves <merchant_reference>   reserves still held back from the merchant
+//   ledger_report --collections                      receivables above the negative balance limit
+//   ledger_report --check                            checks the ledger sums zero
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+    let mut conn = pool.acquire().await?;
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["--merchant", merchant_reference] => {
+            let balances = find_merchant_balances(&mut conn, merchant_reference).await?;
+            println!("{}", serde_json::to_string_pretty(&balances)?);
+        }
+        ["--reserves", merchant_reference] => {
+            let reserves = find_open_reserves(&mut conn, merchant_reference).await?;
+            println!("{}", serde_json::to_string_pretty(&reserves)?);
+        }
+        ["--collections"] => {
+            let receivables = find_receivables_to_collect(&mut conn).await?;
+            println!("{}", serde_json::to_string_pretty(&receivables)?);
+        }
+        ["--check"] => {
+            ensure_ledger_balanced(&mut conn).await?;
+            println!("The ledger is balanced");
+        }
+        _ => return Err(anyhow!("Usage: ledger_report --merchant <merchant_reference> | --reserves <merchant_reference> | --collections | --check")),
+    }
+
+    Ok(())
+}
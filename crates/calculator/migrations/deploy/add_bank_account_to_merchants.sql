@@ -0,0 +1,11 @@
+-- Deploy calculator:add_bank_account_to_merchants to pg
+
+BEGIN;
+
+-- Nullable, merchants without a bank account are disbursed but not paid out
+ALTER TABLE merchants
+    ADD COLUMN iban TEXT,
+    ADD COLUMN bic TEXT,
+    ADD COLUMN account_holder TEXT;
+
+COMMIT;
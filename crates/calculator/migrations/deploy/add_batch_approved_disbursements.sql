@@ -0,0 +1,41 @@
+-- Deploy calculator:add_batch_approved_disbursements to pg
+
+BEGIN;
+
+-- The disbursements the first approval of the batch was given to, the second approval approves these and only these
+CREATE TABLE disbursement_batch_disbursements (
+    batch_id UUID NOT NULL REFERENCES disbursement_batches (id),
+    disbursement_id UUID NOT NULL REFERENCES disbursements (id),
+    PRIMARY KEY (batch_id, disbursement_id)
+);
+
+-- a batch whose disbursements were all voided before its approval
+ALTER TABLE disbursement_batches DROP CONSTRAINT disbursement_batches_status_check;
+ALTER TABLE disbursement_batches ADD CONSTRAINT disbursement_batches_status_check
+    CHECK (status IN ('OPEN', 'PARTIALLY_APPROVED', 'APPROVED', 'CLOSED'));
+
+-- the batches waiting for nothing don't hold the export of their date back anymore
+UPDATE disbursement_batches b SET status = 'CLOSED'
+WHERE b.status <> 'APPROVED'
+AND NOT EXISTS (SELECT 1 FROM disbursements d WHERE d.batch_id = b.id AND d.status = 'CALCULATED');
+
+-- the first approvals given to disbursements voided since are withdrawn, the others freeze what they were given
+DELETE FROM disbursement_batch_totals t USING disbursement_batches b
+WHERE b.id = t.batch_id AND b.status IN ('PARTIALLY_APPROVED', 'CLOSED')
+AND EXISTS (SELECT 1 FROM disbursements d WHERE d.batch_id = b.id AND d.status = 'VOIDED');
+
+DELETE FROM disbursement_batch_approvals a USING disbursement_batches b
+WHERE b.id = a.batch_id AND b.status IN ('PARTIALLY_APPROVED', 'CLOSED')
+AND NOT EXISTS (SELECT 1 FROM disbursement_batch_totals t WHERE t.batch_id = b.id);
+
+UPDATE disbursement_batches b SET status = 'OPEN'
+WHERE b.status = 'PARTIALLY_APPROVED'
+AND NOT EXISTS (SELECT 1 FROM disbursement_batch_approvals a WHERE a.batch_id = b.id);
+
+INSERT INTO disbursement_batch_disbursements (batch_id, disbursement_id)
+SELECT b.id, d.id
+FROM disbursement_batches b
+JOIN disbursements d ON d.batch_id = b.id AND d.status = 'CALCULATED'
+WHERE b.status = 'PARTIALLY_APPROVED';
+
+COMMIT;
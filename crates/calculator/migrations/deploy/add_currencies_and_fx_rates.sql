@@ -0,0 +1,42 @@
+-- Deploy calculator:add_currencies_and_fx_rates to pg
+
+BEGIN;
+
+-- Amounts are in the minor unit of their ISO 4217 currency, everything before was in euro cents
+ALTER TABLE merchants ADD COLUMN payout_currency TEXT NOT NULL DEFAULT 'EUR';
+ALTER TABLE orders ADD COLUMN currency TEXT NOT NULL DEFAULT 'EUR';
+ALTER TABLE order_adjustments ADD COLUMN currency TEXT NOT NULL DEFAULT 'EUR';
+ALTER TABLE disbursements ADD COLUMN currency TEXT NOT NULL DEFAULT 'EUR';
+ALTER TABLE monthly_fees ADD COLUMN currency TEXT NOT NULL DEFAULT 'EUR';
+
+-- amount is converted to the disbursement currency, original_* is what the order (or adjustment) had
+ALTER TABLE disbursement_lines
+    ADD COLUMN original_amount BIGINT,
+    ADD COLUMN original_currency TEXT NOT NULL DEFAULT 'EUR',
+    ADD COLUMN fx_rate BIGINT NOT NULL DEFAULT 100000000;
+
+UPDATE disbursement_lines SET original_amount = amount;
+ALTER TABLE disbursement_lines ALTER COLUMN original_amount SET NOT NULL;
+
+-- 1 base_currency = rate / 10^8 quote_currency, effective from rate_date until a newer one
+CREATE TABLE fx_rates (
+    rate_date DATE NOT NULL,
+    base_currency TEXT NOT NULL,
+    quote_currency TEXT NOT NULL,
+    rate BIGINT NOT NULL CHECK (rate > 0),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (rate_date, base_currency, quote_currency)
+);
+
+-- The ledger balances per currency, conversions go through FX_CLEARING
+ALTER TABLE ledger_postings ADD COLUMN currency TEXT NOT NULL DEFAULT 'EUR';
+
+ALTER TABLE ledger_postings DROP CONSTRAINT ledger_postings_account_check;
+ALTER TABLE ledger_postings ADD CONSTRAINT ledger_postings_account_check
+    CHECK (account IN ('CASH', 'MERCHANT_PAYABLE', 'COMMISSION_REVENUE', 'MONTHLY_FEE_RECEIVABLE', 'MONTHLY_FEE_REVENUE', 'FX_CLEARING'));
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION'));
+
+COMMIT;
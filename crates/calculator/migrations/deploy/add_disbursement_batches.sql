@@ -0,0 +1,48 @@
+-- Deploy calculator:add_disbursement_batches to pg
+
+BEGIN;
+
+-- The disbursements calculated for a date, approved together by two different people before they can be exported
+CREATE TABLE disbursement_batches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    disbursed_on DATE NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('OPEN', 'PARTIALLY_APPROVED', 'APPROVED')),
+    created_at TIMESTAMPTZ NOT NULL,
+    approved_at TIMESTAMPTZ
+);
+
+CREATE INDEX disbursement_batches_disbursed_on_idx ON disbursement_batches (disbursed_on);
+
+CREATE TABLE disbursement_batch_approvals (
+    id BIGSERIAL PRIMARY KEY,
+    batch_id UUID NOT NULL REFERENCES disbursement_batches (id),
+    approver TEXT NOT NULL,
+    approved_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (batch_id, approver)
+);
+
+-- The totals seen by the first approver, the second one has to see the same
+CREATE TABLE disbursement_batch_totals (
+    batch_id UUID NOT NULL REFERENCES disbursement_batches (id),
+    currency TEXT NOT NULL,
+    disbursement_count BIGINT NOT NULL,
+    net_amount BIGINT NOT NULL,
+    PRIMARY KEY (batch_id, currency)
+);
+
+ALTER TABLE disbursements ADD COLUMN batch_id UUID REFERENCES disbursement_batches (id);
+
+CREATE INDEX disbursements_batch_id_idx ON disbursements (batch_id);
+
+-- the disbursements still waiting for their approval go to an open batch of their date
+INSERT INTO disbursement_batches (disbursed_on, status, created_at)
+SELECT DISTINCT disbursed_on, 'OPEN', now()
+FROM disbursements
+WHERE status = 'CALCULATED';
+
+UPDATE disbursements d
+SET batch_id = b.id
+FROM disbursement_batches b
+WHERE d.status = 'CALCULATED' AND b.disbursed_on = d.disbursed_on;
+
+COMMIT;
@@ -0,0 +1,38 @@
+-- Deploy calculator:add_disbursement_status to pg
+
+BEGIN;
+
+ALTER TABLE disbursements
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'CALCULATED'
+        CHECK (status IN ('CALCULATED', 'APPROVED', 'EXPORTED', 'SENT', 'SETTLED', 'RETURNED', 'VOIDED'));
+
+-- the reconciled ones are the only ones we know the fate of
+UPDATE disbursements SET status = 'SETTLED' WHERE settled_on IS NOT NULL;
+
+-- the payout files were exported by date, with every disbursement paying something to a merchant with a bank account:
+-- the ones of an earlier date are already in a file for the bank, reconciling them takes them through SENT
+UPDATE disbursements d SET status = 'EXPORTED'
+FROM merchants m
+WHERE m.merchant_reference = d.merchant_reference
+AND d.settled_on IS NULL AND d.disbursed_on < CURRENT_DATE AND d.net_amount > 0 AND m.iban IS NOT NULL;
+
+CREATE INDEX disbursements_status_idx ON disbursements (status, disbursed_on);
+
+-- Every status a disbursement went through, from_status is NULL for the first one
+CREATE TABLE disbursement_status_history (
+    id BIGSERIAL PRIMARY KEY,
+    disbursement_id UUID NOT NULL REFERENCES disbursements (id),
+    from_status TEXT CHECK (from_status IN ('CALCULATED', 'APPROVED', 'EXPORTED', 'SENT', 'SETTLED', 'RETURNED', 'VOIDED')),
+    to_status TEXT NOT NULL CHECK (to_status IN ('CALCULATED', 'APPROVED', 'EXPORTED', 'SENT', 'SETTLED', 'RETURNED', 'VOIDED')),
+    actor TEXT NOT NULL,
+    note TEXT,
+    changed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX disbursement_status_history_disbursement_id_idx ON disbursement_status_history (disbursement_id);
+
+INSERT INTO disbursement_status_history (disbursement_id, from_status, to_status, actor, note, changed_at)
+SELECT id, NULL, status, 'system', 'Status added to the existing disbursements', now()
+FROM disbursements;
+
+COMMIT;
@@ -0,0 +1,31 @@
+-- Deploy calculator:add_disbursement_voids to pg
+
+BEGIN;
+
+-- The lines of a voided disbursement are kept but paid by nothing, what they disbursed is paid again by the next one
+ALTER TABLE disbursement_lines ADD COLUMN voided BOOLEAN NOT NULL DEFAULT FALSE;
+
+DROP INDEX disbursement_lines_order_id_unique;
+CREATE UNIQUE INDEX disbursement_lines_order_id_unique ON disbursement_lines (order_id) WHERE kind = 'ORDER' AND NOT voided;
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_adjustment_id_key;
+CREATE UNIQUE INDEX disbursement_lines_adjustment_id_unique ON disbursement_lines (adjustment_id) WHERE NOT voided;
+
+DROP INDEX disbursement_lines_reserve_release_unique;
+CREATE UNIQUE INDEX disbursement_lines_reserve_release_unique ON disbursement_lines (reserve_id) WHERE kind = 'RESERVE_RELEASE' AND NOT voided;
+
+DROP INDEX disbursement_lines_receivable_offset_unique;
+CREATE UNIQUE INDEX disbursement_lines_receivable_offset_unique ON disbursement_lines (receivable_id) WHERE kind = 'RECEIVABLE_OFFSET' AND NOT voided;
+
+DROP INDEX disbursement_lines_monthly_fee_unique;
+CREATE UNIQUE INDEX disbursement_lines_monthly_fee_unique ON disbursement_lines (monthly_fee_id) WHERE kind = 'MONTHLY_FEE' AND NOT voided;
+
+DROP INDEX disbursement_lines_payout_return_unique;
+CREATE UNIQUE INDEX disbursement_lines_payout_return_unique ON disbursement_lines (payout_return_id) WHERE kind = 'RETURNED_PAYOUT' AND NOT voided;
+
+-- the reversal of everything a voided disbursement booked
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION', 'RESERVE', 'RECEIVABLE', 'MONTHLY_FEE_DEDUCTION', 'PAYOUT_RETURN', 'VOID'));
+
+COMMIT;
@@ -0,0 +1,15 @@
+-- Deploy calculator:add_event_id_to_order_adjustments to pg
+
+BEGIN;
+
+-- The order_refunded or order_adjusted event that booked the adjustment, a redelivered event is skipped.
+-- The adjustments booked before have no event id, their own id stands in for it
+ALTER TABLE order_adjustments ADD COLUMN event_id UUID;
+
+UPDATE order_adjustments SET event_id = id;
+
+ALTER TABLE order_adjustments
+    ALTER COLUMN event_id SET NOT NULL,
+    ADD CONSTRAINT order_adjustments_event_id_key UNIQUE (event_id);
+
+COMMIT;
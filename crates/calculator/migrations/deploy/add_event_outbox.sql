@@ -0,0 +1,19 @@
+-- Deploy calculator:add_event_outbox to pg
+
+BEGIN;
+
+-- The events stored with the changes they report, published afterwards by the events relay
+CREATE TABLE event_outbox (
+    id UUID PRIMARY KEY,
+    -- the order they are published in
+    position BIGSERIAL NOT NULL UNIQUE,
+    topic TEXT NOT NULL CHECK (topic IN ('disbursement_created', 'monthly_fee_calculated', 'disbursement_status_changed')),
+    merchant_reference TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    published_at TIMESTAMPTZ
+);
+
+CREATE INDEX event_outbox_unpublished_idx ON event_outbox (position) WHERE published_at IS NULL;
+
+COMMIT;
@@ -0,0 +1,53 @@
+-- Deploy calculator:add_invoices to pg
+
+BEGIN;
+
+-- The last number issued of every series and year, incremented in the transaction inserting the invoice
+CREATE TABLE invoice_sequences (
+    kind TEXT NOT NULL CHECK (kind IN ('INVOICE', 'CREDIT_NOTE')),
+    year INTEGER NOT NULL,
+    last_number BIGINT NOT NULL,
+    PRIMARY KEY (kind, year)
+);
+
+-- Invoices of the monthly fee shortfalls and the credit notes reversing them, with negative amounts.
+-- The names are copied when it's issued, an issued invoice never changes.
+CREATE TABLE invoices (
+    id UUID PRIMARY KEY,
+    number TEXT NOT NULL UNIQUE,
+    kind TEXT NOT NULL CHECK (kind IN ('INVOICE', 'CREDIT_NOTE')),
+    merchant_reference TEXT NOT NULL,
+    monthly_fee_id UUID NOT NULL REFERENCES monthly_fees (id),
+    credited_invoice_id UUID REFERENCES invoices (id),
+    note TEXT,
+    issued_on DATE NOT NULL,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    seller_name TEXT NOT NULL,
+    customer_name TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    net_amount BIGINT NOT NULL,
+    -- in basis points, NULL when no VAT is charged
+    vat_rate INTEGER,
+    vat_amount BIGINT NOT NULL,
+    total_amount BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK ((kind = 'CREDIT_NOTE') = (credited_invoice_id IS NOT NULL))
+);
+
+CREATE INDEX invoices_monthly_fee_idx ON invoices (monthly_fee_id);
+
+-- an invoice is credited only once
+CREATE UNIQUE INDEX invoices_credited_invoice_unique ON invoices (credited_invoice_id) WHERE credited_invoice_id IS NOT NULL;
+
+CREATE TABLE invoice_lines (
+    invoice_id UUID NOT NULL REFERENCES invoices (id),
+    position INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    quantity BIGINT NOT NULL,
+    unit_amount BIGINT NOT NULL,
+    amount BIGINT NOT NULL,
+    PRIMARY KEY (invoice_id, position)
+);
+
+COMMIT;
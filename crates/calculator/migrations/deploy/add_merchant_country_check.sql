@@ -0,0 +1,8 @@
+-- Deploy calculator:add_merchant_country_check to pg
+
+BEGIN;
+
+-- the same ISO codes as the VAT rules, a country written any other way never finds its rule
+ALTER TABLE merchants ADD CONSTRAINT merchants_country_check CHECK (country ~ '^[A-Z]{2}$');
+
+COMMIT;
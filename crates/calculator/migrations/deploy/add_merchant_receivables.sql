@@ -0,0 +1,40 @@
+-- Deploy calculator:add_merchant_receivables to pg
+
+BEGIN;
+
+-- Opened by the RECEIVABLE line of disbursement_id when it nets negative, offset by the RECEIVABLE_OFFSET line
+-- of settled_disbursement_id
+CREATE TABLE receivables (
+    id UUID PRIMARY KEY,
+    merchant_reference TEXT NOT NULL,
+    disbursement_id UUID NOT NULL REFERENCES disbursements (id),
+    currency TEXT NOT NULL,
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    opened_on DATE NOT NULL,
+    flagged_for_collection BOOLEAN NOT NULL DEFAULT FALSE,
+    settled_disbursement_id UUID REFERENCES disbursements (id),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX receivables_open_idx ON receivables (merchant_reference, opened_on) WHERE settled_disbursement_id IS NULL;
+
+-- The receivable is inserted after the lines of the disbursement opening it, like the reserves
+ALTER TABLE disbursement_lines
+    ADD COLUMN receivable_id UUID REFERENCES receivables (id) DEFERRABLE INITIALLY DEFERRED;
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_kind_check;
+ALTER TABLE disbursement_lines ADD CONSTRAINT disbursement_lines_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'RESERVE', 'RESERVE_RELEASE', 'RECEIVABLE', 'RECEIVABLE_OFFSET'));
+
+-- a receivable is offset only once
+CREATE UNIQUE INDEX disbursement_lines_receivable_offset_unique ON disbursement_lines (receivable_id) WHERE kind = 'RECEIVABLE_OFFSET';
+
+ALTER TABLE ledger_postings DROP CONSTRAINT ledger_postings_account_check;
+ALTER TABLE ledger_postings ADD CONSTRAINT ledger_postings_account_check
+    CHECK (account IN ('CASH', 'MERCHANT_PAYABLE', 'COMMISSION_REVENUE', 'MONTHLY_FEE_RECEIVABLE', 'MONTHLY_FEE_REVENUE', 'FX_CLEARING', 'MERCHANT_RESERVE', 'MERCHANT_RECEIVABLE'));
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION', 'RESERVE', 'RECEIVABLE'));
+
+COMMIT;
@@ -0,0 +1,26 @@
+-- Deploy calculator:add_minimum_payout_and_carried_balances to pg
+
+BEGIN;
+
+-- In the minor unit of the payout currency, NULL for the global minimum payout
+ALTER TABLE merchants
+    ADD COLUMN minimum_payout BIGINT CHECK (minimum_payout >= 0),
+    ADD COLUMN offboarded_on DATE;
+
+-- The disbursements below the minimum payout that were not created, their orders stay pending
+-- and the disbursement that finally pays them is recorded when it's created
+CREATE TABLE carried_balances (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_reference TEXT NOT NULL,
+    carried_on DATE NOT NULL,
+    currency TEXT NOT NULL,
+    net_amount BIGINT NOT NULL,
+    minimum_payout BIGINT NOT NULL,
+    disbursement_id UUID REFERENCES disbursements (id),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (merchant_reference, carried_on)
+);
+
+CREATE INDEX carried_balances_pending_idx ON carried_balances (merchant_reference) WHERE disbursement_id IS NULL;
+
+COMMIT;
@@ -0,0 +1,29 @@
+-- Deploy calculator:add_monthly_fee_deduction to pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    ADD COLUMN monthly_fee_mode TEXT NOT NULL DEFAULT 'INVOICE' CHECK (monthly_fee_mode IN ('INVOICE', 'DEDUCT'));
+
+-- In deduct mode the fee is taken from the next disbursement of the merchant, disbursement_id
+ALTER TABLE monthly_fees
+    ADD COLUMN mode TEXT NOT NULL DEFAULT 'INVOICE' CHECK (mode IN ('INVOICE', 'DEDUCT')),
+    ADD COLUMN disbursement_id UUID REFERENCES disbursements (id);
+
+CREATE INDEX monthly_fees_to_deduct_idx ON monthly_fees (merchant_reference) WHERE mode = 'DEDUCT' AND disbursement_id IS NULL;
+
+ALTER TABLE disbursement_lines
+    ADD COLUMN monthly_fee_id UUID REFERENCES monthly_fees (id);
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_kind_check;
+ALTER TABLE disbursement_lines ADD CONSTRAINT disbursement_lines_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'RESERVE', 'RESERVE_RELEASE', 'RECEIVABLE', 'RECEIVABLE_OFFSET', 'MONTHLY_FEE'));
+
+-- a monthly fee is deducted only once
+CREATE UNIQUE INDEX disbursement_lines_monthly_fee_unique ON disbursement_lines (monthly_fee_id) WHERE kind = 'MONTHLY_FEE';
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION', 'RESERVE', 'RECEIVABLE', 'MONTHLY_FEE_DEDUCTION'));
+
+COMMIT;
@@ -0,0 +1,14 @@
+-- Deploy calculator:add_open_invoice_unique_index to pg
+
+BEGIN;
+
+-- Set with the credit note, a credited monthly fee can be invoiced again
+ALTER TABLE invoices ADD COLUMN credited BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE invoices i SET credited = TRUE
+WHERE EXISTS (SELECT 1 FROM invoices c WHERE c.credited_invoice_id = i.id);
+
+-- A monthly fee has one invoice not credited at most, two issue runs at the same time can't both invoice it
+CREATE UNIQUE INDEX invoices_open_monthly_fee_unique ON invoices (monthly_fee_id) WHERE kind = 'INVOICE' AND NOT credited;
+
+COMMIT;
@@ -0,0 +1,10 @@
+-- Deploy calculator:add_payout_file_withdrawals to pg
+
+BEGIN;
+
+-- A file recalled from the bank before it was executed, its disbursements are APPROVED again
+ALTER TABLE payout_files
+    ADD COLUMN withdrawn_at TIMESTAMPTZ,
+    ADD COLUMN withdrawn_by TEXT;
+
+COMMIT;
@@ -0,0 +1,48 @@
+-- Deploy calculator:add_payout_files to pg
+
+BEGIN;
+
+-- Every payout file sent to the bank, with the message id the bank knows it by. The content is kept so a lost file
+-- is written again as it was, not exported a second time.
+CREATE TABLE payout_files (
+    id UUID PRIMARY KEY,
+    message_id TEXT NOT NULL UNIQUE,
+    execution_date DATE NOT NULL,
+    format TEXT NOT NULL CHECK (format IN ('xml', 'csv')),
+    path TEXT NOT NULL,
+    -- NULL for the files exported before they were kept
+    content TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX payout_files_execution_date_idx ON payout_files (execution_date);
+
+-- The disbursements paid by each file
+CREATE TABLE payout_file_disbursements (
+    payout_file_id UUID NOT NULL REFERENCES payout_files (id),
+    disbursement_id UUID NOT NULL REFERENCES disbursements (id),
+    PRIMARY KEY (payout_file_id, disbursement_id)
+);
+
+CREATE INDEX payout_file_disbursements_disbursement_id_idx ON payout_file_disbursements (disbursement_id);
+
+-- the files exported so far had the message id of their date, one per date with the disbursements exported on it
+-- (the note of the export is the path, the statuses backfilled by add_disbursement_status have none)
+INSERT INTO payout_files (id, message_id, execution_date, format, path, created_at)
+SELECT exported.id, 'PAYOUTS-' || to_char(exported.disbursed_on, 'YYYYMMDD'), exported.disbursed_on,
+       CASE WHEN exported.path LIKE '%.csv' THEN 'csv' ELSE 'xml' END, COALESCE(exported.path, ''), exported.created_at
+FROM (
+    SELECT gen_random_uuid() AS id, d.disbursed_on,
+           MIN(h.note) FILTER (WHERE h.from_status IS NOT NULL) AS path, MIN(h.changed_at) AS created_at
+    FROM disbursements d
+    JOIN disbursement_status_history h ON h.disbursement_id = d.id AND h.to_status = 'EXPORTED'
+    GROUP BY d.disbursed_on
+) exported;
+
+INSERT INTO payout_file_disbursements (payout_file_id, disbursement_id)
+SELECT DISTINCT f.id, d.id
+FROM disbursements d
+JOIN disbursement_status_history h ON h.disbursement_id = d.id AND h.to_status = 'EXPORTED'
+JOIN payout_files f ON f.execution_date = d.disbursed_on;
+
+COMMIT;
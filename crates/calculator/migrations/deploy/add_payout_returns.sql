@@ -0,0 +1,40 @@
+-- Deploy calculator:add_payout_returns to pg
+
+BEGIN;
+
+-- The payouts sent back by the bank of the merchant, its payouts are held until released_on (new bank details)
+-- and the returned amount is paid again by the RETURNED_PAYOUT line of redisbursement_id
+CREATE TABLE payout_returns (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    disbursement_id UUID NOT NULL UNIQUE REFERENCES disbursements (id),
+    merchant_reference TEXT NOT NULL,
+    returned_on DATE NOT NULL,
+    amount BIGINT NOT NULL,
+    currency TEXT NOT NULL,
+    reason_code TEXT,
+    reason TEXT,
+    source TEXT NOT NULL CHECK (source IN ('PAIN_002', 'CAMT_054', 'EVENT')),
+    iban TEXT,
+    bic TEXT,
+    account_holder TEXT,
+    released_on DATE,
+    redisbursement_id UUID REFERENCES disbursements (id)
+);
+
+CREATE INDEX payout_returns_held_idx ON payout_returns (merchant_reference) WHERE released_on IS NULL;
+
+ALTER TABLE disbursement_lines
+    ADD COLUMN payout_return_id UUID REFERENCES payout_returns (id);
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_kind_check;
+ALTER TABLE disbursement_lines ADD CONSTRAINT disbursement_lines_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'RESERVE', 'RESERVE_RELEASE', 'RECEIVABLE', 'RECEIVABLE_OFFSET', 'MONTHLY_FEE', 'RETURNED_PAYOUT'));
+
+-- a returned payout is paid again only once
+CREATE UNIQUE INDEX disbursement_lines_payout_return_unique ON disbursement_lines (payout_return_id) WHERE kind = 'RETURNED_PAYOUT';
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION', 'RESERVE', 'RECEIVABLE', 'MONTHLY_FEE_DEDUCTION', 'PAYOUT_RETURN'));
+
+COMMIT;
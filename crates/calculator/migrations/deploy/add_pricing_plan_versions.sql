@@ -0,0 +1,45 @@
+-- Deploy calculator:add_pricing_plan_versions to pg
+
+BEGIN;
+
+-- A version prices the orders created from valid_from until the next version of the same plan
+CREATE TABLE pricing_plan_versions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    plan_code TEXT NOT NULL REFERENCES pricing_plans (code),
+    valid_from DATE NOT NULL,
+    fixed_fee BIGINT NOT NULL DEFAULT 0 CHECK (fixed_fee >= 0),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (plan_code, valid_from)
+);
+
+-- The plans received so far become their first version
+INSERT INTO pricing_plan_versions (plan_code, valid_from, fixed_fee)
+SELECT code, DATE '1970-01-01', fixed_fee FROM pricing_plans;
+
+ALTER TABLE pricing_plan_tiers ADD COLUMN version_id UUID REFERENCES pricing_plan_versions (id);
+
+UPDATE pricing_plan_tiers t SET version_id = v.id
+FROM pricing_plan_versions v WHERE v.plan_code = t.plan_code;
+
+ALTER TABLE pricing_plan_tiers
+    DROP CONSTRAINT pricing_plan_tiers_pkey,
+    DROP COLUMN plan_code,
+    ALTER COLUMN version_id SET NOT NULL,
+    ADD PRIMARY KEY (version_id, from_amount);
+
+ALTER TABLE pricing_plans DROP COLUMN fixed_fee;
+
+-- The global 1.00 / 0.95 / 0.85 % tiers are the DEFAULT plan, a new version of them is a new change of this plan
+INSERT INTO pricing_plans (code) VALUES ('DEFAULT');
+INSERT INTO pricing_plan_versions (plan_code, valid_from, fixed_fee) VALUES ('DEFAULT', DATE '1970-01-01', 0);
+INSERT INTO pricing_plan_tiers (version_id, from_amount, fee_rate)
+SELECT v.id, tiers.from_amount, tiers.fee_rate
+FROM pricing_plan_versions v,
+     (VALUES (0, 100), (5000, 95), (30000, 85)) AS tiers (from_amount, fee_rate)
+WHERE v.plan_code = 'DEFAULT';
+
+-- The version every line and adjustment was priced with, e.g. DEFAULT:1970-01-01
+ALTER TABLE disbursement_lines ADD COLUMN pricing_version TEXT NOT NULL DEFAULT 'DEFAULT:1970-01-01';
+ALTER TABLE order_adjustments ADD COLUMN pricing_version TEXT NOT NULL DEFAULT 'DEFAULT:1970-01-01';
+
+COMMIT;
@@ -0,0 +1,48 @@
+-- Deploy calculator:add_rolling_reserves to pg
+
+BEGIN;
+
+-- reserve_rate in basis points of the net sales of every disbursement, held back for hold_days
+CREATE TABLE reserve_policies (
+    merchant_reference TEXT PRIMARY KEY,
+    reserve_rate INTEGER NOT NULL CHECK (reserve_rate BETWEEN 0 AND 10000),
+    hold_days INTEGER NOT NULL CHECK (hold_days > 0)
+);
+
+-- Held by the RESERVE line of disbursement_id, paid back by the RESERVE_RELEASE line of released_disbursement_id
+CREATE TABLE reserves (
+    id UUID PRIMARY KEY,
+    merchant_reference TEXT NOT NULL,
+    disbursement_id UUID NOT NULL REFERENCES disbursements (id),
+    currency TEXT NOT NULL,
+    amount BIGINT NOT NULL CHECK (amount > 0),
+    held_on DATE NOT NULL,
+    release_on DATE NOT NULL,
+    released_disbursement_id UUID REFERENCES disbursements (id),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX reserves_releasable_idx ON reserves (merchant_reference, release_on) WHERE released_disbursement_id IS NULL;
+
+-- Reserve lines don't come from an order. The reserve is inserted after the lines of the disbursement holding it,
+-- so the foreign key is only checked on commit
+ALTER TABLE disbursement_lines
+    ALTER COLUMN order_id DROP NOT NULL,
+    ADD COLUMN reserve_id UUID REFERENCES reserves (id) DEFERRABLE INITIALLY DEFERRED;
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_kind_check;
+ALTER TABLE disbursement_lines ADD CONSTRAINT disbursement_lines_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'RESERVE', 'RESERVE_RELEASE'));
+
+-- a reserve is released only once
+CREATE UNIQUE INDEX disbursement_lines_reserve_release_unique ON disbursement_lines (reserve_id) WHERE kind = 'RESERVE_RELEASE';
+
+ALTER TABLE ledger_postings DROP CONSTRAINT ledger_postings_account_check;
+ALTER TABLE ledger_postings ADD CONSTRAINT ledger_postings_account_check
+    CHECK (account IN ('CASH', 'MERCHANT_PAYABLE', 'COMMISSION_REVENUE', 'MONTHLY_FEE_RECEIVABLE', 'MONTHLY_FEE_REVENUE', 'FX_CLEARING', 'MERCHANT_RESERVE'));
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION', 'RESERVE'));
+
+COMMIT;
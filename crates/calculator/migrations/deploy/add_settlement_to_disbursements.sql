@@ -0,0 +1,12 @@
+-- Deploy calculator:add_settlement_to_disbursements to pg
+
+BEGIN;
+
+-- The booking date and bank reference of the camt.053 entry that confirmed the payout, NULL until it's reconciled
+ALTER TABLE disbursements
+    ADD COLUMN settled_on DATE,
+    ADD COLUMN bank_reference TEXT;
+
+CREATE INDEX disbursements_unsettled_idx ON disbursements (disbursed_on) WHERE settled_on IS NULL;
+
+COMMIT;
@@ -0,0 +1,13 @@
+-- Deploy calculator:add_time_zones to pg
+
+BEGIN;
+
+-- IANA time zone of the merchant, its local days group the orders into disbursements and monthly fees
+ALTER TABLE merchants
+    ADD COLUMN time_zone TEXT NOT NULL DEFAULT 'UTC';
+
+-- Orders keep the whole instant they were created at, the ones received so far were created at midnight UTC
+ALTER TABLE orders
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING created_at::TIMESTAMP AT TIME ZONE 'UTC';
+
+COMMIT;
@@ -0,0 +1,46 @@
+-- Deploy calculator:add_vat to pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    ADD COLUMN country TEXT,
+    ADD COLUMN vat_number TEXT;
+
+-- The standard rate of every country we have merchants in, in basis points
+CREATE TABLE vat_rules (
+    country TEXT PRIMARY KEY CHECK (country ~ '^[A-Z]{2}$'),
+    standard_rate INTEGER NOT NULL CHECK (standard_rate BETWEEN 0 AND 10000),
+    eu_member BOOLEAN NOT NULL
+);
+
+-- VAT on the commissions, both NULL for the disbursements calculated without VAT
+ALTER TABLE disbursements
+    ADD COLUMN vat_treatment TEXT CHECK (vat_treatment IN ('STANDARD', 'REVERSE_CHARGE', 'OUTSIDE_SCOPE')),
+    ADD COLUMN vat_rate INTEGER,
+    ADD COLUMN fee_vat_amount BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN fee_gross_amount BIGINT;
+
+UPDATE disbursements SET fee_gross_amount = fee_amount;
+ALTER TABLE disbursements ALTER COLUMN fee_gross_amount SET NOT NULL;
+
+ALTER TABLE monthly_fees
+    ADD COLUMN vat_treatment TEXT CHECK (vat_treatment IN ('STANDARD', 'REVERSE_CHARGE', 'OUTSIDE_SCOPE')),
+    ADD COLUMN vat_rate INTEGER,
+    ADD COLUMN vat_amount BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN gross_amount BIGINT;
+
+UPDATE monthly_fees SET gross_amount = amount;
+ALTER TABLE monthly_fees ALTER COLUMN gross_amount SET NOT NULL;
+
+-- invoices take the VAT of their monthly fee, vat_rate was the configured rate until now
+ALTER TABLE invoices
+    ADD COLUMN vat_treatment TEXT CHECK (vat_treatment IN ('STANDARD', 'REVERSE_CHARGE', 'OUTSIDE_SCOPE')),
+    ADD COLUMN customer_vat_number TEXT;
+
+UPDATE invoices SET vat_treatment = 'STANDARD' WHERE vat_rate IS NOT NULL;
+
+ALTER TABLE ledger_postings DROP CONSTRAINT ledger_postings_account_check;
+ALTER TABLE ledger_postings ADD CONSTRAINT ledger_postings_account_check
+    CHECK (account IN ('CASH', 'MERCHANT_PAYABLE', 'COMMISSION_REVENUE', 'MONTHLY_FEE_RECEIVABLE', 'MONTHLY_FEE_REVENUE', 'FX_CLEARING', 'MERCHANT_RESERVE', 'MERCHANT_RECEIVABLE', 'VAT_PAYABLE'));
+
+COMMIT;
@@ -0,0 +1,49 @@
+-- Deploy calculator:add_webhooks to pg
+
+BEGIN;
+
+-- The urls the merchants want their events POSTed to, signed with the secret
+CREATE TABLE webhook_endpoints (
+    id UUID PRIMARY KEY,
+    merchant_reference TEXT NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    topics TEXT[] NOT NULL CHECK (topics <@ ARRAY['disbursement_created', 'monthly_fee_calculated', 'disbursement_status_changed']),
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX webhook_endpoints_merchant_reference_idx ON webhook_endpoints (merchant_reference);
+
+-- An outbox event to send to an endpoint, queued with the event and retried with backoff until next_attempt_at is NULL
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY,
+    endpoint_id UUID NOT NULL REFERENCES webhook_endpoints (id),
+    event_id UUID NOT NULL REFERENCES event_outbox (id),
+    topic TEXT NOT NULL,
+    merchant_reference TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('PENDING', 'DELIVERED', 'FAILED')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (endpoint_id, event_id)
+);
+
+CREATE INDEX webhook_deliveries_due_idx ON webhook_deliveries (next_attempt_at) WHERE status = 'PENDING';
+CREATE INDEX webhook_deliveries_merchant_reference_idx ON webhook_deliveries (merchant_reference, created_at);
+
+-- The delivery log, every POST of every delivery and what the endpoint answered
+CREATE TABLE webhook_delivery_attempts (
+    id BIGSERIAL PRIMARY KEY,
+    delivery_id UUID NOT NULL REFERENCES webhook_deliveries (id),
+    attempted_at TIMESTAMPTZ NOT NULL,
+    response_status INTEGER,
+    error TEXT,
+    duration_ms BIGINT NOT NULL
+);
+
+CREATE INDEX webhook_delivery_attempts_delivery_id_idx ON webhook_delivery_attempts (delivery_id);
+
+COMMIT;
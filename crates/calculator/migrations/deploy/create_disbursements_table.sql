@@ -0,0 +1,29 @@
+-- Deploy calculator:create_disbursements_table to pg
+
+BEGIN;
+
+CREATE TABLE disbursements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    reference TEXT UNIQUE NOT NULL,
+    merchant_reference TEXT NOT NULL,
+    disbursed_on DATE NOT NULL,
+    gross_amount BIGINT NOT NULL,
+    fee_amount BIGINT NOT NULL,
+    net_amount BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (merchant_reference, disbursed_on)
+);
+
+-- One line per disbursed order, the unique order_id guarantees every order is disbursed precisely once
+CREATE TABLE disbursement_lines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    disbursement_id UUID NOT NULL REFERENCES disbursements (id),
+    order_id TEXT UNIQUE NOT NULL REFERENCES orders (id),
+    amount BIGINT NOT NULL,
+    fee_rate INTEGER NOT NULL,
+    fee_amount BIGINT NOT NULL
+);
+
+CREATE INDEX disbursement_lines_disbursement_id_idx ON disbursement_lines (disbursement_id);
+
+COMMIT;
@@ -0,0 +1,28 @@
+-- Deploy calculator:create_ledger_tables to pg
+
+BEGIN;
+
+-- An entry is booked only once per kind and source (order, adjustment, disbursement or monthly fee)
+CREATE TABLE journal_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE')),
+    source_id TEXT NOT NULL,
+    merchant_reference TEXT NOT NULL,
+    booked_on DATE NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (kind, source_id)
+);
+
+-- Debits are positive and credits negative, the postings of an entry sum zero
+CREATE TABLE ledger_postings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    journal_entry_id UUID NOT NULL REFERENCES journal_entries (id),
+    merchant_reference TEXT NOT NULL,
+    account TEXT NOT NULL CHECK (account IN ('CASH', 'MERCHANT_PAYABLE', 'COMMISSION_REVENUE', 'MONTHLY_FEE_RECEIVABLE', 'MONTHLY_FEE_REVENUE')),
+    amount BIGINT NOT NULL
+);
+
+CREATE INDEX ledger_postings_journal_entry_id_idx ON ledger_postings (journal_entry_id);
+CREATE INDEX ledger_postings_merchant_reference_account_idx ON ledger_postings (merchant_reference, account);
+
+COMMIT;
@@ -0,0 +1,18 @@
+-- Deploy calculator:create_monthly_fees_table to pg
+
+BEGIN;
+
+-- month is the first day of the month the minimum monthly fee belongs to
+CREATE TABLE monthly_fees (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    merchant_reference TEXT NOT NULL,
+    month DATE NOT NULL,
+    commissions_amount BIGINT NOT NULL,
+    minimum_monthly_fee BIGINT NOT NULL,
+    amount BIGINT NOT NULL,
+    calculated_on DATE NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (merchant_reference, month)
+);
+
+COMMIT;
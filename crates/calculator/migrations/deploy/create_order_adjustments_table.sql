@@ -0,0 +1,29 @@
+-- Deploy calculator:create_order_adjustments_table to pg
+
+BEGIN;
+
+-- Refunds and corrections of orders, amount is the (signed) difference in cents
+-- and fee_rate the rate of the original order, used to reverse its fee
+CREATE TABLE order_adjustments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id TEXT NOT NULL REFERENCES orders (id),
+    merchant_reference TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('REFUND', 'CORRECTION')),
+    amount BIGINT NOT NULL,
+    fee_rate INTEGER NOT NULL,
+    created_at DATE NOT NULL
+);
+
+CREATE INDEX order_adjustments_merchant_reference_created_at_idx ON order_adjustments (merchant_reference, created_at);
+
+-- Adjustments are disbursed as lines of the next disbursement of the merchant,
+-- so an order can now appear in more than one line, but only once as an ORDER line
+ALTER TABLE disbursement_lines
+    ADD COLUMN kind TEXT NOT NULL DEFAULT 'ORDER' CHECK (kind IN ('ORDER', 'ADJUSTMENT')),
+    ADD COLUMN adjustment_id UUID UNIQUE REFERENCES order_adjustments (id);
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_order_id_key;
+
+CREATE UNIQUE INDEX disbursement_lines_order_id_unique ON disbursement_lines (order_id) WHERE kind = 'ORDER';
+
+COMMIT;
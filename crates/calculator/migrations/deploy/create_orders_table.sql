@@ -0,0 +1,15 @@
+-- Deploy calculator:create_orders_table to pg
+
+BEGIN;
+
+-- Amounts are stored in cents
+CREATE TABLE orders (
+    id TEXT PRIMARY KEY,
+    merchant_reference TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    created_at DATE NOT NULL
+);
+
+CREATE INDEX orders_merchant_reference_created_at_idx ON orders (merchant_reference, created_at);
+
+COMMIT;
@@ -0,0 +1,25 @@
+-- Deploy calculator:create_pricing_plans_tables to pg
+
+BEGIN;
+
+-- Custom commission rules negotiated with a merchant, amounts in euro cents and rates in basis points
+CREATE TABLE pricing_plans (
+    code TEXT PRIMARY KEY,
+    fixed_fee BIGINT NOT NULL DEFAULT 0 CHECK (fixed_fee >= 0),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE pricing_plan_tiers (
+    plan_code TEXT NOT NULL REFERENCES pricing_plans (code),
+    from_amount BIGINT NOT NULL CHECK (from_amount >= 0),
+    fee_rate INTEGER NOT NULL CHECK (fee_rate BETWEEN 0 AND 10000),
+    PRIMARY KEY (plan_code, from_amount)
+);
+
+-- NULL is the default 1.00 / 0.95 / 0.85 % tiers
+ALTER TABLE merchants ADD COLUMN pricing_plan TEXT REFERENCES pricing_plans (code);
+
+-- Already included in fee_amount
+ALTER TABLE disbursement_lines ADD COLUMN fixed_fee BIGINT NOT NULL DEFAULT 0;
+
+COMMIT;
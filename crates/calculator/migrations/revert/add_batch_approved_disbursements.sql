@@ -0,0 +1,13 @@
+-- Revert calculator:add_batch_approved_disbursements from pg
+
+BEGIN;
+
+UPDATE disbursement_batches SET status = 'OPEN' WHERE status = 'CLOSED';
+
+ALTER TABLE disbursement_batches DROP CONSTRAINT disbursement_batches_status_check;
+ALTER TABLE disbursement_batches ADD CONSTRAINT disbursement_batches_status_check
+    CHECK (status IN ('OPEN', 'PARTIALLY_APPROVED', 'APPROVED'));
+
+DROP TABLE disbursement_batch_disbursements;
+
+COMMIT;
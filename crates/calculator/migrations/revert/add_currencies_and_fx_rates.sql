@@ -0,0 +1,31 @@
+-- Revert calculator:add_currencies_and_fx_rates from pg
+
+BEGIN;
+
+DELETE FROM ledger_postings WHERE journal_entry_id IN (SELECT id FROM journal_entries WHERE kind = 'CONVERSION');
+DELETE FROM journal_entries WHERE kind = 'CONVERSION';
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE'));
+
+ALTER TABLE ledger_postings DROP CONSTRAINT ledger_postings_account_check;
+ALTER TABLE ledger_postings ADD CONSTRAINT ledger_postings_account_check
+    CHECK (account IN ('CASH', 'MERCHANT_PAYABLE', 'COMMISSION_REVENUE', 'MONTHLY_FEE_RECEIVABLE', 'MONTHLY_FEE_REVENUE'));
+
+ALTER TABLE ledger_postings DROP COLUMN currency;
+
+DROP TABLE IF EXISTS fx_rates;
+
+ALTER TABLE disbursement_lines
+    DROP COLUMN original_amount,
+    DROP COLUMN original_currency,
+    DROP COLUMN fx_rate;
+
+ALTER TABLE monthly_fees DROP COLUMN currency;
+ALTER TABLE disbursements DROP COLUMN currency;
+ALTER TABLE order_adjustments DROP COLUMN currency;
+ALTER TABLE orders DROP COLUMN currency;
+ALTER TABLE merchants DROP COLUMN payout_currency;
+
+COMMIT;
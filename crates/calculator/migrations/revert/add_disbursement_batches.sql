@@ -0,0 +1,15 @@
+-- Revert calculator:add_disbursement_batches from pg
+
+BEGIN;
+
+DROP INDEX IF EXISTS disbursements_batch_id_idx;
+
+ALTER TABLE disbursements DROP COLUMN batch_id;
+
+DROP TABLE IF EXISTS disbursement_batch_totals;
+
+DROP TABLE IF EXISTS disbursement_batch_approvals;
+
+DROP TABLE IF EXISTS disbursement_batches;
+
+COMMIT;
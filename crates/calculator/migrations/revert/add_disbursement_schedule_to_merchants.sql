@@ -0,0 +1,16 @@
+-- Revert calculator:add_disbursement_schedule_to_merchants from pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    DROP COLUMN disbursement_weekday,
+    DROP COLUMN disbursement_day_of_month;
+
+-- the merchants with the new frequencies can't be kept, weekly is the closest one
+UPDATE merchants SET disbursement_frequency = 'WEEKLY' WHERE disbursement_frequency IN ('BIWEEKLY', 'MONTHLY');
+
+ALTER TABLE merchants DROP CONSTRAINT merchants_disbursement_frequency_check;
+ALTER TABLE merchants ADD CONSTRAINT merchants_disbursement_frequency_check
+    CHECK (disbursement_frequency IN ('DAILY', 'WEEKLY'));
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Revert calculator:add_disbursement_status from pg
+
+BEGIN;
+
+DROP TABLE IF EXISTS disbursement_status_history;
+
+DROP INDEX IF EXISTS disbursements_status_idx;
+
+ALTER TABLE disbursements DROP COLUMN status;
+
+COMMIT;
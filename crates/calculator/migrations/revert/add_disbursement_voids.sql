@@ -0,0 +1,29 @@
+-- Revert calculator:add_disbursement_voids from pg
+
+BEGIN;
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION', 'RESERVE', 'RECEIVABLE', 'MONTHLY_FEE_DEDUCTION', 'PAYOUT_RETURN'));
+
+DROP INDEX disbursement_lines_payout_return_unique;
+CREATE UNIQUE INDEX disbursement_lines_payout_return_unique ON disbursement_lines (payout_return_id) WHERE kind = 'RETURNED_PAYOUT';
+
+DROP INDEX disbursement_lines_monthly_fee_unique;
+CREATE UNIQUE INDEX disbursement_lines_monthly_fee_unique ON disbursement_lines (monthly_fee_id) WHERE kind = 'MONTHLY_FEE';
+
+DROP INDEX disbursement_lines_receivable_offset_unique;
+CREATE UNIQUE INDEX disbursement_lines_receivable_offset_unique ON disbursement_lines (receivable_id) WHERE kind = 'RECEIVABLE_OFFSET';
+
+DROP INDEX disbursement_lines_reserve_release_unique;
+CREATE UNIQUE INDEX disbursement_lines_reserve_release_unique ON disbursement_lines (reserve_id) WHERE kind = 'RESERVE_RELEASE';
+
+DROP INDEX disbursement_lines_adjustment_id_unique;
+ALTER TABLE disbursement_lines ADD CONSTRAINT disbursement_lines_adjustment_id_key UNIQUE (adjustment_id);
+
+DROP INDEX disbursement_lines_order_id_unique;
+CREATE UNIQUE INDEX disbursement_lines_order_id_unique ON disbursement_lines (order_id) WHERE kind = 'ORDER';
+
+ALTER TABLE disbursement_lines DROP COLUMN voided;
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Revert calculator:add_event_id_to_order_adjustments from pg
+
+BEGIN;
+
+ALTER TABLE order_adjustments DROP COLUMN IF EXISTS event_id;
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Revert calculator:add_event_outbox from pg
+
+BEGIN;
+
+DROP TABLE IF EXISTS event_outbox;
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Revert calculator:add_invoices from pg
+
+BEGIN;
+
+DROP TABLE IF EXISTS invoice_lines;
+DROP TABLE IF EXISTS invoices;
+DROP TABLE IF EXISTS invoice_sequences;
+
+COMMIT;
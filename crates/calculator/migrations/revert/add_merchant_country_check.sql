@@ -0,0 +1,7 @@
+-- Revert calculator:add_merchant_country_check from pg
+
+BEGIN;
+
+ALTER TABLE merchants DROP CONSTRAINT merchants_country_check;
+
+COMMIT;
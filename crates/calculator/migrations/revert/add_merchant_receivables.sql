@@ -0,0 +1,28 @@
+-- Revert calculator:add_merchant_receivables from pg
+
+BEGIN;
+
+DELETE FROM ledger_postings WHERE journal_entry_id IN (SELECT id FROM journal_entries WHERE kind = 'RECEIVABLE');
+DELETE FROM journal_entries WHERE kind = 'RECEIVABLE';
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION', 'RESERVE'));
+
+ALTER TABLE ledger_postings DROP CONSTRAINT ledger_postings_account_check;
+ALTER TABLE ledger_postings ADD CONSTRAINT ledger_postings_account_check
+    CHECK (account IN ('CASH', 'MERCHANT_PAYABLE', 'COMMISSION_REVENUE', 'MONTHLY_FEE_RECEIVABLE', 'MONTHLY_FEE_REVENUE', 'FX_CLEARING', 'MERCHANT_RESERVE'));
+
+DELETE FROM disbursement_lines WHERE kind IN ('RECEIVABLE', 'RECEIVABLE_OFFSET');
+
+DROP INDEX IF EXISTS disbursement_lines_receivable_offset_unique;
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_kind_check;
+ALTER TABLE disbursement_lines ADD CONSTRAINT disbursement_lines_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'RESERVE', 'RESERVE_RELEASE'));
+
+ALTER TABLE disbursement_lines DROP COLUMN receivable_id;
+
+DROP TABLE IF EXISTS receivables;
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Revert calculator:add_minimum_payout_and_carried_balances from pg
+
+BEGIN;
+
+DROP TABLE carried_balances;
+
+ALTER TABLE merchants
+    DROP COLUMN minimum_payout,
+    DROP COLUMN offboarded_on;
+
+COMMIT;
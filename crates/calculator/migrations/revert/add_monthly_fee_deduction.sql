@@ -0,0 +1,28 @@
+-- Revert calculator:add_monthly_fee_deduction from pg
+
+BEGIN;
+
+DELETE FROM ledger_postings WHERE journal_entry_id IN (SELECT id FROM journal_entries WHERE kind = 'MONTHLY_FEE_DEDUCTION');
+DELETE FROM journal_entries WHERE kind = 'MONTHLY_FEE_DEDUCTION';
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION', 'RESERVE', 'RECEIVABLE'));
+
+DELETE FROM disbursement_lines WHERE kind = 'MONTHLY_FEE';
+
+DROP INDEX IF EXISTS disbursement_lines_monthly_fee_unique;
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_kind_check;
+ALTER TABLE disbursement_lines ADD CONSTRAINT disbursement_lines_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'RESERVE', 'RESERVE_RELEASE', 'RECEIVABLE', 'RECEIVABLE_OFFSET'));
+
+ALTER TABLE disbursement_lines DROP COLUMN monthly_fee_id;
+
+ALTER TABLE monthly_fees
+    DROP COLUMN mode,
+    DROP COLUMN disbursement_id;
+
+ALTER TABLE merchants DROP COLUMN monthly_fee_mode;
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Revert calculator:add_open_invoice_unique_index from pg
+
+BEGIN;
+
+DROP INDEX IF EXISTS invoices_open_monthly_fee_unique;
+
+ALTER TABLE invoices DROP COLUMN IF EXISTS credited;
+
+COMMIT;
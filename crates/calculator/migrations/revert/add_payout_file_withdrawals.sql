@@ -0,0 +1,9 @@
+-- Revert calculator:add_payout_file_withdrawals from pg
+
+BEGIN;
+
+ALTER TABLE payout_files
+    DROP COLUMN withdrawn_at,
+    DROP COLUMN withdrawn_by;
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Revert calculator:add_payout_files from pg
+
+BEGIN;
+
+DROP TABLE payout_file_disbursements;
+DROP TABLE payout_files;
+
+COMMIT;
@@ -0,0 +1,24 @@
+-- Revert calculator:add_payout_returns from pg
+
+BEGIN;
+
+DELETE FROM ledger_postings WHERE journal_entry_id IN (SELECT id FROM journal_entries WHERE kind = 'PAYOUT_RETURN');
+DELETE FROM journal_entries WHERE kind = 'PAYOUT_RETURN';
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION', 'RESERVE', 'RECEIVABLE', 'MONTHLY_FEE_DEDUCTION'));
+
+DELETE FROM disbursement_lines WHERE kind = 'RETURNED_PAYOUT';
+
+DROP INDEX IF EXISTS disbursement_lines_payout_return_unique;
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_kind_check;
+ALTER TABLE disbursement_lines ADD CONSTRAINT disbursement_lines_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'RESERVE', 'RESERVE_RELEASE', 'RECEIVABLE', 'RECEIVABLE_OFFSET', 'MONTHLY_FEE'));
+
+ALTER TABLE disbursement_lines DROP COLUMN payout_return_id;
+
+DROP TABLE IF EXISTS payout_returns;
+
+COMMIT;
@@ -0,0 +1,34 @@
+-- Revert calculator:add_pricing_plan_versions from pg
+
+BEGIN;
+
+ALTER TABLE order_adjustments DROP COLUMN pricing_version;
+ALTER TABLE disbursement_lines DROP COLUMN pricing_version;
+
+DELETE FROM pricing_plan_tiers WHERE version_id IN (SELECT id FROM pricing_plan_versions WHERE plan_code = 'DEFAULT');
+DELETE FROM pricing_plan_versions WHERE plan_code = 'DEFAULT';
+DELETE FROM pricing_plans WHERE code = 'DEFAULT';
+
+-- Only the latest version of every plan is kept
+ALTER TABLE pricing_plans ADD COLUMN fixed_fee BIGINT NOT NULL DEFAULT 0 CHECK (fixed_fee >= 0);
+ALTER TABLE pricing_plan_tiers ADD COLUMN plan_code TEXT REFERENCES pricing_plans (code);
+
+DELETE FROM pricing_plan_tiers t USING pricing_plan_versions v
+WHERE v.id = t.version_id
+AND v.valid_from < (SELECT MAX(latest.valid_from) FROM pricing_plan_versions latest WHERE latest.plan_code = v.plan_code);
+
+UPDATE pricing_plan_tiers t SET plan_code = v.plan_code FROM pricing_plan_versions v WHERE v.id = t.version_id;
+UPDATE pricing_plans p SET fixed_fee = v.fixed_fee
+FROM pricing_plan_versions v
+WHERE v.plan_code = p.code
+AND v.valid_from = (SELECT MAX(latest.valid_from) FROM pricing_plan_versions latest WHERE latest.plan_code = p.code);
+
+ALTER TABLE pricing_plan_tiers
+    DROP CONSTRAINT pricing_plan_tiers_pkey,
+    DROP COLUMN version_id,
+    ALTER COLUMN plan_code SET NOT NULL,
+    ADD PRIMARY KEY (plan_code, from_amount);
+
+DROP TABLE IF EXISTS pricing_plan_versions;
+
+COMMIT;
@@ -0,0 +1,31 @@
+-- Revert calculator:add_rolling_reserves from pg
+
+BEGIN;
+
+DELETE FROM ledger_postings WHERE journal_entry_id IN (SELECT id FROM journal_entries WHERE kind = 'RESERVE');
+DELETE FROM journal_entries WHERE kind = 'RESERVE';
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_kind_check;
+ALTER TABLE journal_entries ADD CONSTRAINT journal_entries_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT', 'COMMISSION', 'PAYOUT', 'MONTHLY_FEE', 'CONVERSION'));
+
+ALTER TABLE ledger_postings DROP CONSTRAINT ledger_postings_account_check;
+ALTER TABLE ledger_postings ADD CONSTRAINT ledger_postings_account_check
+    CHECK (account IN ('CASH', 'MERCHANT_PAYABLE', 'COMMISSION_REVENUE', 'MONTHLY_FEE_RECEIVABLE', 'MONTHLY_FEE_REVENUE', 'FX_CLEARING'));
+
+DELETE FROM disbursement_lines WHERE kind IN ('RESERVE', 'RESERVE_RELEASE');
+
+DROP INDEX IF EXISTS disbursement_lines_reserve_release_unique;
+
+ALTER TABLE disbursement_lines DROP CONSTRAINT disbursement_lines_kind_check;
+ALTER TABLE disbursement_lines ADD CONSTRAINT disbursement_lines_kind_check
+    CHECK (kind IN ('ORDER', 'ADJUSTMENT'));
+
+ALTER TABLE disbursement_lines
+    DROP COLUMN reserve_id,
+    ALTER COLUMN order_id SET NOT NULL;
+
+DROP TABLE IF EXISTS reserves;
+DROP TABLE IF EXISTS reserve_policies;
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Revert calculator:add_settlement_to_disbursements from pg
+
+BEGIN;
+
+DROP INDEX IF EXISTS disbursements_unsettled_idx;
+
+ALTER TABLE disbursements
+    DROP COLUMN settled_on,
+    DROP COLUMN bank_reference;
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Revert calculator:add_time_zones from pg
+
+BEGIN;
+
+ALTER TABLE orders
+    ALTER COLUMN created_at TYPE DATE USING (created_at AT TIME ZONE 'UTC')::DATE;
+
+ALTER TABLE merchants
+    DROP COLUMN time_zone;
+
+COMMIT;
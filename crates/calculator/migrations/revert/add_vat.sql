@@ -0,0 +1,33 @@
+-- Revert calculator:add_vat from pg
+
+BEGIN;
+
+DELETE FROM ledger_postings WHERE account = 'VAT_PAYABLE';
+
+ALTER TABLE ledger_postings DROP CONSTRAINT ledger_postings_account_check;
+ALTER TABLE ledger_postings ADD CONSTRAINT ledger_postings_account_check
+    CHECK (account IN ('CASH', 'MERCHANT_PAYABLE', 'COMMISSION_REVENUE', 'MONTHLY_FEE_RECEIVABLE', 'MONTHLY_FEE_REVENUE', 'FX_CLEARING', 'MERCHANT_RESERVE', 'MERCHANT_RECEIVABLE'));
+
+ALTER TABLE invoices
+    DROP COLUMN vat_treatment,
+    DROP COLUMN customer_vat_number;
+
+ALTER TABLE monthly_fees
+    DROP COLUMN vat_treatment,
+    DROP COLUMN vat_rate,
+    DROP COLUMN vat_amount,
+    DROP COLUMN gross_amount;
+
+ALTER TABLE disbursements
+    DROP COLUMN vat_treatment,
+    DROP COLUMN vat_rate,
+    DROP COLUMN fee_vat_amount,
+    DROP COLUMN fee_gross_amount;
+
+DROP TABLE IF EXISTS vat_rules;
+
+ALTER TABLE merchants
+    DROP COLUMN country,
+    DROP COLUMN vat_number;
+
+COMMIT;
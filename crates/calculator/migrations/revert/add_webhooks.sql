@@ -0,0 +1,9 @@
+-- Revert calculator:add_webhooks from pg
+
+BEGIN;
+
+DROP TABLE IF EXISTS webhook_delivery_attempts;
+DROP TABLE IF EXISTS webhook_deliveries;
+DROP TABLE IF EXISTS webhook_endpoints;
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Revert calculator:create_disbursements_table from pg
+
+BEGIN;
+
+DROP TABLE IF EXISTS disbursement_lines;
+DROP TABLE IF EXISTS disbursements;
+
+COMMIT;
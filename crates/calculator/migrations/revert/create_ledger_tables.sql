@@ -0,0 +1,8 @@
+-- Revert calculator:create_ledger_tables from pg
+
+BEGIN;
+
+DROP TABLE IF EXISTS ledger_postings;
+DROP TABLE IF EXISTS journal_entries;
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Revert calculator:create_monthly_fees_table from pg
+
+BEGIN;
+
+DROP TABLE IF EXISTS monthly_fees;
+
+COMMIT;
@@ -0,0 +1,16 @@
+-- Revert calculator:create_order_adjustments_table from pg
+
+BEGIN;
+
+DELETE FROM disbursement_lines WHERE kind <> 'ORDER';
+
+DROP INDEX IF EXISTS disbursement_lines_order_id_unique;
+
+ALTER TABLE disbursement_lines
+    DROP COLUMN adjustment_id,
+    DROP COLUMN kind,
+    ADD CONSTRAINT disbursement_lines_order_id_key UNIQUE (order_id);
+
+DROP TABLE IF EXISTS order_adjustments;
+
+COMMIT;
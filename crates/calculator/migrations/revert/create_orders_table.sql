@@ -0,0 +1,7 @@
+-- Revert calculator:create_orders_table from pg
+
+BEGIN;
+
+DROP TABLE IF EXISTS orders;
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Revert calculator:create_pricing_plans_tables from pg
+
+BEGIN;
+
+ALTER TABLE disbursement_lines DROP COLUMN fixed_fee;
+ALTER TABLE merchants DROP COLUMN pricing_plan;
+
+DROP TABLE IF EXISTS pricing_plan_tiers;
+DROP TABLE IF EXISTS pricing_plans;
+
+COMMIT;
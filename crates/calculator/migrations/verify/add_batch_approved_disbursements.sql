@@ -0,0 +1,9 @@
+-- Verify calculator:add_batch_approved_disbursements on pg
+
+BEGIN;
+
+SELECT batch_id, disbursement_id
+FROM disbursement_batch_disbursements
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,23 @@
+-- Verify calculator:add_currencies_and_fx_rates on pg
+
+BEGIN;
+
+SELECT rate_date, base_currency, quote_currency, rate
+FROM fx_rates
+WHERE FALSE;
+
+SELECT currency FROM orders WHERE FALSE;
+SELECT currency FROM order_adjustments WHERE FALSE;
+SELECT payout_currency FROM merchants WHERE FALSE;
+SELECT currency FROM monthly_fees WHERE FALSE;
+SELECT currency FROM ledger_postings WHERE FALSE;
+
+SELECT currency, gross_amount
+FROM disbursements
+WHERE FALSE;
+
+SELECT original_amount, original_currency, fx_rate
+FROM disbursement_lines
+WHERE FALSE;
+
+ROLLBACK;
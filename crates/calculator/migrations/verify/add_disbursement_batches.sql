@@ -0,0 +1,21 @@
+-- Verify calculator:add_disbursement_batches on pg
+
+BEGIN;
+
+SELECT batch_id
+FROM disbursements
+WHERE FALSE;
+
+SELECT id, disbursed_on, status, created_at, approved_at
+FROM disbursement_batches
+WHERE FALSE;
+
+SELECT id, batch_id, approver, approved_at
+FROM disbursement_batch_approvals
+WHERE FALSE;
+
+SELECT batch_id, currency, disbursement_count, net_amount
+FROM disbursement_batch_totals
+WHERE FALSE;
+
+ROLLBACK;
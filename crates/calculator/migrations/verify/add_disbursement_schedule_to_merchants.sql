@@ -0,0 +1,9 @@
+-- Verify calculator:add_disbursement_schedule_to_merchants on pg
+
+BEGIN;
+
+SELECT disbursement_weekday, disbursement_day_of_month
+FROM merchants
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,13 @@
+-- Verify calculator:add_disbursement_status on pg
+
+BEGIN;
+
+SELECT status
+FROM disbursements
+WHERE FALSE;
+
+SELECT id, disbursement_id, from_status, to_status, actor, note, changed_at
+FROM disbursement_status_history
+WHERE FALSE;
+
+ROLLBACK;
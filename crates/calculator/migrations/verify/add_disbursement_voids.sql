@@ -0,0 +1,11 @@
+-- Verify calculator:add_disbursement_voids on pg
+
+BEGIN;
+
+SELECT voided
+FROM disbursement_lines
+WHERE FALSE;
+
+SELECT 1/COUNT(*) FROM pg_indexes WHERE indexname = 'disbursement_lines_adjustment_id_unique';
+
+ROLLBACK;
@@ -0,0 +1,9 @@
+-- Verify calculator:add_event_id_to_order_adjustments on pg
+
+BEGIN;
+
+SELECT event_id
+FROM order_adjustments
+WHERE FALSE;
+
+ROLLBACK;
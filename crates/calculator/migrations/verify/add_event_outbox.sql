@@ -0,0 +1,9 @@
+-- Verify calculator:add_event_outbox on pg
+
+BEGIN;
+
+SELECT id, position, topic, merchant_reference, payload, created_at, published_at
+FROM event_outbox
+WHERE FALSE;
+
+ROLLBACK;
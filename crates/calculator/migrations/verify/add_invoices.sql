@@ -0,0 +1,18 @@
+-- Verify calculator:add_invoices on pg
+
+BEGIN;
+
+SELECT kind, year, last_number
+FROM invoice_sequences
+WHERE FALSE;
+
+SELECT id, number, kind, merchant_reference, monthly_fee_id, credited_invoice_id, note, issued_on, period_start, period_end,
+       seller_name, customer_name, currency, net_amount, vat_rate, vat_amount, total_amount
+FROM invoices
+WHERE FALSE;
+
+SELECT invoice_id, position, description, quantity, unit_amount, amount
+FROM invoice_lines
+WHERE FALSE;
+
+ROLLBACK;
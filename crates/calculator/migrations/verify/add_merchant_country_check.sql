@@ -0,0 +1,7 @@
+-- Verify calculator:add_merchant_country_check on pg
+
+BEGIN;
+
+SELECT 1/COUNT(*) FROM pg_constraint WHERE conname = 'merchants_country_check';
+
+ROLLBACK;
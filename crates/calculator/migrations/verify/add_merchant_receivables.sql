@@ -0,0 +1,13 @@
+-- Verify calculator:add_merchant_receivables on pg
+
+BEGIN;
+
+SELECT id, merchant_reference, disbursement_id, currency, amount, opened_on, flagged_for_collection, settled_disbursement_id
+FROM receivables
+WHERE FALSE;
+
+SELECT receivable_id
+FROM disbursement_lines
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,13 @@
+-- Verify calculator:add_minimum_payout_and_carried_balances on pg
+
+BEGIN;
+
+SELECT minimum_payout, offboarded_on
+FROM merchants
+WHERE FALSE;
+
+SELECT id, merchant_reference, carried_on, currency, net_amount, minimum_payout, disbursement_id
+FROM carried_balances
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,17 @@
+-- Verify calculator:add_monthly_fee_deduction on pg
+
+BEGIN;
+
+SELECT monthly_fee_mode
+FROM merchants
+WHERE FALSE;
+
+SELECT mode, disbursement_id
+FROM monthly_fees
+WHERE FALSE;
+
+SELECT monthly_fee_id
+FROM disbursement_lines
+WHERE FALSE;
+
+ROLLBACK;
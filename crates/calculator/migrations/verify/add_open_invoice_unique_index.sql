@@ -0,0 +1,11 @@
+-- Verify calculator:add_open_invoice_unique_index on pg
+
+BEGIN;
+
+SELECT credited
+FROM invoices
+WHERE FALSE;
+
+SELECT 1/COUNT(*) FROM pg_indexes WHERE indexname = 'invoices_open_monthly_fee_unique';
+
+ROLLBACK;
@@ -0,0 +1,9 @@
+-- Verify calculator:add_payout_file_withdrawals on pg
+
+BEGIN;
+
+SELECT withdrawn_at, withdrawn_by
+FROM payout_files
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,13 @@
+-- Verify calculator:add_payout_files on pg
+
+BEGIN;
+
+SELECT id, message_id, execution_date, format, path, content, created_at
+FROM payout_files
+WHERE FALSE;
+
+SELECT payout_file_id, disbursement_id
+FROM payout_file_disbursements
+WHERE FALSE;
+
+ROLLBACK;
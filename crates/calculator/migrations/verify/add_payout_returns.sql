@@ -0,0 +1,13 @@
+-- Verify calculator:add_payout_returns on pg
+
+BEGIN;
+
+SELECT id, disbursement_id, merchant_reference, returned_on, amount, currency, reason_code, reason, source, iban, bic, account_holder, released_on, redisbursement_id
+FROM payout_returns
+WHERE FALSE;
+
+SELECT payout_return_id
+FROM disbursement_lines
+WHERE FALSE;
+
+ROLLBACK;
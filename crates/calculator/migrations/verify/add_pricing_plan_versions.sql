@@ -0,0 +1,18 @@
+-- Verify calculator:add_pricing_plan_versions on pg
+
+BEGIN;
+
+SELECT id, plan_code, valid_from, fixed_fee
+FROM pricing_plan_versions
+WHERE FALSE;
+
+SELECT version_id, from_amount, fee_rate
+FROM pricing_plan_tiers
+WHERE FALSE;
+
+SELECT 1/COUNT(*) FROM pricing_plan_versions WHERE plan_code = 'DEFAULT';
+
+SELECT pricing_version FROM disbursement_lines WHERE FALSE;
+SELECT pricing_version FROM order_adjustments WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,17 @@
+-- Verify calculator:add_rolling_reserves on pg
+
+BEGIN;
+
+SELECT merchant_reference, reserve_rate, hold_days
+FROM reserve_policies
+WHERE FALSE;
+
+SELECT id, merchant_reference, disbursement_id, currency, amount, held_on, release_on, released_disbursement_id
+FROM reserves
+WHERE FALSE;
+
+SELECT reserve_id
+FROM disbursement_lines
+WHERE FALSE;
+
+ROLLBACK;
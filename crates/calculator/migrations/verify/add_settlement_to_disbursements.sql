@@ -0,0 +1,9 @@
+-- Verify calculator:add_settlement_to_disbursements on pg
+
+BEGIN;
+
+SELECT settled_on, bank_reference
+FROM disbursements
+WHERE FALSE;
+
+ROLLBACK;
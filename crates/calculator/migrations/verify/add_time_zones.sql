@@ -0,0 +1,13 @@
+-- Verify calculator:add_time_zones on pg
+
+BEGIN;
+
+SELECT time_zone
+FROM merchants
+WHERE FALSE;
+
+SELECT 1/COUNT(*)
+FROM information_schema.columns
+WHERE table_name = 'orders' AND column_name = 'created_at' AND data_type = 'timestamp with time zone';
+
+ROLLBACK;
@@ -0,0 +1,25 @@
+-- Verify calculator:add_vat on pg
+
+BEGIN;
+
+SELECT country, vat_number
+FROM merchants
+WHERE FALSE;
+
+SELECT country, standard_rate, eu_member
+FROM vat_rules
+WHERE FALSE;
+
+SELECT vat_treatment, vat_rate, fee_vat_amount, fee_gross_amount
+FROM disbursements
+WHERE FALSE;
+
+SELECT vat_treatment, vat_rate, vat_amount, gross_amount
+FROM monthly_fees
+WHERE FALSE;
+
+SELECT vat_treatment, customer_vat_number
+FROM invoices
+WHERE FALSE;
+
+ROLLBACK;
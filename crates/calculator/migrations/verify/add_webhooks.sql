@@ -0,0 +1,17 @@
+-- Verify calculator:add_webhooks on pg
+
+BEGIN;
+
+SELECT id, merchant_reference, url, secret, topics, active, created_at
+FROM webhook_endpoints
+WHERE FALSE;
+
+SELECT id, endpoint_id, event_id, topic, merchant_reference, payload, status, attempts, next_attempt_at, delivered_at, created_at
+FROM webhook_deliveries
+WHERE FALSE;
+
+SELECT id, delivery_id, attempted_at, response_status, error, duration_ms
+FROM webhook_delivery_attempts
+WHERE FALSE;
+
+ROLLBACK;
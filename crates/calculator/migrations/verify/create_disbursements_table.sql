@@ -0,0 +1,13 @@
+-- Verify calculator:create_disbursements_table on pg
+
+BEGIN;
+
+SELECT id, reference, merchant_reference, disbursed_on, gross_amount, fee_amount, net_amount
+FROM disbursements
+WHERE FALSE;
+
+SELECT id, disbursement_id, order_id, amount, fee_rate, fee_amount
+FROM disbursement_lines
+WHERE FALSE;
+
+ROLLBACK;
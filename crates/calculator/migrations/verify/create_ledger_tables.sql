@@ -0,0 +1,13 @@
+-- Verify calculator:create_ledger_tables on pg
+
+BEGIN;
+
+SELECT id, kind, source_id, merchant_reference, booked_on
+FROM journal_entries
+WHERE FALSE;
+
+SELECT id, journal_entry_id, merchant_reference, account, amount
+FROM ledger_postings
+WHERE FALSE;
+
+ROLLBACK;
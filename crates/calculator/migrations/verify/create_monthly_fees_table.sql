@@ -0,0 +1,9 @@
+-- Verify calculator:create_monthly_fees_table on pg
+
+BEGIN;
+
+SELECT id, merchant_reference, month, commissions_amount, minimum_monthly_fee, amount, calculated_on
+FROM monthly_fees
+WHERE FALSE;
+
+ROLLBACK;
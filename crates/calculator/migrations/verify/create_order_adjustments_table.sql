@@ -0,0 +1,13 @@
+-- Verify calculator:create_order_adjustments_table on pg
+
+BEGIN;
+
+SELECT id, order_id, merchant_reference, kind, amount, fee_rate, created_at
+FROM order_adjustments
+WHERE FALSE;
+
+SELECT kind, adjustment_id
+FROM disbursement_lines
+WHERE FALSE;
+
+ROLLBACK;
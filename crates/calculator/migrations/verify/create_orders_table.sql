@@ -0,0 +1,9 @@
+-- Verify calculator:create_orders_table on pg
+
+BEGIN;
+
+SELECT id, merchant_reference, amount, created_at
+FROM orders
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,17 @@
+-- Verify calculator:create_pricing_plans_tables on pg
+
+BEGIN;
+
+-- The fixed fee of a plan and the plan code of its tiers moved to add_pricing_plan_versions
+SELECT code
+FROM pricing_plans
+WHERE FALSE;
+
+SELECT from_amount, fee_rate
+FROM pricing_plan_tiers
+WHERE FALSE;
+
+SELECT pricing_plan FROM merchants WHERE FALSE;
+SELECT fixed_fee FROM disbursement_lines WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,53 @@
+use anyhow::{anyhow, Result};
+use calculator::entities::payout_returns::ReturnStatus;
+use calculator::jobs::payout_returns::{process_payout_returns_handler, ProcessPayoutReturnsJob};
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+
+// Usage: payout_returns --file <pain.002 or camt.054 path>
+// Returns the disbursements the bank sent back and holds the payouts of their merchants until their bank details change.
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let job = parse_args(std::env::args().skip(1))?;
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+
+    let lines = process_payout_returns_handler(job.clone(), &pool).await?;
+
+    for line in &lines {
+        println!(
+            "{} {} {} {}",
+            line.notice.reference,
+            line.notice.returned_on,
+            line.status,
+            line.reason.as_deref().or(line.notice.reason_code.as_deref()).unwrap_or_default()
+        );
+    }
+    println!(
+        "Processed {}: {} returned, {} already returned, {} refused",
+        job.file_path,
+        lines.iter().filter(|line| line.status == ReturnStatus::Returned).count(),
+        lines.iter().filter(|line| line.status == ReturnStatus::AlreadyReturned).count(),
+        lines.iter().filter(|line| line.status == ReturnStatus::Refused).count()
+    );
+
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<ProcessPayoutReturnsJob> {
+    let mut file_path = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--file" => file_path = Some(value()?),
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(ProcessPayoutReturnsJob {
+        file_path: file_path.ok_or_else(|| anyhow!("--file is required"))?,
+    })
+}
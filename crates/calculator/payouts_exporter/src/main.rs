@@ -0,0 +1,91 @@
+use anyhow::{anyhow, Result};
+use calculator::jobs::export_payouts::{
+    export_payouts_handler, reissue_payout_file_handler, withdraw_payout_file_handler, ExportPayoutsJob, PayoutFileFormat,
+};
+use calculator::settings::config::Settings;
+use calculator::settings::payouts::PayoutSettings;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+// Usage:
+//   payouts_exporter --date YYYY-MM-DD [--format xml|csv] --output <path>
+//   payouts_exporter reissue --file <message_id> --output <path>
+//   payouts_exporter withdraw --file <message_id> --actor <name>
+// Exports the approved disbursements of the date as a new payout file for the bank, writes a file exported
+// before again, as it was sent, or withdraws one recalled from the bank (its disbursements are approved again).
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let mut args = std::env::args().skip(1).peekable();
+    let command = args.next_if(|arg| arg == "reissue" || arg == "withdraw");
+    let options = parse_options(args)?;
+
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+
+    if command.as_deref() == Some("withdraw") {
+        let message_id = options.message_id.ok_or_else(|| anyhow!("--file is required"))?;
+        let actor = options.actor.ok_or_else(|| anyhow!("--actor is required"))?;
+        let file = withdraw_payout_file_handler(&pool, &message_id, &actor).await?;
+
+        println!("Withdrew payout file {}, its {} disbursements are APPROVED again", file.message_id, file.disbursement_ids.len());
+        return Ok(());
+    }
+
+    let output_path = options.output_path.ok_or_else(|| anyhow!("--output is required"))?;
+    if command.as_deref() == Some("reissue") {
+        let message_id = options.message_id.ok_or_else(|| anyhow!("--file is required"))?;
+        let file = reissue_payout_file_handler(&pool, &message_id, &output_path).await?;
+
+        println!("Wrote payout file {} ({} payouts, first exported to {}) to {}", file.message_id, file.disbursement_ids.len(), file.path, output_path);
+        return Ok(());
+    }
+
+    let job = ExportPayoutsJob {
+        date: options.date.ok_or_else(|| anyhow!("--date is required"))?,
+        format: options.format,
+        output_path,
+    };
+    let payout_settings = PayoutSettings::from_env();
+    let (file, batch) = export_payouts_handler(job.clone(), &pool, &payout_settings).await?;
+
+    println!("Exported {} payouts to {} (message id {})", batch.instructions.len(), job.output_path, file.message_id);
+    for reference in &batch.skipped {
+        eprintln!("Skipped disbursement {}: no bank account or nothing to pay", reference);
+    }
+
+    Ok(())
+}
+
+struct Options {
+    date: Option<NaiveDate>,
+    format: PayoutFileFormat,
+    output_path: Option<String>,
+    message_id: Option<String>,
+    actor: Option<String>,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options> {
+    let mut options = Options {
+        date: None,
+        format: PayoutFileFormat::Xml,
+        output_path: None,
+        message_id: None,
+        actor: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--date" => options.date = Some(NaiveDate::parse_from_str(&value()?, "%Y-%m-%d")?),
+            "--format" => options.format = value()?.parse()?,
+            "--output" => options.output_path = Some(value()?),
+            "--file" => options.message_id = Some(value()?),
+            "--actor" => options.actor = Some(value()?),
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(options)
+}
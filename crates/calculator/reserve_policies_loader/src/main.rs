@@ -0,0 +1,28 @@
+use anyhow::{anyhow, Result};
+use calculator::repositories::reserves::upsert_reserve_policies;
+use calculator::services::reserves::parse_reserve_policies_csv;
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+
+// Usage: reserve_policies_loader --file <path>
+// The file is `merchant_reference;reserve_percentage;hold_days` with a header, e.g. `padberg_group;10.00;90`
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let path = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["--file", path] => path.to_string(),
+        _ => return Err(anyhow!("Usage: reserve_policies_loader --file <path>")),
+    };
+
+    let policies = parse_reserve_policies_csv(&std::fs::read_to_string(&path)?)?;
+
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+    upsert_reserve_policies(&pool, &policies).await?;
+
+    println!("Loaded {} reserve policies from {}", policies.len(), path);
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::NaiveDate;
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::Arc;
+use crate::jobs::process_disbursements::{process_disbursements_handler, ProcessDisbursementsJob, RunMode, RunOutcome};
+use crate::services::calendar::BusinessCalendar;
+use crate::services::run_diff::RunDiff;
+use crate::settings::disbursements::DisbursementSettings;
+
+pub struct ApiState {
+    pub pool: PgPool,
+    pub calendar: BusinessCalendar,
+    pub disbursement_settings: DisbursementSettings,
+}
+
+// Read only: the runs are only committed by the disbursements runner.
+//   GET /disbursement-runs/{date}/preview   the RunDiff of a run of the date (YYYY-MM-DD), nothing is persisted
+pub fn router(state: Arc<ApiState>) -> Router {
+    Router::new()
+        .route("/disbursement-runs/{date}/preview", get(preview_run))
+        .with_state(state)
+}
+
+async fn preview_run(State(state): State<Arc<ApiState>>, Path(date): Path<NaiveDate>) -> Result<Json<RunDiff>, ApiError> {
+    let job = ProcessDisbursementsJob { date, mode: RunMode::Preview };
+
+    match process_disbursements_handler(job, &state.pool, &state.calendar, &state.disbursement_settings).await? {
+        RunOutcome::Previewed(diff) => Ok(Json(diff)),
+        RunOutcome::Committed(_) => Err(anyhow::anyhow!("The preview of {} committed the run", date).into()),
+    }
+}
+
+// Any failure of the run is a 500 with its error chain.
+pub struct ApiError(anyhow::Error);
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        Self(err)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("{:#}", self.0) }))).into_response()
+    }
+}
@@ -1 +1,20 @@
 pub mod merchants;
+pub mod orders;
+pub mod order_adjustments;
+pub mod disbursements;
+pub mod monthly_fees;
+pub mod ledger;
+pub mod payouts;
+pub mod fx_rates;
+pub mod pricing_plans;
+pub mod carried_balances;
+pub mod reserves;
+pub mod receivables;
+pub mod invoices;
+pub mod vat;
+pub mod statements;
+pub mod reconciliation;
+pub mod disbursement_batches;
+pub mod payout_returns;
+pub mod outbox;
+pub mod webhooks;
@@ -0,0 +1,16 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use uuid::Uuid;
+
+// A disbursement below the minimum payout that was not created. Its orders and adjustments stay pending,
+// so they are paid (each with its own line) by the next disbursement of the merchant, `disbursement_id`.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct CarriedBalance {
+    pub merchant_reference: String,
+    pub carried_on: NaiveDate,
+    // the payout currency of the merchant
+    pub currency: String,
+    pub net_amount: i64,
+    pub minimum_payout: i64,
+    pub disbursement_id: Option<Uuid>,
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+// The disbursements of a run waiting for the sign-off of two different people before they can be paid out.
+// A run that adds disbursements to a date whose batch is already (partially) approved puts them in a new batch.
+#[derive(Serialize, Clone, Debug)]
+pub struct DisbursementBatch {
+    pub id: Uuid,
+    pub disbursed_on: NaiveDate,
+    pub status: BatchStatus,
+    // frozen by the first approval, the second one must see the same
+    pub totals: Vec<BatchTotal>,
+    // the disbursements the first approval was given to, sorted, the second one approves these and only these
+    pub disbursement_ids: Vec<Uuid>,
+    pub approvals: Vec<BatchApproval>,
+    pub created_at: DateTime<Utc>,
+    pub approved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BatchStatus {
+    #[strum(to_string = "OPEN")]
+    Open,
+    // one approval out of two
+    #[strum(to_string = "PARTIALLY_APPROVED")]
+    PartiallyApproved,
+    #[strum(to_string = "APPROVED")]
+    Approved,
+    // every disbursement of the batch was voided before its approval, nothing is left to approve nor to export
+    #[strum(to_string = "CLOSED")]
+    Closed,
+}
+
+// Amounts in different currencies can't be added, so the batch has a total per payout currency.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchTotal {
+    pub currency: String,
+    pub disbursement_count: i64,
+    pub net_amount: i64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchApproval {
+    pub approver: String,
+    pub approved_at: DateTime<Utc>,
+}
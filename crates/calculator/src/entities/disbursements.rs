@@ -0,0 +1,169 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+use crate::entities::vat::VatCharge;
+use crate::services::fees::commission;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Disbursement {
+    pub id: Uuid,
+    pub reference: String,
+    pub merchant_reference: String,
+    pub disbursed_on: NaiveDate,
+    // the payout currency of the merchant, every amount of the disbursement and its lines is in it
+    pub currency: String,
+    pub gross_amount: i64,
+    // the commissions, VAT excluded
+    pub fee_amount: i64,
+    // the VAT on the commissions, None when no VAT is calculated (see services::vat)
+    pub vat: Option<VatCharge>,
+    pub fee_vat_amount: i64,
+    // fee_amount + fee_vat_amount, what the merchant is charged
+    pub fee_gross_amount: i64,
+    pub net_amount: i64,
+    pub status: DisbursementStatus,
+    pub lines: Vec<DisbursementLine>,
+}
+
+// One line per order (or order adjustment) included in the disbursement, so orders, amounts and fees
+// stay identifiable for reporting, plus the lines that don't come from an order (reserves, receivables, monthly fees and returned payouts).
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisbursementLine {
+    pub kind: LineKind,
+    // None for the lines that don't come from an order
+    pub order_id: Option<String>,
+    pub adjustment_id: Option<Uuid>,
+    // the reserve held (or released) by RESERVE and RESERVE_RELEASE lines
+    pub reserve_id: Option<Uuid>,
+    // the receivable opened (or offset) by RECEIVABLE and RECEIVABLE_OFFSET lines
+    pub receivable_id: Option<Uuid>,
+    // the monthly fee deducted by MONTHLY_FEE lines
+    pub monthly_fee_id: Option<Uuid>,
+    // the payout return paid again by RETURNED_PAYOUT lines
+    pub payout_return_id: Option<Uuid>,
+    pub amount: i64,
+    // the order (or adjustment) amount and currency before converting it, with the rate used (see services::fx)
+    pub original_amount: i64,
+    pub original_currency: String,
+    pub fx_rate: i64,
+    pub fee_rate: i32,
+    // the pricing plan version the fee was calculated with (see PricingPlan::version)
+    pub pricing_version: String,
+    // the fixed per-order fee of the pricing plan, converted, already included in fee_amount
+    pub fixed_fee: i64,
+    pub fee_amount: i64,
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LineKind {
+    #[strum(to_string = "ORDER")]
+    Order,
+    // refunds and amount corrections of orders, usually negative
+    #[strum(to_string = "ADJUSTMENT")]
+    Adjustment,
+    // the part of the sales held back by the rolling reserve of the merchant, negative
+    #[strum(to_string = "RESERVE")]
+    Reserve,
+    // a reserve whose hold period elapsed, paid back
+    #[strum(to_string = "RESERVE_RELEASE")]
+    ReserveRelease,
+    // what the merchant owes us when the other lines net negative, it brings the net amount to zero
+    #[strum(to_string = "RECEIVABLE")]
+    Receivable,
+    // a receivable of an earlier disbursement taken from this one, negative
+    #[strum(to_string = "RECEIVABLE_OFFSET")]
+    ReceivableOffset,
+    // the minimum monthly fee of a merchant in deduct mode, negative
+    #[strum(to_string = "MONTHLY_FEE")]
+    MonthlyFee,
+    // a payout the bank of the merchant sent back, paid again once its bank details changed
+    #[strum(to_string = "RETURNED_PAYOUT")]
+    ReturnedPayout,
+}
+
+impl LineKind {
+    // orders and adjustments are the sales of the disbursement, the other lines only change what is paid out
+    pub fn is_sale(&self) -> bool {
+        matches!(self, LineKind::Order | LineKind::Adjustment)
+    }
+}
+
+// The lifecycle of a disbursement once it's calculated, see `DisbursementStatus::can_change_to` for the allowed changes.
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DisbursementStatus {
+    #[strum(to_string = "CALCULATED")]
+    Calculated,
+    // cleared to be paid out
+    #[strum(to_string = "APPROVED")]
+    Approved,
+    // in a payout file for the bank
+    #[strum(to_string = "EXPORTED")]
+    Exported,
+    // the payout file was sent to the bank
+    #[strum(to_string = "SENT")]
+    Sent,
+    // the bank statement confirms the payout left our account
+    #[strum(to_string = "SETTLED")]
+    Settled,
+    // the bank of the merchant sent the payout back
+    #[strum(to_string = "RETURNED")]
+    Returned,
+    // won't be paid out
+    #[strum(to_string = "VOIDED")]
+    Voided,
+}
+
+impl DisbursementStatus {
+    // Forward only, but for a payout file withdrawn from the bank, whose disbursements are approved again: a
+    // disbursement can be voided until it's in a payout file, and a payout can be returned by the bank of the
+    // merchant before or after it shows up in our statement.
+    pub fn can_change_to(self, to: DisbursementStatus) -> bool {
+        use DisbursementStatus::*;
+
+        matches!(
+            (self, to),
+            (Calculated, Approved)
+                | (Calculated, Voided)
+                | (Approved, Exported)
+                | (Approved, Voided)
+                | (Exported, Approved)
+                | (Exported, Sent)
+                | (Sent, Settled)
+                | (Sent, Returned)
+                | (Settled, Returned)
+        )
+    }
+}
+
+// A row of the status history, who changed the status of the disbursement and when.
+// `from` is None for the first one, when the disbursement is calculated.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct DisbursementStatusChange {
+    pub disbursement_id: Uuid,
+    pub from: Option<DisbursementStatus>,
+    pub to: DisbursementStatus,
+    // the user, or the job for the automatic changes
+    pub actor: String,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl Disbursement {
+    // gross and fee amounts are the ones of the sales, the net amount is what is paid out after every line
+    // and the VAT on the commissions. The VAT is rounded once on the total, not per line.
+    pub fn recalculate_totals(&mut self) {
+        self.gross_amount = self.lines.iter().filter(|line| line.kind.is_sale()).map(|line| line.amount).sum();
+        self.fee_amount = self.lines.iter().map(|line| line.fee_amount).sum();
+        self.fee_vat_amount = self.vat.map_or(0, |vat| commission(self.fee_amount, vat.rate));
+        self.fee_gross_amount = self.fee_amount + self.fee_vat_amount;
+        self.net_amount = self.lines.iter().map(|line| line.amount - line.fee_amount).sum::<i64>() - self.fee_vat_amount;
+    }
+
+    // The reference must be alphanumerical and unique, the unique index in the db is the last line of defense.
+    pub fn generate_reference() -> String {
+        Uuid::new_v4().simple().to_string()[..12].to_uppercase()
+    }
+}
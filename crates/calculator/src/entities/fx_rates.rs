@@ -0,0 +1,11 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+// 1 `base_currency` = `rate` / FX_RATE_SCALE `quote_currency`, effective from `rate_date` until a newer rate.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FxRate {
+    pub rate_date: NaiveDate,
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: i64,
+}
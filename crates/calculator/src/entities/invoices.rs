@@ -0,0 +1,66 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+use crate::entities::vat::VatCharge;
+
+// Invoices and credit notes have their own gapless number series per year: INV-2023-000001, CN-2023-000001...
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InvoiceKind {
+    #[strum(to_string = "INVOICE")]
+    Invoice,
+    #[strum(to_string = "CREDIT_NOTE")]
+    CreditNote,
+}
+
+impl InvoiceKind {
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            InvoiceKind::Invoice => "INV",
+            InvoiceKind::CreditNote => "CN",
+        }
+    }
+}
+
+// The invoice of a minimum monthly fee shortfall, or the credit note reversing one.
+// The names are copied when it's issued, an issued invoice never changes.
+// Credit notes carry negative amounts, so the invoices of a merchant can be summed.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Invoice {
+    pub id: Uuid,
+    pub number: String,
+    pub kind: InvoiceKind,
+    pub merchant_reference: String,
+    pub monthly_fee_id: Uuid,
+    // the invoice a credit note reverses
+    pub credited_invoice_id: Option<Uuid>,
+    pub credited_invoice_number: Option<String>,
+    // why a credit note was issued
+    pub note: Option<String>,
+    pub issued_on: NaiveDate,
+    // the first and last day of the month the fee belongs to
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub seller_name: String,
+    pub customer_name: String,
+    // reverse charged invoices must show it
+    pub customer_vat_number: Option<String>,
+    // the payout currency of the merchant
+    pub currency: String,
+    pub lines: Vec<InvoiceLine>,
+    pub net_amount: i64,
+    // the VAT of the monthly fee, None when no VAT is calculated
+    pub vat: Option<VatCharge>,
+    pub vat_amount: i64,
+    pub total_amount: i64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct InvoiceLine {
+    pub description: String,
+    pub quantity: i64,
+    // in the minor unit of the invoice currency, VAT excluded
+    pub unit_amount: i64,
+    pub amount: i64,
+}
@@ -0,0 +1,125 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+// Debits are positive amounts and credits negative ones, so a balanced entry (and the whole ledger) sums zero
+// in every currency.
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Account {
+    // money received from shoppers and paid out to merchants
+    #[strum(to_string = "CASH")]
+    Cash,
+    // what Company XYZ owes the merchant
+    #[strum(to_string = "MERCHANT_PAYABLE")]
+    MerchantPayable,
+    #[strum(to_string = "COMMISSION_REVENUE")]
+    CommissionRevenue,
+    // minimum monthly fee shortfalls, to be charged to the merchant
+    #[strum(to_string = "MONTHLY_FEE_RECEIVABLE")]
+    MonthlyFeeReceivable,
+    #[strum(to_string = "MONTHLY_FEE_REVENUE")]
+    MonthlyFeeRevenue,
+    // both legs of the currency conversions, its balance per currency is our FX position
+    #[strum(to_string = "FX_CLEARING")]
+    FxClearing,
+    // the rolling reserves held back from the merchant, still owed to it
+    #[strum(to_string = "MERCHANT_RESERVE")]
+    MerchantReserve,
+    // what merchants owe us when their refunds exceed their sales
+    #[strum(to_string = "MERCHANT_RECEIVABLE")]
+    MerchantReceivable,
+    // the VAT charged on commissions and monthly fees, owed to the tax authority
+    #[strum(to_string = "VAT_PAYABLE")]
+    VatPayable,
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EntryKind {
+    #[strum(to_string = "ORDER")]
+    Order,
+    #[strum(to_string = "ADJUSTMENT")]
+    Adjustment,
+    #[strum(to_string = "COMMISSION")]
+    Commission,
+    #[strum(to_string = "PAYOUT")]
+    Payout,
+    #[strum(to_string = "MONTHLY_FEE")]
+    MonthlyFee,
+    #[strum(to_string = "CONVERSION")]
+    Conversion,
+    #[strum(to_string = "RESERVE")]
+    Reserve,
+    #[strum(to_string = "RECEIVABLE")]
+    Receivable,
+    // monthly fees collected by deducting them from a disbursement
+    #[strum(to_string = "MONTHLY_FEE_DEDUCTION")]
+    MonthlyFeeDeduction,
+    // payouts the bank of the merchant sent back
+    #[strum(to_string = "PAYOUT_RETURN")]
+    PayoutReturn,
+    // everything a voided disbursement booked, reversed
+    #[strum(to_string = "VOID")]
+    Void,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Posting {
+    pub account: Account,
+    pub currency: String,
+    pub amount: i64,
+}
+
+// `source_id` is the id of what is booked (order, adjustment, disbursement, monthly fee or payout return),
+// an entry is booked only once per kind and source.
+#[derive(Serialize, Clone, Debug)]
+pub struct JournalEntry {
+    pub id: Uuid,
+    pub kind: EntryKind,
+    pub source_id: String,
+    pub merchant_reference: String,
+    pub booked_on: NaiveDate,
+    pub postings: Vec<Posting>,
+}
+
+impl JournalEntry {
+    pub fn new(kind: EntryKind, source_id: String, merchant_reference: String, booked_on: NaiveDate) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            kind,
+            source_id,
+            merchant_reference,
+            booked_on,
+            postings: Vec::new(),
+        }
+    }
+
+    // Postings are only added in debit/credit pairs of the same currency, so an entry is balanced by construction.
+    pub fn transfer(mut self, debit: Account, credit: Account, amount: i64, currency: &str) -> Self {
+        if amount != 0 {
+            self.postings.push(Posting { account: debit, currency: currency.to_string(), amount });
+            self.postings.push(Posting { account: credit, currency: currency.to_string(), amount: -amount });
+        }
+        self
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.postings.iter().all(|posting| {
+            self.postings
+                .iter()
+                .filter(|other| other.currency == posting.currency)
+                .map(|other| other.amount)
+                .sum::<i64>()
+                == 0
+        })
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct AccountBalance {
+    pub account: Account,
+    pub currency: String,
+    pub balance: i64,
+}
@@ -1,23 +1,115 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Weekday};
+use chrono_tz::Tz;
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
-use strum_macros::Display;
+use shared::iban::mask_iban;
+use strum_macros::{Display, EnumString};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone, Debug)]
 #[serde(rename_all = "lowercase")]
 pub struct Merchant {
     pub id: Uuid,
     pub merchant_reference: String,
     pub live_on: NaiveDate,
     pub disbursement_frequency: DisbursementFrequency,
+    // weekly and biweekly merchants are disbursed on it, the weekday of live_on when it's None
+    pub disbursement_weekday: Option<Weekday>,
+    // monthly merchants are disbursed on it (the last day of the shorter months), the day of live_on when it's None
+    pub disbursement_day_of_month: Option<u32>,
+    // in the minor unit of the payout currency
     pub minimum_monthly_fee: i32,
+    // where the disbursements are paid, merchants without it can't be paid out yet
+    pub bank_account: Option<BankAccount>,
+    // ISO 4217, the orders are converted to it when they are disbursed
+    pub payout_currency: String,
+    // the code of its pricing plan, the default tiers when it has none
+    pub pricing_plan: Option<String>,
+    // how its payouts are moved off non business days, the global convention when it's None
+    pub roll_convention: Option<RollConvention>,
+    // IANA name, its days and months are the ones the orders are grouped by
+    pub time_zone: Tz,
+    // in the minor unit of the payout currency, the global minimum payout when it's None
+    pub minimum_payout: Option<i64>,
+    // from this date on everything pending is paid out, whatever its schedule and the minimum payout
+    pub offboarded_on: Option<NaiveDate>,
+    // how the minimum monthly fee shortfall is collected
+    pub monthly_fee_mode: MonthlyFeeMode,
+    // ISO 3166-1 alpha-2, the VAT of its commissions depends on it (see services::vat)
+    pub country: Option<String>,
+    // with the country prefix, merchants without it are not VAT registered
+    pub vat_number: Option<String>,
 }
 
-#[derive(Deserialize, Serialize, Display)]
+// Debug masks the iban, so a merchant can be logged without leaking its account number.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct BankAccount {
+    pub iban: String,
+    pub bic: String,
+    pub account_holder: String,
+}
+
+impl BankAccount {
+    // Only the country, the check digits and the last four characters are kept: ES91****************1332
+    pub fn masked_iban(&self) -> String {
+        mask_iban(&self.iban)
+    }
+}
+
+impl std::fmt::Debug for BankAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BankAccount")
+            .field("iban", &self.masked_iban())
+            .field("bic", &self.bic)
+            .field("account_holder", &self.account_holder)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum DisbursementFrequency {
     #[strum(to_string = "DAILY")]
     Daily,
     #[strum(to_string = "WEEKLY")]
     Weekly,
-}
\ No newline at end of file
+    // every other week, counting from the first disbursement weekday since live_on
+    #[strum(to_string = "BIWEEKLY")]
+    Biweekly,
+    #[strum(to_string = "MONTHLY")]
+    Monthly,
+}
+
+// How weekdays are stored and travel in the events: MONDAY, TUESDAY...
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MONDAY",
+        Weekday::Tue => "TUESDAY",
+        Weekday::Wed => "WEDNESDAY",
+        Weekday::Thu => "THURSDAY",
+        Weekday::Fri => "FRIDAY",
+        Weekday::Sat => "SATURDAY",
+        Weekday::Sun => "SUNDAY",
+    }
+}
+
+// Payouts scheduled on a weekend or a bank holiday are executed on the next or the previous business day.
+#[derive(Deserialize, Serialize, Display, EnumString, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RollConvention {
+    #[default]
+    #[strum(to_string = "NEXT")]
+    Next,
+    #[strum(to_string = "PREVIOUS")]
+    Previous,
+}
+
+// The minimum monthly fee shortfall is invoiced to the merchant, or deducted from its next disbursement.
+#[derive(Deserialize, Serialize, Display, EnumString, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MonthlyFeeMode {
+    #[default]
+    #[strum(to_string = "INVOICE")]
+    Invoice,
+    #[strum(to_string = "DEDUCT")]
+    Deduct,
+}
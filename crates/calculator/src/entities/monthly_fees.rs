@@ -0,0 +1,31 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use uuid::Uuid;
+use crate::entities::merchants::MonthlyFeeMode;
+use crate::entities::vat::VatCharge;
+
+// The minimum monthly fee shortfall of a merchant for a given month.
+// In invoice mode it gets an invoice (see services::invoices), in deduct mode it's taken from the next disbursement of the merchant instead.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct MonthlyFee {
+    pub id: Uuid,
+    pub merchant_reference: String,
+    // first day of the month the fee belongs to
+    pub month: NaiveDate,
+    // the payout currency of the merchant
+    pub currency: String,
+    pub commissions_amount: i64,
+    pub minimum_monthly_fee: i64,
+    // the shortfall, VAT excluded
+    pub amount: i64,
+    // None when no VAT is calculated (see services::vat)
+    pub vat: Option<VatCharge>,
+    pub vat_amount: i64,
+    // amount + vat_amount, what is invoiced or deducted
+    pub gross_amount: i64,
+    pub calculated_on: NaiveDate,
+    // the mode of the merchant when it was calculated
+    pub mode: MonthlyFeeMode,
+    // the disbursement it was deducted from, deduct mode only
+    pub disbursement_id: Option<Uuid>,
+}
@@ -0,0 +1,33 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+// Orders are immutable, a refund or a correction of an already created order is recorded as an adjustment
+// that is disbursed (usually as a negative amount) in the next disbursement of the merchant.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct OrderAdjustment {
+    pub id: Uuid,
+    // the order_refunded (or order_adjusted) event that booked it, a redelivered event books nothing
+    pub event_id: Uuid,
+    pub order_id: String,
+    pub merchant_reference: String,
+    pub kind: AdjustmentKind,
+    // difference with the order amount before the adjustment, in the minor unit of the order currency
+    pub amount: i64,
+    pub currency: String,
+    // the fee rate of the original order, so the fee is reversed with the same tier
+    pub fee_rate: i32,
+    // the pricing plan version of the original order
+    pub pricing_version: String,
+    pub created_at: NaiveDate,
+}
+
+#[derive(Deserialize, Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum AdjustmentKind {
+    #[strum(to_string = "REFUND")]
+    Refund,
+    #[strum(to_string = "CORRECTION")]
+    Correction,
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::services::currencies::DEFAULT_CURRENCY;
+use crate::services::time_zones::local_date;
+
+// amounts are stored in the minor unit (cents for EUR) of the order currency,
+// the same way the merchants minimum_monthly_fee is.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Order {
+    pub id: String,
+    pub merchant_reference: String,
+    pub amount: i64,
+    // ISO 4217, orders created before we supported other currencies are in EUR
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    // the full instant, the day it belongs to depends on the time zone of the merchant
+    #[serde(deserialize_with = "deserialize_created_at")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Order {
+    // The local day of the order for its merchant, the same day that decides when it's disbursed,
+    // used to pick its pricing version (and the fx rates of a provisional fee tier).
+    pub fn created_on(&self, time_zone: Tz) -> NaiveDate {
+        local_date(self.created_at, time_zone)
+    }
+}
+
+// RFC 3339 timestamps, or plain dates (midnight UTC) sent before orders had a time.
+fn deserialize_created_at<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+
+    if let Ok(instant) = DateTime::parse_from_rfc3339(&value) {
+        return Ok(instant.with_timezone(&Utc));
+    }
+
+    NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).expect("midnight is a valid time").and_utc())
+        .map_err(serde::de::Error::custom)
+}
+
+fn default_currency() -> String {
+    DEFAULT_CURRENCY.to_string()
+}
@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+// An event stored in the same transaction as the change it reports, so only committed changes are published,
+// and published afterwards by the relay (see jobs::publish_events). `id` is in the payload as event_id,
+// an event can be published more than once and the consumers dedupe by it.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct OutboxEvent {
+    pub id: Uuid,
+    pub topic: String,
+    // the key of the event, the events of a merchant are published in order
+    pub merchant_reference: String,
+    pub payload: Value,
+    pub created_at: DateTime<Utc>,
+}
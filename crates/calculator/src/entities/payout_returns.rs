@@ -0,0 +1,77 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+// A payout the bank of the merchant sent back (closed account, wrong holder...). The money is owed to the merchant again,
+// and its payouts are held until its bank details change (`released_on`). The RETURNED_PAYOUT line of its next
+// disbursement, `redisbursement_id`, pays it again.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PayoutReturn {
+    pub id: Uuid,
+    // the returned disbursement
+    pub disbursement_id: Uuid,
+    pub merchant_reference: String,
+    pub returned_on: NaiveDate,
+    // the net amount of the disbursement, in its currency
+    pub amount: i64,
+    pub currency: String,
+    // ISO 20022 return reason (AC04 closed account, AC01 incorrect account number...), when the bank gave one
+    pub reason_code: Option<String>,
+    pub reason: Option<String>,
+    pub source: ReturnSource,
+    // the account the payout was returned from, the payouts are held until the merchant has another one
+    pub iban: Option<String>,
+    pub bic: Option<String>,
+    pub account_holder: Option<String>,
+    pub released_on: Option<NaiveDate>,
+    pub redisbursement_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReturnSource {
+    // a payment status report rejecting the transfer
+    #[strum(to_string = "PAIN_002")]
+    Pain002,
+    // a credit notification of our account with the return information
+    #[strum(to_string = "CAMT_054")]
+    Camt054,
+    // the payout_returned event
+    #[strum(to_string = "EVENT")]
+    Event,
+}
+
+// A return as the bank (or the event) reports it, before it's matched with its disbursement.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ReturnNotice {
+    // the end-to-end id of the transfer, the disbursement reference
+    pub reference: String,
+    pub returned_on: NaiveDate,
+    // checked against the disbursement when they are given
+    pub amount: Option<i64>,
+    pub currency: Option<String>,
+    pub reason_code: Option<String>,
+    pub reason: Option<String>,
+    pub source: ReturnSource,
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReturnStatus {
+    #[strum(to_string = "RETURNED")]
+    Returned,
+    // the same return reported again, nothing is done
+    #[strum(to_string = "ALREADY_RETURNED")]
+    AlreadyReturned,
+    // unknown reference, different amount or a disbursement that was never sent, see the reason
+    #[strum(to_string = "REFUSED")]
+    Refused,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ReturnLine {
+    pub notice: ReturnNotice,
+    pub status: ReturnStatus,
+    pub reason: Option<String>,
+}
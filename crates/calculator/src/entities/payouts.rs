@@ -0,0 +1,72 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+use crate::entities::merchants::BankAccount;
+
+// A disbursement ready to be sent to the bank, with the account of the merchant it's paid to.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PayoutInstruction {
+    // the disbursement reference, used as end-to-end id so the bank statement can be matched back
+    pub reference: String,
+    pub merchant_reference: String,
+    pub creditor: BankAccount,
+    pub amount: i64,
+    pub currency: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PayoutBatch {
+    pub execution_date: NaiveDate,
+    pub instructions: Vec<PayoutInstruction>,
+    // disbursements that can't be paid out: merchant without bank account (or with its payouts held), nothing to pay or not in the file currency
+    pub skipped: Vec<String>,
+}
+
+impl PayoutBatch {
+    // The instructions in another currency are moved to the skipped ones.
+    pub fn only_in(mut self, currency: &str) -> Self {
+        let (instructions, others): (Vec<_>, Vec<_>) = self
+            .instructions
+            .into_iter()
+            .partition(|instruction| instruction.currency == currency);
+
+        self.instructions = instructions;
+        self.skipped.extend(others.into_iter().map(|instruction| instruction.reference));
+        self
+    }
+
+    // Only meaningful for a batch in a single currency.
+    pub fn control_sum(&self) -> i64 {
+        self.instructions.iter().map(|instruction| instruction.amount).sum()
+    }
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PayoutFileFormat {
+    // ISO 20022 pain.001.001.03
+    #[strum(to_string = "xml")]
+    Xml,
+    #[strum(to_string = "csv")]
+    Csv,
+}
+
+// A file sent to the bank. The bank knows it by its message id, unique across the files, and pays every
+// disbursement in it once.
+#[derive(Serialize, Clone, Debug)]
+pub struct PayoutFile {
+    pub id: Uuid,
+    pub message_id: String,
+    pub execution_date: NaiveDate,
+    pub format: PayoutFileFormat,
+    pub path: String,
+    pub disbursement_ids: Vec<Uuid>,
+    // what was written, None for the files exported before it was kept
+    #[serde(skip)]
+    pub content: Option<String>,
+    pub created_at: DateTime<Utc>,
+    // set when the file was recalled from the bank (see jobs::export_payouts::withdraw_payout_file_handler)
+    pub withdrawn_at: Option<DateTime<Utc>>,
+    pub withdrawn_by: Option<String>,
+}
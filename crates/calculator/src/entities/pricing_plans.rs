@@ -0,0 +1,69 @@
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+// The 1.00 / 0.95 / 0.85 % tiers of Company XYZ. They only live in the database, seeded by the
+// add_pricing_plan_versions migration, so there's a single copy of them to change (with a new version).
+pub const DEFAULT_PRICING_PLAN: &str = "DEFAULT";
+
+// One version of the commission rules of a merchant, negotiated by sales for the large ones.
+// Amounts are in euro cents (see FEE_TIERS_CURRENCY) and rates in basis points, like the default tiers.
+// A version applies to the orders created from `valid_from` until the next version of the plan,
+// so changing a plan never changes how older orders were (or are recalculated) priced.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PricingPlan {
+    pub code: String,
+    #[serde(default = "since_always")]
+    pub valid_from: NaiveDate,
+    // sorted by from_amount, the first one from 0
+    pub tiers: Vec<PricingTier>,
+    // charged on top of the percentage for every order, not given back on refunds
+    #[serde(default)]
+    pub fixed_fee: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PricingTier {
+    pub from_amount: i64,
+    pub fee_rate: i32,
+}
+
+impl PricingPlan {
+    // What the disbursement lines record, e.g. DEFAULT:1970-01-01
+    pub fn version(&self) -> String {
+        format!("{}:{}", self.code, self.valid_from)
+    }
+
+    // The rate of the highest tier the amount (in euro cents) reaches.
+    pub fn fee_rate(&self, amount: i64) -> i32 {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.from_amount <= amount)
+            .or(self.tiers.first())
+            .map(|tier| tier.fee_rate)
+            .unwrap_or_default()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.code.trim().is_empty() {
+            bail!("A pricing plan needs a code");
+        }
+        if self.tiers.first().map(|tier| tier.from_amount) != Some(0) {
+            bail!("The first tier of the pricing plan {} must start at 0", self.code);
+        }
+        if self.tiers.windows(2).any(|tiers| tiers[0].from_amount >= tiers[1].from_amount) {
+            bail!("The tiers of the pricing plan {} must be sorted by from_amount", self.code);
+        }
+        if self.tiers.iter().any(|tier| !(0..=10_000).contains(&tier.fee_rate)) || self.fixed_fee < 0 {
+            bail!("The pricing plan {} has a negative fee or a rate over 100 %", self.code);
+        }
+
+        Ok(())
+    }
+}
+
+// The first version of a plan applies to every order, the default tiers migration seeds this date too.
+pub fn since_always() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date")
+}
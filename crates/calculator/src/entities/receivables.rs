@@ -0,0 +1,20 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use uuid::Uuid;
+
+// What the merchant owes us when its refunds exceed its sales on a payout the negative balance can't be carried past.
+// Opened by the RECEIVABLE line of `disbursement_id`, which brings its net amount to zero, and offset by
+// the RECEIVABLE_OFFSET line of the next disbursement of the merchant, `settled_disbursement_id`.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Receivable {
+    pub id: Uuid,
+    pub merchant_reference: String,
+    pub disbursement_id: Uuid,
+    // the payout currency of the merchant when it was opened
+    pub currency: String,
+    pub amount: i64,
+    pub opened_on: NaiveDate,
+    // above the negative balance limit, to be collected from the merchant instead of waiting for its next sales
+    pub flagged_for_collection: bool,
+    pub settled_disbursement_id: Option<Uuid>,
+}
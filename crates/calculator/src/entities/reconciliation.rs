@@ -0,0 +1,67 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+// The booked debits of a camt.053 bank statement, the payouts that actually left our account.
+#[derive(Serialize, Clone, Debug)]
+pub struct BankStatement {
+    // Stmt/Id
+    pub id: String,
+    pub iban: Option<String>,
+    pub entries: Vec<BankEntry>,
+}
+
+// One transaction of a booked debit entry, a batch booking has one per payout.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BankEntry {
+    // the end-to-end id we sent in the pain.001, the disbursement reference. None when the bank didn't report it
+    pub reference: Option<String>,
+    // the id the bank gave the entry (AcctSvcrRef)
+    pub bank_reference: Option<String>,
+    pub booked_on: NaiveDate,
+    // positive, in the minor unit of the currency
+    pub amount: i64,
+    pub currency: String,
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReconciliationStatus {
+    // same reference, amount and currency than the disbursement, which is settled
+    #[strum(to_string = "MATCHED")]
+    Matched,
+    // the disbursement was settled by an earlier reconciliation, e.g. the same statement reconciled again
+    #[strum(to_string = "ALREADY_MATCHED")]
+    AlreadyMatched,
+    // the reference is a disbursement but something else doesn't agree, see the reason
+    #[strum(to_string = "MISMATCHED")]
+    Mismatched,
+    // no disbursement with the reference
+    #[strum(to_string = "UNMATCHED")]
+    Unmatched,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ReconciliationLine {
+    pub status: ReconciliationStatus,
+    pub entry: BankEntry,
+    pub disbursement_id: Option<Uuid>,
+    // the net amount and currency of the disbursement
+    pub expected_amount: Option<i64>,
+    pub expected_currency: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ReconciliationReport {
+    // a file can have one statement per account
+    pub statement_ids: Vec<String>,
+    pub lines: Vec<ReconciliationLine>,
+}
+
+impl ReconciliationReport {
+    pub fn count(&self, status: ReconciliationStatus) -> usize {
+        self.lines.iter().filter(|line| line.status == status).count()
+    }
+}
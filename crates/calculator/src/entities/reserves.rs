@@ -0,0 +1,27 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use uuid::Uuid;
+
+// The rolling reserve of a (high risk) merchant: `reserve_rate` of its net sales, in basis points,
+// is held back on every disbursement and paid back `hold_days` later.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ReservePolicy {
+    pub merchant_reference: String,
+    pub reserve_rate: i32,
+    pub hold_days: u32,
+}
+
+// The amount held back by the RESERVE line of `disbursement_id`, released by the RESERVE_RELEASE line
+// of `released_disbursement_id` on the first payout day of the merchant from `release_on`.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Reserve {
+    pub id: Uuid,
+    pub merchant_reference: String,
+    pub disbursement_id: Uuid,
+    // the payout currency of the merchant when it was held
+    pub currency: String,
+    pub amount: i64,
+    pub held_on: NaiveDate,
+    pub release_on: NaiveDate,
+    pub released_disbursement_id: Option<Uuid>,
+}
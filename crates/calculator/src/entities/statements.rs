@@ -0,0 +1,28 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use crate::entities::disbursements::Disbursement;
+use crate::entities::monthly_fees::MonthlyFee;
+
+// What a merchant was paid in a month: every disbursement of the month with its lines (orders, adjustments,
+// reserves, receivables and monthly fees deducted), the monthly fee of the month and the balances around it.
+#[derive(Serialize, Clone, Debug)]
+pub struct Statement {
+    pub merchant_reference: String,
+    // first day of the month of the statement
+    pub month: NaiveDate,
+    // the payout currency of the merchant
+    pub currency: String,
+    pub opening_balances: Vec<StatementBalance>,
+    pub disbursements: Vec<Disbursement>,
+    // calculated at the start of the next month, None until then or when the merchant was not live yet
+    pub monthly_fee: Option<MonthlyFee>,
+    pub closing_balances: Vec<StatementBalance>,
+}
+
+// What Company XYZ owes the merchant in a currency (negative when the merchant owes us): sales not disbursed yet,
+// reserves held back and receivables not offset yet.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct StatementBalance {
+    pub currency: String,
+    pub amount: i64,
+}
@@ -0,0 +1,33 @@
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+
+// The VAT of a country, loaded from the rules file of finance.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct VatRule {
+    // ISO 3166-1 alpha-2
+    pub country: String,
+    // in basis points (21 % == 2100)
+    pub standard_rate: i32,
+    pub eu_member: bool,
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VatTreatment {
+    // charged at the standard rate
+    #[strum(to_string = "STANDARD")]
+    Standard,
+    // VAT registered merchants in another EU country account for it themselves, nothing is charged
+    #[strum(to_string = "REVERSE_CHARGE")]
+    ReverseCharge,
+    // merchants outside the EU, nothing is charged
+    #[strum(to_string = "OUTSIDE_SCOPE")]
+    OutsideScope,
+}
+
+// How the commissions and monthly fees of a merchant are taxed, the rate is 0 when nothing is charged.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VatCharge {
+    pub treatment: VatTreatment,
+    pub rate: i32,
+}
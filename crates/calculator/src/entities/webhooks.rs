@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+// Where a merchant wants its events POSTed. The secret signs every delivery (see services::webhooks::signature),
+// it is only shown when the endpoint is registered. A disabled endpoint gets no new deliveries.
+#[derive(Serialize, Clone, Debug)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub merchant_reference: String,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    // the outbox topics sent to it
+    pub topics: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// An outbox event to send to an endpoint, queued with the event. Retried with backoff until the endpoint answers
+// with a 2xx or the attempts run out, and sent again from scratch when redelivered by hand.
+#[derive(Serialize, Clone, Debug)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub event_id: Uuid,
+    pub topic: String,
+    pub merchant_reference: String,
+    pub payload: Value,
+    pub status: DeliveryStatus,
+    // since it was queued or redelivered
+    pub attempts: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeliveryStatus {
+    #[strum(to_string = "PENDING")]
+    Pending,
+    #[strum(to_string = "DELIVERED")]
+    Delivered,
+    // no attempts left, only a redelivery sends it again
+    #[strum(to_string = "FAILED")]
+    Failed,
+}
+
+// One POST of a delivery, the delivery log.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DeliveryAttempt {
+    pub delivery_id: Uuid,
+    pub attempted_at: DateTime<Utc>,
+    // None when there was no answer (connection refused, timeout)
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+// What sending a delivery to its endpoint gave.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttemptOutcome {
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+impl AttemptOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self.response_status, Some(200..=299))
+    }
+}
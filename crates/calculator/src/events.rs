@@ -1,3 +1,4 @@
 pub mod consumer;
+pub mod publisher;
 pub mod kafka;
-pub mod handlers;
\ No newline at end of file
+pub mod handlers;
@@ -1,2 +1,6 @@
 pub mod merchant_upserted_handler;
+pub mod order_created_handler;
+pub mod order_refunded_handler;
+pub mod order_adjusted_handler;
+pub mod payout_returned_handler;
 pub mod handler;
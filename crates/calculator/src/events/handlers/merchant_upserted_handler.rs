@@ -1,9 +1,15 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use serde_json::Value;
 use sqlx::PgPool;
 use crate::entities::merchants::Merchant;
+use crate::entities::pricing_plans::{PricingPlan, DEFAULT_PRICING_PLAN};
+use crate::repositories::pricing_plans::upsert_pricing_plan;
 use crate::repositories::merchants::upsert_merchants;
+use crate::repositories::payout_returns::{find_held_payout_returns, release_payout_returns};
+use crate::services::payout_returns::bank_details_changed;
+use crate::services::vat::parse_country;
+use crate::services::currencies::{minor_units, DEFAULT_CURRENCY};
 use crate::events::handlers::handler::EventHandler;
 
 pub struct MerchantUpsertedHandler {
@@ -27,6 +33,9 @@ impl MerchantUpsertedHandlerBuilder {
 #[async_trait]
 impl EventHandler for MerchantUpsertedHandler {
     async fn handle(&self, payload: Value) -> Result<()> {
+        // every version of the merchant plan, null (or empty) for the merchants with the default tiers
+        let pricing_plan_versions: Vec<PricingPlan> =
+            serde_json::from_value::<Option<Vec<PricingPlan>>>(payload["pricing_plan_versions"].clone())?.unwrap_or_default();
 
         let merchant = Merchant {
             id: uuid::Uuid::new_v4(),
@@ -39,17 +48,84 @@ impl EventHandler for MerchantUpsertedHandler {
                 "%Y-%m-%d",
             )?,
             disbursement_frequency: serde_json::from_value(payload["disbursement_frequency"].clone())?,
+            disbursement_weekday: payload["disbursement_weekday"]
+                .as_str()
+                .map(|weekday| weekday.parse::<chrono::Weekday>())
+                .transpose()?,
+            // refused rather than truncated when it doesn't fit, the range is checked below
+            disbursement_day_of_month: payload["disbursement_day_of_month"]
+                .as_i64()
+                .map(u32::try_from)
+                .transpose()
+                .map_err(|_| anyhow!("Invalid disbursement day of month: {}", payload["disbursement_day_of_month"]))?,
             minimum_monthly_fee: payload["minimum_monthly_fee"].as_i64().unwrap_or(0) as i32,
+            // null for the merchants without one, a malformed one is refused rather than dropped
+            bank_account: serde_json::from_value(payload["bank_account"].clone())
+                .map_err(|err| anyhow!("Invalid bank account: {}", err))?,
+            // merchants imported before the payout currency existed are paid in euros
+            payout_currency: payload["payout_currency"]
+                .as_str()
+                .unwrap_or(DEFAULT_CURRENCY)
+                .to_uppercase(),
+            pricing_plan: pricing_plan_versions.first().map(|plan| plan.code.clone()),
+            // null for the merchants following the global convention
+            roll_convention: serde_json::from_value(payload["roll_convention"].clone())?,
+            // merchants imported before time zones existed keep grouping their orders by UTC days
+            time_zone: payload["time_zone"]
+                .as_str()
+                .unwrap_or("UTC")
+                .parse::<chrono_tz::Tz>()
+                .map_err(|err| anyhow!("Invalid time zone: {}", err))?,
+            // null for the global minimum payout
+            minimum_payout: payload["minimum_payout"].as_i64(),
+            offboarded_on: serde_json::from_value(payload["offboarded_on"].clone())?,
+            // merchants imported before the deduct mode existed have their monthly fee invoiced
+            monthly_fee_mode: serde_json::from_value::<Option<_>>(payload["monthly_fee_mode"].clone())?.unwrap_or_default(),
+            // null for the merchants without a known country or not VAT registered
+            country: payload["country"].as_str().map(parse_country).transpose()?,
+            vat_number: payload["vat_number"].as_str().map(str::to_string),
         };
 
+        minor_units(&merchant.payout_currency)?;
+        if merchant.disbursement_day_of_month.is_some_and(|day| !(1..=31).contains(&day)) {
+            bail!("Invalid disbursement day of month for merchant {}", merchant.merchant_reference);
+        }
+
+        // the plan travels with the merchant, and it's stored before it so the merchant never points to a missing one
+        for plan in &pricing_plan_versions {
+            plan.validate()?;
+            if Some(&plan.code) != merchant.pricing_plan.as_ref() || plan.code == DEFAULT_PRICING_PLAN {
+                bail!("Invalid pricing plan {} for merchant {}", plan.code, merchant.merchant_reference);
+            }
+        }
+        // the plan, the merchant and the payouts its new bank details release are stored together or not at all
+        let mut tx = self.pool.begin().await?;
+        for plan in &pricing_plan_versions {
+            upsert_pricing_plan(&mut tx, plan).await?;
+        }
+
         println!(
-            "Processed merchant: {} | live_on: {} | fee: {}",
+            "Processed merchant: {} | live_on: {} | fee: {} {} | pricing plan: {} | bank account: {}",
             merchant.merchant_reference,
             merchant.live_on,
-            merchant.minimum_monthly_fee
+            merchant.minimum_monthly_fee,
+            merchant.payout_currency,
+            merchant.pricing_plan.as_deref().unwrap_or(DEFAULT_PRICING_PLAN),
+            merchant.bank_account.as_ref().map(|account| account.masked_iban()).unwrap_or_else(|| "none".to_string())
         );
 
-        upsert_merchants(&self.pool, &[merchant]).await?;
+        upsert_merchants(&mut tx, std::slice::from_ref(&merchant)).await?;
+
+        // the payouts held by a returned payout are released by new bank details, the next disbursement pays it again
+        let released: Vec<uuid::Uuid> = find_held_payout_returns(&mut tx, &merchant.merchant_reference)
+            .await?
+            .iter()
+            .filter(|payout_return| bank_details_changed(payout_return, merchant.bank_account.as_ref()))
+            .map(|payout_return| payout_return.id)
+            .collect();
+        release_payout_returns(&mut tx, &released, chrono::Utc::now().date_naive()).await?;
+
+        tx.commit().await?;
 
         Ok(())
     }
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::repositories::ledger::insert_journal_entries;
+use crate::repositories::order_adjustments::{find_adjustable_order, insert_order_adjustment, order_adjustment_exists};
+use crate::services::order_adjustments::build_correction;
+use crate::services::ledger::adjustment_entry;
+use crate::services::time_zones::parse_local_date;
+use crate::events::handlers::handler::EventHandler;
+
+pub struct OrderAdjustedHandler {
+    pool: PgPool,
+}
+
+impl OrderAdjustedHandler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+pub struct OrderAdjustedHandlerBuilder;
+
+impl OrderAdjustedHandlerBuilder {
+    pub fn build(self, pool: PgPool) -> OrderAdjustedHandler {
+        OrderAdjustedHandler::new(pool)
+    }
+}
+
+#[derive(Deserialize)]
+struct OrderAdjusted {
+    // required, a redelivered event is skipped
+    event_id: Uuid,
+    order_id: String,
+    // the corrected amount of the order, in cents
+    amount: i64,
+    // RFC 3339, or a plain date already in the time zone of the merchant
+    adjusted_at: String,
+}
+
+#[async_trait]
+impl EventHandler for OrderAdjustedHandler {
+    async fn handle(&self, payload: Value) -> Result<()> {
+        let event: OrderAdjusted = serde_json::from_value(payload)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // the order is locked, a redelivery running at the same time waits and skips the event
+        let adjustable = find_adjustable_order(&mut tx, &event.order_id).await?;
+        if order_adjustment_exists(&mut tx, event.event_id).await? {
+            println!("Skipped already processed correction: event {} | order: {}", event.event_id, event.order_id);
+            return Ok(());
+        }
+
+        let correction = build_correction(
+            event.event_id,
+            &adjustable.order,
+            adjustable.current_amount,
+            adjustable.fee_rate,
+            &adjustable.pricing_version,
+            event.amount,
+            parse_local_date(&event.adjusted_at, adjustable.time_zone)?,
+        )?;
+
+        insert_order_adjustment(&mut tx, &correction).await?;
+        insert_journal_entries(&mut tx, &[adjustment_entry(&correction)]).await?;
+
+        tx.commit().await?;
+
+        println!(
+            "Processed order correction: {} | order: {} | amount: {}",
+            correction.id,
+            correction.order_id,
+            correction.amount
+        );
+
+        Ok(())
+    }
+}
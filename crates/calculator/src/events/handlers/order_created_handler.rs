@@ -0,0 +1,54 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::PgPool;
+use crate::entities::orders::Order;
+use crate::repositories::ledger::insert_journal_entries;
+use crate::repositories::orders::insert_orders;
+use crate::services::currencies::minor_units;
+use crate::services::ledger::order_entry;
+use crate::events::handlers::handler::EventHandler;
+
+pub struct OrderCreatedHandler {
+    pool: PgPool,
+}
+
+impl OrderCreatedHandler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+pub struct OrderCreatedHandlerBuilder;
+
+impl OrderCreatedHandlerBuilder {
+    pub fn build(self, pool: PgPool) -> OrderCreatedHandler {
+        OrderCreatedHandler::new(pool)
+    }
+}
+
+#[async_trait]
+impl EventHandler for OrderCreatedHandler {
+    async fn handle(&self, payload: Value) -> Result<()> {
+        // the amount travels in the minor unit of its currency (cents for EUR), like the minimum_monthly_fee of the merchants
+        let order: Order = serde_json::from_value(payload)?;
+        minor_units(&order.currency)?;
+
+        println!(
+            "Processed order: {} | merchant: {} | amount: {} {}",
+            order.id,
+            order.merchant_reference,
+            order.amount,
+            order.currency
+        );
+
+        let mut tx = self.pool.begin().await?;
+
+        insert_orders(&mut tx, std::slice::from_ref(&order)).await?;
+        insert_journal_entries(&mut tx, &[order_entry(&order)]).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::repositories::ledger::insert_journal_entries;
+use crate::repositories::order_adjustments::{find_adjustable_order, insert_order_adjustment, order_adjustment_exists};
+use crate::services::order_adjustments::build_refund;
+use crate::services::ledger::adjustment_entry;
+use crate::services::time_zones::parse_local_date;
+use crate::events::handlers::handler::EventHandler;
+
+pub struct OrderRefundedHandler {
+    pool: PgPool,
+}
+
+impl OrderRefundedHandler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+pub struct OrderRefundedHandlerBuilder;
+
+impl OrderRefundedHandlerBuilder {
+    pub fn build(self, pool: PgPool) -> OrderRefundedHandler {
+        OrderRefundedHandler::new(pool)
+    }
+}
+
+#[derive(Deserialize)]
+struct OrderRefunded {
+    // required, a redelivered event is skipped
+    event_id: Uuid,
+    order_id: String,
+    // in cents, a missing amount refunds everything that is left of the order
+    amount: Option<i64>,
+    // RFC 3339, or a plain date already in the time zone of the merchant
+    refunded_at: String,
+}
+
+#[async_trait]
+impl EventHandler for OrderRefundedHandler {
+    async fn handle(&self, payload: Value) -> Result<()> {
+        let event: OrderRefunded = serde_json::from_value(payload)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // the order is locked, a redelivery running at the same time waits and skips the event
+        let adjustable = find_adjustable_order(&mut tx, &event.order_id).await?;
+        if order_adjustment_exists(&mut tx, event.event_id).await? {
+            println!("Skipped already processed refund: event {} | order: {}", event.event_id, event.order_id);
+            return Ok(());
+        }
+
+        let refund = build_refund(
+            event.event_id,
+            &adjustable.order,
+            adjustable.current_amount,
+            adjustable.fee_rate,
+            &adjustable.pricing_version,
+            event.amount,
+            parse_local_date(&event.refunded_at, adjustable.time_zone)?,
+        )?;
+
+        insert_order_adjustment(&mut tx, &refund).await?;
+        insert_journal_entries(&mut tx, &[adjustment_entry(&refund)]).await?;
+
+        tx.commit().await?;
+
+        println!("Processed refund: {} | order: {} | amount: {}", refund.id, refund.order_id, refund.amount);
+
+        Ok(())
+    }
+}
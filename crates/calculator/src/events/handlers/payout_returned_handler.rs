@@ -0,0 +1,67 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use crate::entities::payout_returns::{ReturnNotice, ReturnSource, ReturnStatus};
+use crate::jobs::payout_returns::process_payout_return;
+use crate::events::handlers::handler::EventHandler;
+
+pub struct PayoutReturnedHandler {
+    pool: PgPool,
+}
+
+impl PayoutReturnedHandler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+pub struct PayoutReturnedHandlerBuilder;
+
+impl PayoutReturnedHandlerBuilder {
+    pub fn build(self, pool: PgPool) -> PayoutReturnedHandler {
+        PayoutReturnedHandler::new(pool)
+    }
+}
+
+#[derive(Deserialize)]
+struct PayoutReturned {
+    disbursement_reference: String,
+    returned_on: NaiveDate,
+    // in cents, checked against the disbursement when given
+    amount: Option<i64>,
+    currency: Option<String>,
+    // ISO 20022 return reason, AC04 for a closed account
+    reason_code: Option<String>,
+    reason: Option<String>,
+}
+
+#[async_trait]
+impl EventHandler for PayoutReturnedHandler {
+    async fn handle(&self, payload: Value) -> Result<()> {
+        let event: PayoutReturned = serde_json::from_value(payload)?;
+        let notice = ReturnNotice {
+            reference: event.disbursement_reference,
+            returned_on: event.returned_on,
+            amount: event.amount,
+            currency: event.currency.map(|currency| currency.to_uppercase()),
+            reason_code: event.reason_code,
+            reason: event.reason,
+            source: ReturnSource::Event,
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let line = process_payout_return(&mut tx, notice).await?;
+        tx.commit().await?;
+
+        if line.status == ReturnStatus::Refused {
+            bail!("Refused the return of {}: {}", line.notice.reference, line.reason.unwrap_or_default());
+        }
+
+        println!("Processed payout return: {} | {}", line.notice.reference, line.status);
+
+        Ok(())
+    }
+}
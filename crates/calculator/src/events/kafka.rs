@@ -1 +1,2 @@
 pub mod consumer;
+pub mod publisher;
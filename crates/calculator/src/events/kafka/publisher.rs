@@ -0,0 +1,51 @@
+use crate::events::publisher::EventPublisher;
+use async_trait::async_trait;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use serde_json::Value;
+use std::time::Duration;
+
+pub struct KafkaPublisher {
+    producer: FutureProducer,
+}
+
+// Same split as the importer: new only keeps the config, build creates the producer and can fail.
+pub struct KafkaPublisherBuilder {
+    brokers: String,
+}
+
+impl KafkaPublisherBuilder {
+    pub fn new(brokers: &str) -> Self {
+        Self {
+            brokers: brokers.to_string(),
+        }
+    }
+
+    pub fn build(self) -> Result<KafkaPublisher, anyhow::Error> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &self.brokers)
+            .create()?;
+        Ok(KafkaPublisher { producer })
+    }
+}
+
+#[async_trait]
+impl EventPublisher for KafkaPublisher {
+    async fn publish(&self, topic: &str, payload: Value) -> Result<(), anyhow::Error> {
+        let payload_str = serde_json::to_string(&payload)?;
+
+        // keyed by merchant, so the events of a merchant land in the same partition and keep their order
+        let key = payload.get("merchant_reference")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        let record = FutureRecord::<_, _>::to(topic)
+            .payload(&payload_str)
+            .key(key);
+
+        match self.producer.send(record, Duration::ZERO).await {
+            Ok(_) => Ok(()),
+            Err((err, _message)) => Err(anyhow::Error::new(err))
+        }
+    }
+}
@@ -0,0 +1,10 @@
+pub mod process_disbursements;
+pub mod disbursement_status;
+pub mod disbursement_batches;
+pub mod export_payouts;
+pub mod reconciliation;
+pub mod payout_returns;
+pub mod invoices;
+pub mod statements;
+pub mod publish_events;
+pub mod webhooks;
@@ -0,0 +1,70 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use crate::entities::disbursement_batches::{BatchStatus, DisbursementBatch};
+use crate::entities::disbursements::{Disbursement, DisbursementStatus};
+use crate::repositories::disbursement_batches::{find_batch_id_of_disbursement, lock_batch, reset_batch_approvals, save_batch_approval};
+use crate::jobs::disbursement_status::save_status_change;
+use crate::repositories::disbursements::{find_disbursements, DisbursementFilter};
+use crate::services::disbursement_batches::{approve_batch, withdraw_approvals};
+use crate::services::disbursement_status::change_status;
+
+// The disbursements approved are the ones of the batch still waiting (the voided ones are left out). With the second
+// approval every one of them is APPROVED, in the same transaction, by the second approver.
+pub async fn approve_batch_handler(pool: &PgPool, batch_id: Uuid, approver: &str) -> Result<DisbursementBatch> {
+    let mut tx = pool.begin().await?;
+
+    let mut batch = lock_batch(&mut tx, batch_id)
+        .await?
+        .ok_or_else(|| anyhow!("Unknown batch {}", batch_id))?;
+
+    let mut disbursements = find_waiting_disbursements(&mut tx, batch_id).await?;
+
+    let approval = approve_batch(&mut batch, approver, &disbursements, Utc::now())?;
+    save_batch_approval(&mut tx, &batch, &approval).await?;
+
+    if batch.status == BatchStatus::Approved {
+        let approvers: Vec<&str> = batch.approvals.iter().map(|approval| approval.approver.as_str()).collect();
+        let note = format!("Batch {} approved by {}", batch.id, approvers.join(" and "));
+
+        for disbursement in &mut disbursements {
+            let change = change_status(disbursement, DisbursementStatus::Approved, &approval.approver, Some(note.clone()), approval.approved_at)?;
+            if !save_status_change(&mut tx, disbursement, &change).await? {
+                bail!("Disbursement {} changed while its batch was approved", disbursement.reference);
+            }
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(batch)
+}
+
+// Called with the disbursement being voided, in its transaction, so its batch never waits for approvals given to
+// something else or for a disbursement that will never be approved.
+pub async fn withdraw_batch_approvals(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<()> {
+    let Some(batch_id) = find_batch_id_of_disbursement(conn, disbursement_id).await? else {
+        return Ok(());
+    };
+    let mut batch = lock_batch(conn, batch_id)
+        .await?
+        .ok_or_else(|| anyhow!("Unknown batch {}", batch_id))?;
+
+    let waiting = find_waiting_disbursements(conn, batch_id).await?;
+    if withdraw_approvals(&mut batch, &waiting) {
+        reset_batch_approvals(conn, &batch).await?;
+    }
+
+    Ok(())
+}
+
+async fn find_waiting_disbursements(conn: &mut PgConnection, batch_id: Uuid) -> Result<Vec<Disbursement>> {
+    let filter = DisbursementFilter {
+        statuses: vec![DisbursementStatus::Calculated],
+        batch_id: Some(batch_id),
+        ..DisbursementFilter::default()
+    };
+
+    Ok(find_disbursements(conn, &filter).await?)
+}
@@ -0,0 +1,98 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{NaiveDate, Utc};
+use sqlx::{PgConnection, PgPool};
+use crate::entities::disbursements::{Disbursement, DisbursementStatus, DisbursementStatusChange};
+use crate::jobs::disbursement_batches::withdraw_batch_approvals;
+use crate::repositories::carried_balances::reopen_carried_balances_settled_by;
+use crate::repositories::disbursements::{find_disbursements_by_reference, update_disbursement_status, void_disbursement_lines};
+use crate::repositories::ledger::{ensure_entries_balanced, insert_journal_entries};
+use crate::repositories::monthly_fees::reopen_monthly_fees_deducted_by;
+use crate::repositories::outbox::insert_outbox_event;
+use crate::repositories::payout_returns::reopen_returns_redisbursed_by;
+use crate::repositories::receivables::{find_receivables_opened_by, reopen_receivables_settled_by, settle_receivables};
+use crate::repositories::reserves::{find_reserves_held_by, mark_reserves_released, reopen_reserves_released_by};
+use crate::services::disbursement_status::change_status;
+use crate::services::ledger::void_entry;
+use crate::services::outbox::disbursement_status_changed;
+
+// The changes made by a user (voiding, confirming the file was sent...), the jobs change the status themselves.
+// Voiding releases what the disbursement paid and withdraws the approvals of its batch in the same transaction.
+// Disbursements are only approved with their batch, by two people (see jobs::disbursement_batches), or again when
+// their payout file is withdrawn (see jobs::export_payouts).
+pub async fn change_disbursement_status_handler(
+    pool: &PgPool,
+    reference: &str,
+    to: DisbursementStatus,
+    actor: &str,
+    note: Option<String>,
+) -> Result<DisbursementStatusChange> {
+    if to == DisbursementStatus::Approved {
+        bail!("Disbursement {} is approved with its batch", reference);
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let mut disbursement = find_disbursements_by_reference(&mut tx, &[reference.to_string()])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Unknown disbursement {}", reference))?;
+
+    if disbursement.status == DisbursementStatus::Exported && to == DisbursementStatus::Voided {
+        bail!("Disbursement {} is in a payout file, the file must be withdrawn before it's voided", reference);
+    }
+
+    let change = change_status(&mut disbursement, to, actor, note, Utc::now())?;
+    if !save_status_change(&mut tx, &disbursement, &change).await? {
+        bail!("Disbursement {} changed while its status was being changed, try again", reference);
+    }
+    if to == DisbursementStatus::Voided {
+        release_voided_disbursement(&mut tx, &disbursement, change.changed_at.date_naive()).await?;
+        withdraw_batch_approvals(&mut tx, disbursement.id).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(change)
+}
+
+// Every change of status is stored in the history and published (disbursement_status_changed) with it, in the transaction
+// of the job that made it. False when someone else changed the disbursement first.
+pub async fn save_status_change(conn: &mut PgConnection, disbursement: &Disbursement, change: &DisbursementStatusChange) -> Result<bool> {
+    if !update_disbursement_status(conn, change).await? {
+        return Ok(false);
+    }
+
+    insert_outbox_event(conn, &disbursement_status_changed(&disbursement.reference, &disbursement.merchant_reference, change)).await?;
+    Ok(true)
+}
+
+// Everything the voided disbursement paid (its orders, adjustments, released reserves, offset receivables, deducted fees
+// and returned payouts) is paid again by the next run, and what it held back or opened is closed by itself.
+// Refused when a later disbursement already paid back its reserve or offset its receivable.
+async fn release_voided_disbursement(conn: &mut PgConnection, disbursement: &Disbursement, voided_on: NaiveDate) -> Result<()> {
+    let reserves = find_reserves_held_by(conn, disbursement.id).await?;
+    let receivables = find_receivables_opened_by(conn, disbursement.id).await?;
+    if reserves.iter().any(|reserve| reserve.released_disbursement_id.is_some())
+        || receivables.iter().any(|receivable| receivable.settled_disbursement_id.is_some())
+    {
+        bail!("Disbursement {} can't be voided, a later disbursement already settled what it held back or opened", disbursement.reference);
+    }
+
+    void_disbursement_lines(conn, disbursement.id).await?;
+    reopen_reserves_released_by(conn, disbursement.id).await?;
+    reopen_receivables_settled_by(conn, disbursement.id).await?;
+    reopen_monthly_fees_deducted_by(conn, disbursement.id).await?;
+    reopen_returns_redisbursed_by(conn, disbursement.id).await?;
+    reopen_carried_balances_settled_by(conn, disbursement.id).await?;
+
+    let reserve_ids: Vec<_> = reserves.iter().map(|reserve| reserve.id).collect();
+    mark_reserves_released(conn, &reserve_ids, disbursement.id).await?;
+    let receivable_ids: Vec<_> = receivables.iter().map(|receivable| receivable.id).collect();
+    settle_receivables(conn, &receivable_ids, disbursement.id).await?;
+
+    let booked = insert_journal_entries(conn, &[void_entry(disbursement, voided_on)]).await?;
+    ensure_entries_balanced(conn, &booked).await?;
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use anyhow::{anyhow, bail, Result};
+use apalis::prelude::Job;
+use chrono::{NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::entities::disbursements::DisbursementStatus;
+use crate::entities::payouts::{PayoutBatch, PayoutFile};
+use crate::repositories::disbursement_batches::find_batches_on;
+use crate::jobs::disbursement_status::save_status_change;
+use crate::repositories::disbursements::{find_disbursements, find_disbursements_by_id, DisbursementFilter};
+use crate::repositories::merchants::find_merchants_by_reference;
+use crate::repositories::payout_files::{count_payout_files_on, find_payout_file, insert_payout_file, mark_payout_file_withdrawn};
+use crate::repositories::payout_returns::find_held_merchant_references;
+use crate::services::disbursement_batches::ensure_approved;
+use crate::services::disbursement_status::{change_status, SYSTEM_ACTOR};
+use crate::services::payout_csv::to_payout_csv;
+use crate::services::payouts::{build_payout_batch, payout_message_id, SEPA_CURRENCY};
+use crate::services::sepa_credit_transfer::to_pain_001;
+use crate::settings::payouts::PayoutSettings;
+
+pub use crate::entities::payouts::PayoutFileFormat;
+
+#[derive(Clone, Debug)]
+pub struct ExportPayoutsJob {
+    pub date: NaiveDate,
+    pub format: PayoutFileFormat,
+    pub output_path: String,
+}
+
+impl Job for ExportPayoutsJob {
+    const NAME: &'static str = "export-payouts";
+}
+
+// Only the approved disbursements are paid out, and they are exported once: they move to EXPORTED with the file they
+// are in, and exporting the date again only writes the ones approved since. Every file gets a message id of its own
+// and is kept with its disbursements, a lost one is written again with `reissue_payout_file_handler`.
+// Nothing is exported while a batch of the date is waiting for its approvals, and the disbursements of the merchants
+// whose payouts are held (a payout was returned) are skipped, they can be exported again once their bank details changed.
+pub async fn export_payouts_handler(job: ExportPayoutsJob, pool: &PgPool, settings: &PayoutSettings) -> Result<(PayoutFile, PayoutBatch)> {
+    let mut tx = pool.begin().await?;
+
+    ensure_approved(&find_batches_on(&mut tx, job.date).await?)?;
+
+    let filter = DisbursementFilter {
+        statuses: vec![DisbursementStatus::Approved],
+        disbursed_from: Some(job.date),
+        disbursed_to: Some(job.date),
+        ..DisbursementFilter::default()
+    };
+    let held = find_held_merchant_references(&mut tx, job.date).await?;
+    let (mut disbursements, held_disbursements): (Vec<_>, Vec<_>) = find_disbursements(&mut tx, &filter)
+        .await?
+        .into_iter()
+        .partition(|disbursement| !held.contains(&disbursement.merchant_reference));
+    let merchant_references: Vec<String> = disbursements
+        .iter()
+        .map(|disbursement| disbursement.merchant_reference.clone())
+        .collect();
+    let merchants = find_merchants_by_reference(&mut tx, &merchant_references).await?;
+
+    let mut batch = build_payout_batch(job.date, &disbursements, &merchants);
+    batch.skipped.extend(held_disbursements.into_iter().map(|disbursement| disbursement.reference));
+
+    let message_id = payout_message_id(job.date, count_payout_files_on(&mut tx, job.date).await? + 1);
+    let created_at = Utc::now();
+    let (batch, content) = match job.format {
+        PayoutFileFormat::Xml => {
+            let batch = batch.only_in(SEPA_CURRENCY);
+            let content = to_pain_001(&batch, settings, &message_id, created_at.naive_utc())?;
+            (batch, content)
+        }
+        PayoutFileFormat::Csv => {
+            let content = to_payout_csv(&batch)?;
+            (batch, content)
+        }
+    };
+
+    disbursements.retain(|disbursement| batch.instructions.iter().any(|instruction| instruction.reference == disbursement.reference));
+    for disbursement in disbursements.iter_mut() {
+        let change = change_status(disbursement, DisbursementStatus::Exported, SYSTEM_ACTOR, Some(job.output_path.clone()), Utc::now())?;
+        if !save_status_change(&mut tx, disbursement, &change).await? {
+            bail!("Disbursement {} changed while it was exported", disbursement.reference);
+        }
+    }
+
+    let file = PayoutFile {
+        id: Uuid::new_v4(),
+        message_id,
+        execution_date: job.date,
+        format: job.format,
+        path: job.output_path.clone(),
+        disbursement_ids: disbursements.iter().map(|disbursement| disbursement.id).collect(),
+        content: Some(content.clone()),
+        created_at,
+        withdrawn_at: None,
+        withdrawn_by: None,
+    };
+    insert_payout_file(&mut tx, &file).await?;
+
+    std::fs::write(&job.output_path, content)?;
+    tx.commit().await?;
+
+    Ok((file, batch))
+}
+
+// Writes a file exported before again, as it was sent (same message id, same transfers), for when it was lost
+// on the way to the bank. The bank refuses it if it already has it, so nothing is paid twice.
+pub async fn reissue_payout_file_handler(pool: &PgPool, message_id: &str, output_path: &str) -> Result<PayoutFile> {
+    let mut conn = pool.acquire().await?;
+
+    let file = find_payout_file(&mut conn, message_id).await?.ok_or_else(|| anyhow!("Unknown payout file {}", message_id))?;
+    if file.withdrawn_at.is_some() {
+        bail!("Payout file {} was withdrawn, its disbursements are exported in a new one", message_id);
+    }
+    let Some(content) = &file.content else {
+        bail!("Payout file {} was exported before the files were kept, it can't be written again", message_id);
+    };
+
+    std::fs::write(output_path, content)?;
+
+    Ok(file)
+}
+
+// For a file recalled from the bank before it was executed: its disbursements are APPROVED again, to be voided or
+// exported in a new file. Refused once one of them was sent, the bank already has the file.
+pub async fn withdraw_payout_file_handler(pool: &PgPool, message_id: &str, actor: &str) -> Result<PayoutFile> {
+    let mut tx = pool.begin().await?;
+
+    let mut file = find_payout_file(&mut tx, message_id).await?.ok_or_else(|| anyhow!("Unknown payout file {}", message_id))?;
+    if file.withdrawn_at.is_some() {
+        bail!("Payout file {} was already withdrawn", message_id);
+    }
+
+    let mut disbursements = find_disbursements_by_id(&mut tx, &file.disbursement_ids).await?;
+    if let Some(sent) = disbursements.iter().find(|disbursement| disbursement.status != DisbursementStatus::Exported) {
+        bail!("Payout file {} can't be withdrawn, disbursement {} is already {}", message_id, sent.reference, sent.status);
+    }
+
+    let withdrawn_at = Utc::now();
+    for disbursement in &mut disbursements {
+        let note = Some(format!("Payout file {} withdrawn", message_id));
+        let change = change_status(disbursement, DisbursementStatus::Approved, actor, note, withdrawn_at)?;
+        if !save_status_change(&mut tx, disbursement, &change).await? {
+            bail!("Disbursement {} changed while its payout file was withdrawn, try again", disbursement.reference);
+        }
+    }
+
+    file.withdrawn_at = Some(withdrawn_at);
+    file.withdrawn_by = Some(actor.trim().to_string());
+    if !mark_payout_file_withdrawn(&mut tx, &file).await? {
+        bail!("Payout file {} was already withdrawn", message_id);
+    }
+
+    tx.commit().await?;
+
+    Ok(file)
+}
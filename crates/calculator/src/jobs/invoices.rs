@@ -0,0 +1,100 @@
+use anyhow::{anyhow, bail, Result};
+use apalis::prelude::Job;
+use chrono::{Datelike, NaiveDate};
+use sqlx::PgPool;
+use strum_macros::{Display, EnumString};
+use crate::entities::invoices::{Invoice, InvoiceKind};
+use crate::repositories::invoices::{find_credit_note_number, find_invoice_by_number, insert_invoice, next_invoice_sequence};
+use crate::repositories::merchants::find_merchants_by_reference;
+use crate::repositories::monthly_fees::find_monthly_fees_to_invoice;
+use crate::services::invoice_html::to_invoice_html;
+use crate::services::invoices::{build_credit_note, build_invoice, invoice_number};
+use crate::services::ubl_invoice::to_ubl;
+use crate::settings::invoices::InvoiceSettings;
+
+#[derive(Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvoiceFormat {
+    #[strum(to_string = "html")]
+    Html,
+    #[strum(to_string = "json")]
+    Json,
+    // UBL 2.1
+    #[strum(to_string = "xml")]
+    Xml,
+}
+
+#[derive(Clone, Debug)]
+pub struct IssueInvoicesJob {
+    // first day of the month of the monthly fees
+    pub month: NaiveDate,
+    pub issued_on: NaiveDate,
+}
+
+impl Job for IssueInvoicesJob {
+    const NAME: &'static str = "issue-invoices";
+}
+
+// Every invoice of the month is issued in one transaction, numbered as it's inserted. Running it again only
+// issues the fees without an invoice yet.
+pub async fn issue_invoices_handler(job: IssueInvoicesJob, pool: &PgPool, settings: &InvoiceSettings) -> Result<Vec<Invoice>> {
+    let mut tx = pool.begin().await?;
+
+    let monthly_fees = find_monthly_fees_to_invoice(&mut tx, job.month).await?;
+    let merchant_references: Vec<String> = monthly_fees
+        .iter()
+        .map(|monthly_fee| monthly_fee.merchant_reference.clone())
+        .collect();
+    let merchants = find_merchants_by_reference(&mut tx, &merchant_references).await?;
+
+    let mut invoices = Vec::new();
+    for monthly_fee in &monthly_fees {
+        let merchant = merchants
+            .iter()
+            .find(|merchant| merchant.merchant_reference == monthly_fee.merchant_reference)
+            .ok_or_else(|| anyhow!("Unknown merchant {}", monthly_fee.merchant_reference))?;
+
+        let sequence = next_invoice_sequence(&mut tx, InvoiceKind::Invoice, job.issued_on.year()).await?;
+        let number = invoice_number(InvoiceKind::Invoice, job.issued_on.year(), sequence);
+        let invoice = build_invoice(monthly_fee, merchant, number, job.issued_on, settings)?;
+
+        insert_invoice(&mut tx, &invoice).await?;
+        invoices.push(invoice);
+    }
+
+    tx.commit().await?;
+
+    Ok(invoices)
+}
+
+pub async fn credit_invoice_handler(pool: &PgPool, number: &str, issued_on: NaiveDate, note: Option<String>) -> Result<Invoice> {
+    let mut tx = pool.begin().await?;
+
+    let invoice = find_invoice_by_number(&mut tx, number)
+        .await?
+        .ok_or_else(|| anyhow!("Unknown invoice {}", number))?;
+    if let Some(credit_note_number) = find_credit_note_number(&mut tx, invoice.id).await? {
+        bail!("{} is already credited by {}", number, credit_note_number);
+    }
+
+    let sequence = next_invoice_sequence(&mut tx, InvoiceKind::CreditNote, issued_on.year()).await?;
+    let credit_note = build_credit_note(&invoice, invoice_number(InvoiceKind::CreditNote, issued_on.year(), sequence), issued_on, note)?;
+
+    insert_invoice(&mut tx, &credit_note).await?;
+    tx.commit().await?;
+
+    Ok(credit_note)
+}
+
+pub async fn render_invoice_handler(pool: &PgPool, number: &str, format: InvoiceFormat) -> Result<String> {
+    let mut conn = pool.acquire().await?;
+
+    let invoice = find_invoice_by_number(&mut conn, number)
+        .await?
+        .ok_or_else(|| anyhow!("Unknown invoice {}", number))?;
+
+    match format {
+        InvoiceFormat::Html => to_invoice_html(&invoice),
+        InvoiceFormat::Json => Ok(serde_json::to_string_pretty(&invoice)?),
+        InvoiceFormat::Xml => to_ubl(&invoice),
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::{bail, Result};
+use apalis::prelude::Job;
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use crate::entities::disbursements::DisbursementStatus;
+use crate::entities::payout_returns::{ReturnLine, ReturnNotice, ReturnStatus};
+use crate::jobs::disbursement_status::save_status_change;
+use crate::repositories::disbursements::find_disbursements_by_reference;
+use crate::repositories::ledger::insert_journal_entries;
+use crate::repositories::merchants::find_merchants_by_reference;
+use crate::repositories::payout_returns::insert_payout_return;
+use crate::services::ledger::payout_return_entry;
+use crate::services::payout_return_files::parse_return_file;
+use crate::services::payout_returns::return_payout;
+
+#[derive(Clone, Debug)]
+pub struct ProcessPayoutReturnsJob {
+    // the pain.002 or camt.054 file of the bank
+    pub file_path: String,
+}
+
+impl Job for ProcessPayoutReturnsJob {
+    const NAME: &'static str = "process-payout-returns";
+}
+
+// Every return of the file is processed in the same transaction, the refused ones are reported and left alone.
+pub async fn process_payout_returns_handler(job: ProcessPayoutReturnsJob, pool: &PgPool) -> Result<Vec<ReturnLine>> {
+    let notices = parse_return_file(&std::fs::read_to_string(&job.file_path)?)?;
+
+    let mut tx = pool.begin().await?;
+
+    let mut lines = Vec::with_capacity(notices.len());
+    for notice in notices {
+        lines.push(process_payout_return(&mut tx, notice).await?);
+    }
+
+    tx.commit().await?;
+
+    Ok(lines)
+}
+
+// The disbursement becomes RETURNED, the money is owed to the merchant again and its payouts are held until its
+// bank details change (see MerchantUpsertedHandler). A return reported twice (the file and the event) is only processed once.
+pub async fn process_payout_return(conn: &mut PgConnection, notice: ReturnNotice) -> Result<ReturnLine> {
+    let disbursement = find_disbursements_by_reference(conn, std::slice::from_ref(&notice.reference))
+        .await?
+        .into_iter()
+        .next();
+
+    let Some(mut disbursement) = disbursement else {
+        return Ok(ReturnLine { notice, status: ReturnStatus::Refused, reason: Some("No disbursement with this reference".to_string()) });
+    };
+    if disbursement.status == DisbursementStatus::Returned {
+        return Ok(ReturnLine { notice, status: ReturnStatus::AlreadyReturned, reason: None });
+    }
+
+    let merchant = find_merchants_by_reference(conn, std::slice::from_ref(&disbursement.merchant_reference))
+        .await?
+        .into_iter()
+        .next();
+    let bank_account = merchant.and_then(|merchant| merchant.bank_account);
+
+    let (changes, payout_return) = match return_payout(&notice, &mut disbursement, bank_account.as_ref(), Utc::now()) {
+        Ok(returned) => returned,
+        Err(err) => return Ok(ReturnLine { notice, status: ReturnStatus::Refused, reason: Some(err.to_string()) }),
+    };
+
+    for change in &changes {
+        if !save_status_change(conn, &disbursement, change).await? {
+            bail!("Disbursement {} changed while it was returned", disbursement.reference);
+        }
+    }
+    insert_payout_return(conn, &payout_return).await?;
+    insert_journal_entries(conn, &[payout_return_entry(&payout_return)]).await?;
+
+    Ok(ReturnLine { notice, status: ReturnStatus::Returned, reason: None })
+}
@@ -0,0 +1,344 @@
+use anyhow::Result;
+use apalis::prelude::Job;
+use chrono::{NaiveDate, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use crate::entities::disbursements::{Disbursement, LineKind};
+use crate::entities::merchants::{Merchant, MonthlyFeeMode};
+use crate::entities::monthly_fees::MonthlyFee;
+use crate::entities::vat::VatCharge;
+use crate::repositories::carried_balances::{find_carried_balances_on, insert_carried_balance, settle_carried_balances};
+use crate::repositories::disbursement_batches::{assign_disbursements_to_batch, find_open_batch, insert_batch};
+use crate::repositories::disbursements::{find_disbursements_on, find_sales_period, insert_disbursement, insert_disbursement_status_change};
+use crate::repositories::fx_rates::find_fx_rates_until;
+use crate::repositories::pricing_plans::find_pricing_plans;
+use crate::repositories::ledger::{ensure_entries_balanced, insert_journal_entries};
+use crate::repositories::merchants::find_live_merchants;
+use crate::repositories::order_adjustments::find_adjustments_to_disburse;
+use crate::repositories::outbox::insert_outbox_event;
+use crate::repositories::payout_returns::{find_held_merchant_references, find_returns_to_redisburse, mark_returns_redisbursed};
+use crate::repositories::monthly_fees::{
+    find_monthly_fee, find_monthly_fees_calculated_on, find_monthly_fees_to_deduct, insert_monthly_fee, mark_monthly_fees_deducted,
+};
+use crate::repositories::orders::{find_orders_created_between, find_orders_to_disburse};
+use crate::repositories::vat::find_vat_rules;
+use crate::repositories::receivables::{
+    find_open_receivables, find_receivables_opened_on, insert_receivable, settle_receivables,
+};
+use crate::repositories::reserves::{
+    find_releasable_reserves, find_reserve_policies, find_reserves_held_on, insert_reserve, mark_reserves_released,
+};
+use crate::services::disbursement_calculator::{
+    build_disbursement, build_monthly_fee, carry_forward, deduct_monthly_fees, empty_disbursement, first_day_of_month,
+    is_payout_day, minimum_payout, must_flush, previous_month, DisbursementRun,
+};
+use crate::services::calendar::BusinessCalendar;
+use crate::services::disbursement_batches::new_batch;
+use crate::services::disbursement_status::calculated;
+use crate::services::fx::FxRates;
+use crate::services::outbox::{disbursement_created, monthly_fee_calculated};
+use crate::services::payout_returns::redisburse_returns;
+use crate::services::pricing::PricingPlans;
+use crate::services::receivables::{
+    exceeds_negative_balance_limit, negative_balance_limit, offset_receivables, open_receivable,
+};
+use crate::services::reserves::{hold_reserve, release_reserves};
+use crate::services::vat::{charge_monthly_fee_vat, charge_vat, VatRules};
+use crate::services::time_zones::start_of_day;
+use crate::settings::disbursements::DisbursementSettings;
+use crate::services::ledger::{disbursement_entries, monthly_fee_entry};
+use crate::services::run_diff::{diff_runs, RunDiff};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunMode {
+    Commit,
+    // calculates everything inside a transaction that is rolled back, and compares it with what is persisted
+    Preview,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProcessDisbursementsJob {
+    pub date: NaiveDate,
+    pub mode: RunMode,
+}
+
+impl Job for ProcessDisbursementsJob {
+    const NAME: &'static str = "process-disbursements";
+}
+
+#[derive(Debug)]
+pub enum RunOutcome {
+    // only what was persisted by this run, merchants already processed for the date are skipped
+    Committed(DisbursementRun),
+    Previewed(RunDiff),
+}
+
+pub async fn process_disbursements_handler(
+    job: ProcessDisbursementsJob,
+    pool: &PgPool,
+    calendar: &BusinessCalendar,
+    settings: &DisbursementSettings,
+) -> Result<RunOutcome> {
+    let mut tx = pool.begin().await?;
+
+    let persisted = find_persisted_run(&mut tx, job.date).await?;
+    let computed = calculate_run(&mut tx, job.date, calendar, settings).await?;
+
+    match job.mode {
+        RunMode::Preview => {
+            tx.rollback().await?;
+            Ok(RunOutcome::Previewed(diff_runs(job.date, &persisted, &computed)))
+        }
+        RunMode::Commit => {
+            let (committed, booked) = persist_run(&mut tx, &persisted, computed).await?;
+            batch_disbursements(&mut tx, job.date, &committed.disbursements).await?;
+            // an unbalanced entry rolls the whole run back
+            ensure_entries_balanced(&mut tx, &booked).await?;
+            tx.commit().await?;
+            Ok(RunOutcome::Committed(committed))
+        }
+    }
+}
+
+pub async fn calculate_run(
+    conn: &mut PgConnection,
+    date: NaiveDate,
+    calendar: &BusinessCalendar,
+    settings: &DisbursementSettings,
+) -> Result<DisbursementRun> {
+    let merchants = find_live_merchants(conn, date).await?;
+    // the whole run converts with the rates effective on its date
+    let rates = FxRates::new(find_fx_rates_until(conn, date).await?);
+    let pricing = PricingPlans::new(find_pricing_plans(conn).await?);
+    let reserve_policies = find_reserve_policies(conn).await?;
+    let vat_rules = VatRules::new(find_vat_rules(conn).await?, settings.vat_home_country.clone());
+    let held = find_held_merchant_references(conn, date).await?;
+    let mut run = DisbursementRun::default();
+
+    // nobody is paid on a non business day, their disbursements are rolled to the next (or previous) one
+    for merchant in merchants.iter().filter(|merchant| is_payout_day(merchant, calendar, date)) {
+        // a merchant without a VAT rule for its country is reported, it doesn't stop the other payouts
+        let vat = match vat_rules.charge_for(merchant) {
+            Ok(vat) => vat,
+            Err(err) => {
+                eprintln!("Skipping merchant {}: {:#}", merchant.merchant_reference, err);
+                run.skipped_merchants.push(merchant.merchant_reference.clone());
+                continue;
+            }
+        };
+        // the first disbursement day of the month checks the minimum monthly fee, deducted right away in deduct mode
+        let monthly_fee = calculate_monthly_fee(conn, merchant, &pricing, &rates, vat, date).await?;
+        // no disbursement is created for the merchants whose payouts are held, their orders (and a fee to deduct)
+        // wait for their new bank details
+        if held.contains(&merchant.merchant_reference) {
+            run.monthly_fees.extend(monthly_fee);
+            continue;
+        }
+        // "yesterday's sales" are the ones of the merchant local day, not the UTC one
+        let created_before = start_of_day(date, merchant.time_zone);
+        let orders = find_orders_to_disburse(conn, &merchant.merchant_reference, created_before, date).await?;
+        let adjustments = find_adjustments_to_disburse(conn, &merchant.merchant_reference, date).await?;
+        let mut monthly_fees_to_deduct = find_monthly_fees_to_deduct(conn, &merchant.merchant_reference, date).await?;
+        monthly_fees_to_deduct.extend(
+            monthly_fee.iter().filter(|fee| fee.mode == MonthlyFeeMode::Deduct && fee.amount > 0).cloned(),
+        );
+        let releasable = find_releasable_reserves(conn, &merchant.merchant_reference, date).await?;
+        let returned = find_returns_to_redisburse(conn, &merchant.merchant_reference, date).await?;
+        let disbursement = build_disbursement(merchant, &orders, &adjustments, &pricing, &rates, date)?
+            .or_else(|| (!releasable.is_empty() || !returned.is_empty()).then(|| empty_disbursement(merchant, date)));
+
+        if let Some(mut disbursement) = disbursement {
+            charge_vat(&mut disbursement, vat);
+            release_reserves(&mut disbursement, &releasable, &rates)?;
+            redisburse_returns(&mut disbursement, &returned, &rates)?;
+            let reserve = reserve_policies
+                .iter()
+                .find(|policy| policy.merchant_reference == merchant.merchant_reference)
+                .and_then(|policy| hold_reserve(&mut disbursement, policy));
+            deduct_monthly_fees(&mut disbursement, &monthly_fees_to_deduct, &rates)?;
+            let receivables = find_open_receivables(conn, &merchant.merchant_reference, date).await?;
+            offset_receivables(&mut disbursement, &receivables, &rates)?;
+
+            let minimum_payout = minimum_payout(merchant, settings.minimum_payout, &rates, date)?;
+            let limit = negative_balance_limit(merchant, settings.negative_balance_limit, &rates, date)?;
+            let over_limit = exceeds_negative_balance_limit(&disbursement, limit);
+            match carry_forward(&disbursement, minimum_payout) {
+                // nothing is held, released, deducted nor offset when the disbursement is not created,
+                // and a negative balance is carried until the end of the month unless it exceeds the limit
+                Some(carried_balance) if !must_flush(merchant, calendar, date) && !over_limit => {
+                    run.carried_balances.push(carried_balance)
+                }
+                _ => {
+                    run.receivables.extend(open_receivable(&mut disbursement, over_limit));
+                    run.reserves.extend(reserve);
+                    run.disbursements.push(disbursement);
+                }
+            }
+        }
+
+        run.monthly_fees.extend(monthly_fee);
+    }
+
+    Ok(run)
+}
+
+// The minimum monthly fee of the previous month is checked on the first disbursement day of the month,
+// a fee stored by an earlier date means it was already checked.
+async fn calculate_monthly_fee(
+    conn: &mut PgConnection,
+    merchant: &Merchant,
+    pricing: &PricingPlans,
+    rates: &FxRates,
+    vat: Option<VatCharge>,
+    date: NaiveDate,
+) -> Result<Option<MonthlyFee>> {
+    let month = previous_month(date);
+
+    let existing = find_monthly_fee(conn, &merchant.merchant_reference, month).await?;
+    if existing.as_ref().is_some_and(|existing| existing.calculated_on != date) {
+        return Ok(None);
+    }
+
+    // the month starts and ends at midnight in the time zone of the merchant
+    let orders = find_orders_created_between(
+        conn,
+        &merchant.merchant_reference,
+        start_of_day(month, merchant.time_zone),
+        start_of_day(first_day_of_month(date), merchant.time_zone),
+    )
+    .await?;
+
+    // recalculating a date keeps the id of the fee stored by it, the lines deducting it point to it
+    let Some(mut monthly_fee) = build_monthly_fee(merchant, &orders, pricing, rates, date)? else {
+        return Ok(None);
+    };
+    charge_monthly_fee_vat(&mut monthly_fee, vat);
+    Ok(Some(MonthlyFee { id: existing.map_or(monthly_fee.id, |existing| existing.id), ..monthly_fee }))
+}
+
+async fn find_persisted_run(conn: &mut PgConnection, date: NaiveDate) -> Result<DisbursementRun> {
+    Ok(DisbursementRun {
+        disbursements: find_disbursements_on(conn, date).await?,
+        monthly_fees: find_monthly_fees_calculated_on(conn, date).await?,
+        carried_balances: find_carried_balances_on(conn, date).await?,
+        reserves: find_reserves_held_on(conn, date).await?,
+        receivables: find_receivables_opened_on(conn, date).await?,
+        skipped_merchants: Vec::new(),
+    })
+}
+
+// The new disbursements of the run wait in the open batch of the date for their approval, a new batch
+// when the one of an earlier run of the date already got an approval.
+async fn batch_disbursements(conn: &mut PgConnection, date: NaiveDate, disbursements: &[Disbursement]) -> Result<()> {
+    if disbursements.is_empty() {
+        return Ok(());
+    }
+
+    let batch = match find_open_batch(conn, date).await? {
+        Some(batch) => batch,
+        None => {
+            let batch = new_batch(date, Utc::now());
+            insert_batch(conn, &batch).await?;
+            batch
+        }
+    };
+
+    let ids: Vec<Uuid> = disbursements.iter().map(|disbursement| disbursement.id).collect();
+    assign_disbursements_to_batch(conn, batch.id, &ids).await?;
+
+    Ok(())
+}
+
+// What the run persisted, with the journal entries it booked.
+async fn persist_run(conn: &mut PgConnection, persisted: &DisbursementRun, computed: DisbursementRun) -> Result<(DisbursementRun, Vec<Uuid>)> {
+    let mut committed = DisbursementRun::default();
+    let mut booked = Vec::new();
+
+    // the monthly fees go first, the disbursements deducting them point to them
+    for monthly_fee in computed.monthly_fees {
+        let already_calculated = persisted
+            .monthly_fees
+            .iter()
+            .any(|existing| existing.merchant_reference == monthly_fee.merchant_reference);
+
+        if !already_calculated {
+            insert_monthly_fee(conn, &monthly_fee).await?;
+            booked.extend(insert_journal_entries(conn, &[monthly_fee_entry(&monthly_fee)]).await?);
+            insert_outbox_event(conn, &monthly_fee_calculated(&monthly_fee, Utc::now())).await?;
+            committed.monthly_fees.push(monthly_fee);
+        }
+    }
+
+    for disbursement in computed.disbursements {
+        let already_disbursed = persisted
+            .disbursements
+            .iter()
+            .any(|existing| existing.merchant_reference == disbursement.merchant_reference);
+
+        if !already_disbursed {
+            insert_disbursement(conn, &disbursement).await?;
+            insert_disbursement_status_change(conn, &calculated(&disbursement, Utc::now())).await?;
+            settle_carried_balances(conn, &disbursement.merchant_reference, disbursement.id).await?;
+
+            let released: Vec<Uuid> = disbursement
+                .lines
+                .iter()
+                .filter(|line| line.kind == LineKind::ReserveRelease)
+                .filter_map(|line| line.reserve_id)
+                .collect();
+            mark_reserves_released(conn, &released, disbursement.id).await?;
+            for reserve in computed.reserves.iter().filter(|reserve| reserve.disbursement_id == disbursement.id) {
+                insert_reserve(conn, reserve).await?;
+                committed.reserves.push(reserve.clone());
+            }
+
+            let offset: Vec<Uuid> = disbursement
+                .lines
+                .iter()
+                .filter(|line| line.kind == LineKind::ReceivableOffset)
+                .filter_map(|line| line.receivable_id)
+                .collect();
+            settle_receivables(conn, &offset, disbursement.id).await?;
+            for receivable in computed.receivables.iter().filter(|receivable| receivable.disbursement_id == disbursement.id) {
+                insert_receivable(conn, receivable).await?;
+                committed.receivables.push(receivable.clone());
+            }
+
+            let deducted: Vec<Uuid> = disbursement
+                .lines
+                .iter()
+                .filter(|line| line.kind == LineKind::MonthlyFee)
+                .filter_map(|line| line.monthly_fee_id)
+                .collect();
+            mark_monthly_fees_deducted(conn, &deducted, disbursement.id).await?;
+
+            let redisbursed: Vec<Uuid> = disbursement
+                .lines
+                .iter()
+                .filter(|line| line.kind == LineKind::ReturnedPayout)
+                .filter_map(|line| line.payout_return_id)
+                .collect();
+            mark_returns_redisbursed(conn, &redisbursed, disbursement.id).await?;
+
+            booked.extend(insert_journal_entries(conn, &disbursement_entries(&disbursement)).await?);
+            let sales_period = find_sales_period(conn, disbursement.id).await?;
+            insert_outbox_event(conn, &disbursement_created(&disbursement, sales_period, Utc::now())).await?;
+            committed.disbursements.push(disbursement);
+        }
+    }
+
+    for carried_balance in computed.carried_balances {
+        let already_carried = persisted
+            .carried_balances
+            .iter()
+            .any(|existing| existing.merchant_reference == carried_balance.merchant_reference);
+
+        if !already_carried {
+            insert_carried_balance(conn, &carried_balance).await?;
+            committed.carried_balances.push(carried_balance);
+        }
+    }
+    committed.skipped_merchants = computed.skipped_merchants;
+
+    Ok((committed, booked))
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use apalis::prelude::Job;
+use chrono::Utc;
+use sqlx::PgPool;
+use crate::events::publisher::EventPublisher;
+use crate::repositories::outbox::{find_unpublished_events, lock_relay, mark_events_published};
+
+#[derive(Clone, Debug)]
+pub struct PublishEventsJob {
+    // how many events are published per transaction
+    pub limit: i64,
+}
+
+impl Job for PublishEventsJob {
+    const NAME: &'static str = "publish-events";
+}
+
+// At least once: the events are marked published after the publisher took them, a crash in between publishes them again.
+// The first failure stops the batch so no event is published before an older one, the published ones are kept.
+pub async fn publish_events_handler<P: EventPublisher>(job: PublishEventsJob, pool: &PgPool, publisher: &P) -> Result<usize> {
+    let mut tx = pool.begin().await?;
+
+    lock_relay(&mut tx).await?;
+    let events = find_unpublished_events(&mut tx, job.limit).await?;
+
+    let mut published = Vec::with_capacity(events.len());
+    let mut failure = None;
+    for event in events {
+        match publisher.publish(&event.topic, event.payload).await {
+            Ok(()) => published.push(event.id),
+            Err(err) => {
+                failure = Some(err.context(format!("Publishing event {} to {}", event.id, event.topic)));
+                break;
+            }
+        }
+    }
+
+    mark_events_published(&mut tx, &published, Utc::now()).await?;
+    tx.commit().await?;
+
+    match failure {
+        Some(err) => Err(err),
+        None => Ok(published.len()),
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::{anyhow, bail, Result};
+use apalis::prelude::Job;
+use chrono::Utc;
+use sqlx::PgPool;
+use crate::entities::disbursements::DisbursementStatus;
+use crate::entities::reconciliation::{ReconciliationReport, ReconciliationStatus};
+use crate::jobs::disbursement_status::save_status_change;
+use crate::repositories::disbursements::{find_disbursements_by_reference, mark_disbursement_settled};
+use crate::services::camt_053::parse_camt_053;
+use crate::services::disbursement_status::{change_status, SYSTEM_ACTOR};
+use crate::services::reconciliation::reconcile;
+use crate::services::reconciliation_csv::to_reconciliation_csv;
+
+#[derive(Clone, Debug)]
+pub struct ReconcileStatementJob {
+    // the camt.053 file of the bank
+    pub statement_path: String,
+    // where the report is written
+    pub output_path: String,
+}
+
+impl Job for ReconcileStatementJob {
+    const NAME: &'static str = "reconcile-bank-statement";
+}
+
+// The matched disbursements are settled in the same transaction, so reconciling the same file again
+// reports them as already matched instead of settling them twice. A payout in the statement was sent,
+// so the exported ones go through SENT first.
+pub async fn reconcile_statement_handler(job: ReconcileStatementJob, pool: &PgPool) -> Result<ReconciliationReport> {
+    let statements = parse_camt_053(&std::fs::read_to_string(&job.statement_path)?)?;
+    let references: Vec<String> = statements
+        .iter()
+        .flat_map(|statement| statement.entries.iter())
+        .filter_map(|entry| entry.reference.clone())
+        .collect();
+
+    let mut tx = pool.begin().await?;
+
+    let mut disbursements = find_disbursements_by_reference(&mut tx, &references).await?;
+    let report = reconcile(&statements, &disbursements);
+
+    for line in report.lines.iter().filter(|line| line.status == ReconciliationStatus::Matched) {
+        let disbursement = disbursements
+            .iter_mut()
+            .find(|disbursement| Some(disbursement.id) == line.disbursement_id)
+            .ok_or_else(|| anyhow!("Matched a disbursement that was not loaded: {:?}", line.disbursement_id))?;
+        let note = Some(format!("Booked on {} ({})", line.entry.booked_on, line.entry.bank_reference.as_deref().unwrap_or("no bank reference")));
+
+        let mut changes = Vec::new();
+        if disbursement.status == DisbursementStatus::Exported {
+            changes.push(change_status(disbursement, DisbursementStatus::Sent, SYSTEM_ACTOR, None, Utc::now())?);
+        }
+        changes.push(change_status(disbursement, DisbursementStatus::Settled, SYSTEM_ACTOR, note, Utc::now())?);
+
+        for change in &changes {
+            if !save_status_change(&mut tx, disbursement, change).await? {
+                bail!("Disbursement {} changed while it was reconciled", disbursement.reference);
+            }
+        }
+        mark_disbursement_settled(&mut tx, disbursement.id, line.entry.booked_on, line.entry.bank_reference.as_deref()).await?;
+    }
+
+    // the report is written once the settlements it reports are committed
+    tx.commit().await?;
+    std::fs::write(&job.output_path, to_reconciliation_csv(&report)?)?;
+
+    Ok(report)
+}
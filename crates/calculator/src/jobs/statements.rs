@@ -0,0 +1,49 @@
+use anyhow::{anyhow, Result};
+use chrono::{Days, NaiveDate};
+use sqlx::PgPool;
+use strum_macros::{Display, EnumString};
+use crate::entities::statements::Statement;
+use crate::repositories::disbursements::find_merchant_disbursements;
+use crate::repositories::ledger::find_merchant_balances_on;
+use crate::repositories::merchants::find_merchants_by_reference;
+use crate::repositories::monthly_fees::find_monthly_fee;
+use crate::services::disbursement_calculator::last_day_of_month;
+use crate::services::statement_csv::to_statement_csv;
+use crate::services::statement_html::to_statement_html;
+use crate::services::statements::build_statement;
+
+#[derive(Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatementFormat {
+    #[strum(to_string = "csv")]
+    Csv,
+    #[strum(to_string = "html")]
+    Html,
+}
+
+// `month` is the first day of the month. Only reads, a statement can be generated again at any time.
+pub async fn statement_handler(pool: &PgPool, merchant_reference: &str, month: NaiveDate) -> Result<Statement> {
+    let mut conn = pool.acquire().await?;
+
+    let merchant = find_merchants_by_reference(&mut conn, &[merchant_reference.to_string()])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Unknown merchant {}", merchant_reference))?;
+
+    let month_end = last_day_of_month(month);
+    let disbursements = find_merchant_disbursements(&mut conn, merchant_reference, month, month_end).await?;
+    let monthly_fee = find_monthly_fee(&mut conn, merchant_reference, month).await?;
+    let opening = find_merchant_balances_on(&mut conn, merchant_reference, month - Days::new(1)).await?;
+    let closing = find_merchant_balances_on(&mut conn, merchant_reference, month_end).await?;
+
+    Ok(build_statement(&merchant, month, disbursements, monthly_fee, &opening, &closing))
+}
+
+pub async fn render_statement_handler(pool: &PgPool, merchant_reference: &str, month: NaiveDate, format: StatementFormat) -> Result<String> {
+    let statement = statement_handler(pool, merchant_reference, month).await?;
+
+    match format {
+        StatementFormat::Csv => to_statement_csv(&statement),
+        StatementFormat::Html => to_statement_html(&statement),
+    }
+}
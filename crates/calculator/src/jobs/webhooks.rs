@@ -0,0 +1,102 @@
+use anyhow::{anyhow, bail, Result};
+use apalis::prelude::Job;
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::entities::webhooks::{DeliveryStatus, WebhookDelivery, WebhookEndpoint};
+use crate::repositories::merchants::find_merchants_by_reference;
+use crate::repositories::webhooks::{find_due_delivery, insert_webhook_endpoint, lock_delivery, save_delivery, save_delivery_attempt};
+use crate::services::webhook_client::WebhookClient;
+use crate::services::webhooks::{new_endpoint, record_attempt, redeliver};
+
+#[derive(Clone, Debug)]
+pub struct DeliverWebhooksJob {
+    // how many deliveries are sent per run at most
+    pub limit: i64,
+}
+
+impl Job for DeliverWebhooksJob {
+    const NAME: &'static str = "deliver-webhooks";
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryReport {
+    pub delivered: usize,
+    // failed attempts with attempts left
+    pub retrying: usize,
+    pub failed: usize,
+}
+
+impl DeliveryReport {
+    pub fn sent(&self) -> usize {
+        self.delivered + self.retrying + self.failed
+    }
+}
+
+// Only the merchants the calculator knows can register an endpoint. The secret is in the endpoint returned,
+// it's the only time it's shown. `allow_local` is for development only (see `new_endpoint`).
+pub async fn register_webhook_endpoint_handler(
+    pool: &PgPool,
+    merchant_reference: &str,
+    url: &str,
+    topics: &[String],
+    allow_local: bool,
+) -> Result<WebhookEndpoint> {
+    let mut tx = pool.begin().await?;
+
+    if find_merchants_by_reference(&mut tx, &[merchant_reference.to_string()]).await?.is_empty() {
+        bail!("Unknown merchant {}", merchant_reference);
+    }
+
+    let endpoint = new_endpoint(merchant_reference, url, topics, allow_local, Utc::now())?;
+    insert_webhook_endpoint(&mut tx, &endpoint).await?;
+
+    tx.commit().await?;
+
+    Ok(endpoint)
+}
+
+// Each delivery is sent and saved in its own transaction, only its row is locked while the endpoint answers.
+// At least once: a crash between the answer and the commit sends it again, the merchants dedupe by event_id.
+pub async fn deliver_webhooks_handler(job: DeliverWebhooksJob, pool: &PgPool, client: &WebhookClient) -> Result<DeliveryReport> {
+    let mut report = DeliveryReport::default();
+
+    while (report.sent() as i64) < job.limit {
+        let mut tx = pool.begin().await?;
+
+        let Some((mut delivery, endpoint)) = find_due_delivery(&mut tx, Utc::now()).await? else {
+            break;
+        };
+
+        let attempted_at = Utc::now();
+        let outcome = client.send(&endpoint, &delivery, attempted_at).await;
+        let attempt = record_attempt(&mut delivery, &outcome, attempted_at);
+        save_delivery_attempt(&mut tx, &delivery, &attempt).await?;
+
+        tx.commit().await?;
+
+        match delivery.status {
+            DeliveryStatus::Delivered => report.delivered += 1,
+            DeliveryStatus::Pending => report.retrying += 1,
+            DeliveryStatus::Failed => report.failed += 1,
+        }
+    }
+
+    Ok(report)
+}
+
+// Queues it again for the next run of the deliveries, with all its attempts.
+pub async fn redeliver_webhook_handler(pool: &PgPool, delivery_id: Uuid) -> Result<WebhookDelivery> {
+    let mut tx = pool.begin().await?;
+
+    let mut delivery = lock_delivery(&mut tx, delivery_id)
+        .await?
+        .ok_or_else(|| anyhow!("Unknown delivery {}", delivery_id))?;
+
+    redeliver(&mut delivery, Utc::now())?;
+    save_delivery(&mut tx, &delivery).await?;
+
+    tx.commit().await?;
+
+    Ok(delivery)
+}
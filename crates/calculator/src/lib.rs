@@ -1,4 +1,7 @@
 pub mod entities;
 pub mod events;
 pub mod settings;
-pub mod repositories;
\ No newline at end of file
+pub mod repositories;
+pub mod services;
+pub mod jobs;
+pub mod api;
@@ -1 +1,18 @@
 pub mod merchants;
+pub mod orders;
+pub mod order_adjustments;
+pub mod disbursements;
+pub mod disbursement_batches;
+pub mod payout_returns;
+pub mod monthly_fees;
+pub mod ledger;
+pub mod fx_rates;
+pub mod pricing_plans;
+pub mod carried_balances;
+pub mod reserves;
+pub mod receivables;
+pub mod invoices;
+pub mod vat;
+pub mod outbox;
+pub mod webhooks;
+pub mod payout_files;
@@ -0,0 +1,72 @@
+use crate::entities::carried_balances::CarriedBalance;
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+pub async fn insert_carried_balance(conn: &mut PgConnection, carried_balance: &CarriedBalance) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO carried_balances (merchant_reference, carried_on, currency, net_amount, minimum_payout) \
+         VALUES ($1, $2, $3, $4, $5)"
+    )
+    .bind(&carried_balance.merchant_reference)
+    .bind(carried_balance.carried_on)
+    .bind(&carried_balance.currency)
+    .bind(carried_balance.net_amount)
+    .bind(carried_balance.minimum_payout)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// The balances carried so far are paid by this disbursement, as it takes every pending order of the merchant.
+pub async fn settle_carried_balances(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    disbursement_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE carried_balances SET disbursement_id = $1 \
+         WHERE merchant_reference = $2 AND disbursement_id IS NULL"
+    )
+    .bind(disbursement_id)
+    .bind(merchant_reference)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// The balances the voided disbursement paid are carried again, its orders are pending again.
+pub async fn reopen_carried_balances_settled_by(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE carried_balances SET disbursement_id = NULL WHERE disbursement_id = $1")
+        .bind(disbursement_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn find_carried_balances_on(conn: &mut PgConnection, date: NaiveDate) -> Result<Vec<CarriedBalance>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT merchant_reference, carried_on, currency, net_amount, minimum_payout, disbursement_id \
+         FROM carried_balances WHERE carried_on = $1 ORDER BY merchant_reference"
+    )
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(carried_balance_from_row).collect()
+}
+
+fn carried_balance_from_row(row: &PgRow) -> Result<CarriedBalance, sqlx::Error> {
+    Ok(CarriedBalance {
+        merchant_reference: row.try_get("merchant_reference")?,
+        carried_on: row.try_get("carried_on")?,
+        currency: row.try_get("currency")?,
+        net_amount: row.try_get("net_amount")?,
+        minimum_payout: row.try_get("minimum_payout")?,
+        disbursement_id: row.try_get("disbursement_id")?,
+    })
+}
@@ -0,0 +1,201 @@
+use crate::entities::disbursement_batches::{BatchApproval, BatchStatus, BatchTotal, DisbursementBatch};
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+pub async fn insert_batch(conn: &mut PgConnection, batch: &DisbursementBatch) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO disbursement_batches (id, disbursed_on, status, created_at) VALUES ($1, $2, $3, $4)")
+        .bind(batch.id)
+        .bind(batch.disbursed_on)
+        .bind(batch.status.to_string())
+        .bind(batch.created_at)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// The batch of the date nobody approved yet, the one a run adds its disbursements to.
+pub async fn find_open_batch(conn: &mut PgConnection, disbursed_on: NaiveDate) -> Result<Option<DisbursementBatch>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT id, disbursed_on, status, created_at, approved_at FROM disbursement_batches \
+         WHERE disbursed_on = $1 AND status = 'OPEN' ORDER BY created_at LIMIT 1"
+    )
+    .bind(disbursed_on)
+    .fetch_optional(conn)
+    .await?;
+
+    row.as_ref().map(batch_from_row).transpose()
+}
+
+pub async fn assign_disbursements_to_batch(conn: &mut PgConnection, batch_id: Uuid, disbursement_ids: &[Uuid]) -> Result<(), sqlx::Error> {
+    if disbursement_ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("UPDATE disbursements SET batch_id = $1 WHERE id = ANY($2) AND batch_id IS NULL")
+        .bind(batch_id)
+        .bind(disbursement_ids)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn find_batches_on(conn: &mut PgConnection, disbursed_on: NaiveDate) -> Result<Vec<DisbursementBatch>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, disbursed_on, status, created_at, approved_at FROM disbursement_batches \
+         WHERE disbursed_on = $1 ORDER BY created_at"
+    )
+    .bind(disbursed_on)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut batches = rows.iter().map(batch_from_row).collect::<Result<Vec<_>, _>>()?;
+    for batch in &mut batches {
+        load_approvals_and_totals(conn, batch).await?;
+    }
+
+    Ok(batches)
+}
+
+// Locked until the transaction ends, so two approvals of the same batch can't interleave.
+pub async fn lock_batch(conn: &mut PgConnection, id: Uuid) -> Result<Option<DisbursementBatch>, sqlx::Error> {
+    let row = sqlx::query("SELECT id, disbursed_on, status, created_at, approved_at FROM disbursement_batches WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let mut batch = batch_from_row(&row)?;
+    load_approvals_and_totals(conn, &mut batch).await?;
+
+    Ok(Some(batch))
+}
+
+// The totals and the disbursements are stored with the first approval, the ones frozen for the second one.
+pub async fn save_batch_approval(conn: &mut PgConnection, batch: &DisbursementBatch, approval: &BatchApproval) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO disbursement_batch_approvals (batch_id, approver, approved_at) VALUES ($1, $2, $3)")
+        .bind(batch.id)
+        .bind(&approval.approver)
+        .bind(approval.approved_at)
+        .execute(&mut *conn)
+        .await?;
+
+    if batch.status == BatchStatus::PartiallyApproved && !batch.totals.is_empty() {
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "INSERT INTO disbursement_batch_totals (batch_id, currency, disbursement_count, net_amount) "
+        );
+
+        query_builder.push_values(&batch.totals, |mut b, total| {
+            b.push_bind(batch.id)
+                .push_bind(&total.currency)
+                .push_bind(total.disbursement_count)
+                .push_bind(total.net_amount);
+        });
+
+        query_builder.build().execute(&mut *conn).await?;
+
+        sqlx::query(
+            "INSERT INTO disbursement_batch_disbursements (batch_id, disbursement_id) SELECT $1, UNNEST($2::UUID[])"
+        )
+        .bind(batch.id)
+        .bind(&batch.disbursement_ids)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    sqlx::query("UPDATE disbursement_batches SET status = $2, approved_at = $3 WHERE id = $1")
+        .bind(batch.id)
+        .bind(batch.status.to_string())
+        .bind(batch.approved_at)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// The approvals withdrawn (see services::disbursement_batches::withdraw_approvals) are forgotten with what they froze.
+pub async fn reset_batch_approvals(conn: &mut PgConnection, batch: &DisbursementBatch) -> Result<(), sqlx::Error> {
+    for table in ["disbursement_batch_disbursements", "disbursement_batch_totals", "disbursement_batch_approvals"] {
+        sqlx::query(&format!("DELETE FROM {} WHERE batch_id = $1", table))
+            .bind(batch.id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    sqlx::query("UPDATE disbursement_batches SET status = $2 WHERE id = $1")
+        .bind(batch.id)
+        .bind(batch.status.to_string())
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn find_batch_id_of_disbursement(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+    let row = sqlx::query("SELECT batch_id FROM disbursements WHERE id = $1")
+        .bind(disbursement_id)
+        .fetch_optional(conn)
+        .await?;
+
+    Ok(row.map(|row| row.try_get("batch_id")).transpose()?.flatten())
+}
+
+async fn load_approvals_and_totals(conn: &mut PgConnection, batch: &mut DisbursementBatch) -> Result<(), sqlx::Error> {
+    let rows = sqlx::query("SELECT approver, approved_at FROM disbursement_batch_approvals WHERE batch_id = $1 ORDER BY approved_at")
+        .bind(batch.id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    batch.approvals = rows
+        .iter()
+        .map(|row| Ok(BatchApproval { approver: row.try_get("approver")?, approved_at: row.try_get("approved_at")? }))
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+    let rows = sqlx::query(
+        "SELECT currency, disbursement_count, net_amount FROM disbursement_batch_totals WHERE batch_id = $1 ORDER BY currency"
+    )
+    .bind(batch.id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    batch.totals = rows
+        .iter()
+        .map(|row| {
+            Ok(BatchTotal {
+                currency: row.try_get("currency")?,
+                disbursement_count: row.try_get("disbursement_count")?,
+                net_amount: row.try_get("net_amount")?,
+            })
+        })
+        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+    let rows = sqlx::query("SELECT disbursement_id FROM disbursement_batch_disbursements WHERE batch_id = $1 ORDER BY disbursement_id")
+        .bind(batch.id)
+        .fetch_all(conn)
+        .await?;
+
+    batch.disbursement_ids = rows.iter().map(|row| row.try_get("disbursement_id")).collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+    Ok(())
+}
+
+fn batch_from_row(row: &PgRow) -> Result<DisbursementBatch, sqlx::Error> {
+    let status: String = row.try_get("status")?;
+
+    Ok(DisbursementBatch {
+        id: row.try_get("id")?,
+        disbursed_on: row.try_get("disbursed_on")?,
+        status: status.parse::<BatchStatus>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        totals: Vec::new(),
+        disbursement_ids: Vec::new(),
+        approvals: Vec::new(),
+        created_at: row.try_get("created_at")?,
+        approved_at: row.try_get("approved_at")?,
+    })
+}
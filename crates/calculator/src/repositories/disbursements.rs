@@ -0,0 +1,362 @@
+use crate::entities::disbursements::{Disbursement, DisbursementLine, DisbursementStatus, DisbursementStatusChange, LineKind};
+use crate::repositories::vat::vat_charge_from_row;
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+// The unique order_id of the ORDER lines (and adjustment_id of the ADJUSTMENT ones, reserve_id of the RESERVE_RELEASE ones,
+// receivable_id of the RECEIVABLE_OFFSET ones, monthly_fee_id of the MONTHLY_FEE ones and payout_return_id of the RETURNED_PAYOUT ones)
+// in disbursement_lines
+// is what guarantees that an order is disbursed precisely once.
+pub async fn insert_disbursement(conn: &mut PgConnection, disbursement: &Disbursement) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO disbursements (id, reference, merchant_reference, disbursed_on, currency, gross_amount, fee_amount, vat_treatment, vat_rate, fee_vat_amount, fee_gross_amount, net_amount, status) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)"
+    )
+    .bind(disbursement.id)
+    .bind(&disbursement.reference)
+    .bind(&disbursement.merchant_reference)
+    .bind(disbursement.disbursed_on)
+    .bind(&disbursement.currency)
+    .bind(disbursement.gross_amount)
+    .bind(disbursement.fee_amount)
+    .bind(disbursement.vat.map(|vat| vat.treatment.to_string()))
+    .bind(disbursement.vat.map(|vat| vat.rate))
+    .bind(disbursement.fee_vat_amount)
+    .bind(disbursement.fee_gross_amount)
+    .bind(disbursement.net_amount)
+    .bind(disbursement.status.to_string())
+    .execute(&mut *conn)
+    .await?;
+
+    if disbursement.lines.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO disbursement_lines \
+         (disbursement_id, kind, order_id, adjustment_id, reserve_id, receivable_id, monthly_fee_id, payout_return_id, amount, original_amount, original_currency, fx_rate, fee_rate, pricing_version, fixed_fee, fee_amount) "
+    );
+
+    query_builder.push_values(&disbursement.lines, |mut b, line| {
+        b.push_bind(disbursement.id)
+            .push_bind(line.kind.to_string())
+            .push_bind(&line.order_id)
+            .push_bind(line.adjustment_id)
+            .push_bind(line.reserve_id)
+            .push_bind(line.receivable_id)
+            .push_bind(line.monthly_fee_id)
+            .push_bind(line.payout_return_id)
+            .push_bind(line.amount)
+            .push_bind(line.original_amount)
+            .push_bind(&line.original_currency)
+            .push_bind(line.fx_rate)
+            .push_bind(line.fee_rate)
+            .push_bind(&line.pricing_version)
+            .push_bind(line.fixed_fee)
+            .push_bind(line.fee_amount);
+    });
+
+    query_builder.build().execute(&mut *conn).await?;
+
+    Ok(())
+}
+
+pub async fn find_disbursements_on(conn: &mut PgConnection, date: NaiveDate) -> Result<Vec<Disbursement>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, reference, merchant_reference, disbursed_on, currency, gross_amount, fee_amount, vat_treatment, vat_rate, fee_vat_amount, fee_gross_amount, net_amount, status \
+         FROM disbursements WHERE disbursed_on = $1 ORDER BY merchant_reference"
+    )
+    .bind(date)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut disbursements = rows.iter().map(disbursement_from_row).collect::<Result<Vec<_>, _>>()?;
+    load_lines(conn, &mut disbursements).await?;
+
+    Ok(disbursements)
+}
+
+// The disbursements of the merchant between both dates included, in the order they were paid.
+pub async fn find_merchant_disbursements(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Disbursement>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, reference, merchant_reference, disbursed_on, currency, gross_amount, fee_amount, vat_treatment, vat_rate, fee_vat_amount, fee_gross_amount, net_amount, status \
+         FROM disbursements WHERE merchant_reference = $1 AND disbursed_on BETWEEN $2 AND $3 ORDER BY disbursed_on, reference"
+    )
+    .bind(merchant_reference)
+    .bind(from)
+    .bind(to)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut disbursements = rows.iter().map(disbursement_from_row).collect::<Result<Vec<_>, _>>()?;
+    load_lines(conn, &mut disbursements).await?;
+
+    Ok(disbursements)
+}
+
+pub async fn find_disbursements_by_reference(conn: &mut PgConnection, references: &[String]) -> Result<Vec<Disbursement>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, reference, merchant_reference, disbursed_on, currency, gross_amount, fee_amount, vat_treatment, vat_rate, fee_vat_amount, fee_gross_amount, net_amount, status \
+         FROM disbursements WHERE reference = ANY($1) ORDER BY reference"
+    )
+    .bind(references)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut disbursements = rows.iter().map(disbursement_from_row).collect::<Result<Vec<_>, _>>()?;
+    load_lines(conn, &mut disbursements).await?;
+
+    Ok(disbursements)
+}
+
+pub async fn find_disbursements_by_id(conn: &mut PgConnection, ids: &[Uuid]) -> Result<Vec<Disbursement>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, reference, merchant_reference, disbursed_on, currency, gross_amount, fee_amount, vat_treatment, vat_rate, fee_vat_amount, fee_gross_amount, net_amount, status \
+         FROM disbursements WHERE id = ANY($1) ORDER BY reference"
+    )
+    .bind(ids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut disbursements = rows.iter().map(disbursement_from_row).collect::<Result<Vec<_>, _>>()?;
+    load_lines(conn, &mut disbursements).await?;
+
+    Ok(disbursements)
+}
+
+// A disbursement is settled once, by the first statement entry matching it.
+pub async fn mark_disbursement_settled(
+    conn: &mut PgConnection,
+    id: Uuid,
+    settled_on: NaiveDate,
+    bank_reference: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE disbursements SET settled_on = $2, bank_reference = $3 WHERE id = $1 AND settled_on IS NULL")
+        .bind(id)
+        .bind(settled_on)
+        .bind(bank_reference)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// Every condition is optional, an empty list of statuses is any status.
+#[derive(Debug, Clone, Default)]
+pub struct DisbursementFilter {
+    pub statuses: Vec<DisbursementStatus>,
+    pub merchant_reference: Option<String>,
+    pub disbursed_from: Option<NaiveDate>,
+    pub disbursed_to: Option<NaiveDate>,
+    pub batch_id: Option<Uuid>,
+}
+
+pub async fn find_disbursements(conn: &mut PgConnection, filter: &DisbursementFilter) -> Result<Vec<Disbursement>, sqlx::Error> {
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "SELECT id, reference, merchant_reference, disbursed_on, currency, gross_amount, fee_amount, vat_treatment, vat_rate, fee_vat_amount, fee_gross_amount, net_amount, status \
+         FROM disbursements WHERE TRUE"
+    );
+
+    if !filter.statuses.is_empty() {
+        let statuses: Vec<String> = filter.statuses.iter().map(|status| status.to_string()).collect();
+        query_builder.push(" AND status = ANY(").push_bind(statuses).push(")");
+    }
+    if let Some(merchant_reference) = &filter.merchant_reference {
+        query_builder.push(" AND merchant_reference = ").push_bind(merchant_reference);
+    }
+    if let Some(disbursed_from) = filter.disbursed_from {
+        query_builder.push(" AND disbursed_on >= ").push_bind(disbursed_from);
+    }
+    if let Some(disbursed_to) = filter.disbursed_to {
+        query_builder.push(" AND disbursed_on <= ").push_bind(disbursed_to);
+    }
+    if let Some(batch_id) = filter.batch_id {
+        query_builder.push(" AND batch_id = ").push_bind(batch_id);
+    }
+    query_builder.push(" ORDER BY disbursed_on, merchant_reference");
+
+    let rows = query_builder.build().fetch_all(&mut *conn).await?;
+
+    let mut disbursements = rows.iter().map(disbursement_from_row).collect::<Result<Vec<_>, _>>()?;
+    load_lines(conn, &mut disbursements).await?;
+
+    Ok(disbursements)
+}
+
+pub async fn insert_disbursement_status_change(conn: &mut PgConnection, change: &DisbursementStatusChange) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO disbursement_status_history (disbursement_id, from_status, to_status, actor, note, changed_at) \
+         VALUES ($1, $2, $3, $4, $5, $6)"
+    )
+    .bind(change.disbursement_id)
+    .bind(change.from.map(|status| status.to_string()))
+    .bind(change.to.to_string())
+    .bind(&change.actor)
+    .bind(&change.note)
+    .bind(change.changed_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// Only moves the disbursement when it's still in the `from` status of the change, false when someone else changed it first.
+// Every change is stored in the history with it (and published, see jobs::disbursement_status::save_status_change).
+pub async fn update_disbursement_status(conn: &mut PgConnection, change: &DisbursementStatusChange) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query("UPDATE disbursements SET status = $2 WHERE id = $1 AND status = $3")
+        .bind(change.disbursement_id)
+        .bind(change.to.to_string())
+        .bind(change.from.map(|status| status.to_string()))
+        .execute(&mut *conn)
+        .await?;
+
+    if updated.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    insert_disbursement_status_change(conn, change).await?;
+    Ok(true)
+}
+
+// The first and the last day of the sales paid by the disbursement, the orders in the time zone of the merchant.
+// None when it only has lines that don't come from an order (a released reserve, a returned payout...).
+pub async fn find_sales_period(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<Option<(NaiveDate, NaiveDate)>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT MIN(day) AS period_from, MAX(day) AS period_to FROM ( \
+            SELECT (o.created_at AT TIME ZONE m.time_zone)::DATE AS day \
+            FROM disbursement_lines l \
+            JOIN disbursements d ON d.id = l.disbursement_id \
+            JOIN merchants m ON m.merchant_reference = d.merchant_reference \
+            JOIN orders o ON o.id = l.order_id \
+            WHERE l.disbursement_id = $1 AND l.kind = 'ORDER' \
+            UNION ALL \
+            SELECT a.created_at AS day \
+            FROM disbursement_lines l JOIN order_adjustments a ON a.id = l.adjustment_id \
+            WHERE l.disbursement_id = $1 AND l.kind = 'ADJUSTMENT' \
+         ) days"
+    )
+    .bind(disbursement_id)
+    .fetch_one(conn)
+    .await?;
+
+    let period_from: Option<NaiveDate> = row.try_get("period_from")?;
+    let period_to: Option<NaiveDate> = row.try_get("period_to")?;
+
+    Ok(period_from.zip(period_to))
+}
+
+pub async fn find_disbursement_status_history(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<Vec<DisbursementStatusChange>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT disbursement_id, from_status, to_status, actor, note, changed_at \
+         FROM disbursement_status_history WHERE disbursement_id = $1 ORDER BY changed_at, id"
+    )
+    .bind(disbursement_id)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let from: Option<String> = row.try_get("from_status")?;
+            let to: String = row.try_get("to_status")?;
+            Ok(DisbursementStatusChange {
+                disbursement_id: row.try_get("disbursement_id")?,
+                from: from
+                    .map(|from| from.parse::<DisbursementStatus>())
+                    .transpose()
+                    .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+                to: to.parse::<DisbursementStatus>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+                actor: row.try_get("actor")?,
+                note: row.try_get("note")?,
+                changed_at: row.try_get("changed_at")?,
+            })
+        })
+        .collect()
+}
+
+async fn load_lines(conn: &mut PgConnection, disbursements: &mut [Disbursement]) -> Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = disbursements.iter().map(|disbursement| disbursement.id).collect();
+
+    let rows = sqlx::query(
+        "SELECT disbursement_id, kind, order_id, adjustment_id, reserve_id, receivable_id, monthly_fee_id, payout_return_id, amount, original_amount, original_currency, fx_rate, fee_rate, pricing_version, fixed_fee, fee_amount \
+         FROM disbursement_lines WHERE disbursement_id = ANY($1) \
+         ORDER BY CASE kind WHEN 'ORDER' THEN 0 WHEN 'ADJUSTMENT' THEN 1 WHEN 'RESERVE_RELEASE' THEN 2 WHEN 'RETURNED_PAYOUT' THEN 3 WHEN 'RESERVE' THEN 4 WHEN 'MONTHLY_FEE' THEN 5 WHEN 'RECEIVABLE_OFFSET' THEN 6 ELSE 7 END, order_id, adjustment_id"
+    )
+    .bind(&ids)
+    .fetch_all(conn)
+    .await?;
+
+    for row in &rows {
+        let disbursement_id: Uuid = row.try_get("disbursement_id")?;
+        let kind: String = row.try_get("kind")?;
+        let line = DisbursementLine {
+            kind: kind.parse::<LineKind>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+            order_id: row.try_get("order_id")?,
+            adjustment_id: row.try_get("adjustment_id")?,
+            reserve_id: row.try_get("reserve_id")?,
+            receivable_id: row.try_get("receivable_id")?,
+            monthly_fee_id: row.try_get("monthly_fee_id")?,
+            payout_return_id: row.try_get("payout_return_id")?,
+            amount: row.try_get("amount")?,
+            original_amount: row.try_get("original_amount")?,
+            original_currency: row.try_get("original_currency")?,
+            fx_rate: row.try_get("fx_rate")?,
+            fee_rate: row.try_get("fee_rate")?,
+            pricing_version: row.try_get("pricing_version")?,
+            fixed_fee: row.try_get("fixed_fee")?,
+            fee_amount: row.try_get("fee_amount")?,
+        };
+
+        if let Some(disbursement) = disbursements.iter_mut().find(|d| d.id == disbursement_id) {
+            disbursement.lines.push(line);
+        }
+    }
+
+    Ok(())
+}
+
+// The fee rate (and pricing version) the order was charged with when it was disbursed, if it already was.
+pub async fn find_disbursed_fee_rate(conn: &mut PgConnection, order_id: &str) -> Result<Option<(i32, String)>, sqlx::Error> {
+    let row = sqlx::query("SELECT fee_rate, pricing_version FROM disbursement_lines WHERE order_id = $1 AND kind = 'ORDER' AND NOT voided")
+        .bind(order_id)
+        .fetch_optional(conn)
+        .await?;
+
+    row.map(|row| Ok((row.try_get("fee_rate")?, row.try_get("pricing_version")?))).transpose()
+}
+
+// The orders, adjustments and the rest the lines of the voided disbursement paid can be paid by another one.
+pub async fn void_disbursement_lines(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE disbursement_lines SET voided = TRUE WHERE disbursement_id = $1")
+        .bind(disbursement_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+fn disbursement_from_row(row: &PgRow) -> Result<Disbursement, sqlx::Error> {
+    let status: String = row.try_get("status")?;
+
+    Ok(Disbursement {
+        id: row.try_get("id")?,
+        reference: row.try_get("reference")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        disbursed_on: row.try_get("disbursed_on")?,
+        currency: row.try_get("currency")?,
+        gross_amount: row.try_get("gross_amount")?,
+        fee_amount: row.try_get("fee_amount")?,
+        vat: vat_charge_from_row(row)?,
+        fee_vat_amount: row.try_get("fee_vat_amount")?,
+        fee_gross_amount: row.try_get("fee_gross_amount")?,
+        net_amount: row.try_get("net_amount")?,
+        status: status.parse::<DisbursementStatus>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        lines: Vec::new(),
+    })
+}
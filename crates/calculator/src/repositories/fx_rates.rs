@@ -0,0 +1,51 @@
+use crate::entities::fx_rates::FxRate;
+use chrono::NaiveDate;
+use sqlx::{PgConnection, PgPool, Row};
+
+// Loading the same rates file twice replaces the rates of those dates.
+pub async fn upsert_fx_rates(pool: &PgPool, rates: &[FxRate]) -> Result<(), sqlx::Error> {
+    if rates.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO fx_rates (rate_date, base_currency, quote_currency, rate) "
+    );
+
+    query_builder.push_values(rates, |mut b, rate| {
+        b.push_bind(rate.rate_date)
+            .push_bind(&rate.base_currency)
+            .push_bind(&rate.quote_currency)
+            .push_bind(rate.rate);
+    });
+
+    query_builder.push(" ON CONFLICT (rate_date, base_currency, quote_currency) DO UPDATE SET rate = EXCLUDED.rate");
+
+    let query = query_builder.build();
+    query.execute(pool).await?;
+
+    Ok(())
+}
+
+// The latest rate of every currency pair on or before `date`, the ones effective on that date.
+pub async fn find_fx_rates_until(conn: &mut PgConnection, date: NaiveDate) -> Result<Vec<FxRate>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT DISTINCT ON (base_currency, quote_currency) rate_date, base_currency, quote_currency, rate \
+         FROM fx_rates WHERE rate_date <= $1 \
+         ORDER BY base_currency, quote_currency, rate_date DESC"
+    )
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(FxRate {
+                rate_date: row.try_get("rate_date")?,
+                base_currency: row.try_get("base_currency")?,
+                quote_currency: row.try_get("quote_currency")?,
+                rate: row.try_get("rate")?,
+            })
+        })
+        .collect()
+}
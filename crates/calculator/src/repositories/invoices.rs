@@ -0,0 +1,160 @@
+use crate::entities::invoices::{Invoice, InvoiceKind, InvoiceLine};
+use crate::repositories::vat::vat_charge_from_row;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+// Takes the next number of the series. The row stays locked until the transaction ends and a rollback
+// gives the number back, so the numbers issued are gapless as long as the invoice is inserted in that transaction.
+pub async fn next_invoice_sequence(conn: &mut PgConnection, kind: InvoiceKind, year: i32) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        "INSERT INTO invoice_sequences (kind, year, last_number) VALUES ($1, $2, 1) \
+         ON CONFLICT (kind, year) DO UPDATE SET last_number = invoice_sequences.last_number + 1 \
+         RETURNING last_number"
+    )
+    .bind(kind.to_string())
+    .bind(year)
+    .fetch_one(conn)
+    .await?;
+
+    row.try_get("last_number")
+}
+
+pub async fn insert_invoice(conn: &mut PgConnection, invoice: &Invoice) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO invoices (id, number, kind, merchant_reference, monthly_fee_id, credited_invoice_id, note, issued_on, period_start, period_end, \
+         seller_name, customer_name, customer_vat_number, currency, net_amount, vat_treatment, vat_rate, vat_amount, total_amount) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)"
+    )
+    .bind(invoice.id)
+    .bind(&invoice.number)
+    .bind(invoice.kind.to_string())
+    .bind(&invoice.merchant_reference)
+    .bind(invoice.monthly_fee_id)
+    .bind(invoice.credited_invoice_id)
+    .bind(&invoice.note)
+    .bind(invoice.issued_on)
+    .bind(invoice.period_start)
+    .bind(invoice.period_end)
+    .bind(&invoice.seller_name)
+    .bind(&invoice.customer_name)
+    .bind(&invoice.customer_vat_number)
+    .bind(&invoice.currency)
+    .bind(invoice.net_amount)
+    .bind(invoice.vat.map(|vat| vat.treatment.to_string()))
+    .bind(invoice.vat.map(|vat| vat.rate))
+    .bind(invoice.vat_amount)
+    .bind(invoice.total_amount)
+    .execute(&mut *conn)
+    .await?;
+
+    // the credited invoice no longer counts as the invoice of its monthly fee, a corrected one can be issued
+    if let Some(credited_invoice_id) = invoice.credited_invoice_id {
+        sqlx::query("UPDATE invoices SET credited = TRUE WHERE id = $1")
+            .bind(credited_invoice_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    if invoice.lines.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO invoice_lines (invoice_id, position, description, quantity, unit_amount, amount) "
+    );
+
+    query_builder.push_values(invoice.lines.iter().enumerate(), |mut b, (position, line)| {
+        b.push_bind(invoice.id)
+            .push_bind(position as i32)
+            .push_bind(&line.description)
+            .push_bind(line.quantity)
+            .push_bind(line.unit_amount)
+            .push_bind(line.amount);
+    });
+
+    let query = query_builder.build();
+    query.execute(conn).await?;
+
+    Ok(())
+}
+
+pub async fn find_invoice_by_number(conn: &mut PgConnection, number: &str) -> Result<Option<Invoice>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT i.id, i.number, i.kind, i.merchant_reference, i.monthly_fee_id, i.credited_invoice_id, c.number AS credited_invoice_number, \
+         i.note, i.issued_on, i.period_start, i.period_end, i.seller_name, i.customer_name, i.customer_vat_number, i.currency, \
+         i.net_amount, i.vat_treatment, i.vat_rate, i.vat_amount, i.total_amount \
+         FROM invoices i \
+         LEFT JOIN invoices c ON c.id = i.credited_invoice_id \
+         WHERE i.number = $1"
+    )
+    .bind(number)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let mut invoice = invoice_from_row(&row)?;
+    invoice.lines = find_invoice_lines(conn, invoice.id).await?;
+
+    Ok(Some(invoice))
+}
+
+// The number of the credit note reversing the invoice, an invoice is credited only once.
+pub async fn find_credit_note_number(conn: &mut PgConnection, invoice_id: Uuid) -> Result<Option<String>, sqlx::Error> {
+    let row = sqlx::query("SELECT number FROM invoices WHERE credited_invoice_id = $1")
+        .bind(invoice_id)
+        .fetch_optional(conn)
+        .await?;
+
+    row.map(|row| row.try_get("number")).transpose()
+}
+
+async fn find_invoice_lines(conn: &mut PgConnection, invoice_id: Uuid) -> Result<Vec<InvoiceLine>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT description, quantity, unit_amount, amount FROM invoice_lines WHERE invoice_id = $1 ORDER BY position"
+    )
+    .bind(invoice_id)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(InvoiceLine {
+                description: row.try_get("description")?,
+                quantity: row.try_get("quantity")?,
+                unit_amount: row.try_get("unit_amount")?,
+                amount: row.try_get("amount")?,
+            })
+        })
+        .collect()
+}
+
+fn invoice_from_row(row: &PgRow) -> Result<Invoice, sqlx::Error> {
+    let kind: String = row.try_get("kind")?;
+
+    Ok(Invoice {
+        id: row.try_get("id")?,
+        number: row.try_get("number")?,
+        kind: kind.parse::<InvoiceKind>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        monthly_fee_id: row.try_get("monthly_fee_id")?,
+        credited_invoice_id: row.try_get("credited_invoice_id")?,
+        credited_invoice_number: row.try_get("credited_invoice_number")?,
+        note: row.try_get("note")?,
+        issued_on: row.try_get("issued_on")?,
+        period_start: row.try_get("period_start")?,
+        period_end: row.try_get("period_end")?,
+        seller_name: row.try_get("seller_name")?,
+        customer_name: row.try_get("customer_name")?,
+        customer_vat_number: row.try_get("customer_vat_number")?,
+        currency: row.try_get("currency")?,
+        lines: Vec::new(),
+        net_amount: row.try_get("net_amount")?,
+        vat: vat_charge_from_row(row)?,
+        vat_amount: row.try_get("vat_amount")?,
+        total_amount: row.try_get("total_amount")?,
+    })
+}
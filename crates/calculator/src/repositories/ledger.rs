@@ -0,0 +1,151 @@
+use crate::entities::ledger::{Account, AccountBalance, JournalEntry};
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+// Booking the same entry (kind and source) twice is a no-op, so consuming an event twice doesn't duplicate money.
+// The ids of the entries booked, the ones already there are left out.
+pub async fn insert_journal_entries(conn: &mut PgConnection, entries: &[JournalEntry]) -> Result<Vec<Uuid>, sqlx::Error> {
+    let mut booked = Vec::new();
+
+    for entry in entries.iter().filter(|entry| !entry.postings.is_empty()) {
+        let inserted = sqlx::query(
+            "INSERT INTO journal_entries (id, kind, source_id, merchant_reference, booked_on) \
+             VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (kind, source_id) DO NOTHING"
+        )
+        .bind(entry.id)
+        .bind(entry.kind.to_string())
+        .bind(&entry.source_id)
+        .bind(&entry.merchant_reference)
+        .bind(entry.booked_on)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+        if inserted == 0 {
+            continue;
+        }
+
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "INSERT INTO ledger_postings (journal_entry_id, merchant_reference, account, currency, amount) "
+        );
+
+        query_builder.push_values(&entry.postings, |mut b, posting| {
+            b.push_bind(entry.id)
+                .push_bind(&entry.merchant_reference)
+                .push_bind(posting.account.to_string())
+                .push_bind(&posting.currency)
+                .push_bind(posting.amount);
+        });
+
+        query_builder.build().execute(&mut *conn).await?;
+        booked.push(entry.id);
+    }
+
+    Ok(booked)
+}
+
+pub async fn find_merchant_balances(conn: &mut PgConnection, merchant_reference: &str) -> Result<Vec<AccountBalance>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT account, currency, SUM(amount)::BIGINT AS balance \
+         FROM ledger_postings WHERE merchant_reference = $1 \
+         GROUP BY account, currency ORDER BY account, currency"
+    )
+    .bind(merchant_reference)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(account_balance_from_row).collect()
+}
+
+// The balances of the merchant accounts with the entries booked up to `date` included.
+pub async fn find_merchant_balances_on(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    date: NaiveDate,
+) -> Result<Vec<AccountBalance>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT p.account, p.currency, SUM(p.amount)::BIGINT AS balance \
+         FROM ledger_postings p \
+         JOIN journal_entries e ON e.id = p.journal_entry_id \
+         WHERE p.merchant_reference = $1 AND e.booked_on <= $2 \
+         GROUP BY p.account, p.currency ORDER BY p.account, p.currency"
+    )
+    .bind(merchant_reference)
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(account_balance_from_row).collect()
+}
+
+// The ids of the journal entries whose postings don't sum zero in some currency, empty when the ledger is consistent.
+pub async fn find_unbalanced_journal_entries(conn: &mut PgConnection) -> Result<Vec<Uuid>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT DISTINCT journal_entry_id FROM ledger_postings \
+         GROUP BY journal_entry_id, currency HAVING SUM(amount) <> 0"
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(|row| row.try_get("journal_entry_id")).collect()
+}
+
+pub async fn find_unbalanced_journal_entries_among(conn: &mut PgConnection, ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT DISTINCT journal_entry_id FROM ledger_postings WHERE journal_entry_id = ANY($1) \
+         GROUP BY journal_entry_id, currency HAVING SUM(amount) <> 0"
+    )
+    .bind(ids)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(|row| row.try_get("journal_entry_id")).collect()
+}
+
+// Amounts in different currencies can't be added, so the ledger is summed per currency.
+pub async fn sum_ledger(conn: &mut PgConnection) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT currency, SUM(amount)::BIGINT AS total FROM ledger_postings GROUP BY currency ORDER BY currency"
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(|row| Ok((row.try_get("currency")?, row.try_get("total")?))).collect()
+}
+
+// What a job checks before committing: the entries it booked are balanced, so a ledger that summed zero still does.
+// Only those entries are read, the whole ledger is checked by the ledger report.
+pub async fn ensure_entries_balanced(conn: &mut PgConnection, ids: &[Uuid]) -> anyhow::Result<()> {
+    let unbalanced = find_unbalanced_journal_entries_among(conn, ids).await?;
+
+    if !unbalanced.is_empty() {
+        anyhow::bail!("The ledger would be unbalanced, unbalanced entries: {:?}", unbalanced);
+    }
+
+    Ok(())
+}
+
+// The invariant of a double-entry ledger: every entry is balanced, so the whole ledger sums zero in every currency.
+pub async fn ensure_ledger_balanced(conn: &mut PgConnection) -> anyhow::Result<()> {
+    let unbalanced = find_unbalanced_journal_entries(conn).await?;
+    let totals: Vec<(String, i64)> = sum_ledger(conn).await?.into_iter().filter(|(_, total)| *total != 0).collect();
+
+    if !unbalanced.is_empty() || !totals.is_empty() {
+        anyhow::bail!("The ledger is unbalanced (totals {:?}), unbalanced entries: {:?}", totals, unbalanced);
+    }
+
+    Ok(())
+}
+
+fn account_balance_from_row(row: &PgRow) -> Result<AccountBalance, sqlx::Error> {
+    let account: String = row.try_get("account")?;
+
+    Ok(AccountBalance {
+        account: account.parse::<Account>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        currency: row.try_get("currency")?,
+        balance: row.try_get("balance")?,
+    })
+}
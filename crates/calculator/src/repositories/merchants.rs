@@ -1,13 +1,18 @@
-use crate::entities::merchants::Merchant;
-use sqlx::PgPool;
+use crate::entities::merchants::{weekday_name, BankAccount, DisbursementFrequency, Merchant, MonthlyFeeMode, RollConvention};
+use chrono::Weekday;
+use chrono_tz::Tz;
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
 
-pub async fn upsert_merchants(pool: &PgPool, merchants: &[Merchant]) -> Result<(), sqlx::Error> {
+pub async fn upsert_merchants(conn: &mut PgConnection, merchants: &[Merchant]) -> Result<(), sqlx::Error> {
     if merchants.is_empty() {
         return Ok(());
     }
 
     let mut query_builder = sqlx::QueryBuilder::new(
-        "INSERT INTO merchants (id, merchant_reference, live_on, disbursement_frequency, minimum_monthly_fee) "
+        "INSERT INTO merchants \
+         (id, merchant_reference, live_on, disbursement_frequency, disbursement_weekday, disbursement_day_of_month, minimum_monthly_fee, iban, bic, account_holder, payout_currency, pricing_plan, roll_convention, time_zone, minimum_payout, offboarded_on, monthly_fee_mode, country, vat_number) "
     );
 
     query_builder.push_values(merchants, |mut b, merchant| {
@@ -15,18 +20,118 @@ pub async fn upsert_merchants(pool: &PgPool, merchants: &[Merchant]) -> Result<(
             .push_bind(&merchant.merchant_reference)
             .push_bind(merchant.live_on)
             .push_bind(merchant.disbursement_frequency.to_string())
-            .push_bind(merchant.minimum_monthly_fee);
+            .push_bind(merchant.disbursement_weekday.map(weekday_name))
+            .push_bind(merchant.disbursement_day_of_month.map(|day| day as i16))
+            .push_bind(merchant.minimum_monthly_fee)
+            .push_bind(merchant.bank_account.as_ref().map(|account| &account.iban))
+            .push_bind(merchant.bank_account.as_ref().map(|account| &account.bic))
+            .push_bind(merchant.bank_account.as_ref().map(|account| &account.account_holder))
+            .push_bind(&merchant.payout_currency)
+            .push_bind(&merchant.pricing_plan)
+            .push_bind(merchant.roll_convention.map(|convention| convention.to_string()))
+            .push_bind(merchant.time_zone.name())
+            .push_bind(merchant.minimum_payout)
+            .push_bind(merchant.offboarded_on)
+            .push_bind(merchant.monthly_fee_mode.to_string())
+            .push_bind(&merchant.country)
+            .push_bind(&merchant.vat_number);
     });
 
     query_builder.push(
         " ON CONFLICT (merchant_reference) DO UPDATE SET \
          live_on = EXCLUDED.live_on, \
          disbursement_frequency = EXCLUDED.disbursement_frequency, \
-         minimum_monthly_fee = EXCLUDED.minimum_monthly_fee"
+         disbursement_weekday = EXCLUDED.disbursement_weekday, \
+         disbursement_day_of_month = EXCLUDED.disbursement_day_of_month, \
+         minimum_monthly_fee = EXCLUDED.minimum_monthly_fee, \
+         iban = EXCLUDED.iban, \
+         bic = EXCLUDED.bic, \
+         account_holder = EXCLUDED.account_holder, \
+         payout_currency = EXCLUDED.payout_currency, \
+         pricing_plan = EXCLUDED.pricing_plan, \
+         roll_convention = EXCLUDED.roll_convention, \
+         time_zone = EXCLUDED.time_zone, \
+         minimum_payout = EXCLUDED.minimum_payout, \
+         offboarded_on = EXCLUDED.offboarded_on, \
+         monthly_fee_mode = EXCLUDED.monthly_fee_mode, \
+         country = EXCLUDED.country, \
+         vat_number = EXCLUDED.vat_number"
     );
 
     let query = query_builder.build();
-    query.execute(pool).await?;
+    query.execute(conn).await?;
 
     Ok(())
 }
+
+pub async fn find_live_merchants(conn: &mut PgConnection, date: NaiveDate) -> Result<Vec<Merchant>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, live_on, disbursement_frequency, disbursement_weekday, disbursement_day_of_month, minimum_monthly_fee, iban, bic, account_holder, payout_currency, pricing_plan, roll_convention, time_zone, minimum_payout, offboarded_on, monthly_fee_mode, country, vat_number \
+         FROM merchants WHERE live_on <= $1 ORDER BY merchant_reference"
+    )
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(merchant_from_row).collect()
+}
+
+pub async fn find_merchants_by_reference(
+    conn: &mut PgConnection,
+    merchant_references: &[String],
+) -> Result<Vec<Merchant>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, live_on, disbursement_frequency, disbursement_weekday, disbursement_day_of_month, minimum_monthly_fee, iban, bic, account_holder, payout_currency, pricing_plan, roll_convention, time_zone, minimum_payout, offboarded_on, monthly_fee_mode, country, vat_number \
+         FROM merchants WHERE merchant_reference = ANY($1) ORDER BY merchant_reference"
+    )
+    .bind(merchant_references)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(merchant_from_row).collect()
+}
+
+fn merchant_from_row(row: &PgRow) -> Result<Merchant, sqlx::Error> {
+    let disbursement_frequency: String = row.try_get("disbursement_frequency")?;
+    let disbursement_weekday: Option<String> = row.try_get("disbursement_weekday")?;
+    let disbursement_day_of_month: Option<i16> = row.try_get("disbursement_day_of_month")?;
+    let roll_convention: Option<String> = row.try_get("roll_convention")?;
+    let time_zone: String = row.try_get("time_zone")?;
+    let monthly_fee_mode: String = row.try_get("monthly_fee_mode")?;
+    let iban: Option<String> = row.try_get("iban")?;
+    let bic: Option<String> = row.try_get("bic")?;
+    let account_holder: Option<String> = row.try_get("account_holder")?;
+
+    Ok(Merchant {
+        id: row.try_get("id")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        live_on: row.try_get("live_on")?,
+        disbursement_frequency: disbursement_frequency
+            .parse::<DisbursementFrequency>()
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        disbursement_weekday: disbursement_weekday
+            .map(|weekday| weekday.parse::<Weekday>())
+            .transpose()
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        disbursement_day_of_month: disbursement_day_of_month.map(|day| day as u32),
+        minimum_monthly_fee: row.try_get("minimum_monthly_fee")?,
+        bank_account: match (iban, bic, account_holder) {
+            (Some(iban), Some(bic), Some(account_holder)) => Some(BankAccount { iban, bic, account_holder }),
+            _ => None,
+        },
+        payout_currency: row.try_get("payout_currency")?,
+        pricing_plan: row.try_get("pricing_plan")?,
+        roll_convention: roll_convention
+            .map(|convention| convention.parse::<RollConvention>())
+            .transpose()
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        time_zone: time_zone.parse::<Tz>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        minimum_payout: row.try_get("minimum_payout")?,
+        offboarded_on: row.try_get("offboarded_on")?,
+        monthly_fee_mode: monthly_fee_mode
+            .parse::<MonthlyFeeMode>()
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        country: row.try_get("country")?,
+        vat_number: row.try_get("vat_number")?,
+    })
+}
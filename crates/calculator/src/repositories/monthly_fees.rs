@@ -0,0 +1,146 @@
+use crate::entities::merchants::MonthlyFeeMode;
+use crate::entities::monthly_fees::MonthlyFee;
+use crate::repositories::vat::vat_charge_from_row;
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+pub async fn insert_monthly_fee(conn: &mut PgConnection, monthly_fee: &MonthlyFee) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO monthly_fees (id, merchant_reference, month, currency, commissions_amount, minimum_monthly_fee, amount, vat_treatment, vat_rate, vat_amount, gross_amount, calculated_on, mode) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)"
+    )
+    .bind(monthly_fee.id)
+    .bind(&monthly_fee.merchant_reference)
+    .bind(monthly_fee.month)
+    .bind(&monthly_fee.currency)
+    .bind(monthly_fee.commissions_amount)
+    .bind(monthly_fee.minimum_monthly_fee)
+    .bind(monthly_fee.amount)
+    .bind(monthly_fee.vat.map(|vat| vat.treatment.to_string()))
+    .bind(monthly_fee.vat.map(|vat| vat.rate))
+    .bind(monthly_fee.vat_amount)
+    .bind(monthly_fee.gross_amount)
+    .bind(monthly_fee.calculated_on)
+    .bind(monthly_fee.mode.to_string())
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn find_monthly_fee(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    month: NaiveDate,
+) -> Result<Option<MonthlyFee>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT id, merchant_reference, month, currency, commissions_amount, minimum_monthly_fee, amount, vat_treatment, vat_rate, vat_amount, gross_amount, calculated_on, mode, disbursement_id \
+         FROM monthly_fees WHERE merchant_reference = $1 AND month = $2"
+    )
+    .bind(merchant_reference)
+    .bind(month)
+    .fetch_optional(conn)
+    .await?;
+
+    row.as_ref().map(monthly_fee_from_row).transpose()
+}
+
+pub async fn find_monthly_fees_calculated_on(conn: &mut PgConnection, date: NaiveDate) -> Result<Vec<MonthlyFee>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, month, currency, commissions_amount, minimum_monthly_fee, amount, vat_treatment, vat_rate, vat_amount, gross_amount, calculated_on, mode, disbursement_id \
+         FROM monthly_fees WHERE calculated_on = $1 ORDER BY merchant_reference"
+    )
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(monthly_fee_from_row).collect()
+}
+
+// The fees of the merchants in deduct mode calculated before `date` and not deducted yet, or deducted by the disbursement
+// of that same date so a preview of an already committed date calculates the same deduction again.
+pub async fn find_monthly_fees_to_deduct(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    date: NaiveDate,
+) -> Result<Vec<MonthlyFee>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT f.id, f.merchant_reference, f.month, f.currency, f.commissions_amount, f.minimum_monthly_fee, f.amount, f.vat_treatment, f.vat_rate, f.vat_amount, f.gross_amount, f.calculated_on, f.mode, f.disbursement_id \
+         FROM monthly_fees f \
+         LEFT JOIN disbursements d ON d.id = f.disbursement_id \
+         WHERE f.merchant_reference = $1 AND f.mode = 'DEDUCT' AND f.amount > 0 AND f.calculated_on < $2 \
+         AND (f.disbursement_id IS NULL OR d.disbursed_on = $2) \
+         ORDER BY f.month"
+    )
+    .bind(merchant_reference)
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(monthly_fee_from_row).collect()
+}
+
+// The shortfalls of the merchants in invoice mode for `month` without an invoice, or whose invoice was credited
+// so a corrected one can be issued. Locked, an issue run at the same time skips them, and the unique index on the
+// invoices not credited refuses the ones it still tries to invoice twice.
+pub async fn find_monthly_fees_to_invoice(conn: &mut PgConnection, month: NaiveDate) -> Result<Vec<MonthlyFee>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT f.id, f.merchant_reference, f.month, f.currency, f.commissions_amount, f.minimum_monthly_fee, f.amount, f.vat_treatment, f.vat_rate, f.vat_amount, f.gross_amount, f.calculated_on, f.mode, f.disbursement_id \
+         FROM monthly_fees f \
+         WHERE f.month = $1 AND f.mode = 'INVOICE' AND f.amount > 0 \
+         AND NOT EXISTS (SELECT 1 FROM invoices i WHERE i.monthly_fee_id = f.id AND i.kind = 'INVOICE' AND NOT i.credited) \
+         ORDER BY f.merchant_reference \
+         FOR UPDATE OF f SKIP LOCKED"
+    )
+    .bind(month)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(monthly_fee_from_row).collect()
+}
+
+pub async fn mark_monthly_fees_deducted(conn: &mut PgConnection, ids: &[Uuid], disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("UPDATE monthly_fees SET disbursement_id = $1 WHERE id = ANY($2) AND disbursement_id IS NULL")
+        .bind(disbursement_id)
+        .bind(ids)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// The fees the voided disbursement deducted are deducted by the next one.
+pub async fn reopen_monthly_fees_deducted_by(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE monthly_fees SET disbursement_id = NULL WHERE disbursement_id = $1")
+        .bind(disbursement_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+fn monthly_fee_from_row(row: &PgRow) -> Result<MonthlyFee, sqlx::Error> {
+    let mode: String = row.try_get("mode")?;
+
+    Ok(MonthlyFee {
+        id: row.try_get("id")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        month: row.try_get("month")?,
+        currency: row.try_get("currency")?,
+        commissions_amount: row.try_get("commissions_amount")?,
+        minimum_monthly_fee: row.try_get("minimum_monthly_fee")?,
+        amount: row.try_get("amount")?,
+        vat: vat_charge_from_row(row)?,
+        vat_amount: row.try_get("vat_amount")?,
+        gross_amount: row.try_get("gross_amount")?,
+        calculated_on: row.try_get("calculated_on")?,
+        mode: mode.parse::<MonthlyFeeMode>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        disbursement_id: row.try_get("disbursement_id")?,
+    })
+}
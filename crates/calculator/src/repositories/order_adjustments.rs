@@ -0,0 +1,137 @@
+use crate::entities::order_adjustments::{AdjustmentKind, OrderAdjustment};
+use crate::entities::orders::Order;
+use crate::repositories::disbursements::find_disbursed_fee_rate;
+use crate::repositories::fx_rates::find_fx_rates_until;
+use crate::repositories::orders::find_order_for_update;
+use crate::repositories::merchants::find_merchants_by_reference;
+use crate::repositories::pricing_plans::find_pricing_plans;
+use crate::services::disbursement_calculator::order_fee_rate;
+use crate::services::fx::FxRates;
+use crate::services::pricing::PricingPlans;
+use anyhow::anyhow;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+// The unique event_id is what guarantees that a refund (or correction) is booked once, however many times its event is delivered.
+pub async fn insert_order_adjustment(conn: &mut PgConnection, adjustment: &OrderAdjustment) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO order_adjustments (id, event_id, order_id, merchant_reference, kind, amount, currency, fee_rate, pricing_version, created_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
+    )
+    .bind(adjustment.id)
+    .bind(adjustment.event_id)
+    .bind(&adjustment.order_id)
+    .bind(&adjustment.merchant_reference)
+    .bind(adjustment.kind.to_string())
+    .bind(adjustment.amount)
+    .bind(&adjustment.currency)
+    .bind(adjustment.fee_rate)
+    .bind(&adjustment.pricing_version)
+    .bind(adjustment.created_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// An order to adjust, locked, with its current amount and the fee rate (and pricing version) it was (or will be) charged with.
+pub struct AdjustableOrder {
+    pub order: Order,
+    pub current_amount: i64,
+    pub fee_rate: i32,
+    pub pricing_version: String,
+    // of the merchant, UTC when we don't know it yet
+    pub time_zone: Tz,
+}
+
+pub async fn find_adjustable_order(conn: &mut PgConnection, order_id: &str) -> anyhow::Result<AdjustableOrder> {
+    let order = find_order_for_update(conn, order_id)
+        .await?
+        .ok_or_else(|| anyhow!("Order {} not found", order_id))?;
+
+    let merchant = find_merchants_by_reference(conn, std::slice::from_ref(&order.merchant_reference)).await?.pop();
+    let time_zone = merchant.as_ref().map(|merchant| merchant.time_zone).unwrap_or(chrono_tz::UTC);
+    let created_on = order.created_on(time_zone);
+
+    let current_amount = order.amount + sum_order_adjustments(conn, order_id).await?;
+    let (fee_rate, pricing_version) = match find_disbursed_fee_rate(conn, order_id).await? {
+        Some(disbursed) => disbursed,
+        // provisional, the order is charged (and the adjustment reversed) with the rates of the day it's disbursed
+        None => {
+            let rates = FxRates::new(find_fx_rates_until(conn, created_on).await?);
+            let pricing = PricingPlans::new(find_pricing_plans(conn).await?);
+            let plan_code = merchant.and_then(|merchant| merchant.pricing_plan);
+            let plan = pricing.for_order(plan_code.as_deref(), created_on)?;
+            (order_fee_rate(&order, plan, &rates, created_on)?, plan.version())
+        }
+    };
+
+    Ok(AdjustableOrder { order, current_amount, fee_rate, pricing_version, time_zone })
+}
+
+// Whether the event already booked its adjustment.
+pub async fn order_adjustment_exists(conn: &mut PgConnection, event_id: Uuid) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT EXISTS (SELECT 1 FROM order_adjustments WHERE event_id = $1) AS booked")
+        .bind(event_id)
+        .fetch_one(conn)
+        .await?;
+
+    row.try_get("booked")
+}
+
+// Sum of the adjustments of the order, to know its current amount.
+pub async fn sum_order_adjustments(conn: &mut PgConnection, order_id: &str) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query("SELECT COALESCE(SUM(amount), 0)::BIGINT AS total FROM order_adjustments WHERE order_id = $1")
+        .bind(order_id)
+        .fetch_one(conn)
+        .await?;
+
+    row.try_get("total")
+}
+
+// Same rules as the orders: the ones created before `date` not disbursed yet, plus the ones disbursed on `date`.
+// The adjustments of an order disbursed before reverse its fee with the rate (and pricing version) it was charged with.
+pub async fn find_adjustments_to_disburse(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    date: NaiveDate,
+) -> Result<Vec<OrderAdjustment>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT a.id, a.event_id, a.order_id, a.merchant_reference, a.kind, a.amount, a.currency, \
+         COALESCE(o.fee_rate, a.fee_rate) AS fee_rate, COALESCE(o.pricing_version, a.pricing_version) AS pricing_version, a.created_at \
+         FROM order_adjustments a \
+         LEFT JOIN disbursement_lines o ON o.order_id = a.order_id AND o.kind = 'ORDER' AND NOT o.voided \
+         LEFT JOIN disbursement_lines l ON l.adjustment_id = a.id AND NOT l.voided \
+         LEFT JOIN disbursements d ON d.id = l.disbursement_id \
+         WHERE a.merchant_reference = $1 \
+         AND a.created_at < $2 \
+         AND (l.id IS NULL OR d.disbursed_on = $2) \
+         ORDER BY a.created_at, a.id"
+    )
+    .bind(merchant_reference)
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(order_adjustment_from_row).collect()
+}
+
+fn order_adjustment_from_row(row: &PgRow) -> Result<OrderAdjustment, sqlx::Error> {
+    let kind: String = row.try_get("kind")?;
+
+    Ok(OrderAdjustment {
+        id: row.try_get("id")?,
+        event_id: row.try_get("event_id")?,
+        order_id: row.try_get("order_id")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        kind: kind.parse::<AdjustmentKind>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        amount: row.try_get("amount")?,
+        currency: row.try_get("currency")?,
+        fee_rate: row.try_get("fee_rate")?,
+        pricing_version: row.try_get("pricing_version")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
@@ -0,0 +1,101 @@
+use crate::entities::orders::Order;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+
+// Orders are immutable, so receiving the same order twice must not fail nor change it.
+pub async fn insert_orders(conn: &mut PgConnection, orders: &[Order]) -> Result<(), sqlx::Error> {
+    if orders.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO orders (id, merchant_reference, amount, currency, created_at) "
+    );
+
+    query_builder.push_values(orders, |mut b, order| {
+        b.push_bind(&order.id)
+            .push_bind(&order.merchant_reference)
+            .push_bind(order.amount)
+            .push_bind(&order.currency)
+            .push_bind(order.created_at);
+    });
+
+    query_builder.push(" ON CONFLICT (id) DO NOTHING");
+
+    let query = query_builder.build();
+    query.execute(conn).await?;
+
+    Ok(())
+}
+
+// Orders created before `created_before` (the start of `date` for the merchant) that are not disbursed yet,
+// plus the ones already disbursed on `date` so a run for a date can be recalculated (and compared) after it was persisted.
+pub async fn find_orders_to_disburse(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    created_before: DateTime<Utc>,
+    date: NaiveDate,
+) -> Result<Vec<Order>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT o.id, o.merchant_reference, o.amount, o.currency, o.created_at \
+         FROM orders o \
+         LEFT JOIN disbursement_lines l ON l.order_id = o.id AND l.kind = 'ORDER' AND NOT l.voided \
+         LEFT JOIN disbursements d ON d.id = l.disbursement_id \
+         WHERE o.merchant_reference = $1 \
+         AND o.created_at < $2 \
+         AND (l.id IS NULL OR d.disbursed_on = $3) \
+         ORDER BY o.created_at, o.id"
+    )
+    .bind(merchant_reference)
+    .bind(created_before)
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(order_from_row).collect()
+}
+
+// `from` is inclusive and `to` exclusive.
+pub async fn find_orders_created_between(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Order>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, amount, currency, created_at \
+         FROM orders \
+         WHERE merchant_reference = $1 AND created_at >= $2 AND created_at < $3 \
+         ORDER BY created_at, id"
+    )
+    .bind(merchant_reference)
+    .bind(from)
+    .bind(to)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(order_from_row).collect()
+}
+
+// Locks the order, so concurrent adjustments of the same order are applied one after the other.
+pub async fn find_order_for_update(conn: &mut PgConnection, id: &str) -> Result<Option<Order>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT id, merchant_reference, amount, currency, created_at FROM orders WHERE id = $1 FOR UPDATE"
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await?;
+
+    row.as_ref().map(order_from_row).transpose()
+}
+
+fn order_from_row(row: &PgRow) -> Result<Order, sqlx::Error> {
+    Ok(Order {
+        id: row.try_get("id")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        amount: row.try_get("amount")?,
+        currency: row.try_get("currency")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
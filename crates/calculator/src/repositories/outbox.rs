@@ -0,0 +1,77 @@
+use crate::entities::outbox::OutboxEvent;
+use crate::repositories::webhooks::queue_webhook_deliveries;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+// Always in the transaction of the change the event reports, with its webhook deliveries.
+pub async fn insert_outbox_event(conn: &mut PgConnection, event: &OutboxEvent) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO event_outbox (id, topic, merchant_reference, payload, created_at) \
+         VALUES ($1, $2, $3, $4::jsonb, $5)"
+    )
+    .bind(event.id)
+    .bind(&event.topic)
+    .bind(&event.merchant_reference)
+    .bind(event.payload.to_string())
+    .bind(event.created_at)
+    .execute(&mut *conn)
+    .await?;
+
+    queue_webhook_deliveries(conn, event).await?;
+
+    Ok(())
+}
+
+// Any number, the same for every relay.
+const RELAY_LOCK: i64 = 48_001;
+
+// Held until the end of the transaction: one relay publishes at a time, a second one waits for the first to mark what it
+// published, so the events of a merchant are never published out of order.
+pub async fn lock_relay(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1)").bind(RELAY_LOCK).execute(conn).await?;
+
+    Ok(())
+}
+
+// The oldest events not published yet, in the order they were stored, locked until they are marked published
+// (see `lock_relay`).
+pub async fn find_unpublished_events(conn: &mut PgConnection, limit: i64) -> Result<Vec<OutboxEvent>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, topic, merchant_reference, payload::TEXT AS payload, created_at \
+         FROM event_outbox WHERE published_at IS NULL \
+         ORDER BY position LIMIT $1 FOR UPDATE"
+    )
+    .bind(limit)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(outbox_event_from_row).collect()
+}
+
+pub async fn mark_events_published(conn: &mut PgConnection, ids: &[Uuid], published_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("UPDATE event_outbox SET published_at = $1 WHERE id = ANY($2)")
+        .bind(published_at)
+        .bind(ids)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+fn outbox_event_from_row(row: &PgRow) -> Result<OutboxEvent, sqlx::Error> {
+    let payload: String = row.try_get("payload")?;
+
+    Ok(OutboxEvent {
+        id: row.try_get("id")?,
+        topic: row.try_get("topic")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        payload: serde_json::from_str(&payload).map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        created_at: row.try_get("created_at")?,
+    })
+}
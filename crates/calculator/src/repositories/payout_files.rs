@@ -0,0 +1,80 @@
+use crate::entities::payouts::{PayoutFile, PayoutFileFormat};
+use chrono::NaiveDate;
+use sqlx::{PgConnection, Row};
+
+// How many files were exported for the date, their message ids are numbered after it.
+pub async fn count_payout_files_on(conn: &mut PgConnection, execution_date: NaiveDate) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query("SELECT COUNT(*) AS count FROM payout_files WHERE execution_date = $1")
+        .bind(execution_date)
+        .fetch_one(conn)
+        .await?;
+
+    row.try_get("count")
+}
+
+pub async fn insert_payout_file(conn: &mut PgConnection, file: &PayoutFile) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO payout_files (id, message_id, execution_date, format, path, content, created_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+    .bind(file.id)
+    .bind(&file.message_id)
+    .bind(file.execution_date)
+    .bind(file.format.to_string())
+    .bind(&file.path)
+    .bind(&file.content)
+    .bind(file.created_at)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("INSERT INTO payout_file_disbursements (payout_file_id, disbursement_id) SELECT $1, UNNEST($2::UUID[])")
+        .bind(file.id)
+        .bind(&file.disbursement_ids)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn find_payout_file(conn: &mut PgConnection, message_id: &str) -> Result<Option<PayoutFile>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT f.id, f.message_id, f.execution_date, f.format, f.path, f.content, f.created_at, f.withdrawn_at, f.withdrawn_by, \
+         ARRAY(SELECT fd.disbursement_id FROM payout_file_disbursements fd WHERE fd.payout_file_id = f.id ORDER BY fd.disbursement_id) \
+             AS disbursement_ids \
+         FROM payout_files f WHERE f.message_id = $1"
+    )
+    .bind(message_id)
+    .fetch_optional(conn)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let format: String = row.try_get("format")?;
+
+    Ok(Some(PayoutFile {
+        id: row.try_get("id")?,
+        message_id: row.try_get("message_id")?,
+        execution_date: row.try_get("execution_date")?,
+        format: format.parse::<PayoutFileFormat>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        path: row.try_get("path")?,
+        disbursement_ids: row.try_get("disbursement_ids")?,
+        content: row.try_get("content")?,
+        created_at: row.try_get("created_at")?,
+        withdrawn_at: row.try_get("withdrawn_at")?,
+        withdrawn_by: row.try_get("withdrawn_by")?,
+    }))
+}
+
+// False when the file was already withdrawn.
+pub async fn mark_payout_file_withdrawn(conn: &mut PgConnection, file: &PayoutFile) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE payout_files SET withdrawn_at = $2, withdrawn_by = $3 WHERE id = $1 AND withdrawn_at IS NULL")
+        .bind(file.id)
+        .bind(file.withdrawn_at)
+        .bind(&file.withdrawn_by)
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
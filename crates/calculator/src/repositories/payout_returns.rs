@@ -0,0 +1,139 @@
+use crate::entities::payout_returns::{PayoutReturn, ReturnSource};
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+// The unique disbursement_id is what guarantees that a payout is returned (and paid again) only once.
+pub async fn insert_payout_return(conn: &mut PgConnection, payout_return: &PayoutReturn) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO payout_returns \
+         (id, disbursement_id, merchant_reference, returned_on, amount, currency, reason_code, reason, source, iban, bic, account_holder) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)"
+    )
+    .bind(payout_return.id)
+    .bind(payout_return.disbursement_id)
+    .bind(&payout_return.merchant_reference)
+    .bind(payout_return.returned_on)
+    .bind(payout_return.amount)
+    .bind(&payout_return.currency)
+    .bind(&payout_return.reason_code)
+    .bind(&payout_return.reason)
+    .bind(payout_return.source.to_string())
+    .bind(&payout_return.iban)
+    .bind(&payout_return.bic)
+    .bind(&payout_return.account_holder)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// The merchants whose payouts are held on `date`: a payout was returned and their bank details didn't change since.
+pub async fn find_held_merchant_references(conn: &mut PgConnection, date: NaiveDate) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT DISTINCT merchant_reference FROM payout_returns \
+         WHERE returned_on <= $1 AND (released_on IS NULL OR released_on > $1) \
+         ORDER BY merchant_reference"
+    )
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(|row| row.try_get("merchant_reference")).collect()
+}
+
+pub async fn find_held_payout_returns(conn: &mut PgConnection, merchant_reference: &str) -> Result<Vec<PayoutReturn>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, disbursement_id, merchant_reference, returned_on, amount, currency, reason_code, reason, source, iban, bic, account_holder, released_on, redisbursement_id \
+         FROM payout_returns WHERE merchant_reference = $1 AND released_on IS NULL ORDER BY returned_on, id"
+    )
+    .bind(merchant_reference)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(payout_return_from_row).collect()
+}
+
+pub async fn release_payout_returns(conn: &mut PgConnection, ids: &[Uuid], released_on: NaiveDate) -> Result<(), sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("UPDATE payout_returns SET released_on = $1 WHERE id = ANY($2) AND released_on IS NULL")
+        .bind(released_on)
+        .bind(ids)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// The returns to pay again on `date`: released by then and not paid yet, or paid by the disbursement of that same date
+// so a preview of an already committed date pays them again.
+pub async fn find_returns_to_redisburse(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    date: NaiveDate,
+) -> Result<Vec<PayoutReturn>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT r.id, r.disbursement_id, r.merchant_reference, r.returned_on, r.amount, r.currency, r.reason_code, r.reason, r.source, \
+         r.iban, r.bic, r.account_holder, r.released_on, r.redisbursement_id \
+         FROM payout_returns r \
+         LEFT JOIN disbursements d ON d.id = r.redisbursement_id \
+         WHERE r.merchant_reference = $1 AND r.released_on <= $2 \
+         AND (r.redisbursement_id IS NULL OR d.disbursed_on = $2) \
+         ORDER BY r.returned_on, r.id"
+    )
+    .bind(merchant_reference)
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(payout_return_from_row).collect()
+}
+
+pub async fn mark_returns_redisbursed(conn: &mut PgConnection, ids: &[Uuid], disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("UPDATE payout_returns SET redisbursement_id = $1 WHERE id = ANY($2) AND redisbursement_id IS NULL")
+        .bind(disbursement_id)
+        .bind(ids)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// The returns the voided disbursement paid again are paid by the next one.
+pub async fn reopen_returns_redisbursed_by(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE payout_returns SET redisbursement_id = NULL WHERE redisbursement_id = $1")
+        .bind(disbursement_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+fn payout_return_from_row(row: &PgRow) -> Result<PayoutReturn, sqlx::Error> {
+    let source: String = row.try_get("source")?;
+
+    Ok(PayoutReturn {
+        id: row.try_get("id")?,
+        disbursement_id: row.try_get("disbursement_id")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        returned_on: row.try_get("returned_on")?,
+        amount: row.try_get("amount")?,
+        currency: row.try_get("currency")?,
+        reason_code: row.try_get("reason_code")?,
+        reason: row.try_get("reason")?,
+        source: source.parse::<ReturnSource>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        iban: row.try_get("iban")?,
+        bic: row.try_get("bic")?,
+        account_holder: row.try_get("account_holder")?,
+        released_on: row.try_get("released_on")?,
+        redisbursement_id: row.try_get("redisbursement_id")?,
+    })
+}
@@ -0,0 +1,109 @@
+use anyhow::bail;
+use chrono::NaiveDate;
+use crate::entities::pricing_plans::{PricingPlan, PricingTier};
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+// A version (plan and valid_from) is immutable: receiving it again stores nothing, and receiving it with
+// other tiers or fixed fee is refused, a change of the plan is a new version with a later valid_from.
+// The other versions of the plan are kept, they still price the orders created while they were effective.
+// Runs in the transaction of the caller, with the merchant that carries the plan.
+pub async fn upsert_pricing_plan(conn: &mut PgConnection, plan: &PricingPlan) -> anyhow::Result<()> {
+    sqlx::query("INSERT INTO pricing_plans (code) VALUES ($1) ON CONFLICT (code) DO NOTHING")
+        .bind(&plan.code)
+        .execute(&mut *conn)
+        .await?;
+
+    let inserted = sqlx::query(
+        "INSERT INTO pricing_plan_versions (plan_code, valid_from, fixed_fee) VALUES ($1, $2, $3) \
+         ON CONFLICT (plan_code, valid_from) DO NOTHING \
+         RETURNING id"
+    )
+    .bind(&plan.code)
+    .bind(plan.valid_from)
+    .bind(plan.fixed_fee)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(inserted) = inserted else {
+        let stored = find_pricing_plan_version(conn, &plan.code, plan.valid_from).await?;
+        if stored.as_ref() != Some(plan) {
+            bail!("The pricing plan version {} already exists with other tiers or fixed fee", plan.version());
+        }
+        return Ok(());
+    };
+    let version_id: Uuid = inserted.try_get("id")?;
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO pricing_plan_tiers (version_id, from_amount, fee_rate) "
+    );
+
+    query_builder.push_values(&plan.tiers, |mut b, tier| {
+        b.push_bind(version_id)
+            .push_bind(tier.from_amount)
+            .push_bind(tier.fee_rate);
+    });
+
+    query_builder.build().execute(conn).await?;
+
+    Ok(())
+}
+
+async fn find_pricing_plan_version(
+    conn: &mut PgConnection,
+    plan_code: &str,
+    valid_from: NaiveDate,
+) -> Result<Option<PricingPlan>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT v.plan_code, v.valid_from, v.fixed_fee, t.from_amount, t.fee_rate \
+         FROM pricing_plan_versions v JOIN pricing_plan_tiers t ON t.version_id = v.id \
+         WHERE v.plan_code = $1 AND v.valid_from = $2 \
+         ORDER BY t.from_amount"
+    )
+    .bind(plan_code)
+    .bind(valid_from)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(plans_from_rows(&rows)?.pop())
+}
+
+// Every version of every plan, the default tiers included.
+pub async fn find_pricing_plans(conn: &mut PgConnection) -> Result<Vec<PricingPlan>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT v.plan_code, v.valid_from, v.fixed_fee, t.from_amount, t.fee_rate \
+         FROM pricing_plan_versions v JOIN pricing_plan_tiers t ON t.version_id = v.id \
+         ORDER BY v.plan_code, v.valid_from, t.from_amount"
+    )
+    .fetch_all(conn)
+    .await?;
+
+    plans_from_rows(&rows)
+}
+
+// One row per tier, sorted by plan code and valid_from.
+fn plans_from_rows(rows: &[PgRow]) -> Result<Vec<PricingPlan>, sqlx::Error> {
+    let mut plans: Vec<PricingPlan> = Vec::new();
+
+    for row in rows {
+        let code: String = row.try_get("plan_code")?;
+        let valid_from = row.try_get("valid_from")?;
+        let tier = PricingTier {
+            from_amount: row.try_get("from_amount")?,
+            fee_rate: row.try_get("fee_rate")?,
+        };
+
+        match plans.last_mut() {
+            Some(plan) if plan.code == code && plan.valid_from == valid_from => plan.tiers.push(tier),
+            _ => plans.push(PricingPlan {
+                code,
+                valid_from,
+                tiers: vec![tier],
+                fixed_fee: row.try_get("fixed_fee")?,
+            }),
+        }
+    }
+
+    Ok(plans)
+}
@@ -0,0 +1,119 @@
+use crate::entities::receivables::Receivable;
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+pub async fn insert_receivable(conn: &mut PgConnection, receivable: &Receivable) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO receivables (id, merchant_reference, disbursement_id, currency, amount, opened_on, flagged_for_collection) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+    .bind(receivable.id)
+    .bind(&receivable.merchant_reference)
+    .bind(receivable.disbursement_id)
+    .bind(&receivable.currency)
+    .bind(receivable.amount)
+    .bind(receivable.opened_on)
+    .bind(receivable.flagged_for_collection)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// The receivables to offset on `date`: opened before it and not settled yet, or settled by the disbursement
+// of that same date so a preview of an already committed date calculates the same offset again.
+pub async fn find_open_receivables(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    date: NaiveDate,
+) -> Result<Vec<Receivable>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT r.id, r.merchant_reference, r.disbursement_id, r.currency, r.amount, r.opened_on, r.flagged_for_collection, r.settled_disbursement_id \
+         FROM receivables r \
+         LEFT JOIN disbursements d ON d.id = r.settled_disbursement_id \
+         WHERE r.merchant_reference = $1 AND r.opened_on < $2 AND NOT r.flagged_for_collection \
+         AND (r.settled_disbursement_id IS NULL OR d.disbursed_on = $2) \
+         ORDER BY r.opened_on, r.id"
+    )
+    .bind(merchant_reference)
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(receivable_from_row).collect()
+}
+
+pub async fn settle_receivables(conn: &mut PgConnection, ids: &[Uuid], disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("UPDATE receivables SET settled_disbursement_id = $1 WHERE id = ANY($2) AND settled_disbursement_id IS NULL")
+        .bind(disbursement_id)
+        .bind(ids)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// The receivables the voided disbursement offset are open again.
+pub async fn reopen_receivables_settled_by(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE receivables SET settled_disbursement_id = NULL WHERE settled_disbursement_id = $1 AND disbursement_id <> $1")
+        .bind(disbursement_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn find_receivables_opened_by(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<Vec<Receivable>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, disbursement_id, currency, amount, opened_on, flagged_for_collection, settled_disbursement_id \
+         FROM receivables WHERE disbursement_id = $1 ORDER BY id"
+    )
+    .bind(disbursement_id)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(receivable_from_row).collect()
+}
+
+pub async fn find_receivables_opened_on(conn: &mut PgConnection, date: NaiveDate) -> Result<Vec<Receivable>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, disbursement_id, currency, amount, opened_on, flagged_for_collection, settled_disbursement_id \
+         FROM receivables WHERE opened_on = $1 ORDER BY merchant_reference"
+    )
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(receivable_from_row).collect()
+}
+
+// The open receivables above the negative balance limit, what finance has to collect from the merchants.
+pub async fn find_receivables_to_collect(conn: &mut PgConnection) -> Result<Vec<Receivable>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, disbursement_id, currency, amount, opened_on, flagged_for_collection, settled_disbursement_id \
+         FROM receivables WHERE flagged_for_collection AND settled_disbursement_id IS NULL ORDER BY opened_on, merchant_reference"
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(receivable_from_row).collect()
+}
+
+fn receivable_from_row(row: &PgRow) -> Result<Receivable, sqlx::Error> {
+    Ok(Receivable {
+        id: row.try_get("id")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        disbursement_id: row.try_get("disbursement_id")?,
+        currency: row.try_get("currency")?,
+        amount: row.try_get("amount")?,
+        opened_on: row.try_get("opened_on")?,
+        flagged_for_collection: row.try_get("flagged_for_collection")?,
+        settled_disbursement_id: row.try_get("settled_disbursement_id")?,
+    })
+}
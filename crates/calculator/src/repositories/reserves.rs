@@ -0,0 +1,163 @@
+use crate::entities::reserves::{Reserve, ReservePolicy};
+use chrono::NaiveDate;
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, PgPool, Row};
+use uuid::Uuid;
+
+// Loading the policies file again replaces the policies of those merchants.
+pub async fn upsert_reserve_policies(pool: &PgPool, policies: &[ReservePolicy]) -> Result<(), sqlx::Error> {
+    if policies.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new(
+        "INSERT INTO reserve_policies (merchant_reference, reserve_rate, hold_days) "
+    );
+
+    query_builder.push_values(policies, |mut b, policy| {
+        b.push_bind(&policy.merchant_reference)
+            .push_bind(policy.reserve_rate)
+            .push_bind(policy.hold_days as i32);
+    });
+
+    query_builder.push(
+        " ON CONFLICT (merchant_reference) DO UPDATE SET reserve_rate = EXCLUDED.reserve_rate, hold_days = EXCLUDED.hold_days"
+    );
+
+    let query = query_builder.build();
+    query.execute(pool).await?;
+
+    Ok(())
+}
+
+pub async fn find_reserve_policies(conn: &mut PgConnection) -> Result<Vec<ReservePolicy>, sqlx::Error> {
+    let rows = sqlx::query("SELECT merchant_reference, reserve_rate, hold_days FROM reserve_policies ORDER BY merchant_reference")
+        .fetch_all(conn)
+        .await?;
+
+    rows.iter()
+        .map(|row| {
+            let hold_days: i32 = row.try_get("hold_days")?;
+            Ok(ReservePolicy {
+                merchant_reference: row.try_get("merchant_reference")?,
+                reserve_rate: row.try_get("reserve_rate")?,
+                hold_days: hold_days as u32,
+            })
+        })
+        .collect()
+}
+
+pub async fn insert_reserve(conn: &mut PgConnection, reserve: &Reserve) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO reserves (id, merchant_reference, disbursement_id, currency, amount, held_on, release_on) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+    .bind(reserve.id)
+    .bind(&reserve.merchant_reference)
+    .bind(reserve.disbursement_id)
+    .bind(&reserve.currency)
+    .bind(reserve.amount)
+    .bind(reserve.held_on)
+    .bind(reserve.release_on)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// The reserves to pay back on `date`: not released yet, or released by the disbursement of that same date
+// so a preview of an already committed date calculates the same release again.
+pub async fn find_releasable_reserves(
+    conn: &mut PgConnection,
+    merchant_reference: &str,
+    date: NaiveDate,
+) -> Result<Vec<Reserve>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT r.id, r.merchant_reference, r.disbursement_id, r.currency, r.amount, r.held_on, r.release_on, r.released_disbursement_id \
+         FROM reserves r \
+         LEFT JOIN disbursements d ON d.id = r.released_disbursement_id \
+         WHERE r.merchant_reference = $1 AND r.release_on <= $2 \
+         AND (r.released_disbursement_id IS NULL OR d.disbursed_on = $2) \
+         ORDER BY r.held_on, r.id"
+    )
+    .bind(merchant_reference)
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(reserve_from_row).collect()
+}
+
+pub async fn mark_reserves_released(conn: &mut PgConnection, ids: &[Uuid], disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("UPDATE reserves SET released_disbursement_id = $1 WHERE id = ANY($2) AND released_disbursement_id IS NULL")
+        .bind(disbursement_id)
+        .bind(ids)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+// The reserves the voided disbursement released are releasable again.
+pub async fn reopen_reserves_released_by(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE reserves SET released_disbursement_id = NULL WHERE released_disbursement_id = $1 AND disbursement_id <> $1")
+        .bind(disbursement_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn find_reserves_held_by(conn: &mut PgConnection, disbursement_id: Uuid) -> Result<Vec<Reserve>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, disbursement_id, currency, amount, held_on, release_on, released_disbursement_id \
+         FROM reserves WHERE disbursement_id = $1 ORDER BY id"
+    )
+    .bind(disbursement_id)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(reserve_from_row).collect()
+}
+
+pub async fn find_reserves_held_on(conn: &mut PgConnection, date: NaiveDate) -> Result<Vec<Reserve>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, disbursement_id, currency, amount, held_on, release_on, released_disbursement_id \
+         FROM reserves WHERE held_on = $1 ORDER BY merchant_reference"
+    )
+    .bind(date)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(reserve_from_row).collect()
+}
+
+// What is still held back from the merchant, with the date each reserve is paid back from.
+pub async fn find_open_reserves(conn: &mut PgConnection, merchant_reference: &str) -> Result<Vec<Reserve>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, merchant_reference, disbursement_id, currency, amount, held_on, release_on, released_disbursement_id \
+         FROM reserves WHERE merchant_reference = $1 AND released_disbursement_id IS NULL ORDER BY release_on, id"
+    )
+    .bind(merchant_reference)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(reserve_from_row).collect()
+}
+
+fn reserve_from_row(row: &PgRow) -> Result<Reserve, sqlx::Error> {
+    Ok(Reserve {
+        id: row.try_get("id")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        disbursement_id: row.try_get("disbursement_id")?,
+        currency: row.try_get("currency")?,
+        amount: row.try_get("amount")?,
+        held_on: row.try_get("held_on")?,
+        release_on: row.try_get("release_on")?,
+        released_disbursement_id: row.try_get("released_disbursement_id")?,
+    })
+}
@@ -0,0 +1,58 @@
+use crate::entities::vat::{VatCharge, VatRule, VatTreatment};
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, PgPool, Row};
+
+// Loading the rules file again replaces the rules of those countries.
+pub async fn upsert_vat_rules(pool: &PgPool, rules: &[VatRule]) -> Result<(), sqlx::Error> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = sqlx::QueryBuilder::new("INSERT INTO vat_rules (country, standard_rate, eu_member) ");
+
+    query_builder.push_values(rules, |mut b, rule| {
+        b.push_bind(&rule.country)
+            .push_bind(rule.standard_rate)
+            .push_bind(rule.eu_member);
+    });
+
+    query_builder.push(
+        " ON CONFLICT (country) DO UPDATE SET standard_rate = EXCLUDED.standard_rate, eu_member = EXCLUDED.eu_member"
+    );
+
+    let query = query_builder.build();
+    query.execute(pool).await?;
+
+    Ok(())
+}
+
+pub async fn find_vat_rules(conn: &mut PgConnection) -> Result<Vec<VatRule>, sqlx::Error> {
+    let rows = sqlx::query("SELECT country, standard_rate, eu_member FROM vat_rules ORDER BY country")
+        .fetch_all(conn)
+        .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(VatRule {
+                country: row.try_get("country")?,
+                standard_rate: row.try_get("standard_rate")?,
+                eu_member: row.try_get("eu_member")?,
+            })
+        })
+        .collect()
+}
+
+// The vat_treatment and vat_rate columns of the records charged with VAT, both NULL when no VAT was calculated.
+pub(crate) fn vat_charge_from_row(row: &PgRow) -> Result<Option<VatCharge>, sqlx::Error> {
+    let treatment: Option<String> = row.try_get("vat_treatment")?;
+    let rate: Option<i32> = row.try_get("vat_rate")?;
+
+    treatment
+        .map(|treatment| {
+            Ok(VatCharge {
+                treatment: treatment.parse::<VatTreatment>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+                rate: rate.unwrap_or(0),
+            })
+        })
+        .transpose()
+}
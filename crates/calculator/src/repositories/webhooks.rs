@@ -0,0 +1,220 @@
+use crate::entities::outbox::OutboxEvent;
+use crate::entities::webhooks::{DeliveryAttempt, DeliveryStatus, WebhookDelivery, WebhookEndpoint};
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgConnection, Row};
+use uuid::Uuid;
+
+const ENDPOINT_COLUMNS: &str = "e.id AS endpoint_id, e.merchant_reference AS endpoint_merchant_reference, e.url, e.secret, e.topics, e.active, e.created_at AS endpoint_created_at";
+const DELIVERY_COLUMNS: &str = "d.id, d.endpoint_id, d.event_id, d.topic, d.merchant_reference, d.payload::TEXT AS payload, d.status, d.attempts, d.next_attempt_at, d.delivered_at, d.created_at";
+
+// Every condition is optional, an empty list of statuses is any status.
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryFilter {
+    pub merchant_reference: Option<String>,
+    pub statuses: Vec<DeliveryStatus>,
+    pub event_id: Option<Uuid>,
+    // the latest ones
+    pub limit: Option<i64>,
+}
+
+pub async fn insert_webhook_endpoint(conn: &mut PgConnection, endpoint: &WebhookEndpoint) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO webhook_endpoints (id, merchant_reference, url, secret, topics, active, created_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+    .bind(endpoint.id)
+    .bind(&endpoint.merchant_reference)
+    .bind(&endpoint.url)
+    .bind(&endpoint.secret)
+    .bind(&endpoint.topics)
+    .bind(endpoint.active)
+    .bind(endpoint.created_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn find_webhook_endpoints(conn: &mut PgConnection, merchant_reference: Option<&str>) -> Result<Vec<WebhookEndpoint>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM webhook_endpoints e WHERE ($1::TEXT IS NULL OR e.merchant_reference = $1) \
+         ORDER BY e.merchant_reference, e.created_at",
+        ENDPOINT_COLUMNS
+    ))
+    .bind(merchant_reference)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter().map(endpoint_from_row).collect()
+}
+
+// False when there's no such endpoint.
+pub async fn set_webhook_endpoint_active(conn: &mut PgConnection, id: Uuid, active: bool) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE webhook_endpoints SET active = $2 WHERE id = $1")
+        .bind(id)
+        .bind(active)
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+// A delivery per active endpoint of the merchant that wants the topic, in the transaction of the event so no event
+// is stored without its deliveries.
+pub async fn queue_webhook_deliveries(conn: &mut PgConnection, event: &OutboxEvent) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO webhook_deliveries (id, endpoint_id, event_id, topic, merchant_reference, payload, status, attempts, next_attempt_at, created_at) \
+         SELECT gen_random_uuid(), id, $1, $2, $3, $4::jsonb, 'PENDING', 0, $5, $5 \
+         FROM webhook_endpoints WHERE merchant_reference = $3 AND active AND $2 = ANY(topics)"
+    )
+    .bind(event.id)
+    .bind(&event.topic)
+    .bind(&event.merchant_reference)
+    .bind(event.payload.to_string())
+    .bind(event.created_at)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// The oldest pending delivery due by `now` of an active endpoint, locked while it is sent. Skipped by the other
+// senders instead of waiting, so they never send the same one twice at the same time.
+pub async fn find_due_delivery(
+    conn: &mut PgConnection,
+    now: DateTime<Utc>,
+) -> Result<Option<(WebhookDelivery, WebhookEndpoint)>, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        "SELECT {}, {} FROM webhook_deliveries d JOIN webhook_endpoints e ON e.id = d.endpoint_id \
+         WHERE d.status = 'PENDING' AND d.next_attempt_at <= $1 AND e.active \
+         ORDER BY d.next_attempt_at LIMIT 1 FOR UPDATE OF d SKIP LOCKED",
+        DELIVERY_COLUMNS, ENDPOINT_COLUMNS
+    ))
+    .bind(now)
+    .fetch_optional(conn)
+    .await?;
+
+    row.as_ref()
+        .map(|row| Ok((delivery_from_row(row)?, endpoint_from_row(row)?)))
+        .transpose()
+}
+
+pub async fn lock_delivery(conn: &mut PgConnection, id: Uuid) -> Result<Option<WebhookDelivery>, sqlx::Error> {
+    let row = sqlx::query(&format!("SELECT {} FROM webhook_deliveries d WHERE d.id = $1 FOR UPDATE", DELIVERY_COLUMNS))
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+
+    row.as_ref().map(delivery_from_row).transpose()
+}
+
+// The state of the delivery after the attempt and the attempt itself, in the delivery log.
+pub async fn save_delivery_attempt(conn: &mut PgConnection, delivery: &WebhookDelivery, attempt: &DeliveryAttempt) -> Result<(), sqlx::Error> {
+    save_delivery(&mut *conn, delivery).await?;
+
+    sqlx::query(
+        "INSERT INTO webhook_delivery_attempts (delivery_id, attempted_at, response_status, error, duration_ms) \
+         VALUES ($1, $2, $3, $4, $5)"
+    )
+    .bind(attempt.delivery_id)
+    .bind(attempt.attempted_at)
+    .bind(attempt.response_status)
+    .bind(&attempt.error)
+    .bind(attempt.duration_ms)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn save_delivery(conn: &mut PgConnection, delivery: &WebhookDelivery) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE webhook_deliveries SET status = $2, attempts = $3, next_attempt_at = $4, delivered_at = $5 WHERE id = $1"
+    )
+    .bind(delivery.id)
+    .bind(delivery.status.to_string())
+    .bind(delivery.attempts)
+    .bind(delivery.next_attempt_at)
+    .bind(delivery.delivered_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn find_deliveries(conn: &mut PgConnection, filter: &DeliveryFilter) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+    let mut query_builder = sqlx::QueryBuilder::new(format!("SELECT {} FROM webhook_deliveries d WHERE TRUE", DELIVERY_COLUMNS));
+
+    if !filter.statuses.is_empty() {
+        let statuses: Vec<String> = filter.statuses.iter().map(|status| status.to_string()).collect();
+        query_builder.push(" AND d.status = ANY(").push_bind(statuses).push(")");
+    }
+    if let Some(merchant_reference) = &filter.merchant_reference {
+        query_builder.push(" AND d.merchant_reference = ").push_bind(merchant_reference);
+    }
+    if let Some(event_id) = filter.event_id {
+        query_builder.push(" AND d.event_id = ").push_bind(event_id);
+    }
+    query_builder.push(" ORDER BY d.created_at DESC, d.id");
+    if let Some(limit) = filter.limit {
+        query_builder.push(" LIMIT ").push_bind(limit);
+    }
+
+    let rows = query_builder.build().fetch_all(conn).await?;
+
+    rows.iter().map(delivery_from_row).collect()
+}
+
+pub async fn find_delivery_attempts(conn: &mut PgConnection, delivery_id: Uuid) -> Result<Vec<DeliveryAttempt>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT delivery_id, attempted_at, response_status, error, duration_ms FROM webhook_delivery_attempts \
+         WHERE delivery_id = $1 ORDER BY attempted_at, id"
+    )
+    .bind(delivery_id)
+    .fetch_all(conn)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(DeliveryAttempt {
+                delivery_id: row.try_get("delivery_id")?,
+                attempted_at: row.try_get("attempted_at")?,
+                response_status: row.try_get("response_status")?,
+                error: row.try_get("error")?,
+                duration_ms: row.try_get("duration_ms")?,
+            })
+        })
+        .collect()
+}
+
+fn endpoint_from_row(row: &PgRow) -> Result<WebhookEndpoint, sqlx::Error> {
+    Ok(WebhookEndpoint {
+        id: row.try_get("endpoint_id")?,
+        merchant_reference: row.try_get("endpoint_merchant_reference")?,
+        url: row.try_get("url")?,
+        secret: row.try_get("secret")?,
+        topics: row.try_get("topics")?,
+        active: row.try_get("active")?,
+        created_at: row.try_get("endpoint_created_at")?,
+    })
+}
+
+fn delivery_from_row(row: &PgRow) -> Result<WebhookDelivery, sqlx::Error> {
+    let payload: String = row.try_get("payload")?;
+    let status: String = row.try_get("status")?;
+
+    Ok(WebhookDelivery {
+        id: row.try_get("id")?,
+        endpoint_id: row.try_get("endpoint_id")?,
+        event_id: row.try_get("event_id")?,
+        topic: row.try_get("topic")?,
+        merchant_reference: row.try_get("merchant_reference")?,
+        payload: serde_json::from_str(&payload).map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        status: status.parse::<DeliveryStatus>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        attempts: row.try_get("attempts")?,
+        next_attempt_at: row.try_get("next_attempt_at")?,
+        delivered_at: row.try_get("delivered_at")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
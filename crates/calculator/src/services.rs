@@ -0,0 +1,32 @@
+pub mod currencies;
+pub mod fx;
+pub mod calendar;
+pub mod time_zones;
+pub mod fees;
+pub mod pricing;
+pub mod order_adjustments;
+pub mod disbursement_calculator;
+pub mod reserves;
+pub mod receivables;
+pub mod vat;
+pub mod disbursement_status;
+pub mod disbursement_batches;
+pub mod run_diff;
+pub mod outbox;
+pub mod webhooks;
+pub mod webhook_client;
+pub mod ledger;
+pub mod payouts;
+pub mod sepa_credit_transfer;
+pub mod payout_csv;
+pub mod camt_053;
+pub mod reconciliation;
+pub mod reconciliation_csv;
+pub mod payout_return_files;
+pub mod payout_returns;
+pub mod invoices;
+pub mod invoice_html;
+pub mod ubl_invoice;
+pub mod statements;
+pub mod statement_csv;
+pub mod statement_html;
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use std::collections::{BTreeMap, BTreeSet};
+use crate::entities::merchants::{Merchant, RollConvention};
+use crate::settings::calendar::CalendarSettings;
+
+// The holidays of the euro (TARGET2 closing days) when the file doesn't say the currency.
+pub const DEFAULT_HOLIDAYS_CURRENCY: &str = "EUR";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BankHoliday {
+    pub date: NaiveDate,
+    // the payouts in this currency are not executed on it
+    pub currency: String,
+}
+
+// The days the bank executes transfers in a currency: every weekday that is not a holiday of that currency
+// (TARGET2 closing days for the euro, the UK bank holidays for the pound...).
+#[derive(Clone, Debug, Default)]
+pub struct BusinessCalendar {
+    holidays: BTreeMap<String, BTreeSet<NaiveDate>>,
+    // for the merchants without their own convention
+    roll_convention: RollConvention,
+}
+
+impl BusinessCalendar {
+    pub fn new(holidays: Vec<BankHoliday>, roll_convention: RollConvention) -> Self {
+        let mut by_currency: BTreeMap<String, BTreeSet<NaiveDate>> = BTreeMap::new();
+        for holiday in holidays {
+            by_currency.entry(holiday.currency).or_default().insert(holiday.date);
+        }
+
+        Self { holidays: by_currency, roll_convention }
+    }
+
+    // Only weekends are skipped for a currency without holidays.
+    pub fn is_business_day(&self, date: NaiveDate, currency: &str) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+            && !self.holidays.get(currency).is_some_and(|holidays| holidays.contains(&date))
+    }
+
+    pub fn roll_convention_for(&self, merchant: &Merchant) -> RollConvention {
+        merchant.roll_convention.unwrap_or(self.roll_convention)
+    }
+
+    // The business day a payout in `currency` scheduled on `date` is executed on.
+    pub fn roll(&self, date: NaiveDate, convention: RollConvention, currency: &str) -> NaiveDate {
+        let mut rolled = date;
+        while !self.is_business_day(rolled, currency) {
+            rolled = step(rolled, convention);
+        }
+        rolled
+    }
+
+    // Every scheduled day whose payout is executed on `date`: itself and the non business days next to it
+    // that roll to it (the weekend before a monday with NEXT). None when `date` is not a business day.
+    pub fn scheduled_days_paid_on(&self, date: NaiveDate, convention: RollConvention, currency: &str) -> Vec<NaiveDate> {
+        if !self.is_business_day(date, currency) {
+            return Vec::new();
+        }
+
+        let backwards = match convention {
+            RollConvention::Next => RollConvention::Previous,
+            RollConvention::Previous => RollConvention::Next,
+        };
+
+        let mut days = vec![date];
+        let mut skipped = step(date, backwards);
+        while !self.is_business_day(skipped, currency) {
+            days.push(skipped);
+            skipped = step(skipped, backwards);
+        }
+        days
+    }
+}
+
+fn step(date: NaiveDate, convention: RollConvention) -> NaiveDate {
+    match convention {
+        RollConvention::Next => date + Days::new(1),
+        RollConvention::Previous => date - Days::new(1),
+    }
+}
+
+// One holiday per row, `date;name;currency` with a header, see tests/fixtures/target2_holidays.csv.
+// Without the currency column (or with it empty) the holiday is a TARGET2 one.
+pub fn parse_holidays_csv(content: &str) -> Result<Vec<BankHoliday>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let mut holidays = Vec::new();
+
+    for result in reader.records() {
+        let record = result?;
+        let currency = record.get(2).map(str::trim).filter(|currency| !currency.is_empty()).unwrap_or(DEFAULT_HOLIDAYS_CURRENCY);
+        holidays.push(BankHoliday {
+            date: NaiveDate::parse_from_str(record[0].trim(), "%Y-%m-%d")?,
+            currency: currency.to_uppercase(),
+        });
+    }
+
+    Ok(holidays)
+}
+
+// Only weekends are skipped when no holidays file is configured.
+pub fn load_business_calendar(settings: &CalendarSettings) -> Result<BusinessCalendar> {
+    let holidays = match &settings.holidays_csv_path {
+        Some(path) => parse_holidays_csv(&std::fs::read_to_string(path)?)?,
+        None => Vec::new(),
+    };
+
+    Ok(BusinessCalendar::new(holidays, settings.roll_convention))
+}
@@ -0,0 +1,106 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::NaiveDate;
+use roxmltree::{Document, Node};
+use crate::entities::reconciliation::{BankEntry, BankStatement};
+use crate::services::currencies::parse_amount;
+
+const CAMT_053_NAMESPACE_PREFIX: &str = "urn:iso:std:iso:20022:tech:xsd:camt.053.001.";
+// what banks put in the end-to-end id when the ordering party didn't give one
+pub(crate) const NOT_PROVIDED: &str = "NOTPROVIDED";
+
+/*
+    ISO 20022 camt.053 (bank to customer statement), any version from 001.02 on: the elements we read didn't change,
+    only the status became a code (<Sts><Cd>BOOK</Cd></Sts>) in 001.08.
+    Only booked debits are kept, credits and pending entries are not payouts. An entry booked as a batch has one
+    transaction per payout (TxDtls), each with its own amount. An entry with a single transaction without amount takes the one of the entry.
+*/
+pub fn parse_camt_053(content: &str) -> Result<Vec<BankStatement>> {
+    let document = Document::parse(content)?;
+    let root = document.root_element();
+    if !root.tag_name().namespace().is_some_and(|namespace| namespace.starts_with(CAMT_053_NAMESPACE_PREFIX)) {
+        bail!("Not a camt.053 statement: {:?}", root.tag_name().namespace());
+    }
+
+    let report = child(root, "BkToCstmrStmt").ok_or_else(|| anyhow!("BkToCstmrStmt is missing"))?;
+    children(report, "Stmt").map(parse_statement).collect()
+}
+
+fn parse_statement(statement: Node) -> Result<BankStatement> {
+    let id = text(statement, &["Id"]).ok_or_else(|| anyhow!("Stmt/Id is missing"))?;
+    let iban = text(statement, &["Acct", "Id", "IBAN"]);
+
+    let mut entries = Vec::new();
+    for entry in children(statement, "Ntry") {
+        let status = text(entry, &["Sts"]).or_else(|| text(entry, &["Sts", "Cd"]));
+        if text(entry, &["CdtDbtInd"]).as_deref() != Some("DBIT") || status.as_deref() != Some("BOOK") {
+            continue;
+        }
+
+        let booked_on = text(entry, &["BookgDt", "Dt"])
+            .or_else(|| text(entry, &["BookgDt", "DtTm"]).map(|date_time| date_time.chars().take(10).collect()))
+            .ok_or_else(|| anyhow!("Entry without booking date in statement {}", id))?;
+        let booked_on = NaiveDate::parse_from_str(&booked_on, "%Y-%m-%d")?;
+        let bank_reference = text(entry, &["AcctSvcrRef"]);
+        let (entry_amount, entry_currency) = amount(entry, &["Amt"])?.ok_or_else(|| anyhow!("Entry without amount in statement {}", id))?;
+
+        let transactions: Vec<Node> = children(entry, "NtryDtls").flat_map(|details| children(details, "TxDtls")).collect();
+        if transactions.is_empty() {
+            entries.push(BankEntry { reference: None, bank_reference, booked_on, amount: entry_amount, currency: entry_currency });
+            continue;
+        }
+
+        let single = transactions.len() == 1;
+        for transaction in transactions {
+            let transaction_amount = match amount(transaction, &["AmtDtls", "TxAmt", "Amt"])? {
+                Some(amount) => Some(amount),
+                None => amount(transaction, &["Amt"])?,
+            };
+            let (amount, currency) = match transaction_amount {
+                Some(amount) => amount,
+                None if single => (entry_amount, entry_currency.clone()),
+                None => bail!("Batch entry {:?} has a transaction without amount", bank_reference),
+            };
+
+            entries.push(BankEntry {
+                reference: text(transaction, &["Refs", "EndToEndId"]).filter(|reference| reference != NOT_PROVIDED),
+                bank_reference: bank_reference.clone(),
+                booked_on,
+                amount,
+                currency,
+            });
+        }
+    }
+
+    Ok(BankStatement { id, iban, entries })
+}
+
+pub(crate) fn children<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+pub(crate) fn child<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+fn descendant<'a, 'input>(node: Node<'a, 'input>, path: &[&'static str]) -> Option<Node<'a, 'input>> {
+    path.iter().try_fold(node, |node, name| child(node, name))
+}
+
+// The trimmed text of the element at the path, None when it's missing or has no text (an element with children).
+pub(crate) fn text(node: Node, path: &[&'static str]) -> Option<String> {
+    descendant(node, path)
+        .and_then(|node| node.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+pub(crate) fn amount(node: Node, path: &[&'static str]) -> Result<Option<(i64, String)>> {
+    let Some(node) = descendant(node, path) else {
+        return Ok(None);
+    };
+    let currency = node.attribute("Ccy").ok_or_else(|| anyhow!("Amount without currency"))?.to_string();
+    let value = node.text().map(str::trim).unwrap_or_default();
+
+    Ok(Some((parse_amount(value, &currency)?, currency)))
+}
@@ -0,0 +1,45 @@
+use anyhow::{anyhow, Result};
+
+pub const DEFAULT_CURRENCY: &str = "EUR";
+
+// The decimals of the currency (see shared::currencies), amounts are stored as integers of that unit.
+pub fn minor_units(currency: &str) -> Result<u32> {
+    shared::currencies::minor_units(currency).ok_or_else(|| anyhow!("Unsupported currency: {}", currency))
+}
+
+// Minor units to the decimal representation banks expect: 12345 EUR -> "123.45", 12345 JPY -> "12345".
+pub fn format_amount(amount: i64, currency: &str) -> Result<String> {
+    let units = minor_units(currency)?;
+    let sign = if amount < 0 { "-" } else { "" };
+
+    if units == 0 {
+        return Ok(format!("{}{}", sign, amount.abs()));
+    }
+
+    let factor = 10_i64.pow(units);
+    Ok(format!(
+        "{}{}.{:0width$}",
+        sign,
+        amount.abs() / factor,
+        amount.abs() % factor,
+        width = units as usize
+    ))
+}
+
+// The other way around, for the amounts of the bank files: "123.45" EUR -> 12345. More decimals than the currency has are an error.
+pub fn parse_amount(value: &str, currency: &str) -> Result<i64> {
+    let units = minor_units(currency)?;
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, value),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if integer.is_empty() || fraction.len() > units as usize || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("Invalid {} amount: {}", currency, value));
+    }
+
+    let fraction = format!("{:0<width$}", fraction, width = units as usize);
+    let amount = format!("{}{}", integer, fraction).parse::<i64>().map_err(|_| anyhow!("Invalid {} amount: {}", currency, value))?;
+
+    Ok(sign * amount)
+}
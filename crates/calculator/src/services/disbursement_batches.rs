@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+use crate::entities::disbursement_batches::{BatchApproval, BatchStatus, BatchTotal, DisbursementBatch};
+use crate::entities::disbursements::Disbursement;
+
+pub fn new_batch(disbursed_on: NaiveDate, created_at: DateTime<Utc>) -> DisbursementBatch {
+    DisbursementBatch {
+        id: Uuid::new_v4(),
+        disbursed_on,
+        status: BatchStatus::Open,
+        totals: Vec::new(),
+        disbursement_ids: Vec::new(),
+        approvals: Vec::new(),
+        created_at,
+        approved_at: None,
+    }
+}
+
+pub fn batch_totals(disbursements: &[Disbursement]) -> Vec<BatchTotal> {
+    let mut by_currency: BTreeMap<&str, BatchTotal> = BTreeMap::new();
+    for disbursement in disbursements {
+        let total = by_currency.entry(disbursement.currency.as_str()).or_insert_with(|| BatchTotal {
+            currency: disbursement.currency.clone(),
+            disbursement_count: 0,
+            net_amount: 0,
+        });
+        total.disbursement_count += 1;
+        total.net_amount += disbursement.net_amount;
+    }
+
+    by_currency.into_values().collect()
+}
+
+fn disbursement_ids(disbursements: &[Disbursement]) -> Vec<Uuid> {
+    let mut ids: Vec<Uuid> = disbursements.iter().map(|disbursement| disbursement.id).collect();
+    ids.sort();
+    ids
+}
+
+// Four eyes: the first approval freezes the disbursements it was given and their totals, the second one has to come
+// from someone else and see the very same ones (see `withdraw_approvals` for what changes them in between).
+pub fn approve_batch(
+    batch: &mut DisbursementBatch,
+    approver: &str,
+    disbursements: &[Disbursement],
+    approved_at: DateTime<Utc>,
+) -> Result<BatchApproval> {
+    let approver = approver.trim();
+    if approver.is_empty() {
+        bail!("Batch {} can't be approved without an approver", batch.id);
+    }
+    if batch.status == BatchStatus::Approved {
+        bail!("Batch {} is already approved", batch.id);
+    }
+    if batch.status == BatchStatus::Closed {
+        bail!("Batch {} is closed, its disbursements were voided", batch.id);
+    }
+    if batch.approvals.iter().any(|approval| approval.approver.eq_ignore_ascii_case(approver)) {
+        bail!("Batch {} was already approved by {}, it needs a second approver", batch.id, approver);
+    }
+    if disbursements.is_empty() {
+        bail!("Batch {} has nothing to approve", batch.id);
+    }
+
+    let totals = batch_totals(disbursements);
+    let ids = disbursement_ids(disbursements);
+    if batch.approvals.is_empty() {
+        batch.totals = totals;
+        batch.disbursement_ids = ids;
+        batch.status = BatchStatus::PartiallyApproved;
+    } else {
+        if totals != batch.totals || ids != batch.disbursement_ids {
+            bail!("Batch {} changed since its first approval: {:?} were approved, now {:?}", batch.id, batch.totals, totals);
+        }
+        batch.status = BatchStatus::Approved;
+        batch.approved_at = Some(approved_at);
+    }
+
+    let approval = BatchApproval { approver: approver.to_string(), approved_at };
+    batch.approvals.push(approval.clone());
+
+    Ok(approval)
+}
+
+// A disbursement of the batch was voided before the batch was approved: a first approval was given to other
+// disbursements so it's withdrawn, and the batch starts over, or is closed when nothing is left to approve.
+// `waiting` are the disbursements of the batch still waiting for their approval. True when the batch changed.
+pub fn withdraw_approvals(batch: &mut DisbursementBatch, waiting: &[Disbursement]) -> bool {
+    if matches!(batch.status, BatchStatus::Approved | BatchStatus::Closed) {
+        return false;
+    }
+    if !waiting.is_empty() && batch.status == BatchStatus::Open {
+        return false;
+    }
+
+    batch.status = if waiting.is_empty() { BatchStatus::Closed } else { BatchStatus::Open };
+    batch.totals.clear();
+    batch.disbursement_ids.clear();
+    batch.approvals.clear();
+    true
+}
+
+// The payouts of a date are exported only when every batch of the date is approved (or closed, with nothing to pay).
+pub fn ensure_approved(batches: &[DisbursementBatch]) -> Result<()> {
+    if let Some(batch) = batches.iter().find(|batch| !matches!(batch.status, BatchStatus::Approved | BatchStatus::Closed)) {
+        bail!(
+            "Batch {} of {} is not approved ({} of 2 approvals), its payouts can't be exported",
+            batch.id,
+            batch.disbursed_on,
+            batch.approvals.len()
+        );
+    }
+
+    Ok(())
+}
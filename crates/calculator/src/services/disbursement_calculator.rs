@@ -0,0 +1,305 @@
+use anyhow::Result;
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::Serialize;
+use uuid::Uuid;
+use crate::entities::carried_balances::CarriedBalance;
+use crate::entities::reserves::Reserve;
+use crate::entities::receivables::Receivable;
+use crate::entities::disbursements::{Disbursement, DisbursementLine, DisbursementStatus, LineKind};
+use crate::entities::merchants::{DisbursementFrequency, Merchant, RollConvention};
+use crate::entities::monthly_fees::MonthlyFee;
+use crate::entities::order_adjustments::OrderAdjustment;
+use crate::entities::orders::Order;
+use crate::entities::pricing_plans::PricingPlan;
+use crate::services::calendar::BusinessCalendar;
+use crate::services::currencies::DEFAULT_CURRENCY;
+use crate::services::fees::{commission, FEE_TIERS_CURRENCY};
+use crate::services::fx::FxRates;
+use crate::services::pricing::PricingPlans;
+
+// Everything a run calculates for a given date, before (or without) persisting it.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct DisbursementRun {
+    pub disbursements: Vec<Disbursement>,
+    pub monthly_fees: Vec<MonthlyFee>,
+    // the disbursements below the minimum payout, not created
+    pub carried_balances: Vec<CarriedBalance>,
+    // held back by the disbursements of the run, their releases are lines of the disbursements
+    pub reserves: Vec<Reserve>,
+    // opened by the disbursements of the run that net negative, their offsets are lines of the disbursements
+    pub receivables: Vec<Receivable>,
+    // the merchants left out of the run because their VAT can't be charged, the others are still paid
+    pub skipped_merchants: Vec<String>,
+}
+
+pub fn is_disbursement_day(merchant: &Merchant, date: NaiveDate) -> bool {
+    if merchant.live_on > date {
+        return false;
+    }
+
+    let weekday = merchant.disbursement_weekday.unwrap_or(merchant.live_on.weekday());
+
+    match merchant.disbursement_frequency {
+        DisbursementFrequency::Daily => true,
+        // weekly merchants are disbursed on the same weekday as their live_on date, unless they chose another one
+        DisbursementFrequency::Weekly => date.weekday() == weekday,
+        DisbursementFrequency::Biweekly => {
+            let days_to_first = (7 + weekday.num_days_from_monday() - merchant.live_on.weekday().num_days_from_monday()) % 7;
+            let first = merchant.live_on + Days::new(days_to_first as u64);
+            date >= first && (date - first).num_days() % 14 == 0
+        }
+        // a day of month the month doesn't have (31 in April) falls on its last day
+        DisbursementFrequency::Monthly => {
+            let day = merchant.disbursement_day_of_month.unwrap_or(merchant.live_on.day());
+            date.day() == day.min(last_day_of_month(date).day())
+        }
+    }
+}
+
+// The merchant is paid on `date` when it's a business day of its payout currency and a scheduled disbursement day rolls to it.
+// The orders of the skipped days are folded into that payout, as it takes every order not disbursed yet.
+pub fn is_payout_day(merchant: &Merchant, calendar: &BusinessCalendar, date: NaiveDate) -> bool {
+    let currency = &merchant.payout_currency;
+    if date < merchant.live_on {
+        return false;
+    }
+
+    // offboarded merchants are paid what is left on the next business day
+    if merchant.offboarded_on.is_some_and(|offboarded_on| offboarded_on <= date) {
+        return calendar.is_business_day(date, currency);
+    }
+
+    let mut scheduled_days = calendar.scheduled_days_paid_on(date, calendar.roll_convention_for(merchant), currency);
+    // with PREVIOUS, the days right after live_on would roll back to before it, they are paid on the first business day instead
+    if date == calendar.roll(merchant.live_on, RollConvention::Next, currency) {
+        scheduled_days.extend(merchant.live_on.iter_days().take_while(|day| *day < date));
+    }
+
+    scheduled_days.into_iter().any(|scheduled| is_disbursement_day(merchant, scheduled))
+}
+
+// The minimum payout of the merchant, or the global one (in euro cents) converted to its payout currency.
+pub fn minimum_payout(merchant: &Merchant, global_minimum_payout: i64, rates: &FxRates, date: NaiveDate) -> Result<i64> {
+    match merchant.minimum_payout {
+        Some(minimum_payout) => Ok(minimum_payout),
+        None if global_minimum_payout == 0 => Ok(0),
+        None => Ok(rates.convert(global_minimum_payout, DEFAULT_CURRENCY, &merchant.payout_currency, date)?.amount),
+    }
+}
+
+// A disbursement below the minimum payout is carried forward instead of created: its orders and adjustments
+// stay pending and the next disbursement of the merchant takes them with the new ones.
+pub fn carry_forward(disbursement: &Disbursement, minimum_payout: i64) -> Option<CarriedBalance> {
+    if disbursement.net_amount >= minimum_payout {
+        return None;
+    }
+
+    Some(CarriedBalance {
+        merchant_reference: disbursement.merchant_reference.clone(),
+        carried_on: disbursement.disbursed_on,
+        currency: disbursement.currency.clone(),
+        net_amount: disbursement.net_amount,
+        minimum_payout,
+        disbursement_id: None,
+    })
+}
+
+// Nothing is carried into the next month nor after offboarding, so the last payout day of the month
+// (and every payout day once offboarded) pays everything pending whatever the minimum payout.
+pub fn must_flush(merchant: &Merchant, calendar: &BusinessCalendar, date: NaiveDate) -> bool {
+    if merchant.offboarded_on.is_some_and(|offboarded_on| offboarded_on <= date) {
+        return true;
+    }
+
+    date.iter_days()
+        .skip(1)
+        .take_while(|next| next.month() == date.month())
+        .all(|next| !is_payout_day(merchant, calendar, next))
+}
+
+// Groups the given orders and order adjustments (all of them created before `date`) into a single disbursement.
+// Adjustments of already disbursed orders go into the next one, the original disbursement is never touched.
+// Every line is converted to the payout currency of the merchant with the rates effective on `date`, the same rates
+// that pick the fee tier of the orders, charged with the version of the merchant pricing plan effective when they were created.
+pub fn build_disbursement(
+    merchant: &Merchant,
+    orders: &[Order],
+    adjustments: &[OrderAdjustment],
+    pricing: &PricingPlans,
+    rates: &FxRates,
+    date: NaiveDate,
+) -> Result<Option<Disbursement>> {
+    if orders.is_empty() && adjustments.is_empty() {
+        return Ok(None);
+    }
+
+    let currency = &merchant.payout_currency;
+    let mut lines = Vec::with_capacity(orders.len() + adjustments.len());
+
+    for order in orders {
+        let plan = pricing.for_order(merchant.pricing_plan.as_deref(), order.created_on(merchant.time_zone))?;
+        let fee_rate = order_fee_rate(order, plan, rates, date)?;
+        let fixed_fee = rates.convert(plan.fixed_fee, FEE_TIERS_CURRENCY, currency, date)?.amount;
+        let converted = rates.convert(order.amount, &order.currency, currency, date)?;
+        lines.push(DisbursementLine {
+            kind: LineKind::Order,
+            order_id: Some(order.id.clone()),
+            adjustment_id: None,
+            reserve_id: None,
+            receivable_id: None,
+            monthly_fee_id: None,
+            payout_return_id: None,
+            amount: converted.amount,
+            original_amount: order.amount,
+            original_currency: order.currency.clone(),
+            fx_rate: converted.rate,
+            fee_rate,
+            pricing_version: plan.version(),
+            fixed_fee,
+            fee_amount: commission(converted.amount, fee_rate) + fixed_fee,
+        });
+    }
+
+    for adjustment in adjustments {
+        // an order disbursed with its adjustment has just got its fee rate, on the same date the adjustment is converted,
+        // and that's the one reversed (the one the adjustment was booked with was only provisional)
+        let (fee_rate, pricing_version) = lines
+            .iter()
+            .find(|line| line.kind == LineKind::Order && line.order_id.as_ref() == Some(&adjustment.order_id))
+            .map_or((adjustment.fee_rate, adjustment.pricing_version.clone()), |line| (line.fee_rate, line.pricing_version.clone()));
+        let converted = rates.convert(adjustment.amount, &adjustment.currency, currency, date)?;
+        lines.push(DisbursementLine {
+            kind: LineKind::Adjustment,
+            order_id: Some(adjustment.order_id.clone()),
+            adjustment_id: Some(adjustment.id),
+            reserve_id: None,
+            receivable_id: None,
+            monthly_fee_id: None,
+            payout_return_id: None,
+            amount: converted.amount,
+            original_amount: adjustment.amount,
+            original_currency: adjustment.currency.clone(),
+            fx_rate: converted.rate,
+            fee_rate,
+            pricing_version,
+            fixed_fee: 0,
+            fee_amount: commission(converted.amount, fee_rate),
+        });
+    }
+
+    let mut disbursement = empty_disbursement(merchant, date);
+    disbursement.lines = lines;
+    disbursement.recalculate_totals();
+
+    Ok(Some(disbursement))
+}
+
+// A disbursement of the merchant without lines yet, in its payout currency.
+pub fn empty_disbursement(merchant: &Merchant, date: NaiveDate) -> Disbursement {
+    Disbursement {
+        id: Uuid::new_v4(),
+        reference: Disbursement::generate_reference(),
+        merchant_reference: merchant.merchant_reference.clone(),
+        disbursed_on: date,
+        currency: merchant.payout_currency.clone(),
+        gross_amount: 0,
+        fee_amount: 0,
+        vat: None,
+        fee_vat_amount: 0,
+        fee_gross_amount: 0,
+        net_amount: 0,
+        status: DisbursementStatus::Calculated,
+        lines: Vec::new(),
+    }
+}
+
+// The tier of an order is picked by its amount in euros, whatever currency it was paid in.
+pub fn order_fee_rate(order: &Order, plan: &PricingPlan, rates: &FxRates, date: NaiveDate) -> Result<i32> {
+    let converted = rates.convert(order.amount, &order.currency, FEE_TIERS_CURRENCY, date)?;
+    Ok(plan.fee_rate(converted.amount))
+}
+
+// Checks the minimum monthly fee of the month before `date` against the commissions
+// generated by the orders created in that month, both in the payout currency of the merchant.
+pub fn build_monthly_fee(
+    merchant: &Merchant,
+    previous_month_orders: &[Order],
+    pricing: &PricingPlans,
+    rates: &FxRates,
+    date: NaiveDate,
+) -> Result<Option<MonthlyFee>> {
+    // a merchant that was not live yet in the previous month has nothing to reach,
+    // and one offboarded before it is charged for the month it left, never again
+    if merchant.live_on >= first_day_of_month(date)
+        || merchant.offboarded_on.is_some_and(|offboarded_on| offboarded_on < previous_month(date))
+    {
+        return Ok(None);
+    }
+
+    let mut commissions_amount = 0;
+    for order in previous_month_orders {
+        let plan = pricing.for_order(merchant.pricing_plan.as_deref(), order.created_on(merchant.time_zone))?;
+        let fixed_fee = rates.convert(plan.fixed_fee, FEE_TIERS_CURRENCY, &merchant.payout_currency, date)?.amount;
+        let fee_rate = order_fee_rate(order, plan, rates, date)?;
+        let converted = rates.convert(order.amount, &order.currency, &merchant.payout_currency, date)?;
+        commissions_amount += commission(converted.amount, fee_rate) + fixed_fee;
+    }
+    let minimum_monthly_fee = merchant.minimum_monthly_fee as i64;
+    let amount = (minimum_monthly_fee - commissions_amount).max(0);
+
+    Ok(Some(MonthlyFee {
+        id: Uuid::new_v4(),
+        merchant_reference: merchant.merchant_reference.clone(),
+        month: previous_month(date),
+        currency: merchant.payout_currency.clone(),
+        commissions_amount,
+        minimum_monthly_fee,
+        amount,
+        vat: None,
+        vat_amount: 0,
+        gross_amount: amount,
+        calculated_on: date,
+        mode: merchant.monthly_fee_mode,
+        disbursement_id: None,
+    }))
+}
+
+// Takes the monthly fees of a merchant in deduct mode from its disbursement, one MONTHLY_FEE line each so every fee
+// is deducted once, VAT included. What the disbursement can't cover leaves it negative, carried forward like any negative balance.
+pub fn deduct_monthly_fees(disbursement: &mut Disbursement, monthly_fees: &[MonthlyFee], rates: &FxRates) -> Result<()> {
+    for monthly_fee in monthly_fees {
+        let converted = rates.convert(monthly_fee.gross_amount, &monthly_fee.currency, &disbursement.currency, disbursement.disbursed_on)?;
+        disbursement.lines.push(DisbursementLine {
+            kind: LineKind::MonthlyFee,
+            order_id: None,
+            adjustment_id: None,
+            reserve_id: None,
+            receivable_id: None,
+            monthly_fee_id: Some(monthly_fee.id),
+            payout_return_id: None,
+            amount: -converted.amount,
+            original_amount: -monthly_fee.gross_amount,
+            original_currency: monthly_fee.currency.clone(),
+            fx_rate: converted.rate,
+            fee_rate: 0,
+            pricing_version: String::new(),
+            fixed_fee: 0,
+            fee_amount: 0,
+        });
+    }
+
+    disbursement.recalculate_totals();
+    Ok(())
+}
+
+pub fn first_day_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("every month has a first day")
+}
+
+pub fn previous_month(date: NaiveDate) -> NaiveDate {
+    first_day_of_month(date) - Months::new(1)
+}
+
+pub fn last_day_of_month(date: NaiveDate) -> NaiveDate {
+    first_day_of_month(date) + Months::new(1) - Days::new(1)
+}
@@ -0,0 +1,46 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use crate::entities::disbursements::{Disbursement, DisbursementStatus, DisbursementStatusChange};
+
+// The actor of the changes made by the jobs (calculation, export, reconciliation), the others are made by a user.
+pub const SYSTEM_ACTOR: &str = "system";
+
+// The history row of a disbursement that was just calculated.
+pub fn calculated(disbursement: &Disbursement, changed_at: DateTime<Utc>) -> DisbursementStatusChange {
+    DisbursementStatusChange {
+        disbursement_id: disbursement.id,
+        from: None,
+        to: DisbursementStatus::Calculated,
+        actor: SYSTEM_ACTOR.to_string(),
+        note: None,
+        changed_at,
+    }
+}
+
+// Moves the disbursement to `to` when the transition is allowed, the change must be stored with it (see repositories::disbursements).
+pub fn change_status(
+    disbursement: &mut Disbursement,
+    to: DisbursementStatus,
+    actor: &str,
+    note: Option<String>,
+    changed_at: DateTime<Utc>,
+) -> Result<DisbursementStatusChange> {
+    if actor.trim().is_empty() {
+        bail!("The status of {} can't be changed without an actor", disbursement.reference);
+    }
+    if !disbursement.status.can_change_to(to) {
+        bail!("Disbursement {} can't go from {} to {}", disbursement.reference, disbursement.status, to);
+    }
+
+    let change = DisbursementStatusChange {
+        disbursement_id: disbursement.id,
+        from: Some(disbursement.status),
+        to,
+        actor: actor.trim().to_string(),
+        note,
+        changed_at,
+    };
+    disbursement.status = to;
+
+    Ok(change)
+}
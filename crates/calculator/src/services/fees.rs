@@ -0,0 +1,36 @@
+use anyhow::{bail, Result};
+
+// The tiers are defined in euros, orders in other currencies are converted to it to pick their rate.
+// The rates themselves are the DEFAULT pricing plan, seeded by the migrations (see entities::pricing_plans).
+pub const FEE_TIERS_CURRENCY: &str = "EUR";
+
+// We are dealing with money, so everything is calculated in minor units with integers
+// and the commission is rounded half up to the minor unit.
+// Negative amounts (refunds) are rounded the same way, so a full refund reverses exactly the charged fee.
+pub fn commission(amount: i64, rate: i32) -> i64 {
+    if amount < 0 {
+        return -commission(-amount, rate);
+    }
+
+    (amount * rate as i64 + 5_000) / 10_000
+}
+
+// "7.5" -> 750 basis points, between 0 and 100 %. For the rates of the files loaded by finance and risk.
+pub fn parse_percentage(value: &str) -> Result<i32> {
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if integer.is_empty() || fraction.len() > 2 || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        bail!("Invalid percentage: {}", value);
+    }
+
+    let rate = integer.parse::<i32>()? * 100 + format!("{:0<2}", fraction).parse::<i32>()?;
+    if rate > 10_000 {
+        bail!("Invalid percentage: {}", value);
+    }
+
+    Ok(rate)
+}
+
+// 750 basis points -> "7.50", the other way around.
+pub fn format_percentage(rate: i32) -> String {
+    format!("{}.{:02}", rate / 100, rate % 100)
+}
@@ -0,0 +1,114 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::NaiveDate;
+use crate::entities::fx_rates::FxRate;
+use crate::services::currencies::{minor_units, DEFAULT_CURRENCY};
+
+// Rates are stored as integers with 8 decimals, 0.86023 -> 86_023_000.
+pub const FX_RATE_SCALE: i64 = 100_000_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conversion {
+    pub amount: i64,
+    pub rate: i64,
+}
+
+// The rates known at a given moment, usually loaded from the fx_rates table for a disbursement run.
+#[derive(Clone, Debug, Default)]
+pub struct FxRates {
+    rates: Vec<FxRate>,
+}
+
+impl FxRates {
+    pub fn new(rates: Vec<FxRate>) -> Self {
+        Self { rates }
+    }
+
+    // The rate effective on `date`: the direct one, the inverse one, or crossed through EUR.
+    pub fn rate(&self, from: &str, to: &str, date: NaiveDate) -> Result<i64> {
+        if from == to {
+            return Ok(FX_RATE_SCALE);
+        }
+        if let Some(rate) = self.effective_rate(from, to, date) {
+            return Ok(rate);
+        }
+        if let Some(rate) = self.effective_rate(to, from, date) {
+            return Ok(divide_rounding(FX_RATE_SCALE as i128 * FX_RATE_SCALE as i128, rate as i128) as i64);
+        }
+        if from != DEFAULT_CURRENCY && to != DEFAULT_CURRENCY {
+            let to_default = self.rate(from, DEFAULT_CURRENCY, date)?;
+            let from_default = self.rate(DEFAULT_CURRENCY, to, date)?;
+            return Ok(divide_rounding(to_default as i128 * from_default as i128, FX_RATE_SCALE as i128) as i64);
+        }
+
+        Err(anyhow!("No {}/{} rate on or before {}", from, to, date))
+    }
+
+    // Converts an amount in the minor unit of `from` to the minor unit of `to`, rounding half up to it.
+    pub fn convert(&self, amount: i64, from: &str, to: &str, date: NaiveDate) -> Result<Conversion> {
+        let rate = self.rate(from, to, date)?;
+        if from == to {
+            return Ok(Conversion { amount, rate });
+        }
+
+        let numerator = amount as i128 * rate as i128 * 10_i128.pow(minor_units(to)?);
+        let denominator = FX_RATE_SCALE as i128 * 10_i128.pow(minor_units(from)?);
+
+        Ok(Conversion { amount: divide_rounding(numerator, denominator) as i64, rate })
+    }
+
+    fn effective_rate(&self, base: &str, quote: &str, date: NaiveDate) -> Option<i64> {
+        self.rates
+            .iter()
+            .filter(|rate| rate.base_currency == base && rate.quote_currency == quote && rate.rate_date <= date)
+            .max_by_key(|rate| rate.rate_date)
+            .map(|rate| rate.rate)
+    }
+}
+
+// Rounds half away from zero, so converting a refund gives exactly the negative of converting the order.
+fn divide_rounding(numerator: i128, denominator: i128) -> i128 {
+    let quotient = (numerator.abs() * 2 + denominator) / (denominator * 2);
+    if numerator < 0 { -quotient } else { quotient }
+}
+
+// The local rates file: `rate_date;base_currency;quote_currency;rate` with a header, e.g. `2023-01-02;EUR;GBP;0.88415`.
+pub fn parse_fx_rates_csv(content: &str) -> Result<Vec<FxRate>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .has_headers(true)
+        .from_reader(content.as_bytes());
+
+    let mut rates = Vec::new();
+
+    for result in reader.records() {
+        let record = result?;
+        let base_currency = record[1].trim().to_uppercase();
+        let quote_currency = record[2].trim().to_uppercase();
+        minor_units(&base_currency)?;
+        minor_units(&quote_currency)?;
+
+        rates.push(FxRate {
+            rate_date: NaiveDate::parse_from_str(record[0].trim(), "%Y-%m-%d")?,
+            base_currency,
+            quote_currency,
+            rate: parse_rate(record[3].trim())?,
+        });
+    }
+
+    Ok(rates)
+}
+
+// Parsed from the decimal text, a float would already lose precision.
+fn parse_rate(value: &str) -> Result<i64> {
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if integer.is_empty() || fraction.len() > 8 || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        bail!("Invalid fx rate: {}", value);
+    }
+
+    let rate = integer.parse::<i64>()? * FX_RATE_SCALE + format!("{:0<8}", fraction).parse::<i64>()?;
+    if rate == 0 {
+        bail!("Invalid fx rate: {}", value);
+    }
+
+    Ok(rate)
+}
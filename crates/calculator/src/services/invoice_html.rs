@@ -0,0 +1,80 @@
+use anyhow::Result;
+use crate::entities::invoices::{Invoice, InvoiceKind};
+use crate::entities::vat::VatTreatment;
+use crate::services::currencies::format_amount;
+use crate::services::fees::format_percentage;
+use crate::services::sepa_credit_transfer::escape;
+
+// A self contained page the merchant can open or print, no external stylesheet or template engine.
+pub fn to_invoice_html(invoice: &Invoice) -> Result<String> {
+    let title = match invoice.kind {
+        InvoiceKind::Invoice => "Invoice",
+        InvoiceKind::CreditNote => "Credit note",
+    };
+    let amount = |value: i64| -> Result<String> { Ok(format!("{} {}", format_amount(value, &invoice.currency)?, escape(&invoice.currency))) };
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n");
+    html.push_str("<html lang=\"en\">\n");
+    html.push_str("<head>\n");
+    html.push_str("  <meta charset=\"UTF-8\">\n");
+    html.push_str(&format!("  <title>{} {}</title>\n", title, escape(&invoice.number)));
+    html.push_str("  <style>body { font-family: sans-serif; } table { border-collapse: collapse; width: 100%; } th, td { padding: 4px 8px; border-bottom: 1px solid #ddd; } .amount { text-align: right; }</style>\n");
+    html.push_str("</head>\n");
+    html.push_str("<body>\n");
+
+    html.push_str(&format!("  <h1>{} {}</h1>\n", title, escape(&invoice.number)));
+    html.push_str("  <dl>\n");
+    html.push_str(&format!("    <dt>Issued on</dt><dd>{}</dd>\n", invoice.issued_on.format("%Y-%m-%d")));
+    html.push_str(&format!(
+        "    <dt>Period</dt><dd>{} to {}</dd>\n",
+        invoice.period_start.format("%Y-%m-%d"),
+        invoice.period_end.format("%Y-%m-%d")
+    ));
+    html.push_str(&format!("    <dt>From</dt><dd>{}</dd>\n", escape(&invoice.seller_name)));
+    html.push_str(&format!(
+        "    <dt>To</dt><dd>{} ({})</dd>\n",
+        escape(&invoice.customer_name),
+        escape(&invoice.merchant_reference)
+    ));
+    if let Some(vat_number) = &invoice.customer_vat_number {
+        html.push_str(&format!("    <dt>VAT number</dt><dd>{}</dd>\n", escape(vat_number)));
+    }
+    if let Some(credited_invoice_number) = &invoice.credited_invoice_number {
+        html.push_str(&format!("    <dt>Credits invoice</dt><dd>{}</dd>\n", escape(credited_invoice_number)));
+    }
+    if let Some(note) = &invoice.note {
+        html.push_str(&format!("    <dt>Note</dt><dd>{}</dd>\n", escape(note)));
+    }
+    html.push_str("  </dl>\n");
+
+    html.push_str("  <table>\n");
+    html.push_str("    <thead><tr><th>Description</th><th class=\"amount\">Quantity</th><th class=\"amount\">Unit price</th><th class=\"amount\">Amount</th></tr></thead>\n");
+    html.push_str("    <tbody>\n");
+    for line in &invoice.lines {
+        html.push_str(&format!(
+            "      <tr><td>{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n",
+            escape(&line.description),
+            line.quantity,
+            amount(line.unit_amount)?,
+            amount(line.amount)?
+        ));
+    }
+    html.push_str("    </tbody>\n");
+    html.push_str("    <tfoot>\n");
+    html.push_str(&format!("      <tr><th colspan=\"3\">Net</th><td class=\"amount\">{}</td></tr>\n", amount(invoice.net_amount)?));
+    let vat_label = match invoice.vat {
+        Some(vat) if vat.treatment == VatTreatment::Standard => format!("VAT {} %", format_percentage(vat.rate)),
+        Some(vat) if vat.treatment == VatTreatment::ReverseCharge => "VAT reverse charged to the customer".to_string(),
+        _ => "VAT not applicable".to_string(),
+    };
+    html.push_str(&format!("      <tr><th colspan=\"3\">{}</th><td class=\"amount\">{}</td></tr>\n", vat_label, amount(invoice.vat_amount)?));
+    html.push_str(&format!("      <tr><th colspan=\"3\">Total</th><td class=\"amount\">{}</td></tr>\n", amount(invoice.total_amount)?));
+    html.push_str("    </tfoot>\n");
+    html.push_str("  </table>\n");
+
+    html.push_str("</body>\n");
+    html.push_str("</html>\n");
+
+    Ok(html)
+}
@@ -0,0 +1,109 @@
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use uuid::Uuid;
+use crate::entities::invoices::{Invoice, InvoiceKind, InvoiceLine};
+use crate::entities::merchants::{Merchant, MonthlyFeeMode};
+use crate::entities::monthly_fees::MonthlyFee;
+use crate::services::disbursement_calculator::last_day_of_month;
+use crate::settings::invoices::InvoiceSettings;
+
+// INV-2023-000001, the sequence restarts every year and is taken from invoice_sequences in the same
+// transaction that inserts the invoice, so a failed issue never leaves a hole.
+pub fn invoice_number(kind: InvoiceKind, year: i32, sequence: i64) -> String {
+    format!("{}-{}-{:06}", kind.prefix(), year, sequence)
+}
+
+// Who the invoice is addressed to, the account holder when the merchant has a bank account.
+fn customer_name(merchant: &Merchant) -> String {
+    merchant
+        .bank_account
+        .as_ref()
+        .map(|bank_account| bank_account.account_holder.clone())
+        .unwrap_or_else(|| merchant.merchant_reference.clone())
+}
+
+// One line with the minimum monthly fee and one with the commissions already charged in the month,
+// what's left is the shortfall. The VAT is the one calculated with the fee (see services::vat).
+pub fn build_invoice(
+    monthly_fee: &MonthlyFee,
+    merchant: &Merchant,
+    number: String,
+    issued_on: NaiveDate,
+    settings: &InvoiceSettings,
+) -> Result<Invoice> {
+    if monthly_fee.mode != MonthlyFeeMode::Invoice {
+        bail!("The monthly fee of {} for {} is deducted from its disbursements", monthly_fee.merchant_reference, monthly_fee.month);
+    }
+    if monthly_fee.amount <= 0 {
+        bail!("The monthly fee of {} for {} has no shortfall to invoice", monthly_fee.merchant_reference, monthly_fee.month);
+    }
+
+    let month = monthly_fee.month.format("%B %Y");
+    let lines = vec![
+        invoice_line(format!("Minimum monthly fee, {}", month), monthly_fee.minimum_monthly_fee),
+        invoice_line(format!("Commissions charged in {}", month), -monthly_fee.commissions_amount),
+    ];
+    let net_amount: i64 = lines.iter().map(|line| line.amount).sum();
+
+    Ok(Invoice {
+        id: Uuid::new_v4(),
+        number,
+        kind: InvoiceKind::Invoice,
+        merchant_reference: monthly_fee.merchant_reference.clone(),
+        monthly_fee_id: monthly_fee.id,
+        credited_invoice_id: None,
+        credited_invoice_number: None,
+        note: None,
+        issued_on,
+        period_start: monthly_fee.month,
+        period_end: last_day_of_month(monthly_fee.month),
+        seller_name: settings.seller_name.clone(),
+        customer_name: customer_name(merchant),
+        customer_vat_number: merchant.vat_number.clone(),
+        currency: monthly_fee.currency.clone(),
+        lines,
+        net_amount,
+        vat: monthly_fee.vat,
+        vat_amount: monthly_fee.vat_amount,
+        total_amount: net_amount + monthly_fee.vat_amount,
+    })
+}
+
+// Reverses the whole invoice, same lines and VAT with the opposite sign. A corrected invoice is issued
+// again for the monthly fee once its invoice is credited.
+pub fn build_credit_note(invoice: &Invoice, number: String, issued_on: NaiveDate, note: Option<String>) -> Result<Invoice> {
+    if invoice.kind != InvoiceKind::Invoice {
+        bail!("{} is a credit note, only invoices can be credited", invoice.number);
+    }
+    if issued_on < invoice.issued_on {
+        bail!("The credit note can't be issued before the invoice {} ({})", invoice.number, invoice.issued_on);
+    }
+
+    Ok(Invoice {
+        id: Uuid::new_v4(),
+        number,
+        kind: InvoiceKind::CreditNote,
+        credited_invoice_id: Some(invoice.id),
+        credited_invoice_number: Some(invoice.number.clone()),
+        note,
+        issued_on,
+        lines: invoice
+            .lines
+            .iter()
+            .map(|line| InvoiceLine { unit_amount: -line.unit_amount, amount: -line.amount, ..line.clone() })
+            .collect(),
+        net_amount: -invoice.net_amount,
+        vat_amount: -invoice.vat_amount,
+        total_amount: -invoice.total_amount,
+        ..invoice.clone()
+    })
+}
+
+fn invoice_line(description: String, unit_amount: i64) -> InvoiceLine {
+    InvoiceLine {
+        description,
+        quantity: 1,
+        unit_amount,
+        amount: unit_amount,
+    }
+}
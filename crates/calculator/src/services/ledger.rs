@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use chrono::NaiveDate;
+use crate::entities::disbursements::{Disbursement, LineKind};
+use crate::entities::ledger::{Account, EntryKind, JournalEntry, Posting};
+use crate::entities::monthly_fees::MonthlyFee;
+use crate::entities::order_adjustments::OrderAdjustment;
+use crate::entities::payout_returns::PayoutReturn;
+use crate::entities::orders::Order;
+
+// The shopper pays the order to us, and we owe it to the merchant, in the currency of the order.
+// Booked on its UTC day, like the cash it moves.
+pub fn order_entry(order: &Order) -> JournalEntry {
+    JournalEntry::new(EntryKind::Order, order.id.clone(), order.merchant_reference.clone(), order.created_at.date_naive())
+        .transfer(Account::Cash, Account::MerchantPayable, order.amount, &order.currency)
+}
+
+// Negative for refunds: we give the money back to the shopper and owe less to the merchant.
+pub fn adjustment_entry(adjustment: &OrderAdjustment) -> JournalEntry {
+    JournalEntry::new(
+        EntryKind::Adjustment,
+        adjustment.id.to_string(),
+        adjustment.merchant_reference.clone(),
+        adjustment.created_at,
+    )
+    .transfer(Account::Cash, Account::MerchantPayable, adjustment.amount, &adjustment.currency)
+}
+
+// What we owe the merchant in the currencies of its orders is first converted to the payout currency,
+// the released reserves are added to it, the commissions with their VAT, the new reserve, the monthly fees deducted and the receivables
+// offset are taken from it, and the rest is paid out (what is still missing becomes a receivable of the merchant).
+pub fn disbursement_entries(disbursement: &Disbursement) -> Vec<JournalEntry> {
+    let source_id = disbursement.id.to_string();
+
+    let conversion = conversion_entry(disbursement);
+
+    let reserve = reserve_entry(disbursement);
+
+    let monthly_fee_deduction = monthly_fee_deduction_entry(disbursement);
+
+    let receivable = receivable_entry(disbursement);
+
+    let commission = JournalEntry::new(
+        EntryKind::Commission,
+        source_id.clone(),
+        disbursement.merchant_reference.clone(),
+        disbursement.disbursed_on,
+    )
+    .transfer(Account::MerchantPayable, Account::CommissionRevenue, disbursement.fee_amount, &disbursement.currency)
+    .transfer(Account::MerchantPayable, Account::VatPayable, disbursement.fee_vat_amount, &disbursement.currency);
+
+    let payout = JournalEntry::new(
+        EntryKind::Payout,
+        source_id,
+        disbursement.merchant_reference.clone(),
+        disbursement.disbursed_on,
+    )
+    .transfer(Account::MerchantPayable, Account::Cash, disbursement.net_amount, &disbursement.currency);
+
+    vec![conversion, reserve, monthly_fee_deduction, receivable, commission, payout]
+}
+
+// Voiding a disbursement takes back everything it booked: its orders are owed to the merchant again, the fees and
+// receivables it collected are open again, and the payout never left our account.
+pub fn void_entry(disbursement: &Disbursement, voided_on: NaiveDate) -> JournalEntry {
+    let mut entry = JournalEntry::new(EntryKind::Void, disbursement.id.to_string(), disbursement.merchant_reference.clone(), voided_on);
+    entry.postings = disbursement_entries(disbursement)
+        .into_iter()
+        .flat_map(|booked| booked.postings)
+        .map(|posting| Posting { amount: -posting.amount, ..posting })
+        .collect();
+    entry
+}
+
+// The fee was booked into MONTHLY_FEE_RECEIVABLE when it was calculated, deducting it collects it from what we owe the merchant.
+fn monthly_fee_deduction_entry(disbursement: &Disbursement) -> JournalEntry {
+    disbursement.lines.iter().filter(|line| line.kind == LineKind::MonthlyFee).fold(
+        JournalEntry::new(
+            EntryKind::MonthlyFeeDeduction,
+            disbursement.id.to_string(),
+            disbursement.merchant_reference.clone(),
+            disbursement.disbursed_on,
+        ),
+        |entry, line| {
+            entry.transfer(Account::MerchantPayable, Account::MonthlyFeeReceivable, -line.original_amount, &line.original_currency)
+        },
+    )
+}
+
+// Offsets are booked in the currency the receivable was opened in, like released reserves.
+fn receivable_entry(disbursement: &Disbursement) -> JournalEntry {
+    disbursement.lines.iter().fold(
+        JournalEntry::new(
+            EntryKind::Receivable,
+            disbursement.id.to_string(),
+            disbursement.merchant_reference.clone(),
+            disbursement.disbursed_on,
+        ),
+        |entry, line| match line.kind {
+            LineKind::Receivable => {
+                entry.transfer(Account::MerchantReceivable, Account::MerchantPayable, line.amount, &disbursement.currency)
+            }
+            LineKind::ReceivableOffset => {
+                entry.transfer(Account::MerchantPayable, Account::MerchantReceivable, -line.original_amount, &line.original_currency)
+            }
+            _ => entry,
+        },
+    )
+}
+
+// The reserve is still owed to the merchant, it only moves to MERCHANT_RESERVE until it is released
+// (in the currency it was held in, the conversion entry takes it to the payout currency).
+fn reserve_entry(disbursement: &Disbursement) -> JournalEntry {
+    disbursement.lines.iter().fold(
+        JournalEntry::new(
+            EntryKind::Reserve,
+            disbursement.id.to_string(),
+            disbursement.merchant_reference.clone(),
+            disbursement.disbursed_on,
+        ),
+        |entry, line| match line.kind {
+            LineKind::Reserve => {
+                entry.transfer(Account::MerchantPayable, Account::MerchantReserve, -line.amount, &disbursement.currency)
+            }
+            LineKind::ReserveRelease => {
+                entry.transfer(Account::MerchantReserve, Account::MerchantPayable, line.original_amount, &line.original_currency)
+            }
+            _ => entry,
+        },
+    )
+}
+
+// One pair of legs per original currency, going through FX_CLEARING. Empty when nothing was converted.
+fn conversion_entry(disbursement: &Disbursement) -> JournalEntry {
+    let mut by_currency: BTreeMap<&str, (i64, i64)> = BTreeMap::new();
+    for line in disbursement.lines.iter().filter(|line| line.original_currency != disbursement.currency) {
+        let totals = by_currency.entry(line.original_currency.as_str()).or_default();
+        totals.0 += line.original_amount;
+        totals.1 += line.amount;
+    }
+
+    by_currency.into_iter().fold(
+        JournalEntry::new(
+            EntryKind::Conversion,
+            disbursement.id.to_string(),
+            disbursement.merchant_reference.clone(),
+            disbursement.disbursed_on,
+        ),
+        |entry, (currency, (original_amount, converted_amount))| {
+            entry
+                .transfer(Account::MerchantPayable, Account::FxClearing, original_amount, currency)
+                .transfer(Account::FxClearing, Account::MerchantPayable, converted_amount, &disbursement.currency)
+        },
+    )
+}
+
+// The merchant is charged the fee with its VAT, the VAT part is owed to the tax authority.
+pub fn monthly_fee_entry(monthly_fee: &MonthlyFee) -> JournalEntry {
+    JournalEntry::new(
+        EntryKind::MonthlyFee,
+        format!("{}:{}", monthly_fee.merchant_reference, monthly_fee.month),
+        monthly_fee.merchant_reference.clone(),
+        monthly_fee.calculated_on,
+    )
+    .transfer(Account::MonthlyFeeReceivable, Account::MonthlyFeeRevenue, monthly_fee.amount, &monthly_fee.currency)
+    .transfer(Account::MonthlyFeeReceivable, Account::VatPayable, monthly_fee.vat_amount, &monthly_fee.currency)
+}
+
+// The returned payout is back in our account and owed to the merchant again, until a RETURNED_PAYOUT line pays it.
+// Booked against the returned disbursement, like its payout, so a payout is returned only once.
+pub fn payout_return_entry(payout_return: &PayoutReturn) -> JournalEntry {
+    JournalEntry::new(
+        EntryKind::PayoutReturn,
+        payout_return.disbursement_id.to_string(),
+        payout_return.merchant_reference.clone(),
+        payout_return.returned_on,
+    )
+    .transfer(Account::Cash, Account::MerchantPayable, payout_return.amount, &payout_return.currency)
+}
@@ -0,0 +1,81 @@
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use uuid::Uuid;
+use crate::entities::order_adjustments::{AdjustmentKind, OrderAdjustment};
+use crate::entities::orders::Order;
+
+// `current_amount` is the order amount after the adjustments it already had,
+// `refunded_amount` defaults to all of it. The fee is reversed with the rate (and pricing version) of the order.
+// `event_id` is the id of the order_refunded event, an adjustment is booked once per event.
+pub fn build_refund(
+    event_id: Uuid,
+    order: &Order,
+    current_amount: i64,
+    fee_rate: i32,
+    pricing_version: &str,
+    refunded_amount: Option<i64>,
+    date: NaiveDate,
+) -> Result<OrderAdjustment> {
+    let refunded_amount = refunded_amount.unwrap_or(current_amount);
+
+    if refunded_amount <= 0 || refunded_amount > current_amount {
+        bail!(
+            "Cannot refund {} of order {}, the refundable amount is {}",
+            refunded_amount,
+            order.id,
+            current_amount
+        );
+    }
+
+    Ok(build_adjustment(event_id, order, AdjustmentKind::Refund, -refunded_amount, fee_rate, pricing_version, date))
+}
+
+pub fn build_correction(
+    event_id: Uuid,
+    order: &Order,
+    current_amount: i64,
+    fee_rate: i32,
+    pricing_version: &str,
+    corrected_amount: i64,
+    date: NaiveDate,
+) -> Result<OrderAdjustment> {
+    if corrected_amount < 0 {
+        bail!("Cannot correct order {} to a negative amount ({})", order.id, corrected_amount);
+    }
+    if corrected_amount == current_amount {
+        bail!("Order {} already has an amount of {}", order.id, corrected_amount);
+    }
+
+    Ok(build_adjustment(
+        event_id,
+        order,
+        AdjustmentKind::Correction,
+        corrected_amount - current_amount,
+        fee_rate,
+        pricing_version,
+        date,
+    ))
+}
+
+fn build_adjustment(
+    event_id: Uuid,
+    order: &Order,
+    kind: AdjustmentKind,
+    amount: i64,
+    fee_rate: i32,
+    pricing_version: &str,
+    date: NaiveDate,
+) -> OrderAdjustment {
+    OrderAdjustment {
+        id: Uuid::new_v4(),
+        event_id,
+        order_id: order.id.clone(),
+        merchant_reference: order.merchant_reference.clone(),
+        kind,
+        amount,
+        currency: order.currency.clone(),
+        fee_rate,
+        pricing_version: pricing_version.to_string(),
+        created_at: date,
+    }
+}
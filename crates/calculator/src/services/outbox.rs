@@ -0,0 +1,86 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use crate::entities::disbursements::{Disbursement, DisbursementStatusChange};
+use crate::entities::monthly_fees::MonthlyFee;
+use crate::entities::outbox::OutboxEvent;
+
+pub const DISBURSEMENT_CREATED: &str = "disbursement_created";
+pub const MONTHLY_FEE_CALCULATED: &str = "monthly_fee_calculated";
+pub const DISBURSEMENT_STATUS_CHANGED: &str = "disbursement_status_changed";
+
+// The amounts travel in the minor unit of their currency, like in the events we consume. The period is the first and
+// the last day of the sales it pays (see repositories::disbursements::find_sales_period), null without sales.
+pub fn disbursement_created(disbursement: &Disbursement, sales_period: Option<(NaiveDate, NaiveDate)>, created_at: DateTime<Utc>) -> OutboxEvent {
+    event(
+        DISBURSEMENT_CREATED,
+        &disbursement.merchant_reference,
+        json!({
+            "disbursement_id": disbursement.id,
+            "reference": disbursement.reference,
+            "disbursed_on": disbursement.disbursed_on,
+            "period_from": sales_period.map(|(from, _)| from),
+            "period_to": sales_period.map(|(_, to)| to),
+            "currency": disbursement.currency,
+            "gross_amount": disbursement.gross_amount,
+            "fee_amount": disbursement.fee_amount,
+            "fee_vat_amount": disbursement.fee_vat_amount,
+            "net_amount": disbursement.net_amount,
+            "status": disbursement.status,
+            "order_count": disbursement.lines.iter().filter(|line| line.kind.is_sale()).count(),
+        }),
+        created_at,
+    )
+}
+
+pub fn monthly_fee_calculated(monthly_fee: &MonthlyFee, created_at: DateTime<Utc>) -> OutboxEvent {
+    event(
+        MONTHLY_FEE_CALCULATED,
+        &monthly_fee.merchant_reference,
+        json!({
+            "monthly_fee_id": monthly_fee.id,
+            "month": monthly_fee.month,
+            "currency": monthly_fee.currency,
+            "commissions_amount": monthly_fee.commissions_amount,
+            "minimum_monthly_fee": monthly_fee.minimum_monthly_fee,
+            "amount": monthly_fee.amount,
+            "vat_amount": monthly_fee.vat_amount,
+            "gross_amount": monthly_fee.gross_amount,
+            "mode": monthly_fee.mode,
+            "calculated_on": monthly_fee.calculated_on,
+        }),
+        created_at,
+    )
+}
+
+// The change only knows the disbursement id, the reference is what the merchants and the bank know it by.
+pub fn disbursement_status_changed(reference: &str, merchant_reference: &str, change: &DisbursementStatusChange) -> OutboxEvent {
+    event(
+        DISBURSEMENT_STATUS_CHANGED,
+        merchant_reference,
+        json!({
+            "disbursement_id": change.disbursement_id,
+            "reference": reference,
+            "from": change.from,
+            "to": change.to,
+            "actor": change.actor,
+            "note": change.note,
+            "changed_at": change.changed_at,
+        }),
+        change.changed_at,
+    )
+}
+
+fn event(topic: &str, merchant_reference: &str, mut payload: Value, created_at: DateTime<Utc>) -> OutboxEvent {
+    let id = Uuid::new_v4();
+    payload["event_id"] = json!(id);
+    payload["merchant_reference"] = json!(merchant_reference);
+
+    OutboxEvent {
+        id,
+        topic: topic.to_string(),
+        merchant_reference: merchant_reference.to_string(),
+        payload,
+        created_at,
+    }
+}
@@ -0,0 +1,25 @@
+use anyhow::Result;
+use crate::entities::payouts::PayoutBatch;
+use crate::services::currencies::format_amount;
+
+// The same batch as the pain.001 file, for the banks that don't accept XML and the payouts that aren't in euros.
+pub fn to_payout_csv(batch: &PayoutBatch) -> Result<String> {
+    let mut writer = csv::WriterBuilder::new().delimiter(b';').from_writer(Vec::new());
+
+    writer.write_record(["reference", "merchant_reference", "account_holder", "iban", "bic", "amount", "currency", "execution_date"])?;
+
+    for instruction in &batch.instructions {
+        writer.write_record([
+            instruction.reference.as_str(),
+            instruction.merchant_reference.as_str(),
+            instruction.creditor.account_holder.as_str(),
+            instruction.creditor.iban.as_str(),
+            instruction.creditor.bic.as_str(),
+            &format_amount(instruction.amount, &instruction.currency)?,
+            instruction.currency.as_str(),
+            &batch.execution_date.format("%Y-%m-%d").to_string(),
+        ])?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
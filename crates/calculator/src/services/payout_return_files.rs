@@ -0,0 +1,114 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::NaiveDate;
+use roxmltree::{Document, Node};
+use crate::entities::payout_returns::{ReturnNotice, ReturnSource};
+use crate::services::camt_053::{amount, child, children, text, NOT_PROVIDED};
+
+const PAIN_002_NAMESPACE_PREFIX: &str = "urn:iso:std:iso:20022:tech:xsd:pain.002.001.";
+const CAMT_054_NAMESPACE_PREFIX: &str = "urn:iso:std:iso:20022:tech:xsd:camt.054.001.";
+// the status of a transfer the bank rejected
+const REJECTED: &str = "RJCT";
+
+// The banks report the returned payouts either as a pain.002 rejecting the transfers of our pain.001,
+// or as a camt.054 crediting our account with the return information, the file tells which one it is.
+pub fn parse_return_file(content: &str) -> Result<Vec<ReturnNotice>> {
+    let document = Document::parse(content)?;
+    let root = document.root_element();
+    let namespace = root.tag_name().namespace().unwrap_or_default();
+
+    if namespace.starts_with(PAIN_002_NAMESPACE_PREFIX) {
+        parse_pain_002(root)
+    } else if namespace.starts_with(CAMT_054_NAMESPACE_PREFIX) {
+        parse_camt_054(root)
+    } else {
+        bail!("Not a pain.002 nor a camt.054 file: {:?}", namespace)
+    }
+}
+
+/*
+    ISO 20022 pain.002 (customer payment status report): only the rejected transactions are returns, the accepted
+    and pending ones are left out. The report has no booking date, the payouts are returned on the day it was created.
+*/
+fn parse_pain_002(root: Node) -> Result<Vec<ReturnNotice>> {
+    let report = child(root, "CstmrPmtStsRpt").ok_or_else(|| anyhow!("CstmrPmtStsRpt is missing"))?;
+    let returned_on = text(report, &["GrpHdr", "CreDtTm"])
+        .map(|date_time| date_time.chars().take(10).collect::<String>())
+        .ok_or_else(|| anyhow!("GrpHdr/CreDtTm is missing"))?;
+    let returned_on = NaiveDate::parse_from_str(&returned_on, "%Y-%m-%d")?;
+
+    let mut notices = Vec::new();
+    for transaction in children(report, "OrgnlPmtInfAndSts").flat_map(|payment| children(payment, "TxInfAndSts")) {
+        if text(transaction, &["TxSts"]).as_deref() != Some(REJECTED) {
+            continue;
+        }
+
+        let reference = text(transaction, &["OrgnlEndToEndId"])
+            .filter(|reference| reference != NOT_PROVIDED)
+            .ok_or_else(|| anyhow!("Rejected transaction without end-to-end id"))?;
+        let (amount, currency) = amount(transaction, &["OrgnlTxRef", "Amt", "InstdAmt"])?.unzip();
+
+        notices.push(ReturnNotice {
+            reference,
+            returned_on,
+            amount,
+            currency,
+            reason_code: text(transaction, &["StsRsnInf", "Rsn", "Cd"]),
+            reason: text(transaction, &["StsRsnInf", "AddtlInf"]),
+            source: ReturnSource::Pain002,
+        });
+    }
+
+    Ok(notices)
+}
+
+/*
+    ISO 20022 camt.054 (debit credit notification): a returned payout is a booked credit whose transaction
+    has the return information (RtrInf), the other credits are not returns.
+*/
+fn parse_camt_054(root: Node) -> Result<Vec<ReturnNotice>> {
+    let report = child(root, "BkToCstmrDbtCdtNtfctn").ok_or_else(|| anyhow!("BkToCstmrDbtCdtNtfctn is missing"))?;
+
+    let mut notices = Vec::new();
+    for entry in children(report, "Ntfctn").flat_map(|notification| children(notification, "Ntry")) {
+        let status = text(entry, &["Sts"]).or_else(|| text(entry, &["Sts", "Cd"]));
+        if text(entry, &["CdtDbtInd"]).as_deref() != Some("CRDT") || status.as_deref() != Some("BOOK") {
+            continue;
+        }
+
+        let transactions: Vec<Node> = children(entry, "NtryDtls")
+            .flat_map(|details| children(details, "TxDtls"))
+            .collect();
+        let single = transactions.len() == 1;
+
+        for transaction in transactions.into_iter().filter(|transaction| child(*transaction, "RtrInf").is_some()) {
+            let returned_on = text(entry, &["BookgDt", "Dt"])
+                .or_else(|| text(entry, &["BookgDt", "DtTm"]).map(|date_time| date_time.chars().take(10).collect()))
+                .ok_or_else(|| anyhow!("Returned entry without booking date"))?;
+            let reference = text(transaction, &["Refs", "EndToEndId"])
+                .filter(|reference| reference != NOT_PROVIDED)
+                .ok_or_else(|| anyhow!("Returned transaction without end-to-end id"))?;
+            let transaction_amount = match amount(transaction, &["AmtDtls", "TxAmt", "Amt"])? {
+                Some(amount) => Some(amount),
+                None => amount(transaction, &["Amt"])?,
+            };
+            let (amount, currency) = match transaction_amount {
+                Some(amount) => Some(amount),
+                None if single => amount(entry, &["Amt"])?,
+                None => None,
+            }
+            .unzip();
+
+            notices.push(ReturnNotice {
+                reference,
+                returned_on: NaiveDate::parse_from_str(&returned_on, "%Y-%m-%d")?,
+                amount,
+                currency,
+                reason_code: text(transaction, &["RtrInf", "Rsn", "Cd"]),
+                reason: text(transaction, &["RtrInf", "AddtlInf"]),
+                source: ReturnSource::Camt054,
+            });
+        }
+    }
+
+    Ok(notices)
+}
@@ -0,0 +1,106 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::entities::disbursements::{Disbursement, DisbursementLine, DisbursementStatus, DisbursementStatusChange, LineKind};
+use crate::entities::merchants::BankAccount;
+use crate::entities::payout_returns::{PayoutReturn, ReturnNotice};
+use crate::services::currencies::format_amount;
+use crate::services::disbursement_status::{change_status, SYSTEM_ACTOR};
+use crate::services::fx::FxRates;
+
+// A payout can only come back once it was sent, a return of an exported one means the file reached the bank,
+// so it goes through SENT first. The whole net amount comes back, a return of another amount is refused.
+// `bank_account` is the one of the merchant, the one the payout was sent to.
+pub fn return_payout(
+    notice: &ReturnNotice,
+    disbursement: &mut Disbursement,
+    bank_account: Option<&BankAccount>,
+    returned_at: DateTime<Utc>,
+) -> Result<(Vec<DisbursementStatusChange>, PayoutReturn)> {
+    if notice.currency.as_ref().is_some_and(|currency| *currency != disbursement.currency) {
+        bail!("Returned in {}, the disbursement is in {}", notice.currency.as_deref().unwrap_or_default(), disbursement.currency);
+    }
+    if let Some(amount) = notice.amount.filter(|amount| *amount != disbursement.net_amount) {
+        bail!(
+            "Returned {}, the disbursement is {}",
+            format_amount(amount, &disbursement.currency).unwrap_or_else(|_| amount.to_string()),
+            format_amount(disbursement.net_amount, &disbursement.currency).unwrap_or_else(|_| disbursement.net_amount.to_string())
+        );
+    }
+
+    let note = [notice.reason_code.as_deref(), notice.reason.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut changes = Vec::new();
+    if disbursement.status == DisbursementStatus::Exported {
+        changes.push(change_status(disbursement, DisbursementStatus::Sent, SYSTEM_ACTOR, None, returned_at)?);
+    }
+    changes.push(change_status(
+        disbursement,
+        DisbursementStatus::Returned,
+        SYSTEM_ACTOR,
+        Some(format!("Returned on {} ({})", notice.returned_on, if note.is_empty() { "no reason given" } else { &note })),
+        returned_at,
+    )?);
+
+    let payout_return = PayoutReturn {
+        id: Uuid::new_v4(),
+        disbursement_id: disbursement.id,
+        merchant_reference: disbursement.merchant_reference.clone(),
+        returned_on: notice.returned_on,
+        amount: disbursement.net_amount,
+        currency: disbursement.currency.clone(),
+        reason_code: notice.reason_code.clone(),
+        reason: notice.reason.clone(),
+        source: notice.source,
+        iban: bank_account.map(|account| account.iban.clone()),
+        bic: bank_account.map(|account| account.bic.clone()),
+        account_holder: bank_account.map(|account| account.account_holder.clone()),
+        released_on: None,
+        redisbursement_id: None,
+    };
+
+    Ok((changes, payout_return))
+}
+
+// The payouts of the merchant are held until it has another bank account than the one the payout was returned from,
+// any of the iban, the bic or the holder. A merchant without bank account can't be paid anyway.
+pub fn bank_details_changed(payout_return: &PayoutReturn, bank_account: Option<&BankAccount>) -> bool {
+    let Some(bank_account) = bank_account else {
+        return false;
+    };
+
+    payout_return.iban.as_deref() != Some(bank_account.iban.as_str())
+        || payout_return.bic.as_deref() != Some(bank_account.bic.as_str())
+        || payout_return.account_holder.as_deref() != Some(bank_account.account_holder.as_str())
+}
+
+// Pays the released returns again, one RETURNED_PAYOUT line each so every return is paid once. A return in another
+// currency is converted to the current payout currency of the merchant.
+pub fn redisburse_returns(disbursement: &mut Disbursement, payout_returns: &[PayoutReturn], rates: &FxRates) -> Result<()> {
+    for payout_return in payout_returns {
+        let converted = rates.convert(payout_return.amount, &payout_return.currency, &disbursement.currency, disbursement.disbursed_on)?;
+        disbursement.lines.push(DisbursementLine {
+            kind: LineKind::ReturnedPayout,
+            order_id: None,
+            adjustment_id: None,
+            reserve_id: None,
+            receivable_id: None,
+            monthly_fee_id: None,
+            payout_return_id: Some(payout_return.id),
+            amount: converted.amount,
+            original_amount: payout_return.amount,
+            original_currency: payout_return.currency.clone(),
+            fx_rate: converted.rate,
+            fee_rate: 0,
+            pricing_version: String::new(),
+            fixed_fee: 0,
+            fee_amount: 0,
+        });
+    }
+
+    disbursement.recalculate_totals();
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use chrono::NaiveDate;
+use crate::entities::disbursements::Disbursement;
+use crate::entities::merchants::Merchant;
+use crate::entities::payouts::{PayoutBatch, PayoutInstruction};
+
+// SEPA credit transfers are only in euros, disbursements in other currencies are only exported as CSV.
+pub const SEPA_CURRENCY: &str = "EUR";
+
+// The message id of the `sequence`th file of the date (from 1), banks refuse a message id they already received.
+pub fn payout_message_id(execution_date: NaiveDate, sequence: i64) -> String {
+    format!("PAYOUTS-{}-{:03}", execution_date.format("%Y%m%d"), sequence)
+}
+
+pub fn build_payout_batch(execution_date: NaiveDate, disbursements: &[Disbursement], merchants: &[Merchant]) -> PayoutBatch {
+    let mut batch = PayoutBatch {
+        execution_date,
+        instructions: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for disbursement in disbursements {
+        let bank_account = merchants
+            .iter()
+            .find(|merchant| merchant.merchant_reference == disbursement.merchant_reference)
+            .and_then(|merchant| merchant.bank_account.clone());
+
+        match bank_account {
+            Some(creditor) if disbursement.net_amount > 0 => batch.instructions.push(PayoutInstruction {
+                reference: disbursement.reference.clone(),
+                merchant_reference: disbursement.merchant_reference.clone(),
+                creditor,
+                amount: disbursement.net_amount,
+                currency: disbursement.currency.clone(),
+            }),
+            _ => batch.skipped.push(disbursement.reference.clone()),
+        }
+    }
+
+    batch
+}
@@ -0,0 +1,36 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::NaiveDate;
+use crate::entities::pricing_plans::{PricingPlan, DEFAULT_PRICING_PLAN};
+
+// Every version of the pricing plans (the default tiers included), loaded once for a disbursement run.
+#[derive(Clone, Debug)]
+pub struct PricingPlans {
+    versions: Vec<PricingPlan>,
+}
+
+impl PricingPlans {
+    pub fn new(versions: Vec<PricingPlan>) -> Self {
+        Self { versions }
+    }
+
+    // The version effective when the order was created, of the merchant plan or else of the default tiers.
+    // Merchants without a plan (or with one that started after the order) get the default tiers,
+    // a plan we never received is an error rather than silently the default tiers.
+    pub fn for_order(&self, plan_code: Option<&str>, created_at: NaiveDate) -> Result<&PricingPlan> {
+        if let Some(code) = plan_code.filter(|code| !self.versions.iter().any(|version| &version.code == code)) {
+            bail!("Unknown pricing plan {}", code);
+        }
+
+        plan_code
+            .and_then(|code| self.effective_version(code, created_at))
+            .or_else(|| self.effective_version(DEFAULT_PRICING_PLAN, created_at))
+            .ok_or_else(|| anyhow!("No {} pricing plan version effective on {}", DEFAULT_PRICING_PLAN, created_at))
+    }
+
+    fn effective_version(&self, code: &str, date: NaiveDate) -> Option<&PricingPlan> {
+        self.versions
+            .iter()
+            .filter(|version| version.code == code && version.valid_from <= date)
+            .max_by_key(|version| version.valid_from)
+    }
+}
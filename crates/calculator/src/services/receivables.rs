@@ -0,0 +1,92 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use uuid::Uuid;
+use crate::entities::disbursements::{Disbursement, DisbursementLine, LineKind};
+use crate::entities::merchants::Merchant;
+use crate::entities::receivables::Receivable;
+use crate::services::currencies::DEFAULT_CURRENCY;
+use crate::services::fx::{FxRates, FX_RATE_SCALE};
+
+// The global negative balance limit (in euro cents) converted to the payout currency of the merchant, None when there's no limit.
+pub fn negative_balance_limit(
+    merchant: &Merchant,
+    global_limit: Option<i64>,
+    rates: &FxRates,
+    date: NaiveDate,
+) -> Result<Option<i64>> {
+    global_limit
+        .map(|limit| Ok(rates.convert(limit, DEFAULT_CURRENCY, &merchant.payout_currency, date)?.amount))
+        .transpose()
+}
+
+pub fn exceeds_negative_balance_limit(disbursement: &Disbursement, limit: Option<i64>) -> bool {
+    limit.is_some_and(|limit| -disbursement.net_amount > limit)
+}
+
+// Takes what the merchant owes us from the disbursement, one RECEIVABLE_OFFSET line per receivable so every receivable
+// is offset once. A receivable opened in another currency is converted to the current payout currency of the merchant.
+pub fn offset_receivables(disbursement: &mut Disbursement, receivables: &[Receivable], rates: &FxRates) -> Result<()> {
+    for receivable in receivables {
+        let converted = rates.convert(receivable.amount, &receivable.currency, &disbursement.currency, disbursement.disbursed_on)?;
+        disbursement.lines.push(DisbursementLine {
+            kind: LineKind::ReceivableOffset,
+            order_id: None,
+            adjustment_id: None,
+            reserve_id: None,
+            receivable_id: Some(receivable.id),
+            monthly_fee_id: None,
+            payout_return_id: None,
+            amount: -converted.amount,
+            original_amount: -receivable.amount,
+            original_currency: receivable.currency.clone(),
+            fx_rate: converted.rate,
+            fee_rate: 0,
+            pricing_version: String::new(),
+            fixed_fee: 0,
+            fee_amount: 0,
+        });
+    }
+
+    disbursement.recalculate_totals();
+    Ok(())
+}
+
+// A negative net amount can't be sent to the bank: it becomes a receivable of the merchant, offset against
+// its next disbursements, and the disbursement pays out nothing.
+pub fn open_receivable(disbursement: &mut Disbursement, flagged_for_collection: bool) -> Option<Receivable> {
+    if disbursement.net_amount >= 0 {
+        return None;
+    }
+
+    let receivable = Receivable {
+        id: Uuid::new_v4(),
+        merchant_reference: disbursement.merchant_reference.clone(),
+        disbursement_id: disbursement.id,
+        currency: disbursement.currency.clone(),
+        amount: -disbursement.net_amount,
+        opened_on: disbursement.disbursed_on,
+        flagged_for_collection,
+        settled_disbursement_id: None,
+    };
+
+    disbursement.lines.push(DisbursementLine {
+        kind: LineKind::Receivable,
+        order_id: None,
+        adjustment_id: None,
+        reserve_id: None,
+        receivable_id: Some(receivable.id),
+        monthly_fee_id: None,
+        payout_return_id: None,
+        amount: receivable.amount,
+        original_amount: receivable.amount,
+        original_currency: disbursement.currency.clone(),
+        fx_rate: FX_RATE_SCALE,
+        fee_rate: 0,
+        pricing_version: String::new(),
+        fixed_fee: 0,
+        fee_amount: 0,
+    });
+    disbursement.recalculate_totals();
+
+    Some(receivable)
+}
@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+use crate::entities::disbursements::{Disbursement, DisbursementStatus};
+use crate::entities::reconciliation::{BankEntry, BankStatement, ReconciliationLine, ReconciliationReport, ReconciliationStatus};
+use crate::services::currencies::format_amount;
+
+// Matches the booked debits of the statements with the disbursements by reference, the payout is right when the net amount
+// and the currency agree. `disbursements` are the ones with a reference in the statements, only the exported (or sent) ones
+// can be matched: paying a disbursement twice in the statements, or one that was never exported, is a mismatch.
+pub fn reconcile(statements: &[BankStatement], disbursements: &[Disbursement]) -> ReconciliationReport {
+    let mut settled: HashSet<&str> = HashSet::new();
+    let mut lines = Vec::new();
+
+    for entry in statements.iter().flat_map(|statement| statement.entries.iter()) {
+        let disbursement = entry
+            .reference
+            .as_deref()
+            .and_then(|reference| disbursements.iter().find(|disbursement| disbursement.reference == reference));
+
+        let Some(disbursement) = disbursement else {
+            let reason = match entry.reference {
+                Some(_) => "No disbursement with this reference",
+                None => "No end-to-end id",
+            };
+            lines.push(line(ReconciliationStatus::Unmatched, entry, None, Some(reason.to_string())));
+            continue;
+        };
+
+        if disbursement.status == DisbursementStatus::Settled {
+            lines.push(line(ReconciliationStatus::AlreadyMatched, entry, Some(disbursement), Some("Already settled".to_string())));
+            continue;
+        }
+
+        let reason = if settled.contains(disbursement.reference.as_str()) {
+            Some("Paid twice in the statements".to_string())
+        } else if !matches!(disbursement.status, DisbursementStatus::Exported | DisbursementStatus::Sent) {
+            Some(format!("The disbursement is {}", disbursement.status))
+        } else if entry.currency != disbursement.currency {
+            Some(format!("Paid in {}, the disbursement is in {}", entry.currency, disbursement.currency))
+        } else if entry.amount != disbursement.net_amount {
+            Some(format!(
+                "Paid {}, the disbursement is {}",
+                format_amount(entry.amount, &entry.currency).unwrap_or_else(|_| entry.amount.to_string()),
+                format_amount(disbursement.net_amount, &disbursement.currency).unwrap_or_else(|_| disbursement.net_amount.to_string())
+            ))
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => lines.push(line(ReconciliationStatus::Mismatched, entry, Some(disbursement), Some(reason))),
+            None => {
+                settled.insert(disbursement.reference.as_str());
+                lines.push(line(ReconciliationStatus::Matched, entry, Some(disbursement), None));
+            }
+        }
+    }
+
+    ReconciliationReport {
+        statement_ids: statements.iter().map(|statement| statement.id.clone()).collect(),
+        lines,
+    }
+}
+
+fn line(status: ReconciliationStatus, entry: &BankEntry, disbursement: Option<&Disbursement>, reason: Option<String>) -> ReconciliationLine {
+    ReconciliationLine {
+        status,
+        entry: entry.clone(),
+        disbursement_id: disbursement.map(|disbursement| disbursement.id),
+        expected_amount: disbursement.map(|disbursement| disbursement.net_amount),
+        expected_currency: disbursement.map(|disbursement| disbursement.currency.clone()),
+        reason,
+    }
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use crate::entities::reconciliation::ReconciliationReport;
+use crate::services::currencies::format_amount;
+
+// One row per bank entry, the status tells the matched, mismatched and unmatched ones apart.
+pub fn to_reconciliation_csv(report: &ReconciliationReport) -> Result<String> {
+    let mut writer = csv::WriterBuilder::new().delimiter(b';').from_writer(Vec::new());
+
+    writer.write_record([
+        "status", "reference", "bank_reference", "booked_on", "amount", "currency", "expected_amount", "expected_currency", "reason",
+    ])?;
+
+    for line in &report.lines {
+        let expected_amount = match (line.expected_amount, &line.expected_currency) {
+            (Some(amount), Some(currency)) => format_amount(amount, currency)?,
+            _ => String::new(),
+        };
+
+        writer.write_record([
+            line.status.to_string().as_str(),
+            line.entry.reference.as_deref().unwrap_or_default(),
+            line.entry.bank_reference.as_deref().unwrap_or_default(),
+            &line.entry.booked_on.format("%Y-%m-%d").to_string(),
+            &format_amount(line.entry.amount, &line.entry.currency)?,
+            line.entry.currency.as_str(),
+            &expected_amount,
+            line.expected_currency.as_deref().unwrap_or_default(),
+            line.reason.as_deref().unwrap_or_default(),
+        ])?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
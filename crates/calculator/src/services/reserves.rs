@@ -0,0 +1,109 @@
+use anyhow::{bail, Result};
+use chrono::Days;
+use uuid::Uuid;
+use crate::entities::disbursements::{Disbursement, DisbursementLine, LineKind};
+use crate::entities::reserves::{Reserve, ReservePolicy};
+use crate::services::fees::{commission, parse_percentage};
+use crate::services::fx::{FxRates, FX_RATE_SCALE};
+
+// Pays back the reserves whose hold period elapsed, one RESERVE_RELEASE line each so every reserve is released once.
+// A reserve held in another currency is converted to the current payout currency of the merchant.
+pub fn release_reserves(disbursement: &mut Disbursement, reserves: &[Reserve], rates: &FxRates) -> Result<()> {
+    for reserve in reserves {
+        let converted = rates.convert(reserve.amount, &reserve.currency, &disbursement.currency, disbursement.disbursed_on)?;
+        disbursement.lines.push(DisbursementLine {
+            kind: LineKind::ReserveRelease,
+            order_id: None,
+            adjustment_id: None,
+            reserve_id: Some(reserve.id),
+            receivable_id: None,
+            monthly_fee_id: None,
+            payout_return_id: None,
+            amount: converted.amount,
+            original_amount: reserve.amount,
+            original_currency: reserve.currency.clone(),
+            fx_rate: converted.rate,
+            fee_rate: 0,
+            pricing_version: String::new(),
+            fixed_fee: 0,
+            fee_amount: 0,
+        });
+    }
+
+    disbursement.recalculate_totals();
+    Ok(())
+}
+
+// Holds back the reserve rate of the net sales of the disbursement (orders and adjustments after fees),
+// nothing when refunds exceed the sales.
+pub fn hold_reserve(disbursement: &mut Disbursement, policy: &ReservePolicy) -> Option<Reserve> {
+    let net_sales: i64 = disbursement
+        .lines
+        .iter()
+        .filter(|line| line.kind.is_sale())
+        .map(|line| line.amount - line.fee_amount)
+        .sum();
+
+    let amount = commission(net_sales, policy.reserve_rate);
+    if amount <= 0 {
+        return None;
+    }
+
+    let reserve = Reserve {
+        id: Uuid::new_v4(),
+        merchant_reference: disbursement.merchant_reference.clone(),
+        disbursement_id: disbursement.id,
+        currency: disbursement.currency.clone(),
+        amount,
+        held_on: disbursement.disbursed_on,
+        release_on: disbursement.disbursed_on + Days::new(policy.hold_days as u64),
+        released_disbursement_id: None,
+    };
+
+    disbursement.lines.push(DisbursementLine {
+        kind: LineKind::Reserve,
+        order_id: None,
+        adjustment_id: None,
+        reserve_id: Some(reserve.id),
+        receivable_id: None,
+        monthly_fee_id: None,
+        payout_return_id: None,
+        amount: -amount,
+        original_amount: -amount,
+        original_currency: disbursement.currency.clone(),
+        fx_rate: FX_RATE_SCALE,
+        fee_rate: 0,
+        pricing_version: String::new(),
+        fixed_fee: 0,
+        fee_amount: 0,
+    });
+    disbursement.recalculate_totals();
+
+    Some(reserve)
+}
+
+// The policies file of risk: `merchant_reference;reserve_percentage;hold_days` with a header, e.g. `padberg_group;10.00;90`.
+pub fn parse_reserve_policies_csv(content: &str) -> Result<Vec<ReservePolicy>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .has_headers(true)
+        .from_reader(content.as_bytes());
+
+    let mut policies = Vec::new();
+
+    for result in reader.records() {
+        let record = result?;
+        let hold_days: u32 = record[2].trim().parse()?;
+        if hold_days == 0 {
+            bail!("The hold period of {} must be at least one day", record[0].trim());
+        }
+
+        policies.push(ReservePolicy {
+            merchant_reference: record[0].trim().to_string(),
+            reserve_rate: parse_percentage(record[1].trim())?,
+            hold_days,
+        });
+    }
+
+    Ok(policies)
+}
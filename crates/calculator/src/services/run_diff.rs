@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use chrono::NaiveDate;
+use serde::Serialize;
+use crate::entities::carried_balances::CarriedBalance;
+use crate::entities::disbursements::{Disbursement, DisbursementLine, LineKind};
+use crate::entities::monthly_fees::MonthlyFee;
+use crate::entities::receivables::Receivable;
+use crate::services::disbursement_calculator::DisbursementRun;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffStatus {
+    // calculated now, nothing persisted yet
+    Added,
+    // persisted, but the calculation does not produce it anymore
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DiffEntry<T> {
+    pub merchant_reference: String,
+    pub status: DiffStatus,
+    pub persisted: Option<T>,
+    pub computed: Option<T>,
+}
+
+// What a run for `date` would change compared to what is already persisted for that date.
+#[derive(Serialize, Clone, Debug)]
+pub struct RunDiff {
+    pub date: NaiveDate,
+    pub disbursements: Vec<DiffEntry<Disbursement>>,
+    pub monthly_fees: Vec<DiffEntry<MonthlyFee>>,
+    pub carried_balances: Vec<DiffEntry<CarriedBalance>>,
+    pub receivables: Vec<DiffEntry<Receivable>>,
+}
+
+impl RunDiff {
+    pub fn has_changes(&self) -> bool {
+        self.disbursements.iter().any(|entry| entry.status != DiffStatus::Unchanged)
+            || self.monthly_fees.iter().any(|entry| entry.status != DiffStatus::Unchanged)
+            || self.carried_balances.iter().any(|entry| entry.status != DiffStatus::Unchanged)
+            || self.receivables.iter().any(|entry| entry.status != DiffStatus::Unchanged)
+    }
+}
+
+pub fn diff_runs(date: NaiveDate, persisted: &DisbursementRun, computed: &DisbursementRun) -> RunDiff {
+    RunDiff {
+        date,
+        disbursements: diff_by_merchant(
+            &persisted.disbursements,
+            &computed.disbursements,
+            |disbursement| &disbursement.merchant_reference,
+            same_disbursement,
+        ),
+        monthly_fees: diff_by_merchant(
+            &persisted.monthly_fees,
+            &computed.monthly_fees,
+            |monthly_fee| &monthly_fee.merchant_reference,
+            // the disbursement deducting a persisted fee is only known once it's persisted
+            |a, b| MonthlyFee { disbursement_id: None, ..a.clone() } == *b,
+        ),
+        // the disbursement paying a persisted balance is only known later
+        carried_balances: diff_by_merchant(
+            &persisted.carried_balances,
+            &computed.carried_balances,
+            |carried_balance| &carried_balance.merchant_reference,
+            |a, b| CarriedBalance { disbursement_id: None, ..a.clone() } == *b,
+        ),
+        // opened by a disbursement, so its ids are generated on every calculation as well
+        receivables: diff_by_merchant(
+            &persisted.receivables,
+            &computed.receivables,
+            |receivable| &receivable.merchant_reference,
+            |a, b| (&a.currency, a.amount, a.opened_on, a.flagged_for_collection) == (&b.currency, b.amount, b.opened_on, b.flagged_for_collection),
+        ),
+    }
+}
+
+// ids and references are generated on every calculation, so they are not compared
+// (neither the ids of the reserve held and the receivable opened, they are new on every calculation too).
+fn same_disbursement(persisted: &Disbursement, computed: &Disbursement) -> bool {
+    let comparable_lines = |disbursement: &Disbursement| {
+        let mut lines: Vec<DisbursementLine> = disbursement
+            .lines
+            .iter()
+            .map(|line| match line.kind {
+                LineKind::Reserve | LineKind::Receivable => DisbursementLine { reserve_id: None, receivable_id: None, ..line.clone() },
+                _ => line.clone(),
+            })
+            .collect();
+        lines.sort_by_key(|line| {
+            (line.kind.to_string(), line.order_id.clone(), line.adjustment_id, line.reserve_id, line.receivable_id, line.payout_return_id)
+        });
+        lines
+    };
+    let persisted_lines = comparable_lines(persisted);
+    let computed_lines = comparable_lines(computed);
+
+    persisted.currency == computed.currency
+        && persisted.gross_amount == computed.gross_amount
+        && persisted.fee_amount == computed.fee_amount
+        && persisted.vat == computed.vat
+        && persisted.fee_vat_amount == computed.fee_vat_amount
+        && persisted.net_amount == computed.net_amount
+        && persisted_lines == computed_lines
+}
+
+// There is at most one disbursement, one monthly fee, one carried balance and one receivable per merchant and date,
+// so the merchant is the key.
+fn diff_by_merchant<T: Clone>(
+    persisted: &[T],
+    computed: &[T],
+    merchant_reference: impl Fn(&T) -> &String,
+    same: impl Fn(&T, &T) -> bool,
+) -> Vec<DiffEntry<T>> {
+    let mut by_merchant: BTreeMap<String, (Option<T>, Option<T>)> = BTreeMap::new();
+
+    for item in persisted {
+        by_merchant.entry(merchant_reference(item).clone()).or_default().0 = Some(item.clone());
+    }
+    for item in computed {
+        by_merchant.entry(merchant_reference(item).clone()).or_default().1 = Some(item.clone());
+    }
+
+    by_merchant
+        .into_iter()
+        .map(|(merchant_reference, (persisted, computed))| {
+            let status = match (&persisted, &computed) {
+                (None, _) => DiffStatus::Added,
+                (_, None) => DiffStatus::Removed,
+                (Some(p), Some(c)) if same(p, c) => DiffStatus::Unchanged,
+                _ => DiffStatus::Changed,
+            };
+            DiffEntry { merchant_reference, status, persisted, computed }
+        })
+        .collect()
+}
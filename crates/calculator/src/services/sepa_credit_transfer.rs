@@ -0,0 +1,92 @@
+use anyhow::{bail, Result};
+use chrono::NaiveDateTime;
+use crate::entities::payouts::PayoutBatch;
+use crate::services::currencies::format_amount;
+use crate::services::payouts::SEPA_CURRENCY;
+use crate::settings::payouts::PayoutSettings;
+
+const PAIN_001_NAMESPACE: &str = "urn:iso:std:iso:20022:tech:xsd:pain.001.001.03";
+// ISO 20022 Max70Text, the longest name the schema accepts
+const MAX_NAME_LENGTH: usize = 70;
+
+/*
+    ISO 20022 pain.001.001.03 (SEPA credit transfer initiation) with one payment information block
+    for the whole batch and one transaction per disbursement.
+    The document is small and flat, so it's written by hand instead of pulling an xml crate,
+    every text value goes through `escape`.
+    SEPA only moves euros, the batch must be filtered with `PayoutBatch::only_in(SEPA_CURRENCY)` first.
+    The message id must be new to the bank (see services::payouts::payout_message_id).
+*/
+pub fn to_pain_001(batch: &PayoutBatch, debtor: &PayoutSettings, message_id: &str, created_at: NaiveDateTime) -> Result<String> {
+    if let Some(instruction) = batch.instructions.iter().find(|instruction| instruction.currency != SEPA_CURRENCY) {
+        bail!("Payout {} is in {}, SEPA credit transfers are only in {}", instruction.reference, instruction.currency, SEPA_CURRENCY);
+    }
+
+    let number_of_transactions = batch.instructions.len();
+    let control_sum = format_amount(batch.control_sum(), SEPA_CURRENCY)?;
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!("<Document xmlns=\"{}\">\n", PAIN_001_NAMESPACE));
+    xml.push_str("  <CstmrCdtTrfInitn>\n");
+
+    xml.push_str("    <GrpHdr>\n");
+    xml.push_str(&format!("      <MsgId>{}</MsgId>\n", escape(message_id)));
+    xml.push_str(&format!("      <CreDtTm>{}</CreDtTm>\n", created_at.format("%Y-%m-%dT%H:%M:%S")));
+    xml.push_str(&format!("      <NbOfTxs>{}</NbOfTxs>\n", number_of_transactions));
+    xml.push_str(&format!("      <CtrlSum>{}</CtrlSum>\n", control_sum));
+    xml.push_str(&format!("      <InitgPty><Nm>{}</Nm></InitgPty>\n", escape(&truncate(&debtor.debtor_name))));
+    xml.push_str("    </GrpHdr>\n");
+
+    xml.push_str("    <PmtInf>\n");
+    xml.push_str(&format!("      <PmtInfId>{}-1</PmtInfId>\n", escape(message_id)));
+    xml.push_str("      <PmtMtd>TRF</PmtMtd>\n");
+    xml.push_str(&format!("      <NbOfTxs>{}</NbOfTxs>\n", number_of_transactions));
+    xml.push_str(&format!("      <CtrlSum>{}</CtrlSum>\n", control_sum));
+    xml.push_str("      <PmtTpInf><SvcLvl><Cd>SEPA</Cd></SvcLvl></PmtTpInf>\n");
+    xml.push_str(&format!("      <ReqdExctnDt>{}</ReqdExctnDt>\n", batch.execution_date.format("%Y-%m-%d")));
+    xml.push_str(&format!("      <Dbtr><Nm>{}</Nm></Dbtr>\n", escape(&truncate(&debtor.debtor_name))));
+    xml.push_str(&format!("      <DbtrAcct><Id><IBAN>{}</IBAN></Id></DbtrAcct>\n", escape(&debtor.debtor_iban)));
+    xml.push_str(&format!("      <DbtrAgt><FinInstnId><BIC>{}</BIC></FinInstnId></DbtrAgt>\n", escape(&debtor.debtor_bic)));
+    xml.push_str("      <ChrgBr>SLEV</ChrgBr>\n");
+
+    for instruction in &batch.instructions {
+        xml.push_str("      <CdtTrfTxInf>\n");
+        xml.push_str(&format!("        <PmtId><EndToEndId>{}</EndToEndId></PmtId>\n", escape(&instruction.reference)));
+        xml.push_str(&format!(
+            "        <Amt><InstdAmt Ccy=\"{}\">{}</InstdAmt></Amt>\n",
+            escape(&instruction.currency),
+            format_amount(instruction.amount, &instruction.currency)?
+        ));
+        xml.push_str(&format!(
+            "        <CdtrAgt><FinInstnId><BIC>{}</BIC></FinInstnId></CdtrAgt>\n",
+            escape(&instruction.creditor.bic)
+        ));
+        xml.push_str(&format!("        <Cdtr><Nm>{}</Nm></Cdtr>\n", escape(&truncate(&instruction.creditor.account_holder))));
+        xml.push_str(&format!("        <CdtrAcct><Id><IBAN>{}</IBAN></Id></CdtrAcct>\n", escape(&instruction.creditor.iban)));
+        xml.push_str(&format!(
+            "        <RmtInf><Ustrd>Disbursement {}</Ustrd></RmtInf>\n",
+            escape(&instruction.reference)
+        ));
+        xml.push_str("      </CdtTrfTxInf>\n");
+    }
+
+    xml.push_str("    </PmtInf>\n");
+    xml.push_str("  </CstmrCdtTrfInitn>\n");
+    xml.push_str("</Document>\n");
+
+    Ok(xml)
+}
+
+fn truncate(value: &str) -> String {
+    value.chars().take(MAX_NAME_LENGTH).collect()
+}
+
+pub(crate) fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
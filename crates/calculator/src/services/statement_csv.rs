@@ -0,0 +1,104 @@
+use anyhow::Result;
+use crate::entities::statements::{Statement, StatementBalance};
+use crate::services::currencies::format_amount;
+use crate::services::disbursement_calculator::last_day_of_month;
+use crate::services::fees::format_percentage;
+
+// One row per disbursement line, with the fee tier (rate) the order was charged, followed by the VAT on the fees and the total
+// of each disbursement. The balances and the monthly fee get a row of their own, the `type` column tells them apart.
+pub fn to_statement_csv(statement: &Statement) -> Result<String> {
+    let mut writer = csv::WriterBuilder::new().delimiter(b';').from_writer(Vec::new());
+
+    writer.write_record([
+        "type", "disbursement_reference", "date", "order_id", "gross_amount", "fee_rate", "fee_amount", "net_amount", "currency",
+    ])?;
+
+    let month_start = statement.month.format("%Y-%m-%d").to_string();
+    for balance in &statement.opening_balances {
+        write_balance(&mut writer, "OPENING_BALANCE", &month_start, balance)?;
+    }
+
+    for disbursement in &statement.disbursements {
+        let currency = disbursement.currency.as_str();
+        let disbursed_on = disbursement.disbursed_on.format("%Y-%m-%d").to_string();
+
+        for line in &disbursement.lines {
+            let is_sale = line.kind.is_sale();
+            writer.write_record([
+                line.kind.to_string().as_str(),
+                disbursement.reference.as_str(),
+                &disbursed_on,
+                line.order_id.as_deref().unwrap_or_default(),
+                &if is_sale { format_amount(line.amount, currency)? } else { String::new() },
+                &if is_sale { format_percentage(line.fee_rate) } else { String::new() },
+                &if is_sale { format_amount(line.fee_amount, currency)? } else { String::new() },
+                &format_amount(line.amount - line.fee_amount, currency)?,
+                currency,
+            ])?;
+        }
+
+        if disbursement.fee_vat_amount != 0 {
+            writer.write_record([
+                "FEE_VAT",
+                disbursement.reference.as_str(),
+                &disbursed_on,
+                "",
+                "",
+                &disbursement.vat.map(|vat| format_percentage(vat.rate)).unwrap_or_default(),
+                &format_amount(disbursement.fee_vat_amount, currency)?,
+                &format_amount(-disbursement.fee_vat_amount, currency)?,
+                currency,
+            ])?;
+        }
+
+        writer.write_record([
+            "DISBURSEMENT",
+            disbursement.reference.as_str(),
+            &disbursed_on,
+            "",
+            &format_amount(disbursement.gross_amount, currency)?,
+            "",
+            &format_amount(disbursement.fee_gross_amount, currency)?,
+            &format_amount(disbursement.net_amount, currency)?,
+            currency,
+        ])?;
+    }
+
+    // the shortfall of the month, charged with its VAT (invoiced, or deducted from a disbursement of the next month)
+    if let Some(monthly_fee) = &statement.monthly_fee {
+        writer.write_record([
+            format!("MONTHLY_FEE_{}", monthly_fee.mode).as_str(),
+            "",
+            &monthly_fee.calculated_on.format("%Y-%m-%d").to_string(),
+            "",
+            "",
+            "",
+            &format_amount(monthly_fee.gross_amount, &monthly_fee.currency)?,
+            "",
+            monthly_fee.currency.as_str(),
+        ])?;
+    }
+
+    let month_end = last_day_of_month(statement.month).format("%Y-%m-%d").to_string();
+    for balance in &statement.closing_balances {
+        write_balance(&mut writer, "CLOSING_BALANCE", &month_end, balance)?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+fn write_balance(writer: &mut csv::Writer<Vec<u8>>, kind: &str, date: &str, balance: &StatementBalance) -> Result<()> {
+    writer.write_record([
+        kind,
+        "",
+        date,
+        "",
+        "",
+        "",
+        "",
+        &format_amount(balance.amount, &balance.currency)?,
+        balance.currency.as_str(),
+    ])?;
+
+    Ok(())
+}
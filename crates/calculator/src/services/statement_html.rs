@@ -0,0 +1,116 @@
+use anyhow::Result;
+use crate::entities::merchants::MonthlyFeeMode;
+use crate::entities::statements::{Statement, StatementBalance};
+use crate::services::currencies::format_amount;
+use crate::services::disbursement_calculator::last_day_of_month;
+use crate::services::fees::format_percentage;
+use crate::services::sepa_credit_transfer::escape;
+
+// Self contained like the invoices, one table per disbursement so the merchant can match every order with the payout it was in.
+pub fn to_statement_html(statement: &Statement) -> Result<String> {
+    let amount = |value: i64, currency: &str| -> Result<String> { Ok(format!("{} {}", format_amount(value, currency)?, escape(currency))) };
+    let title = format!("Statement {}, {}", statement.merchant_reference, statement.month.format("%B %Y"));
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n");
+    html.push_str("<html lang=\"en\">\n");
+    html.push_str("<head>\n");
+    html.push_str("  <meta charset=\"UTF-8\">\n");
+    html.push_str(&format!("  <title>{}</title>\n", escape(&title)));
+    html.push_str("  <style>body { font-family: sans-serif; } table { border-collapse: collapse; width: 100%; } th, td { padding: 4px 8px; border-bottom: 1px solid #ddd; } .amount { text-align: right; }</style>\n");
+    html.push_str("</head>\n");
+    html.push_str("<body>\n");
+
+    html.push_str(&format!("  <h1>{}</h1>\n", escape(&title)));
+    html.push_str(&balances_html(&format!("Opening balance on {}", statement.month.format("%Y-%m-%d")), &statement.opening_balances, &statement.currency, &amount)?);
+
+    if statement.disbursements.is_empty() {
+        html.push_str("  <p>No disbursements this month.</p>\n");
+    }
+
+    for disbursement in &statement.disbursements {
+        let currency = disbursement.currency.as_str();
+        html.push_str(&format!(
+            "  <h2>Disbursement {} on {}</h2>\n",
+            escape(&disbursement.reference),
+            disbursement.disbursed_on.format("%Y-%m-%d")
+        ));
+        html.push_str("  <table>\n");
+        html.push_str("    <thead><tr><th>Line</th><th>Order</th><th class=\"amount\">Gross</th><th class=\"amount\">Fee tier</th><th class=\"amount\">Fee</th><th class=\"amount\">Net</th></tr></thead>\n");
+        html.push_str("    <tbody>\n");
+        for line in &disbursement.lines {
+            let (gross, fee_tier, fee) = if line.kind.is_sale() {
+                (amount(line.amount, currency)?, format!("{} %", format_percentage(line.fee_rate)), amount(line.fee_amount, currency)?)
+            } else {
+                (String::new(), String::new(), String::new())
+            };
+            html.push_str(&format!(
+                "      <tr><td>{}</td><td>{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n",
+                line.kind,
+                escape(line.order_id.as_deref().unwrap_or_default()),
+                gross,
+                fee_tier,
+                fee,
+                amount(line.amount - line.fee_amount, currency)?
+            ));
+        }
+        html.push_str("    </tbody>\n");
+        html.push_str("    <tfoot>\n");
+        if disbursement.fee_vat_amount != 0 {
+            let rate = disbursement.vat.map(|vat| format!(" {} %", format_percentage(vat.rate))).unwrap_or_default();
+            html.push_str(&format!(
+                "      <tr><th colspan=\"5\">VAT{} on the fees</th><td class=\"amount\">{}</td></tr>\n",
+                rate,
+                amount(-disbursement.fee_vat_amount, currency)?
+            ));
+        }
+        html.push_str(&format!(
+            "      <tr><th colspan=\"5\">Paid out</th><td class=\"amount\">{}</td></tr>\n",
+            amount(disbursement.net_amount, currency)?
+        ));
+        html.push_str("    </tfoot>\n");
+        html.push_str("  </table>\n");
+    }
+
+    html.push_str("  <h2>Minimum monthly fee</h2>\n");
+    match &statement.monthly_fee {
+        Some(monthly_fee) => html.push_str(&format!(
+            "  <p>Minimum {}, commissions {}, {} {} VAT included</p>\n",
+            amount(monthly_fee.minimum_monthly_fee, &monthly_fee.currency)?,
+            amount(monthly_fee.commissions_amount, &monthly_fee.currency)?,
+            match monthly_fee.mode {
+                MonthlyFeeMode::Invoice => "invoiced",
+                MonthlyFeeMode::Deduct => "deducted from the next disbursement",
+            },
+            amount(monthly_fee.gross_amount, &monthly_fee.currency)?
+        )),
+        None => html.push_str("  <p>Not calculated yet.</p>\n"),
+    }
+
+    html.push_str(&balances_html(
+        &format!("Closing balance on {}", last_day_of_month(statement.month).format("%Y-%m-%d")),
+        &statement.closing_balances,
+        &statement.currency,
+        &amount,
+    )?);
+
+    html.push_str("</body>\n");
+    html.push_str("</html>\n");
+
+    Ok(html)
+}
+
+// Zero in the payout currency when nothing is owed either way.
+fn balances_html(title: &str, balances: &[StatementBalance], currency: &str, amount: &dyn Fn(i64, &str) -> Result<String>) -> Result<String> {
+    let values = if balances.is_empty() {
+        amount(0, currency)?
+    } else {
+        balances
+            .iter()
+            .map(|balance| amount(balance.amount, &balance.currency))
+            .collect::<Result<Vec<_>>>()?
+            .join(", ")
+    };
+
+    Ok(format!("  <p><strong>{}:</strong> {}</p>\n", escape(title), values))
+}
@@ -0,0 +1,44 @@
+use std::collections::BTreeMap;
+use chrono::NaiveDate;
+use crate::entities::disbursements::Disbursement;
+use crate::entities::ledger::{Account, AccountBalance};
+use crate::entities::merchants::Merchant;
+use crate::entities::monthly_fees::MonthlyFee;
+use crate::entities::statements::{Statement, StatementBalance};
+
+// `opening` and `closing` are the merchant account balances at the end of the day before the month and at the end of its last day.
+pub fn build_statement(
+    merchant: &Merchant,
+    month: NaiveDate,
+    disbursements: Vec<Disbursement>,
+    monthly_fee: Option<MonthlyFee>,
+    opening: &[AccountBalance],
+    closing: &[AccountBalance],
+) -> Statement {
+    Statement {
+        merchant_reference: merchant.merchant_reference.clone(),
+        month,
+        currency: merchant.payout_currency.clone(),
+        opening_balances: merchant_balances(opening),
+        disbursements,
+        monthly_fee,
+        closing_balances: merchant_balances(closing),
+    }
+}
+
+// Credits are negative in the ledger, so what we owe the merchant is the negated sum of MERCHANT_PAYABLE, MERCHANT_RESERVE and
+// MERCHANT_RECEIVABLE. MONTHLY_FEE_RECEIVABLE is left out, the invoiced fees are paid apart and the statement shows the fee anyway.
+pub fn merchant_balances(balances: &[AccountBalance]) -> Vec<StatementBalance> {
+    let mut by_currency: BTreeMap<&str, i64> = BTreeMap::new();
+    for balance in balances {
+        if matches!(balance.account, Account::MerchantPayable | Account::MerchantReserve | Account::MerchantReceivable) {
+            *by_currency.entry(balance.currency.as_str()).or_default() -= balance.balance;
+        }
+    }
+
+    by_currency
+        .into_iter()
+        .filter(|(_, amount)| *amount != 0)
+        .map(|(currency, amount)| StatementBalance { currency: currency.to_string(), amount })
+        .collect()
+}
@@ -0,0 +1,29 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+// The instant a local day starts in `time_zone`. When a DST transition skips midnight
+// the day starts with its first local time that exists (00:30 or 01:00).
+pub fn start_of_day(date: NaiveDate, time_zone: Tz) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+
+    (0..96)
+        .map(|quarters| midnight + Duration::minutes(15 * quarters))
+        .find_map(|local| time_zone.from_local_datetime(&local).earliest())
+        .expect("no time zone skips a whole day")
+        .with_timezone(&Utc)
+}
+
+pub fn local_date(instant: DateTime<Utc>, time_zone: Tz) -> NaiveDate {
+    instant.with_timezone(&time_zone).date_naive()
+}
+
+// The local day of an event: RFC 3339 timestamps are converted to the time zone of the merchant,
+// plain dates are taken as its local day already.
+pub fn parse_local_date(value: &str, time_zone: Tz) -> Result<NaiveDate> {
+    if let Ok(instant) = DateTime::parse_from_rfc3339(value) {
+        return Ok(local_date(instant.with_timezone(&Utc), time_zone));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|err| anyhow!("Invalid date {}: {}", value, err))
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+use crate::entities::invoices::{Invoice, InvoiceKind};
+use crate::entities::vat::VatTreatment;
+use crate::services::currencies::format_amount;
+use crate::services::sepa_credit_transfer::escape;
+
+const UBL_INVOICE_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:Invoice-2";
+const UBL_CREDIT_NOTE_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CreditNote-2";
+const CAC_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2";
+const CBC_NAMESPACE: &str = "urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2";
+// EN 16931, the european e-invoicing semantic model
+const CUSTOMIZATION_ID: &str = "urn:cen.eu:en16931:2017";
+// UNTDID 1001: commercial invoice and credit note
+const INVOICE_TYPE_CODE: &str = "380";
+const CREDIT_NOTE_TYPE_CODE: &str = "381";
+// UN/ECE rec 20 "one", every line is a single unit
+const UNIT_CODE: &str = "C62";
+
+/*
+    UBL 2.1 Invoice, or CreditNote for credit notes, with one tax subtotal: standard rated (S), reverse charged (AE)
+    or outside the scope of VAT (O), the last one as well when no VAT was calculated.
+    UBL credit notes carry positive amounts, the document type already says they are credited,
+    so the negative amounts of our credit notes are written without the sign.
+    Written by hand like the pain.001 files, every text value goes through `escape`.
+*/
+pub fn to_ubl(invoice: &Invoice) -> Result<String> {
+    let (root, namespace, type_code_tag, type_code, line_tag, quantity_tag) = match invoice.kind {
+        InvoiceKind::Invoice => ("Invoice", UBL_INVOICE_NAMESPACE, "InvoiceTypeCode", INVOICE_TYPE_CODE, "InvoiceLine", "InvoicedQuantity"),
+        InvoiceKind::CreditNote => ("CreditNote", UBL_CREDIT_NOTE_NAMESPACE, "CreditNoteTypeCode", CREDIT_NOTE_TYPE_CODE, "CreditNoteLine", "CreditedQuantity"),
+    };
+    let sign = if invoice.kind == InvoiceKind::CreditNote { -1 } else { 1 };
+    let amount = |value: i64| -> Result<String> {
+        Ok(format!(
+            "currencyID=\"{}\">{}",
+            escape(&invoice.currency),
+            format_amount(value * sign, &invoice.currency)?
+        ))
+    };
+    let (tax_category, exemption_reason) = match invoice.vat {
+        Some(vat) if vat.treatment == VatTreatment::Standard => {
+            (format!("<cbc:ID>S</cbc:ID><cbc:Percent>{}.{:02}</cbc:Percent>", vat.rate / 100, vat.rate % 100), "")
+        }
+        Some(vat) if vat.treatment == VatTreatment::ReverseCharge => (
+            "<cbc:ID>AE</cbc:ID><cbc:Percent>0.00</cbc:Percent>".to_string(),
+            "<cbc:TaxExemptionReasonCode>VATEX-EU-AE</cbc:TaxExemptionReasonCode><cbc:TaxExemptionReason>Reverse charge</cbc:TaxExemptionReason>",
+        ),
+        _ => ("<cbc:ID>O</cbc:ID>".to_string(), "<cbc:TaxExemptionReason>Not subject to VAT</cbc:TaxExemptionReason>"),
+    };
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<{} xmlns=\"{}\" xmlns:cac=\"{}\" xmlns:cbc=\"{}\">\n",
+        root, namespace, CAC_NAMESPACE, CBC_NAMESPACE
+    ));
+    xml.push_str("  <cbc:UBLVersionID>2.1</cbc:UBLVersionID>\n");
+    xml.push_str(&format!("  <cbc:CustomizationID>{}</cbc:CustomizationID>\n", CUSTOMIZATION_ID));
+    xml.push_str(&format!("  <cbc:ID>{}</cbc:ID>\n", escape(&invoice.number)));
+    xml.push_str(&format!("  <cbc:IssueDate>{}</cbc:IssueDate>\n", invoice.issued_on.format("%Y-%m-%d")));
+    xml.push_str(&format!("  <cbc:{}>{}</cbc:{}>\n", type_code_tag, type_code, type_code_tag));
+    if let Some(note) = &invoice.note {
+        xml.push_str(&format!("  <cbc:Note>{}</cbc:Note>\n", escape(note)));
+    }
+    xml.push_str(&format!("  <cbc:DocumentCurrencyCode>{}</cbc:DocumentCurrencyCode>\n", escape(&invoice.currency)));
+    xml.push_str(&format!("  <cbc:BuyerReference>{}</cbc:BuyerReference>\n", escape(&invoice.merchant_reference)));
+    xml.push_str(&format!(
+        "  <cac:InvoicePeriod><cbc:StartDate>{}</cbc:StartDate><cbc:EndDate>{}</cbc:EndDate></cac:InvoicePeriod>\n",
+        invoice.period_start.format("%Y-%m-%d"),
+        invoice.period_end.format("%Y-%m-%d")
+    ));
+    if let Some(credited_invoice_number) = &invoice.credited_invoice_number {
+        xml.push_str(&format!(
+            "  <cac:BillingReference><cac:InvoiceDocumentReference><cbc:ID>{}</cbc:ID></cac:InvoiceDocumentReference></cac:BillingReference>\n",
+            escape(credited_invoice_number)
+        ));
+    }
+    xml.push_str(&format!(
+        "  <cac:AccountingSupplierParty><cac:Party><cac:PartyLegalEntity><cbc:RegistrationName>{}</cbc:RegistrationName></cac:PartyLegalEntity></cac:Party></cac:AccountingSupplierParty>\n",
+        escape(&invoice.seller_name)
+    ));
+    let customer_tax_scheme = invoice
+        .customer_vat_number
+        .as_ref()
+        .map(|vat_number| {
+            format!(
+                "<cac:PartyTaxScheme><cbc:CompanyID>{}</cbc:CompanyID><cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:PartyTaxScheme>",
+                escape(vat_number)
+            )
+        })
+        .unwrap_or_default();
+    xml.push_str(&format!(
+        "  <cac:AccountingCustomerParty><cac:Party>{}<cac:PartyLegalEntity><cbc:RegistrationName>{}</cbc:RegistrationName></cac:PartyLegalEntity></cac:Party></cac:AccountingCustomerParty>\n",
+        customer_tax_scheme,
+        escape(&invoice.customer_name)
+    ));
+
+    xml.push_str("  <cac:TaxTotal>\n");
+    xml.push_str(&format!("    <cbc:TaxAmount {}</cbc:TaxAmount>\n", amount(invoice.vat_amount)?));
+    xml.push_str("    <cac:TaxSubtotal>\n");
+    xml.push_str(&format!("      <cbc:TaxableAmount {}</cbc:TaxableAmount>\n", amount(invoice.net_amount)?));
+    xml.push_str(&format!("      <cbc:TaxAmount {}</cbc:TaxAmount>\n", amount(invoice.vat_amount)?));
+    xml.push_str(&format!(
+        "      <cac:TaxCategory>{}{}<cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:TaxCategory>\n",
+        tax_category, exemption_reason
+    ));
+    xml.push_str("    </cac:TaxSubtotal>\n");
+    xml.push_str("  </cac:TaxTotal>\n");
+
+    xml.push_str("  <cac:LegalMonetaryTotal>\n");
+    xml.push_str(&format!("    <cbc:LineExtensionAmount {}</cbc:LineExtensionAmount>\n", amount(invoice.net_amount)?));
+    xml.push_str(&format!("    <cbc:TaxExclusiveAmount {}</cbc:TaxExclusiveAmount>\n", amount(invoice.net_amount)?));
+    xml.push_str(&format!("    <cbc:TaxInclusiveAmount {}</cbc:TaxInclusiveAmount>\n", amount(invoice.total_amount)?));
+    xml.push_str(&format!("    <cbc:PayableAmount {}</cbc:PayableAmount>\n", amount(invoice.total_amount)?));
+    xml.push_str("  </cac:LegalMonetaryTotal>\n");
+
+    for (index, line) in invoice.lines.iter().enumerate() {
+        // prices can't be negative, a line taking something off the invoice has a negative quantity instead
+        let quantity = if line.unit_amount * sign < 0 { -line.quantity } else { line.quantity };
+        let price = format_amount((line.unit_amount * sign).abs(), &invoice.currency)?;
+
+        xml.push_str(&format!("  <cac:{}>\n", line_tag));
+        xml.push_str(&format!("    <cbc:ID>{}</cbc:ID>\n", index + 1));
+        xml.push_str(&format!("    <cbc:{} unitCode=\"{}\">{}</cbc:{}>\n", quantity_tag, UNIT_CODE, quantity, quantity_tag));
+        xml.push_str(&format!("    <cbc:LineExtensionAmount {}</cbc:LineExtensionAmount>\n", amount(line.amount)?));
+        xml.push_str(&format!(
+            "    <cac:Item><cbc:Name>{}</cbc:Name><cac:ClassifiedTaxCategory>{}<cac:TaxScheme><cbc:ID>VAT</cbc:ID></cac:TaxScheme></cac:ClassifiedTaxCategory></cac:Item>\n",
+            escape(&line.description),
+            tax_category
+        ));
+        xml.push_str(&format!(
+            "    <cac:Price><cbc:PriceAmount currencyID=\"{}\">{}</cbc:PriceAmount></cac:Price>\n",
+            escape(&invoice.currency),
+            price
+        ));
+        xml.push_str(&format!("  </cac:{}>\n", line_tag));
+    }
+
+    xml.push_str(&format!("</{}>\n", root));
+
+    Ok(xml)
+}
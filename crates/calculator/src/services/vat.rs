@@ -0,0 +1,97 @@
+use anyhow::{anyhow, bail, Result};
+use crate::entities::disbursements::Disbursement;
+use crate::entities::merchants::Merchant;
+use crate::entities::monthly_fees::MonthlyFee;
+use crate::entities::vat::{VatCharge, VatRule, VatTreatment};
+use crate::services::fees::{commission, parse_percentage};
+
+// The VAT rules and the country Company XYZ is established in. Without a home country no VAT is calculated.
+#[derive(Debug, Clone, Default)]
+pub struct VatRules {
+    rules: Vec<VatRule>,
+    home_country: Option<String>,
+}
+
+impl VatRules {
+    pub fn new(rules: Vec<VatRule>, home_country: Option<String>) -> Self {
+        Self { rules, home_country }
+    }
+
+    // Our commissions are services to businesses:
+    // - merchants in our country (or without a country) pay our standard rate
+    // - VAT registered merchants in another EU country are reverse charged
+    // - merchants in another EU country without a VAT number pay the standard rate of their country
+    // - merchants outside the EU (or anywhere when we are not in the EU) are outside the scope of our VAT
+    pub fn charge_for(&self, merchant: &Merchant) -> Result<Option<VatCharge>> {
+        let Some(home_country) = &self.home_country else {
+            return Ok(None);
+        };
+
+        let home = self.rule(home_country)?;
+        let country = merchant.country.as_deref().unwrap_or(home_country);
+        if country == home_country {
+            return Ok(Some(VatCharge { treatment: VatTreatment::Standard, rate: home.standard_rate }));
+        }
+
+        let rule = self.rule(country)?;
+        let charge = if !home.eu_member || !rule.eu_member {
+            VatCharge { treatment: VatTreatment::OutsideScope, rate: 0 }
+        } else if merchant.vat_number.is_some() {
+            VatCharge { treatment: VatTreatment::ReverseCharge, rate: 0 }
+        } else {
+            VatCharge { treatment: VatTreatment::Standard, rate: rule.standard_rate }
+        };
+
+        Ok(Some(charge))
+    }
+
+    // A country without a rule is an error, charging nothing by mistake is worse than stopping the run.
+    fn rule(&self, country: &str) -> Result<&VatRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.country == country)
+            .ok_or_else(|| anyhow!("No VAT rule for country {}", country))
+    }
+}
+
+// The VAT is charged on the commissions of the disbursement and taken from what is paid out.
+pub fn charge_vat(disbursement: &mut Disbursement, vat: Option<VatCharge>) {
+    disbursement.vat = vat;
+    disbursement.recalculate_totals();
+}
+
+pub fn charge_monthly_fee_vat(monthly_fee: &mut MonthlyFee, vat: Option<VatCharge>) {
+    monthly_fee.vat = vat;
+    monthly_fee.vat_amount = vat.map_or(0, |vat| commission(monthly_fee.amount, vat.rate));
+    monthly_fee.gross_amount = monthly_fee.amount + monthly_fee.vat_amount;
+}
+
+// An ISO 3166-1 alpha-2 code, upper-cased, the way the rules and the merchants store it.
+pub fn parse_country(value: &str) -> Result<String> {
+    let country = value.trim().to_uppercase();
+    if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+        bail!("Invalid country: {}", value);
+    }
+    Ok(country)
+}
+
+// The rules file of finance: `country;standard_rate;eu_member` with a header, e.g. `ES;21.00;true`.
+pub fn parse_vat_rules_csv(content: &str) -> Result<Vec<VatRule>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .has_headers(true)
+        .from_reader(content.as_bytes());
+
+    let mut rules = Vec::new();
+
+    for result in reader.records() {
+        let record = result?;
+        rules.push(VatRule {
+            country: parse_country(&record[0])?,
+            standard_rate: parse_percentage(record[1].trim())?,
+            eu_member: record[2].trim().to_lowercase().parse()?,
+        });
+    }
+
+    Ok(rules)
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::header::{CONTENT_TYPE, USER_AGENT};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Url};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::entities::webhooks::{AttemptOutcome, WebhookDelivery, WebhookEndpoint};
+use crate::services::webhooks::{is_local_ip, signature, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+
+// How much of the answer of a failed attempt goes to the delivery log.
+const MAX_LOGGED_BODY: usize = 500;
+
+// POSTs the deliveries to the endpoints of the merchants. Redirects are not followed, the signed body would be
+// sent somewhere the merchant didn't register. The hosts are checked again when sending, on the addresses they
+// resolve to then: a public name registered before can point to our own network by now. `allow_local` lifts
+// the check, only for trying the webhooks on a development machine.
+#[derive(Clone)]
+pub struct WebhookClient {
+    client: Client,
+    allow_local: bool,
+}
+
+impl WebhookClient {
+    pub fn new(timeout: Duration, allow_local: bool) -> Result<Self> {
+        let mut builder = Client::builder()
+            .timeout(timeout)
+            .redirect(Policy::none());
+        if !allow_local {
+            builder = builder.dns_resolver(Arc::new(PublicResolver));
+        }
+
+        Ok(Self { client: builder.build()?, allow_local })
+    }
+
+    // Never fails, what went wrong is the outcome of the attempt.
+    pub async fn send(&self, endpoint: &WebhookEndpoint, delivery: &WebhookDelivery, sent_at: DateTime<Utc>) -> AttemptOutcome {
+        let body = delivery.payload.to_string();
+        let timestamp = sent_at.timestamp();
+
+        // reqwest doesn't resolve the ip hosts, they are checked here
+        if !self.allow_local && is_local_ip_host(&endpoint.url) {
+            return AttemptOutcome {
+                response_status: None,
+                error: Some(format!("{} is on a local or private network", endpoint.url)),
+                duration_ms: 0,
+            };
+        }
+
+        let started = Instant::now();
+        let response = self.client
+            .post(&endpoint.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(USER_AGENT, "calculator-webhooks")
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .header(EVENT_HEADER, &delivery.topic)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature(&endpoint.secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await;
+
+        let (response_status, error) = match response {
+            Ok(response) => {
+                let status = response.status();
+                let error = if status.is_success() {
+                    None
+                } else {
+                    let text = response.text().await.unwrap_or_default();
+                    Some(text.chars().take(MAX_LOGGED_BODY).collect::<String>()).filter(|text| !text.is_empty())
+                };
+                (Some(status.as_u16()), error)
+            }
+            Err(err) => (None, Some(error_chain(&err))),
+        };
+
+        AttemptOutcome {
+            response_status,
+            error,
+            duration_ms: started.elapsed().as_millis() as i64,
+        }
+    }
+}
+
+fn is_local_ip_host(url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    let host = url.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+
+    host.parse::<IpAddr>().is_ok_and(|ip| is_local_ip(&ip))
+}
+
+// Resolves the hosts with the system resolver and keeps only their public addresses, so a name pointing to
+// localhost, loopback, private or link-local addresses is never connected to.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|address| !is_local_ip(&address.ip()))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("{} resolves to a local or private network", host).into());
+            }
+
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+// reqwest's own message is only "error sending request", the cause is in the sources.
+fn error_chain(err: &(dyn std::error::Error + 'static)) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+
+    message
+}
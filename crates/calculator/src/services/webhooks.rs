@@ -0,0 +1,160 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use sha2::Sha256;
+use uuid::Uuid;
+use crate::entities::webhooks::{AttemptOutcome, DeliveryAttempt, DeliveryStatus, WebhookDelivery, WebhookEndpoint};
+use crate::services::outbox::{DISBURSEMENT_CREATED, DISBURSEMENT_STATUS_CHANGED, MONTHLY_FEE_CALCULATED};
+
+pub const WEBHOOK_TOPICS: [&str; 3] = [DISBURSEMENT_CREATED, MONTHLY_FEE_CALCULATED, DISBURSEMENT_STATUS_CHANGED];
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+// The wait after each failed attempt, the delivery fails when they run out (some 22 hours after the first one).
+const RETRY_DELAYS_MINUTES: [i64; 7] = [1, 5, 30, 60, 180, 360, 720];
+
+// No topics means all of them. The url must be https and not on our own network (localhost, loopback, private
+// or link-local addresses), the deliveries would reach the internal services. `allow_local` lifts both, it's
+// only for trying the webhooks on a development machine.
+pub fn new_endpoint(merchant_reference: &str, url: &str, topics: &[String], allow_local: bool, created_at: DateTime<Utc>) -> Result<WebhookEndpoint> {
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(err) => bail!("Invalid webhook url {}: {}", url, err),
+    };
+    let Some(host) = parsed.host_str() else {
+        bail!("Invalid webhook url {}: it must be an https url", url);
+    };
+    if !allow_local {
+        if parsed.scheme() != "https" {
+            bail!("Invalid webhook url {}: it must be an https url", url);
+        }
+        if is_local_host(host) {
+            bail!("Invalid webhook url {}: the host is on a local or private network", url);
+        }
+    } else if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("Invalid webhook url {}: it must be an http(s) url", url);
+    }
+    if let Some(topic) = topics.iter().find(|topic| !WEBHOOK_TOPICS.contains(&topic.as_str())) {
+        bail!("Unknown webhook topic {}, expected one of {}", topic, WEBHOOK_TOPICS.join(", "));
+    }
+
+    let topics = if topics.is_empty() {
+        WEBHOOK_TOPICS.iter().map(|topic| topic.to_string()).collect()
+    } else {
+        topics.to_vec()
+    };
+
+    Ok(WebhookEndpoint {
+        id: Uuid::new_v4(),
+        merchant_reference: merchant_reference.to_string(),
+        url: url.to_string(),
+        secret: format!("whsec_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+        topics,
+        active: true,
+        created_at,
+    })
+}
+
+// The url parser already turned the IPv4 spellings (decimal, hex...) into the dotted form.
+fn is_local_host(host: &str) -> bool {
+    match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => is_local_ip(&ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.');
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+    }
+}
+
+pub(crate) fn is_local_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_local_ipv4(ip),
+        IpAddr::V6(ip) => is_local_ipv6(ip),
+    }
+}
+
+// 0.0.0.0/8, 127.0.0.0/8, 10.0.0.0/8, 172.16.0.0/12, 192.168.0.0/16, 169.254.0.0/16 and 100.64.0.0/10.
+fn is_local_ipv4(ip: &Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_broadcast() || first == 0 || (first == 100 && (64..128).contains(&second))
+}
+
+// ::, ::1, fc00::/7, fe80::/10, and the IPv4 ones mapped to IPv6.
+fn is_local_ipv6(ip: &Ipv6Addr) -> bool {
+    if let Some(ipv4) = ip.to_ipv4_mapped() {
+        return is_local_ipv4(&ipv4);
+    }
+    let first = ip.segments()[0];
+    ip.is_loopback() || ip.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+}
+
+// HMAC-SHA256 of "<timestamp>.<body>" with the secret of the endpoint, hex encoded. The timestamp is signed too
+// so a captured delivery can't be replayed later with a new one, the merchants reject the old timestamps.
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// What a merchant does with a delivery, in constant time.
+pub fn verify_signature(secret: &str, timestamp: i64, body: &str, header: &str) -> bool {
+    let Some(expected) = header.strip_prefix("sha256=").and_then(|digest| hex::decode(digest).ok()) else {
+        return false;
+    };
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    mac.verify_slice(&expected).is_ok()
+}
+
+// When to try again after `attempts` attempts that failed, None once they ran out.
+pub fn next_attempt_at(attempts: i32, attempted_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let delay = RETRY_DELAYS_MINUTES.get(usize::try_from(attempts).ok()?.checked_sub(1)?)?;
+
+    Some(attempted_at + Duration::minutes(*delay))
+}
+
+// Any 2xx delivers it, anything else (another status, no answer at all) is retried.
+pub fn record_attempt(delivery: &mut WebhookDelivery, outcome: &AttemptOutcome, attempted_at: DateTime<Utc>) -> DeliveryAttempt {
+    delivery.attempts += 1;
+
+    if outcome.is_success() {
+        delivery.status = DeliveryStatus::Delivered;
+        delivery.delivered_at = Some(attempted_at);
+        delivery.next_attempt_at = None;
+    } else {
+        delivery.next_attempt_at = next_attempt_at(delivery.attempts, attempted_at);
+        if delivery.next_attempt_at.is_none() {
+            delivery.status = DeliveryStatus::Failed;
+        }
+    }
+
+    DeliveryAttempt {
+        delivery_id: delivery.id,
+        attempted_at,
+        response_status: outcome.response_status.map(i32::from),
+        error: outcome.error.clone(),
+        duration_ms: outcome.duration_ms,
+    }
+}
+
+// Sends it again right away with all the attempts, failed or delivered (the merchant lost it). The log keeps the
+// earlier attempts.
+pub fn redeliver(delivery: &mut WebhookDelivery, redelivered_at: DateTime<Utc>) -> Result<()> {
+    if delivery.status == DeliveryStatus::Pending {
+        bail!("Delivery {} is still pending, it is retried on its own", delivery.id);
+    }
+
+    delivery.status = DeliveryStatus::Pending;
+    delivery.attempts = 0;
+    delivery.next_attempt_at = Some(redelivered_at);
+    delivery.delivered_at = None;
+
+    Ok(())
+}
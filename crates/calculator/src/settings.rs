@@ -1 +1,5 @@
 pub mod config;
+pub mod payouts;
+pub mod calendar;
+pub mod disbursements;
+pub mod invoices;
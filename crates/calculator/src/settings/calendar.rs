@@ -0,0 +1,24 @@
+use std::env;
+use crate::entities::merchants::RollConvention;
+
+// Only needed by the disbursement runs, both variables are optional.
+#[derive(Debug, Clone)]
+pub struct CalendarSettings {
+    pub holidays_csv_path: Option<String>,
+    pub roll_convention: RollConvention,
+}
+
+impl CalendarSettings {
+    pub fn from_env() -> Self {
+        let holidays_csv_path = env::var("HOLIDAYS_CSV_PATH").ok().filter(|path| !path.is_empty());
+
+        let roll_convention = env::var("PAYOUT_ROLL_CONVENTION")
+            .map(|value| value.parse().expect("PAYOUT_ROLL_CONVENTION must be NEXT or PREVIOUS"))
+            .unwrap_or_default();
+
+        CalendarSettings {
+            holidays_csv_path,
+            roll_convention,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::env;
+
+// Only needed by the disbursement runs.
+#[derive(Debug, Clone, Default)]
+pub struct DisbursementSettings {
+    // in euro cents, converted to the payout currency of each merchant. 0 pays out every disbursement
+    pub minimum_payout: i64,
+    // in euro cents, a negative balance above it is flagged for collection right away. None never flags it
+    pub negative_balance_limit: Option<i64>,
+    // ISO 3166-1 alpha-2 country Company XYZ charges VAT from, None calculates no VAT
+    pub vat_home_country: Option<String>,
+}
+
+impl DisbursementSettings {
+    pub fn from_env() -> Self {
+        let minimum_payout = env::var("MINIMUM_PAYOUT_AMOUNT")
+            .map(|value| value.parse().expect("MINIMUM_PAYOUT_AMOUNT must be an amount in euro cents"))
+            .unwrap_or(0);
+
+        let negative_balance_limit = env::var("NEGATIVE_BALANCE_LIMIT")
+            .ok()
+            .map(|value| value.parse().expect("NEGATIVE_BALANCE_LIMIT must be an amount in euro cents"));
+
+        let vat_home_country = env::var("VAT_HOME_COUNTRY")
+            .ok()
+            .filter(|country| !country.is_empty())
+            .map(|country| country.to_uppercase());
+
+        DisbursementSettings {
+            minimum_payout,
+            negative_balance_limit,
+            vat_home_country,
+        }
+    }
+}
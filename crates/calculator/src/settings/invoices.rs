@@ -0,0 +1,16 @@
+use std::env;
+
+// Only needed to issue invoices, the issued ones keep the values they were issued with.
+#[derive(Debug, Clone, Default)]
+pub struct InvoiceSettings {
+    pub seller_name: String,
+}
+
+impl InvoiceSettings {
+    pub fn from_env() -> Self {
+        let seller_name = env::var("INVOICE_SELLER_NAME")
+            .expect("INVOICE_SELLER_NAME must be set in .env or .env.test");
+
+        InvoiceSettings { seller_name }
+    }
+}
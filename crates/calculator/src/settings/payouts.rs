@@ -0,0 +1,28 @@
+use std::env;
+
+// The Company XYZ account the payouts are sent from, only needed to export payout files.
+#[derive(Debug, Clone)]
+pub struct PayoutSettings {
+    pub debtor_name: String,
+    pub debtor_iban: String,
+    pub debtor_bic: String,
+}
+
+impl PayoutSettings {
+    pub fn from_env() -> Self {
+        let debtor_name = env::var("PAYOUT_DEBTOR_NAME")
+            .expect("PAYOUT_DEBTOR_NAME must be set in .env or .env.test");
+
+        let debtor_iban = env::var("PAYOUT_DEBTOR_IBAN")
+            .expect("PAYOUT_DEBTOR_IBAN must be set in .env or .env.test");
+
+        let debtor_bic = env::var("PAYOUT_DEBTOR_BIC")
+            .expect("PAYOUT_DEBTOR_BIC must be set in .env or .env.test");
+
+        PayoutSettings {
+            debtor_name,
+            debtor_iban,
+            debtor_bic,
+        }
+    }
+}
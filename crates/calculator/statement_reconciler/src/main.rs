@@ -0,0 +1,49 @@
+use anyhow::{anyhow, Result};
+use calculator::entities::reconciliation::ReconciliationStatus;
+use calculator::jobs::reconciliation::{reconcile_statement_handler, ReconcileStatementJob};
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+
+// Usage: statement_reconciler --file <camt.053 path> --output <report path>
+// Matches the payouts booked in the bank statement with the disbursements and settles the matched ones.
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let job = parse_args(std::env::args().skip(1))?;
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+
+    let report = reconcile_statement_handler(job.clone(), &pool).await?;
+
+    println!(
+        "Reconciled statements {}: {} matched, {} already matched, {} mismatched, {} unmatched, report in {}",
+        report.statement_ids.join(", "),
+        report.count(ReconciliationStatus::Matched),
+        report.count(ReconciliationStatus::AlreadyMatched),
+        report.count(ReconciliationStatus::Mismatched),
+        report.count(ReconciliationStatus::Unmatched),
+        job.output_path
+    );
+
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<ReconcileStatementJob> {
+    let mut statement_path = None;
+    let mut output_path = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--file" => statement_path = Some(value()?),
+            "--output" => output_path = Some(value()?),
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(ReconcileStatementJob {
+        statement_path: statement_path.ok_or_else(|| anyhow!("--file is required"))?,
+        output_path: output_path.ok_or_else(|| anyhow!("--output is required"))?,
+    })
+}
@@ -0,0 +1,41 @@
+use anyhow::{anyhow, Result};
+use calculator::jobs::statements::{render_statement_handler, StatementFormat};
+use calculator::settings::config::Settings;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+// Usage: statements --merchant <merchant_reference> --month YYYY-MM [--format csv|html] --output <path>
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let mut merchant_reference = None;
+    let mut month = None;
+    let mut format = StatementFormat::Csv;
+    let mut output_path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--merchant" => merchant_reference = Some(value()?),
+            "--month" => month = Some(NaiveDate::parse_from_str(&format!("{}-01", value()?), "%Y-%m-%d")?),
+            "--format" => format = value()?.parse()?,
+            "--output" => output_path = Some(value()?),
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    let merchant_reference = merchant_reference.ok_or_else(|| anyhow!("--merchant is required"))?;
+    let month = month.ok_or_else(|| anyhow!("--month is required"))?;
+    let output_path = output_path.ok_or_else(|| anyhow!("--output is required"))?;
+
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+    let content = render_statement_handler(&pool, &merchant_reference, month, format).await?;
+
+    std::fs::write(&output_path, content)?;
+    println!("Rendered the {} statement of {} to {}", month.format("%Y-%m"), merchant_reference, output_path);
+
+    Ok(())
+}
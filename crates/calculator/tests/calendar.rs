@@ -0,0 +1,101 @@
+use calculator::entities::merchants::{DisbursementFrequency, RollConvention};
+use calculator::services::calendar::{parse_holidays_csv, BankHoliday, BusinessCalendar};
+use calculator::services::disbursement_calculator::is_payout_day;
+mod utils;
+
+use utils::{date, merchant};
+
+fn calendar(roll_convention: RollConvention) -> BusinessCalendar {
+    let holidays = parse_holidays_csv(&std::fs::read_to_string("tests/fixtures/target2_holidays.csv").unwrap()).unwrap();
+    BusinessCalendar::new(holidays, roll_convention)
+}
+
+#[test]
+fn it_rolls_weekends_and_holidays_to_the_next_or_previous_business_day() {
+    let calendar = calendar(RollConvention::Next);
+
+    // easter 2023, good friday and easter monday are TARGET2 holidays
+    assert!(!calendar.is_business_day(date("2023-04-07"), "EUR"));
+    assert!(!calendar.is_business_day(date("2023-04-08"), "EUR"));
+    assert!(!calendar.is_business_day(date("2023-04-10"), "EUR"));
+    assert!(calendar.is_business_day(date("2023-04-11"), "EUR"));
+
+    assert_eq!(calendar.roll(date("2023-04-07"), RollConvention::Next, "EUR"), date("2023-04-11"));
+    assert_eq!(calendar.roll(date("2023-04-07"), RollConvention::Previous, "EUR"), date("2023-04-06"));
+    assert_eq!(calendar.roll(date("2023-04-12"), RollConvention::Next, "EUR"), date("2023-04-12"));
+
+    assert_eq!(
+        calendar.scheduled_days_paid_on(date("2023-04-11"), RollConvention::Next, "EUR"),
+        vec![date("2023-04-11"), date("2023-04-10"), date("2023-04-09"), date("2023-04-08"), date("2023-04-07")]
+    );
+    assert!(calendar.scheduled_days_paid_on(date("2023-04-08"), RollConvention::Next, "EUR").is_empty());
+}
+
+#[test]
+fn it_pays_the_skipped_disbursement_days_on_the_rolled_business_day() {
+    // 2022-01-07 was a friday
+    let weekly = merchant("2022-01-07", DisbursementFrequency::Weekly, 0);
+    let daily = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let next = calendar(RollConvention::Next);
+
+    assert!(!is_payout_day(&weekly, &next, date("2023-04-07")));
+    assert!(is_payout_day(&weekly, &next, date("2023-04-11")));
+    assert!(is_payout_day(&weekly, &next, date("2023-04-14")));
+    assert!(!is_payout_day(&daily, &next, date("2023-01-07")));
+    assert!(is_payout_day(&daily, &next, date("2023-01-09")));
+
+    // the merchant convention wins over the global one
+    let mut previous = weekly.clone();
+    previous.roll_convention = Some(RollConvention::Previous);
+    assert!(is_payout_day(&previous, &next, date("2023-04-06")));
+    assert!(!is_payout_day(&previous, &next, date("2023-04-11")));
+}
+
+#[test]
+fn it_rolls_monthly_disbursements_falling_on_a_holiday() {
+    let mut monthly = merchant("2022-01-01", DisbursementFrequency::Monthly, 0);
+    monthly.disbursement_day_of_month = Some(1);
+    let calendar = calendar(RollConvention::Next);
+
+    // 2023-01-01 was a sunday and new year's day
+    assert!(!is_payout_day(&monthly, &calendar, date("2023-01-01")));
+    assert!(is_payout_day(&monthly, &calendar, date("2023-01-02")));
+    assert!(!is_payout_day(&monthly, &calendar, date("2023-01-03")));
+}
+
+#[test]
+fn it_skips_the_holidays_of_the_payout_currency_only() {
+    let mut holidays = parse_holidays_csv(&std::fs::read_to_string("tests/fixtures/target2_holidays.csv").unwrap()).unwrap();
+    // the coronation bank holiday in the UK, a TARGET2 business day
+    holidays.push(BankHoliday { date: date("2023-05-08"), currency: "GBP".to_string() });
+    let calendar = BusinessCalendar::new(holidays, RollConvention::Next);
+
+    let euros = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let mut pounds = euros.clone();
+    pounds.payout_currency = "GBP".to_string();
+
+    // labour day is only a TARGET2 holiday here
+    assert!(!is_payout_day(&euros, &calendar, date("2023-05-01")));
+    assert!(is_payout_day(&pounds, &calendar, date("2023-05-01")));
+    assert!(is_payout_day(&euros, &calendar, date("2023-05-08")));
+    assert!(!is_payout_day(&pounds, &calendar, date("2023-05-08")));
+
+    let parsed = parse_holidays_csv("date;name;currency\n2023-05-08;Coronation;gbp\n2023-05-01;Labour Day;\n").unwrap();
+    assert_eq!(parsed[0], BankHoliday { date: date("2023-05-08"), currency: "GBP".to_string() });
+    assert_eq!(parsed[1].currency, "EUR");
+}
+
+#[test]
+fn it_pays_on_the_first_business_day_the_days_that_would_roll_back_before_live_on() {
+    // live on saturday 2023-04-08, in the easter weekend, paid weekly on saturdays
+    let mut weekly = merchant("2023-04-08", DisbursementFrequency::Weekly, 0);
+    weekly.roll_convention = Some(RollConvention::Previous);
+    let calendar = calendar(RollConvention::Next);
+
+    // the 8th would roll back to the 6th, before the merchant was live
+    assert!(!is_payout_day(&weekly, &calendar, date("2023-04-06")));
+    assert!(is_payout_day(&weekly, &calendar, date("2023-04-11")));
+    assert!(!is_payout_day(&weekly, &calendar, date("2023-04-12")));
+    // the next saturday rolls back to friday as usual
+    assert!(is_payout_day(&weekly, &calendar, date("2023-04-14")));
+}
@@ -0,0 +1,98 @@
+use calculator::entities::disbursement_batches::{BatchStatus, BatchTotal};
+use calculator::entities::disbursements::Disbursement;
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::services::disbursement_batches::{approve_batch, batch_totals, ensure_approved, new_batch, withdraw_approvals};
+use calculator::services::disbursement_calculator::empty_disbursement;
+use chrono::Utc;
+mod utils;
+
+use utils::{date, merchant};
+
+fn disbursement(net_amount: i64, currency: &str) -> Disbursement {
+    let mut disbursement = empty_disbursement(&merchant("2022-01-01", DisbursementFrequency::Daily, 0), date("2023-01-02"));
+    disbursement.net_amount = net_amount;
+    disbursement.currency = currency.to_string();
+    disbursement
+}
+
+fn total(currency: &str, disbursement_count: i64, net_amount: i64) -> BatchTotal {
+    BatchTotal { currency: currency.to_string(), disbursement_count, net_amount }
+}
+
+#[test]
+fn it_totals_the_batch_per_currency() {
+    let disbursements = vec![disbursement(1000, "EUR"), disbursement(250, "GBP"), disbursement(500, "EUR")];
+
+    assert_eq!(batch_totals(&disbursements), vec![total("EUR", 2, 1500), total("GBP", 1, 250)]);
+}
+
+#[test]
+fn it_needs_two_different_approvers() {
+    let mut batch = new_batch(date("2023-01-02"), Utc::now());
+
+    let disbursements = [disbursement(1000, "EUR"), disbursement(500, "EUR")];
+
+    approve_batch(&mut batch, "alice", &disbursements, Utc::now()).unwrap();
+    assert_eq!(batch.status, BatchStatus::PartiallyApproved);
+    assert!(ensure_approved(std::slice::from_ref(&batch)).is_err());
+
+    assert!(approve_batch(&mut batch, " Alice ", &disbursements, Utc::now()).is_err());
+    assert!(approve_batch(&mut batch, "", &disbursements, Utc::now()).is_err());
+    assert_eq!(batch.status, BatchStatus::PartiallyApproved);
+
+    let approval = approve_batch(&mut batch, "bob", &disbursements, Utc::now()).unwrap();
+    assert_eq!(batch.status, BatchStatus::Approved);
+    assert_eq!(batch.approved_at, Some(approval.approved_at));
+    assert_eq!(batch.approvals.len(), 2);
+    assert!(ensure_approved(&[batch.clone()]).is_ok());
+
+    assert!(approve_batch(&mut batch, "carol", &disbursements, Utc::now()).is_err());
+}
+
+#[test]
+fn it_refuses_the_second_approval_when_the_disbursements_changed() {
+    let mut batch = new_batch(date("2023-01-02"), Utc::now());
+    let disbursements = [disbursement(1000, "EUR"), disbursement(500, "EUR")];
+
+    assert!(approve_batch(&mut batch, "alice", &[], Utc::now()).is_err());
+
+    approve_batch(&mut batch, "alice", &disbursements, Utc::now()).unwrap();
+    let added = [disbursements[0].clone(), disbursements[1].clone(), disbursement(200, "EUR")];
+    assert!(approve_batch(&mut batch, "bob", &added, Utc::now()).is_err());
+    // same totals, other disbursements
+    let swapped = [disbursements[0].clone(), disbursement(500, "EUR")];
+    assert!(approve_batch(&mut batch, "bob", &swapped, Utc::now()).is_err());
+    assert_eq!(batch.status, BatchStatus::PartiallyApproved);
+    assert_eq!(batch.totals, vec![total("EUR", 2, 1500)]);
+}
+
+#[test]
+fn it_withdraws_the_first_approval_when_a_disbursement_is_voided() {
+    let mut batch = new_batch(date("2023-01-02"), Utc::now());
+    let disbursements = [disbursement(1000, "EUR"), disbursement(500, "EUR")];
+    approve_batch(&mut batch, "alice", &disbursements, Utc::now()).unwrap();
+
+    assert!(withdraw_approvals(&mut batch, &disbursements[1..]));
+    assert_eq!(batch.status, BatchStatus::Open);
+    assert!(batch.approvals.is_empty() && batch.totals.is_empty() && batch.disbursement_ids.is_empty());
+    // alice approves what is left again, bob can approve it with her
+    approve_batch(&mut batch, "alice", &disbursements[1..], Utc::now()).unwrap();
+    approve_batch(&mut batch, "bob", &disbursements[1..], Utc::now()).unwrap();
+    assert_eq!(batch.status, BatchStatus::Approved);
+    assert!(!withdraw_approvals(&mut batch, &[]));
+
+    // a batch left with nothing to approve is closed, it doesn't hold the export of its date back
+    let mut voided = new_batch(date("2023-01-02"), Utc::now());
+    assert!(withdraw_approvals(&mut voided, &[]));
+    assert_eq!(voided.status, BatchStatus::Closed);
+    assert!(approve_batch(&mut voided, "alice", &disbursements, Utc::now()).is_err());
+    assert!(ensure_approved(&[batch, voided]).is_ok());
+}
+
+#[test]
+fn it_only_exports_when_every_batch_is_approved() {
+    let batch = new_batch(date("2023-01-02"), Utc::now());
+
+    assert!(ensure_approved(&[]).is_ok());
+    assert!(ensure_approved(&[batch]).is_err());
+}
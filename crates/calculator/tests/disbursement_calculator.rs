@@ -0,0 +1,193 @@
+use calculator::entities::disbursements::LineKind;
+use calculator::entities::merchants::{DisbursementFrequency, Merchant, MonthlyFeeMode};
+use calculator::services::calendar::BusinessCalendar;
+use calculator::services::disbursement_calculator::{
+    build_disbursement, build_monthly_fee, carry_forward, deduct_monthly_fees, is_disbursement_day, minimum_payout, must_flush,
+    DisbursementRun,
+};
+use calculator::services::fees::commission;
+use calculator::services::run_diff::{diff_runs, DiffStatus};
+use calculator::services::fx::{parse_fx_rates_csv, FxRates};
+use chrono::Weekday;
+use rstest::rstest;
+mod utils;
+
+use utils::{date, default_plan, default_pricing, merchant, order};
+
+#[rstest]
+#[case(4_999, 100, 50)]
+#[case(5_000, 95, 48)]
+#[case(29_999, 95, 285)]
+#[case(30_000, 85, 255)]
+#[case(6_174, 95, 59)]
+fn it_applies_the_commission_tier_and_rounds_half_up(#[case] amount: i64, #[case] rate: i32, #[case] fee: i64) {
+    assert_eq!(default_plan().fee_rate(amount), rate);
+    assert_eq!(commission(amount, rate), fee);
+}
+
+#[test]
+fn it_disburses_daily_merchants_every_day_and_weekly_merchants_on_the_live_on_weekday() {
+    let daily = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let weekly = merchant("2022-01-01", DisbursementFrequency::Weekly, 0);
+
+    assert!(is_disbursement_day(&daily, date("2023-01-03")));
+    assert!(!is_disbursement_day(&daily, date("2021-12-31")));
+    // 2022-01-01 was a saturday
+    assert!(is_disbursement_day(&weekly, date("2023-01-07")));
+    assert!(!is_disbursement_day(&weekly, date("2023-01-06")));
+}
+
+#[test]
+fn it_disburses_on_the_chosen_weekday_every_other_week_for_biweekly_merchants() {
+    let mut weekly = merchant("2022-01-01", DisbursementFrequency::Weekly, 0);
+    weekly.disbursement_weekday = Some(Weekday::Fri);
+    let mut biweekly = merchant("2022-01-01", DisbursementFrequency::Biweekly, 0);
+    biweekly.disbursement_weekday = Some(Weekday::Fri);
+
+    assert!(is_disbursement_day(&weekly, date("2023-01-13")));
+    assert!(!is_disbursement_day(&weekly, date("2023-01-07")));
+    // the first friday since live_on was 2022-01-07, 52 weeks before
+    assert!(is_disbursement_day(&biweekly, date("2023-01-06")));
+    assert!(!is_disbursement_day(&biweekly, date("2023-01-13")));
+    assert!(is_disbursement_day(&biweekly, date("2023-01-20")));
+}
+
+#[test]
+fn it_disburses_monthly_merchants_on_their_day_or_the_last_day_of_shorter_months() {
+    let live_on_day = merchant("2022-01-15", DisbursementFrequency::Monthly, 0);
+    let mut end_of_month = merchant("2022-01-15", DisbursementFrequency::Monthly, 0);
+    end_of_month.disbursement_day_of_month = Some(31);
+
+    assert!(is_disbursement_day(&live_on_day, date("2023-02-15")));
+    assert!(!is_disbursement_day(&live_on_day, date("2023-02-16")));
+    assert!(is_disbursement_day(&end_of_month, date("2023-01-31")));
+    assert!(is_disbursement_day(&end_of_month, date("2023-04-30")));
+    assert!(!is_disbursement_day(&end_of_month, date("2023-04-29")));
+}
+
+#[test]
+fn it_groups_the_orders_in_one_disbursement_with_one_line_per_order() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let orders = vec![order("056d024481a9", 6_174, "2023-01-01"), order("70530cdc7b59", 37_333, "2023-01-01")];
+
+    let disbursement = build_disbursement(&merchant, &orders, &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+
+    assert_eq!(disbursement.lines.len(), 2);
+    assert_eq!(disbursement.gross_amount, 43_507);
+    assert_eq!(disbursement.fee_amount, 59 + 317);
+    assert_eq!(disbursement.net_amount, 43_507 - 376);
+    assert_eq!(disbursement.reference.len(), 12);
+    assert!(disbursement.reference.chars().all(|c| c.is_ascii_alphanumeric()));
+    assert!(build_disbursement(&merchant, &[], &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().is_none());
+}
+
+#[test]
+fn it_charges_the_minimum_monthly_fee_shortfall_of_the_previous_month() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 2_900);
+    let orders = vec![order("056d024481a9", 100_000, "2023-01-15")];
+
+    let monthly_fee = build_monthly_fee(&merchant, &orders, &default_pricing(), &FxRates::default(), date("2023-02-01")).unwrap().unwrap();
+
+    assert_eq!(monthly_fee.month, date("2023-01-01"));
+    assert_eq!(monthly_fee.commissions_amount, 850);
+    assert_eq!(monthly_fee.amount, 2_050);
+
+    let not_live_yet = utils::merchant("2023-02-01", DisbursementFrequency::Daily, 2_900);
+    assert!(build_monthly_fee(&not_live_yet, &[], &default_pricing(), &FxRates::default(), date("2023-02-01")).unwrap().is_none());
+}
+
+#[test]
+fn it_charges_the_minimum_monthly_fee_of_the_offboarding_month_and_never_after() {
+    let mut offboarded = merchant("2022-01-01", DisbursementFrequency::Daily, 2_900);
+    offboarded.offboarded_on = Some(date("2023-01-20"));
+
+    let january = build_monthly_fee(&offboarded, &[], &default_pricing(), &FxRates::default(), date("2023-02-01")).unwrap().unwrap();
+    assert_eq!((january.month, january.amount), (date("2023-01-01"), 2_900));
+
+    assert!(build_monthly_fee(&offboarded, &[], &default_pricing(), &FxRates::default(), date("2023-03-01")).unwrap().is_none());
+    assert!(build_monthly_fee(&offboarded, &[], &default_pricing(), &FxRates::default(), date("2023-07-03")).unwrap().is_none());
+}
+
+#[test]
+fn it_deducts_the_monthly_fee_from_the_first_disbursement_of_the_month_in_deduct_mode() {
+    let merchant = Merchant { monthly_fee_mode: MonthlyFeeMode::Deduct, ..merchant("2022-01-01", DisbursementFrequency::Daily, 2_900) };
+    let previous_month_orders = vec![order("056d024481a9", 100_000, "2023-01-15")];
+    let monthly_fee = build_monthly_fee(&merchant, &previous_month_orders, &default_pricing(), &FxRates::default(), date("2023-02-01")).unwrap().unwrap();
+    assert_eq!(monthly_fee.mode, MonthlyFeeMode::Deduct);
+
+    let orders = vec![order("70530cdc7b59", 10_000, "2023-01-31")];
+    let mut disbursement = build_disbursement(&merchant, &orders, &[], &default_pricing(), &FxRates::default(), date("2023-02-01")).unwrap().unwrap();
+    deduct_monthly_fees(&mut disbursement, std::slice::from_ref(&monthly_fee), &FxRates::default()).unwrap();
+
+    let deduction = disbursement.lines.last().unwrap();
+    assert_eq!((deduction.kind, deduction.amount, deduction.monthly_fee_id), (LineKind::MonthlyFee, -2_050, Some(monthly_fee.id)));
+    assert_eq!((disbursement.gross_amount, disbursement.fee_amount, disbursement.net_amount), (10_000, 95, 7_855));
+
+    // what the disbursement can't cover leaves it negative, to be carried forward
+    let small_orders = vec![order("70530cdc7b59", 1_000, "2023-01-31")];
+    let mut small = build_disbursement(&merchant, &small_orders, &[], &default_pricing(), &FxRates::default(), date("2023-02-01")).unwrap().unwrap();
+    deduct_monthly_fees(&mut small, std::slice::from_ref(&monthly_fee), &FxRates::default()).unwrap();
+    assert_eq!(small.net_amount, -1_060);
+}
+
+#[test]
+fn it_diffs_a_computed_run_against_the_persisted_one() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let persisted_disbursement =
+        build_disbursement(&merchant, &[order("056d024481a9", 6_174, "2023-01-01")], &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+    let recalculated_disbursement =
+        build_disbursement(&merchant, &[order("056d024481a9", 6_174, "2023-01-01")], &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+
+    let persisted = DisbursementRun { disbursements: vec![persisted_disbursement], ..Default::default() };
+    let unchanged = DisbursementRun { disbursements: vec![recalculated_disbursement], ..Default::default() };
+
+    let diff = diff_runs(date("2023-01-02"), &persisted, &unchanged);
+    assert_eq!(diff.disbursements[0].status, DiffStatus::Unchanged);
+    assert!(!diff.has_changes());
+
+    let diff = diff_runs(date("2023-01-02"), &DisbursementRun::default(), &persisted);
+    assert_eq!(diff.disbursements[0].status, DiffStatus::Added);
+
+    let diff = diff_runs(date("2023-01-02"), &persisted, &DisbursementRun::default());
+    assert_eq!(diff.disbursements[0].status, DiffStatus::Removed);
+    assert!(diff.has_changes());
+}
+
+#[test]
+fn it_carries_forward_the_disbursements_below_the_minimum_payout() {
+    let mut merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let small = build_disbursement(&merchant, &[order("a", 40, "2023-01-01")], &[], &default_pricing(), &FxRates::default(), date("2023-01-02"))
+        .unwrap()
+        .unwrap();
+
+    let carried = carry_forward(&small, 100).unwrap();
+    assert_eq!(carried.net_amount, 40);
+    assert_eq!(carried.carried_on, date("2023-01-02"));
+    assert!(carried.disbursement_id.is_none());
+    assert!(carry_forward(&small, 40).is_none());
+
+    // the merchant minimum wins, the global one is in euros
+    let rates = FxRates::new(parse_fx_rates_csv(&std::fs::read_to_string("tests/fixtures/fx_rates.csv").unwrap()).unwrap());
+    assert_eq!(minimum_payout(&merchant, 100, &rates, date("2023-01-02")).unwrap(), 100);
+    merchant.payout_currency = "GBP".to_string();
+    assert_eq!(minimum_payout(&merchant, 100, &rates, date("2023-01-02")).unwrap(), 88);
+    merchant.minimum_payout = Some(500);
+    assert_eq!(minimum_payout(&merchant, 100, &rates, date("2023-01-02")).unwrap(), 500);
+}
+
+#[test]
+fn it_flushes_the_carried_balances_on_the_last_payout_day_of_the_month_and_on_offboarding() {
+    let calendar = BusinessCalendar::default();
+    // 2022-01-07 was a friday
+    let mut weekly = merchant("2022-01-07", DisbursementFrequency::Weekly, 0);
+    let daily = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+
+    assert!(!must_flush(&weekly, &calendar, date("2023-03-24")));
+    assert!(must_flush(&weekly, &calendar, date("2023-03-31")));
+    assert!(!must_flush(&daily, &calendar, date("2023-04-27")));
+    // the weekend is paid on monday, in may
+    assert!(must_flush(&daily, &calendar, date("2023-04-28")));
+
+    weekly.offboarded_on = Some(date("2023-03-20"));
+    assert!(must_flush(&weekly, &calendar, date("2023-03-21")));
+}
@@ -0,0 +1,311 @@
+use calculator::api::{router, ApiState};
+use calculator::entities::disbursement_batches::{BatchStatus, DisbursementBatch};
+use calculator::entities::disbursements::DisbursementStatus;
+use calculator::entities::ledger::AccountBalance;
+use calculator::entities::merchants::{DisbursementFrequency, Merchant};
+use calculator::entities::orders::Order;
+use calculator::entities::payout_returns::{PayoutReturn, ReturnSource};
+use calculator::entities::vat::VatRule;
+use calculator::jobs::disbursement_batches::approve_batch_handler;
+use calculator::jobs::disbursement_status::change_disbursement_status_handler;
+use calculator::jobs::process_disbursements::{process_disbursements_handler, ProcessDisbursementsJob, RunMode, RunOutcome};
+use calculator::repositories::disbursement_batches::find_batches_on;
+use calculator::repositories::ledger::find_merchant_balances;
+use calculator::repositories::merchants::upsert_merchants;
+use calculator::repositories::orders::insert_orders;
+use calculator::repositories::payout_returns::insert_payout_return;
+use calculator::repositories::vat::upsert_vat_rules;
+use calculator::services::calendar::BusinessCalendar;
+use calculator::services::disbursement_batches::ensure_approved;
+use calculator::services::run_diff::{DiffStatus, RunDiff};
+use calculator::settings::config::Settings;
+use calculator::settings::disbursements::DisbursementSettings;
+use chrono::NaiveDate;
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool, Row};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use uuid::Uuid;
+mod utils;
+
+use utils::{clean_db, date, merchant, order};
+
+async fn run(pool: &PgPool, date: NaiveDate, mode: RunMode) -> RunOutcome {
+    let job = ProcessDisbursementsJob { date, mode };
+    process_disbursements_handler(job, pool, &BusinessCalendar::default(), &DisbursementSettings::default()).await.unwrap()
+}
+
+async fn preview(pool: &PgPool, date: NaiveDate) -> RunDiff {
+    match run(pool, date, RunMode::Preview).await {
+        RunOutcome::Previewed(diff) => diff,
+        RunOutcome::Committed(_) => panic!("a preview committed the run"),
+    }
+}
+
+async fn count(pool: &PgPool, table: &str) -> i64 {
+    sqlx::query(&format!("SELECT COUNT(*) AS count FROM {}", table)).fetch_one(pool).await.unwrap().get("count")
+}
+
+#[tokio::test]
+async fn it_previews_a_run_commits_it_and_previews_no_changes_after() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    upsert_merchants(&mut pool.acquire().await.unwrap(), &[merchant("2022-01-01", DisbursementFrequency::Daily, 0)]).await.unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    insert_orders(&mut conn, &[order("order_1", 10_000, "2023-01-01"), order("order_2", 40_000, "2023-01-01")]).await.unwrap();
+    drop(conn);
+
+    let before = preview(&pool, date("2023-01-02")).await;
+    assert!(before.has_changes());
+    assert_eq!(before.disbursements.len(), 1);
+    assert_eq!(before.disbursements[0].status, DiffStatus::Added);
+    let previewed = before.disbursements[0].computed.clone().unwrap();
+    assert_eq!((previewed.gross_amount, previewed.lines.len()), (50_000, 2));
+
+    // the preview rolled everything back
+    assert_eq!(count(&pool, "disbursements").await, 0);
+    assert_eq!(count(&pool, "journal_entries").await, 0);
+    assert_eq!(count(&pool, "event_outbox").await, 0);
+
+    let RunOutcome::Committed(committed) = run(&pool, date("2023-01-02"), RunMode::Commit).await else {
+        panic!("the run was not committed");
+    };
+    assert_eq!(committed.disbursements.len(), 1);
+    assert_eq!(
+        (committed.disbursements[0].gross_amount, committed.disbursements[0].net_amount),
+        (previewed.gross_amount, previewed.net_amount)
+    );
+    assert_eq!(count(&pool, "disbursements").await, 1);
+
+    let after = preview(&pool, date("2023-01-02")).await;
+    assert!(!after.has_changes());
+    assert_eq!(after.disbursements[0].status, DiffStatus::Unchanged);
+
+    // committing the date again disburses nothing twice
+    let RunOutcome::Committed(again) = run(&pool, date("2023-01-02"), RunMode::Commit).await else {
+        panic!("the run was not committed");
+    };
+    assert!(again.disbursements.is_empty());
+    assert_eq!(count(&pool, "disbursements").await, 1);
+}
+
+#[tokio::test]
+async fn it_serves_the_preview_of_a_run_over_http() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    let mut conn = pool.acquire().await.unwrap();
+    upsert_merchants(&mut conn, &[merchant("2022-01-01", DisbursementFrequency::Daily, 0)]).await.unwrap();
+    insert_orders(&mut conn, &[order("order_1", 10_000, "2023-01-01")]).await.unwrap();
+    drop(conn);
+
+    let state = ApiState { pool: pool.clone(), calendar: BusinessCalendar::default(), disbursement_settings: DisbursementSettings::default() };
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/disbursement-runs", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router(Arc::new(state))).await.unwrap() });
+
+    let response = reqwest::get(format!("{}/2023-01-02/preview", url)).await.unwrap();
+    assert_eq!(response.status(), 200);
+    let served: Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    let previewed = preview(&pool, date("2023-01-02")).await;
+    assert_eq!(served["date"], "2023-01-02");
+    assert_eq!(served["disbursements"].as_array().unwrap().len(), previewed.disbursements.len());
+    assert_eq!(served["disbursements"][0]["status"], "added");
+    assert_eq!(served["disbursements"][0]["computed"]["net_amount"], previewed.disbursements[0].computed.as_ref().unwrap().net_amount);
+    assert_eq!(count(&pool, "disbursements").await, 0);
+
+    assert_eq!(reqwest::get(format!("{}/2023-02-30/preview", url)).await.unwrap().status(), 400);
+}
+
+#[tokio::test]
+async fn it_skips_a_merchant_without_a_vat_rule_and_pays_the_others() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    upsert_vat_rules(&pool, &[VatRule { country: "ES".to_string(), standard_rate: 2_100, eu_member: true }]).await.unwrap();
+    let spanish = Merchant { country: Some("ES".to_string()), ..merchant("2022-01-01", DisbursementFrequency::Daily, 0) };
+    let french = Merchant {
+        id: Uuid::new_v4(),
+        merchant_reference: "rempel_inc".to_string(),
+        country: Some("FR".to_string()),
+        ..merchant("2022-01-01", DisbursementFrequency::Daily, 0)
+    };
+    let mut conn = pool.acquire().await.unwrap();
+    upsert_merchants(&mut conn, &[spanish, french]).await.unwrap();
+    insert_orders(
+        &mut conn,
+        &[
+            order("order_1", 10_000, "2023-01-01"),
+            Order { merchant_reference: "rempel_inc".to_string(), ..order("order_2", 20_000, "2023-01-01") },
+        ],
+    )
+    .await
+    .unwrap();
+    drop(conn);
+
+    let vat_settings = DisbursementSettings { vat_home_country: Some("ES".to_string()), ..Default::default() };
+    let job = ProcessDisbursementsJob { date: date("2023-01-02"), mode: RunMode::Commit };
+    let RunOutcome::Committed(committed) =
+        process_disbursements_handler(job, &pool, &BusinessCalendar::default(), &vat_settings).await.unwrap()
+    else {
+        panic!("the run was not committed");
+    };
+
+    assert_eq!(committed.disbursements.len(), 1);
+    assert_eq!(committed.disbursements[0].merchant_reference, "padberg_group");
+    assert_eq!(committed.skipped_merchants, vec!["rempel_inc".to_string()]);
+}
+
+#[tokio::test]
+async fn it_pays_the_orders_of_a_voided_disbursement_in_the_next_run() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    let mut conn = pool.acquire().await.unwrap();
+    upsert_merchants(&mut conn, &[merchant("2022-01-01", DisbursementFrequency::Daily, 0)]).await.unwrap();
+    insert_orders(&mut conn, &[order("order_1", 10_000, "2023-01-01"), order("order_2", 40_000, "2023-01-01")]).await.unwrap();
+    let booked = balances(&mut conn).await;
+    drop(conn);
+
+    let RunOutcome::Committed(first) = run(&pool, date("2023-01-02"), RunMode::Commit).await else {
+        panic!("the run was not committed");
+    };
+    let voided = &first.disbursements[0];
+    change_disbursement_status_handler(&pool, &voided.reference, DisbursementStatus::Voided, "alice", None).await.unwrap();
+
+    // the payout is reversed, the merchant is owed its orders as if they were never disbursed
+    let mut conn = pool.acquire().await.unwrap();
+    assert_eq!(balances(&mut conn).await, booked);
+    drop(conn);
+    assert_eq!(count(&pool, "disbursement_lines WHERE voided").await, 2);
+
+    let RunOutcome::Committed(next) = run(&pool, date("2023-01-03"), RunMode::Commit).await else {
+        panic!("the run was not committed");
+    };
+    assert_eq!(next.disbursements.len(), 1);
+    assert_ne!(next.disbursements[0].id, voided.id);
+    assert_eq!(
+        (next.disbursements[0].gross_amount, next.disbursements[0].net_amount, next.disbursements[0].lines.len()),
+        (voided.gross_amount, voided.net_amount, 2)
+    );
+
+    // voiding twice reverses nothing twice
+    assert!(change_disbursement_status_handler(&pool, &voided.reference, DisbursementStatus::Voided, "alice", None).await.is_err());
+}
+
+// the balances that aren't zero
+async fn balances(conn: &mut PgConnection) -> Vec<AccountBalance> {
+    find_merchant_balances(conn, "padberg_group").await.unwrap().into_iter().filter(|balance| balance.balance != 0).collect()
+}
+
+#[tokio::test]
+async fn it_restarts_the_approval_of_a_batch_when_its_disbursements_are_voided() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    let rempel = Merchant {
+        id: Uuid::new_v4(),
+        merchant_reference: "rempel_inc".to_string(),
+        ..merchant("2022-01-01", DisbursementFrequency::Daily, 0)
+    };
+    let mut conn = pool.acquire().await.unwrap();
+    upsert_merchants(&mut conn, &[merchant("2022-01-01", DisbursementFrequency::Daily, 0), rempel]).await.unwrap();
+    insert_orders(
+        &mut conn,
+        &[
+            order("order_1", 10_000, "2023-01-01"),
+            Order { merchant_reference: "rempel_inc".to_string(), ..order("order_2", 20_000, "2023-01-01") },
+        ],
+    )
+    .await
+    .unwrap();
+    drop(conn);
+
+    let RunOutcome::Committed(committed) = run(&pool, date("2023-01-02"), RunMode::Commit).await else {
+        panic!("the run was not committed");
+    };
+    let batch_id = batches_on(&pool, date("2023-01-02")).await[0].id;
+    approve_batch_handler(&pool, batch_id, "alice").await.unwrap();
+
+    // alice approved both, bob can't approve only one of them with her
+    let (voided, kept) = (&committed.disbursements[0], &committed.disbursements[1]);
+    change_disbursement_status_handler(&pool, &voided.reference, DisbursementStatus::Voided, "alice", None).await.unwrap();
+    let batch = approve_batch_handler(&pool, batch_id, "bob").await.unwrap();
+    assert_eq!((batch.status, batch.approvals.len()), (BatchStatus::PartiallyApproved, 1));
+    let batch = approve_batch_handler(&pool, batch_id, "alice").await.unwrap();
+    assert_eq!((batch.status, batch.disbursement_ids.clone()), (BatchStatus::Approved, vec![kept.id]));
+    assert!(ensure_approved(&batches_on(&pool, date("2023-01-02")).await).is_ok());
+
+    // the next run pays the voided one again, voiding it again leaves its batch with nothing to approve
+    let RunOutcome::Committed(next) = run(&pool, date("2023-01-03"), RunMode::Commit).await else {
+        panic!("the run was not committed");
+    };
+    change_disbursement_status_handler(&pool, &next.disbursements[0].reference, DisbursementStatus::Voided, "alice", None).await.unwrap();
+    let batches = batches_on(&pool, date("2023-01-03")).await;
+    assert_eq!(batches[0].status, BatchStatus::Closed);
+    assert!(ensure_approved(&batches).is_ok());
+}
+
+async fn batches_on(pool: &PgPool, date: NaiveDate) -> Vec<DisbursementBatch> {
+    find_batches_on(&mut pool.acquire().await.unwrap(), date).await.unwrap()
+}
+
+#[tokio::test]
+async fn it_calculates_the_monthly_fee_of_a_merchant_whose_payouts_are_held() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    let mut conn = pool.acquire().await.unwrap();
+    upsert_merchants(&mut conn, &[merchant("2022-01-01", DisbursementFrequency::Daily, 1_500)]).await.unwrap();
+    insert_orders(&mut conn, &[order("order_1", 10_000, "2023-01-15"), order("order_2", 20_000, "2023-01-31")]).await.unwrap();
+    drop(conn);
+
+    let RunOutcome::Committed(committed) = run(&pool, date("2023-01-16"), RunMode::Commit).await else {
+        panic!("the run was not committed");
+    };
+    let returned = &committed.disbursements[0];
+    let mut conn = pool.acquire().await.unwrap();
+    insert_payout_return(
+        &mut conn,
+        &PayoutReturn {
+            id: Uuid::new_v4(),
+            disbursement_id: returned.id,
+            merchant_reference: returned.merchant_reference.clone(),
+            returned_on: date("2023-01-20"),
+            amount: returned.net_amount,
+            currency: returned.currency.clone(),
+            reason_code: Some("AC04".to_string()),
+            reason: None,
+            source: ReturnSource::Event,
+            iban: None,
+            bic: None,
+            account_holder: None,
+            released_on: None,
+            redisbursement_id: None,
+        },
+    )
+    .await
+    .unwrap();
+    drop(conn);
+
+    // held, nothing is paid, but the minimum monthly fee of January is still checked on the first day of February
+    let RunOutcome::Committed(held) = run(&pool, date("2023-02-01"), RunMode::Commit).await else {
+        panic!("the run was not committed");
+    };
+    assert!(held.disbursements.is_empty());
+    assert_eq!(held.monthly_fees.len(), 1);
+    assert_eq!((held.monthly_fees[0].month, held.monthly_fees[0].amount), (date("2023-01-01"), 1_215));
+}
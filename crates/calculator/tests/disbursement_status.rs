@@ -0,0 +1,46 @@
+use calculator::entities::disbursements::DisbursementStatus;
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::services::disbursement_calculator::empty_disbursement;
+use calculator::services::disbursement_status::{calculated, change_status, SYSTEM_ACTOR};
+use chrono::Utc;
+use rstest::rstest;
+mod utils;
+
+use utils::{date, merchant};
+use DisbursementStatus::*;
+
+#[rstest]
+#[case(Calculated, Approved, true)]
+#[case(Calculated, Voided, true)]
+#[case(Calculated, Exported, false)]
+#[case(Approved, Exported, true)]
+#[case(Exported, Sent, true)]
+#[case(Exported, Voided, false)]
+#[case(Exported, Approved, true)]
+#[case(Sent, Settled, true)]
+#[case(Sent, Voided, false)]
+#[case(Settled, Returned, true)]
+#[case(Settled, Calculated, false)]
+#[case(Voided, Approved, false)]
+#[case(Returned, Settled, false)]
+fn it_only_allows_the_transitions_of_the_lifecycle(#[case] from: DisbursementStatus, #[case] to: DisbursementStatus, #[case] allowed: bool) {
+    assert_eq!(from.can_change_to(to), allowed);
+}
+
+#[test]
+fn it_records_who_changed_the_status_and_when() {
+    let mut disbursement = empty_disbursement(&merchant("2022-01-01", DisbursementFrequency::Daily, 0), date("2023-01-02"));
+    let now = Utc::now();
+
+    let first = calculated(&disbursement, now);
+    assert_eq!((first.from, first.to, first.actor.as_str()), (None, Calculated, SYSTEM_ACTOR));
+
+    let change = change_status(&mut disbursement, Approved, " alice ", Some("Checked".to_string()), now).unwrap();
+    assert_eq!(disbursement.status, Approved);
+    assert_eq!((change.disbursement_id, change.from, change.to), (disbursement.id, Some(Calculated), Approved));
+    assert_eq!((change.actor.as_str(), change.note.as_deref(), change.changed_at), ("alice", Some("Checked"), now));
+
+    assert!(change_status(&mut disbursement, Settled, "alice", None, now).is_err());
+    assert!(change_status(&mut disbursement, Exported, " ", None, now).is_err());
+    assert_eq!(disbursement.status, Approved);
+}
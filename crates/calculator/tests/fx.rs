@@ -0,0 +1,104 @@
+use calculator::entities::ledger::Account;
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::services::disbursement_calculator::build_disbursement;
+use calculator::services::fx::{parse_fx_rates_csv, FxRates, FX_RATE_SCALE};
+use calculator::services::ledger::{disbursement_entries, order_entry};
+use calculator::services::order_adjustments::build_refund;
+use std::collections::HashMap;
+use uuid::Uuid;
+mod utils;
+
+use utils::{date, default_pricing, merchant, order, order_in};
+
+fn rates() -> FxRates {
+    FxRates::new(parse_fx_rates_csv(&std::fs::read_to_string("tests/fixtures/fx_rates.csv").unwrap()).unwrap())
+}
+
+#[test]
+fn it_uses_the_latest_rate_effective_on_the_date() {
+    let rates = rates();
+
+    assert_eq!(rates.rate("EUR", "GBP", date("2023-01-01")).unwrap(), 88_000_000);
+    assert_eq!(rates.rate("EUR", "GBP", date("2023-01-05")).unwrap(), 88_415_000);
+    assert_eq!(rates.rate("EUR", "EUR", date("2023-01-05")).unwrap(), FX_RATE_SCALE);
+    // no rates before the first date of the file
+    assert!(rates.rate("EUR", "GBP", date("2022-12-31")).is_err());
+    assert!(rates.rate("EUR", "USD", date("2023-01-05")).is_err());
+}
+
+#[test]
+fn it_converts_with_inverse_and_cross_rates_rounding_to_the_minor_unit() {
+    let rates = rates();
+
+    // 100.00 GBP at 1 / 0.88415
+    let to_euros = rates.convert(10_000, "GBP", "EUR", date("2023-01-02")).unwrap();
+    assert_eq!(to_euros.rate, 113_102_980);
+    assert_eq!(to_euros.amount, 11_310);
+    assert_eq!(rates.convert(-10_000, "GBP", "EUR", date("2023-01-02")).unwrap().amount, -11_310);
+
+    // through EUR
+    assert_eq!(rates.convert(10_000, "GBP", "CHF", date("2023-01-02")).unwrap().amount, 11_171);
+
+    // JPY has no decimals
+    assert_eq!(rates.convert(10_000, "EUR", "JPY", date("2023-01-02")).unwrap().amount, 14_050);
+}
+
+#[test]
+fn it_rejects_rates_files_with_invalid_rates_or_currencies() {
+    assert!(parse_fx_rates_csv("rate_date;base_currency;quote_currency;rate\n2023-01-01;EUR;GBP;-0.88\n").is_err());
+    assert!(parse_fx_rates_csv("rate_date;base_currency;quote_currency;rate\n2023-01-01;EUR;GBP;0\n").is_err());
+    assert!(parse_fx_rates_csv("rate_date;base_currency;quote_currency;rate\n2023-01-01;EUR;XXX;1.1\n").is_err());
+}
+
+#[test]
+fn it_disburses_orders_in_the_payout_currency_keeping_the_original_amounts() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let pounds = order_in("056d024481a9", 10_000, "GBP", "2023-01-01");
+    let euros = order("70530cdc7b59", 6_174, "2023-01-01");
+
+    let disbursement = build_disbursement(&merchant, &[pounds.clone(), euros.clone()], &[], &default_pricing(), &rates(), date("2023-01-02"))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(disbursement.currency, "EUR");
+    assert_eq!(disbursement.lines[0].amount, 11_310);
+    assert_eq!(disbursement.lines[0].original_amount, 10_000);
+    assert_eq!(disbursement.lines[0].original_currency, "GBP");
+    assert_eq!(disbursement.lines[0].fee_rate, 95);
+    assert_eq!(disbursement.lines[1].fx_rate, FX_RATE_SCALE);
+    assert_eq!(disbursement.gross_amount, 11_310 + 6_174);
+
+    // without a rate the merchant can't be disbursed
+    assert!(build_disbursement(&merchant, std::slice::from_ref(&pounds), &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).is_err());
+
+    let mut entries = vec![order_entry(&pounds), order_entry(&euros)];
+    entries.extend(disbursement_entries(&disbursement));
+    assert!(entries.iter().all(|entry| entry.is_balanced()));
+
+    let mut balances: HashMap<(Account, String), i64> = HashMap::new();
+    for posting in entries.iter().flat_map(|entry| entry.postings.iter()) {
+        *balances.entry((posting.account, posting.currency.clone())).or_default() += posting.amount;
+    }
+
+    // nothing is owed to the merchant in any currency, we keep the pounds and paid euros for them
+    assert_eq!(balances[&(Account::MerchantPayable, "GBP".to_string())], 0);
+    assert_eq!(balances[&(Account::MerchantPayable, "EUR".to_string())], 0);
+    assert_eq!(balances[&(Account::FxClearing, "GBP".to_string())], -10_000);
+    assert_eq!(balances[&(Account::FxClearing, "EUR".to_string())], 11_310);
+}
+
+#[test]
+fn it_picks_the_fee_tier_and_converts_with_the_rates_of_the_same_day() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    // 44.10 GBP is 50.11 EUR on the day it was paid (medium tier) but 49.88 EUR when it's disbursed (small tier)
+    let order = order_in("gbp_order", 4_410, "GBP", "2023-01-01");
+    // booked before the order was disbursed, with a provisional rate
+    let refund = build_refund(Uuid::new_v4(), &order, 4_410, 95, "DEFAULT:1970-01-01", Some(1_000), date("2023-01-02")).unwrap();
+
+    let disbursement = build_disbursement(&merchant, &[order], &[refund], &default_pricing(), &rates(), date("2023-01-03")).unwrap().unwrap();
+
+    let (order_line, refund_line) = (&disbursement.lines[0], &disbursement.lines[1]);
+    assert_eq!((order_line.amount, order_line.fee_rate), (4_988, 100));
+    assert_eq!((refund_line.amount, refund_line.fee_rate, refund_line.fee_amount), (-1_131, 100, -11));
+    assert_eq!(refund_line.pricing_version, order_line.pricing_version);
+}
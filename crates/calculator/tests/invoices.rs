@@ -0,0 +1,158 @@
+use calculator::entities::invoices::InvoiceKind;
+use calculator::entities::merchants::{BankAccount, DisbursementFrequency, Merchant, MonthlyFeeMode};
+use calculator::entities::monthly_fees::MonthlyFee;
+use calculator::entities::vat::{VatCharge, VatTreatment};
+use calculator::jobs::invoices::{credit_invoice_handler, issue_invoices_handler, IssueInvoicesJob};
+use calculator::repositories::invoices::insert_invoice;
+use calculator::repositories::merchants::upsert_merchants;
+use calculator::repositories::monthly_fees::insert_monthly_fee;
+use calculator::services::invoice_html::to_invoice_html;
+use calculator::services::invoices::{build_credit_note, build_invoice, invoice_number};
+use calculator::services::ubl_invoice::to_ubl;
+use calculator::settings::config::Settings;
+use calculator::settings::invoices::InvoiceSettings;
+use sqlx::PgPool;
+use uuid::Uuid;
+mod utils;
+
+use utils::{clean_db, date};
+
+fn monthly_fee() -> MonthlyFee {
+    MonthlyFee {
+        id: Uuid::new_v4(),
+        merchant_reference: "padberg_group".to_string(),
+        month: date("2023-01-01"),
+        currency: "EUR".to_string(),
+        commissions_amount: 1_250,
+        minimum_monthly_fee: 2_900,
+        amount: 1_650,
+        // 21 % of 16.50, rounded half up
+        vat: Some(VatCharge { treatment: VatTreatment::Standard, rate: 2_100 }),
+        vat_amount: 347,
+        gross_amount: 1_997,
+        calculated_on: date("2023-02-01"),
+        mode: MonthlyFeeMode::Invoice,
+        disbursement_id: None,
+    }
+}
+
+fn merchant() -> Merchant {
+    Merchant {
+        bank_account: Some(BankAccount {
+            iban: "ES9121000418450200051332".to_string(),
+            bic: "CAIXESBBXXX".to_string(),
+            account_holder: "Padberg & Sons".to_string(),
+        }),
+        ..utils::merchant("2022-01-01", DisbursementFrequency::Daily, 2_900)
+    }
+}
+
+fn settings() -> InvoiceSettings {
+    InvoiceSettings { seller_name: "Company XYZ".to_string() }
+}
+
+#[test]
+fn it_invoices_the_shortfall_of_the_monthly_fee_with_its_vat() {
+    let number = invoice_number(InvoiceKind::Invoice, 2023, 7);
+    let invoice = build_invoice(&monthly_fee(), &merchant(), number, date("2023-02-01"), &settings()).unwrap();
+
+    assert_eq!(invoice.number, "INV-2023-000007");
+    assert_eq!(invoice.customer_name, "Padberg & Sons");
+    assert_eq!(invoice.lines.iter().map(|line| line.amount).collect::<Vec<_>>(), vec![2_900, -1_250]);
+    assert_eq!(invoice.net_amount, 1_650);
+    assert_eq!(invoice.vat_amount, 347);
+    assert_eq!(invoice.total_amount, 1_997);
+    assert_eq!(invoice.period_end, date("2023-01-31"));
+
+    let without_vat = MonthlyFee { vat: None, vat_amount: 0, gross_amount: 1_650, ..monthly_fee() };
+    let invoice = build_invoice(&without_vat, &merchant(), "INV-2023-000008".to_string(), date("2023-02-01"), &settings()).unwrap();
+    assert_eq!((invoice.vat_amount, invoice.total_amount), (0, 1_650));
+
+    let deducted = MonthlyFee { mode: MonthlyFeeMode::Deduct, ..monthly_fee() };
+    assert!(build_invoice(&deducted, &merchant(), "INV-2023-000009".to_string(), date("2023-02-01"), &settings()).is_err());
+}
+
+#[test]
+fn it_credits_the_whole_invoice_once() {
+    let invoice = build_invoice(&monthly_fee(), &merchant(), "INV-2023-000001".to_string(), date("2023-02-01"), &settings()).unwrap();
+
+    let credit_note = build_credit_note(&invoice, invoice_number(InvoiceKind::CreditNote, 2023, 1), date("2023-02-10"), Some("Wrong VAT rate".to_string())).unwrap();
+
+    assert_eq!(credit_note.number, "CN-2023-000001");
+    assert_eq!(credit_note.credited_invoice_id, Some(invoice.id));
+    assert_eq!(credit_note.total_amount + invoice.total_amount, 0);
+    assert_eq!(credit_note.vat_amount + invoice.vat_amount, 0);
+    assert_eq!(credit_note.lines.iter().map(|line| line.amount).collect::<Vec<_>>(), vec![-2_900, 1_250]);
+
+    assert!(build_credit_note(&credit_note, "CN-2023-000002".to_string(), date("2023-02-10"), None).is_err());
+    assert!(build_credit_note(&invoice, "CN-2023-000002".to_string(), date("2023-01-31"), None).is_err());
+}
+
+#[test]
+fn it_renders_the_invoice_as_html_and_ubl() {
+    let invoice = build_invoice(&monthly_fee(), &merchant(), "INV-2023-000001".to_string(), date("2023-02-01"), &settings()).unwrap();
+
+    let html = to_invoice_html(&invoice).unwrap();
+    assert!(html.contains("<h1>Invoice INV-2023-000001</h1>"));
+    assert!(html.contains("Padberg &amp; Sons"));
+    assert!(html.contains("VAT 21.00 %"));
+    assert!(html.contains("19.97 EUR"));
+
+    let xml = to_ubl(&invoice).unwrap();
+    assert!(xml.contains("<cbc:InvoiceTypeCode>380</cbc:InvoiceTypeCode>"));
+    assert!(xml.contains("<cbc:PayableAmount currencyID=\"EUR\">19.97</cbc:PayableAmount>"));
+    // the commissions line takes the amount off with a negative quantity, prices are never negative
+    assert!(xml.contains("<cbc:InvoicedQuantity unitCode=\"C62\">-1</cbc:InvoicedQuantity>"));
+    assert!(xml.contains("<cbc:PriceAmount currencyID=\"EUR\">12.50</cbc:PriceAmount>"));
+
+    let credit_note = build_credit_note(&invoice, "CN-2023-000001".to_string(), date("2023-02-10"), None).unwrap();
+    let xml = to_ubl(&credit_note).unwrap();
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<CreditNote "));
+    assert!(xml.contains("<cac:InvoiceDocumentReference><cbc:ID>INV-2023-000001</cbc:ID></cac:InvoiceDocumentReference>"));
+    assert!(xml.contains("<cbc:PayableAmount currencyID=\"EUR\">19.97</cbc:PayableAmount>"));
+
+    let reverse_charged = MonthlyFee {
+        vat: Some(VatCharge { treatment: VatTreatment::ReverseCharge, rate: 0 }),
+        vat_amount: 0,
+        gross_amount: 1_650,
+        ..monthly_fee()
+    };
+    let customer = Merchant { country: Some("DE".to_string()), vat_number: Some("DE123456789".to_string()), ..merchant() };
+    let invoice = build_invoice(&reverse_charged, &customer, "INV-2023-000002".to_string(), date("2023-02-01"), &settings()).unwrap();
+    assert!(to_invoice_html(&invoice).unwrap().contains("VAT reverse charged to the customer"));
+    let xml = to_ubl(&invoice).unwrap();
+    assert!(xml.contains("<cbc:ID>AE</cbc:ID>"));
+    assert!(xml.contains("<cbc:CompanyID>DE123456789</cbc:CompanyID>"));
+}
+
+#[tokio::test]
+async fn it_invoices_a_monthly_fee_once_when_two_runs_issue_at_the_same_time() {
+    dotenvy::from_filename(".env.test").ok();
+    let pool = PgPool::connect(&Settings::from_env().database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    upsert_merchants(&mut pool.acquire().await.unwrap(), &[merchant()]).await.unwrap();
+    let monthly_fee = monthly_fee();
+    insert_monthly_fee(&mut pool.acquire().await.unwrap(), &monthly_fee).await.unwrap();
+
+    let job = || IssueInvoicesJob { month: date("2023-01-01"), issued_on: date("2023-02-01") };
+    let invoice_settings = settings();
+    let (first, second) = tokio::join!(
+        issue_invoices_handler(job(), &pool, &invoice_settings),
+        issue_invoices_handler(job(), &pool, &invoice_settings)
+    );
+    let issued: Vec<String> = first.unwrap().into_iter().chain(second.unwrap()).map(|invoice| invoice.number).collect();
+    assert_eq!(issued, vec!["INV-2023-000001".to_string()]);
+
+    // the index refuses a second invoice of the fee, the number it took is given back with its transaction
+    let mut tx = pool.begin().await.unwrap();
+    let duplicate = build_invoice(&monthly_fee, &merchant(), "INV-2023-000002".to_string(), date("2023-02-01"), &invoice_settings).unwrap();
+    assert!(insert_invoice(&mut tx, &duplicate).await.is_err());
+    tx.rollback().await.unwrap();
+
+    // once credited, the corrected invoice takes the next number
+    credit_invoice_handler(&pool, "INV-2023-000001", date("2023-02-10"), None).await.unwrap();
+    let corrected = issue_invoices_handler(job(), &pool, &invoice_settings).await.unwrap();
+    assert_eq!(corrected.iter().map(|invoice| invoice.number.as_str()).collect::<Vec<_>>(), vec!["INV-2023-000002"]);
+    assert!(issue_invoices_handler(job(), &pool, &invoice_settings).await.unwrap().is_empty());
+}
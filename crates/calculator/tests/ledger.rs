@@ -0,0 +1,64 @@
+use calculator::entities::ledger::{Account, Posting};
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::repositories::ledger::{ensure_entries_balanced, ensure_ledger_balanced, insert_journal_entries};
+use calculator::services::disbursement_calculator::build_disbursement;
+use calculator::services::ledger::{adjustment_entry, disbursement_entries, order_entry};
+use calculator::services::order_adjustments::build_refund;
+use calculator::services::fx::FxRates;
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+mod utils;
+
+use utils::{clean_db, date, default_pricing, merchant, order};
+
+#[test]
+fn it_books_orders_commissions_payouts_and_refunds_as_balanced_entries() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let first = order("056d024481a9", 6_174, "2023-01-01");
+    let second = order("70530cdc7b59", 37_333, "2023-01-01");
+    let disbursement = build_disbursement(&merchant, &[first.clone(), second.clone()], &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+    let refund = build_refund(Uuid::new_v4(), &first, 6_174, 95, "DEFAULT:1970-01-01", None, date("2023-01-03")).unwrap();
+    let refund_disbursement = build_disbursement(&merchant, &[], std::slice::from_ref(&refund), &default_pricing(), &FxRates::default(), date("2023-01-04")).unwrap().unwrap();
+
+    let mut entries = vec![order_entry(&first), order_entry(&second), adjustment_entry(&refund)];
+    entries.extend(disbursement_entries(&disbursement));
+    entries.extend(disbursement_entries(&refund_disbursement));
+
+    assert!(entries.iter().all(|entry| entry.is_balanced()));
+
+    let mut balances: HashMap<Account, i64> = HashMap::new();
+    for Posting { account, amount, .. } in entries.iter().flat_map(|entry| entry.postings.iter().cloned()) {
+        *balances.entry(account).or_default() += amount;
+    }
+
+    assert_eq!(balances.values().sum::<i64>(), 0);
+    // everything owed to the merchant was paid out
+    assert_eq!(balances[&Account::MerchantPayable], 0);
+    // only the commission of the order that was not refunded is kept
+    assert_eq!(balances[&Account::CommissionRevenue], -317);
+}
+
+#[tokio::test]
+async fn it_checks_only_the_entries_a_job_booked() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    let mut tx = pool.begin().await.unwrap();
+    let balanced = order_entry(&order("056d024481a9", 6_174, "2023-01-01"));
+    let mut unbalanced = order_entry(&order("70530cdc7b59", 37_333, "2023-01-01"));
+    unbalanced.postings[0].amount += 1;
+
+    let booked = insert_journal_entries(&mut tx, &[balanced.clone(), unbalanced.clone()]).await.unwrap();
+    assert_eq!(booked, vec![balanced.id, unbalanced.id]);
+    // booked before, nothing is booked again
+    assert!(insert_journal_entries(&mut tx, std::slice::from_ref(&balanced)).await.unwrap().is_empty());
+
+    ensure_entries_balanced(&mut tx, &[balanced.id]).await.unwrap();
+    assert!(ensure_entries_balanced(&mut tx, &booked).await.is_err());
+    assert!(ensure_ledger_balanced(&mut tx).await.is_err());
+    tx.rollback().await.unwrap();
+}
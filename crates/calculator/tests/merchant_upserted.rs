@@ -0,0 +1,134 @@
+use calculator::events::handlers::handler::EventHandler;
+use calculator::events::handlers::merchant_upserted_handler::MerchantUpsertedHandler;
+use calculator::repositories::pricing_plans::find_pricing_plans;
+use calculator::settings::config::Settings;
+use serde_json::{json, Value};
+use sqlx::{PgPool, Row};
+mod utils;
+
+use utils::clean_db;
+
+fn payload() -> Value {
+    json!({
+        "merchant_reference": "padberg_group",
+        "live_on": "2022-01-01",
+        "disbursement_frequency": "DAILY",
+        "minimum_monthly_fee": 1_500,
+        "bank_account": {
+            "iban": "ES9121000418450200051332",
+            "bic": "CAIXESBBXXX",
+            "account_holder": "Padberg Group & Sons"
+        }
+    })
+}
+
+async fn handler() -> (MerchantUpsertedHandler, PgPool) {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    (MerchantUpsertedHandler::new(pool.clone()), pool)
+}
+
+async fn stored_iban(pool: &PgPool) -> Option<Option<String>> {
+    sqlx::query("SELECT iban FROM merchants WHERE merchant_reference = 'padberg_group'")
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+        .map(|row| row.get("iban"))
+}
+
+#[tokio::test]
+async fn it_refuses_a_merchant_with_a_malformed_bank_account() {
+    let (handler, pool) = handler().await;
+
+    let mut malformed = payload();
+    malformed["bank_account"] = json!({"iban": "ES9121000418450200051332"});
+    assert!(handler.handle(malformed).await.is_err());
+    assert_eq!(stored_iban(&pool).await, None);
+
+    let mut without = payload();
+    without["bank_account"] = Value::Null;
+    handler.handle(without).await.unwrap();
+    assert_eq!(stored_iban(&pool).await, Some(None));
+
+    handler.handle(payload()).await.unwrap();
+    assert_eq!(stored_iban(&pool).await, Some(Some("ES9121000418450200051332".to_string())));
+}
+
+#[tokio::test]
+async fn it_never_rewrites_a_pricing_plan_version_it_already_received() {
+    let (handler, pool) = handler().await;
+    let version = json!({"code": "SILVER", "valid_from": "2023-01-01", "tiers": [{"from_amount": 0, "fee_rate": 90}], "fixed_fee": 10});
+
+    let mut with_plan = payload();
+    with_plan["pricing_plan_versions"] = json!([version.clone()]);
+    handler.handle(with_plan.clone()).await.unwrap();
+    // every merchant on the plan carries it, receiving the same version again changes nothing
+    handler.handle(with_plan).await.unwrap();
+
+    let mut rewritten = version.clone();
+    rewritten["tiers"][0]["fee_rate"] = json!(70);
+    let mut changed = payload();
+    changed["minimum_monthly_fee"] = json!(2_000);
+    changed["pricing_plan_versions"] = json!([rewritten]);
+    assert!(handler.handle(changed).await.is_err());
+
+    // neither the version nor the merchant that carried it changed
+    let plans = find_pricing_plans(&mut pool.acquire().await.unwrap()).await.unwrap();
+    let silver = plans.iter().find(|plan| plan.version() == "SILVER:2023-01-01").unwrap();
+    assert_eq!((silver.tiers[0].fee_rate, silver.fixed_fee), (90, 10));
+    let fee: i32 = sqlx::query("SELECT minimum_monthly_fee FROM merchants WHERE merchant_reference = 'padberg_group'")
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .get("minimum_monthly_fee");
+    assert_eq!(fee, 1_500);
+
+    // a new version of the plan is a later valid_from
+    let mut next = rewritten.clone();
+    next["valid_from"] = json!("2023-06-01");
+    let mut next_version = payload();
+    next_version["pricing_plan_versions"] = json!([version, next]);
+    handler.handle(next_version).await.unwrap();
+}
+
+#[tokio::test]
+async fn it_refuses_a_disbursement_day_of_month_out_of_range() {
+    let (handler, pool) = handler().await;
+
+    // 4294967297 would be truncated to the 1st
+    for day in [json!(4_294_967_297_u64), json!(-1), json!(0), json!(32)] {
+        let mut monthly = payload();
+        monthly["disbursement_frequency"] = json!("MONTHLY");
+        monthly["disbursement_day_of_month"] = day;
+        assert!(handler.handle(monthly).await.is_err());
+    }
+    assert_eq!(stored_iban(&pool).await, None);
+
+    let mut monthly = payload();
+    monthly["disbursement_frequency"] = json!("MONTHLY");
+    monthly["disbursement_day_of_month"] = json!(31);
+    handler.handle(monthly).await.unwrap();
+    assert!(stored_iban(&pool).await.is_some());
+}
+
+#[tokio::test]
+async fn it_refuses_a_country_that_is_not_an_iso_code() {
+    let (handler, pool) = handler().await;
+
+    for country in ["ESP", "E", "España", "1A"] {
+        let mut invalid = payload();
+        invalid["country"] = json!(country);
+        assert!(handler.handle(invalid).await.is_err());
+    }
+    assert_eq!(stored_iban(&pool).await, None);
+
+    let mut spanish = payload();
+    spanish["country"] = json!("es");
+    handler.handle(spanish).await.unwrap();
+    let country: Option<String> =
+        sqlx::query("SELECT country FROM merchants").fetch_one(&pool).await.unwrap().get("country");
+    assert_eq!(country.as_deref(), Some("ES"));
+}
@@ -0,0 +1,97 @@
+use calculator::entities::disbursements::LineKind;
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::entities::order_adjustments::AdjustmentKind;
+use calculator::events::handlers::handler::EventHandler;
+use calculator::events::handlers::order_adjusted_handler::OrderAdjustedHandler;
+use calculator::events::handlers::order_refunded_handler::OrderRefundedHandler;
+use calculator::repositories::merchants::upsert_merchants;
+use calculator::repositories::orders::insert_orders;
+use calculator::services::disbursement_calculator::build_disbursement;
+use calculator::services::order_adjustments::{build_correction, build_refund};
+use calculator::services::fx::FxRates;
+use calculator::settings::config::Settings;
+use serde_json::json;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+mod utils;
+
+use utils::{clean_db, date, default_pricing, merchant, order};
+
+#[test]
+fn it_refunds_the_whole_order_by_default_and_reverses_the_original_fee() {
+    let order = order("056d024481a9", 6_174, "2023-01-01");
+    let refund = build_refund(Uuid::new_v4(), &order, 6_174, 95, "DEFAULT:1970-01-01", None, date("2023-01-05")).unwrap();
+
+    assert_eq!(refund.kind, AdjustmentKind::Refund);
+    assert_eq!(refund.amount, -6_174);
+
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let disbursement = build_disbursement(&merchant, &[], &[refund], &default_pricing(), &FxRates::default(), date("2023-01-06")).unwrap().unwrap();
+
+    assert_eq!(disbursement.lines[0].kind, LineKind::Adjustment);
+    // reversed with the pricing version the order was charged with
+    assert_eq!(disbursement.lines[0].pricing_version, "DEFAULT:1970-01-01");
+    assert_eq!(disbursement.gross_amount, -6_174);
+    // the order was charged 59 cents, all of them are given back
+    assert_eq!(disbursement.fee_amount, -59);
+    assert_eq!(disbursement.net_amount, -6_115);
+}
+
+#[test]
+fn it_does_not_refund_more_than_what_is_left_of_the_order() {
+    let order = order("056d024481a9", 6_174, "2023-01-01");
+
+    assert!(build_refund(Uuid::new_v4(), &order, 1_000, 95, "DEFAULT:1970-01-01", Some(1_001), date("2023-01-05")).is_err());
+    assert!(build_refund(Uuid::new_v4(), &order, 1_000, 95, "DEFAULT:1970-01-01", Some(0), date("2023-01-05")).is_err());
+    assert_eq!(build_refund(Uuid::new_v4(), &order, 1_000, 95, "DEFAULT:1970-01-01", Some(400), date("2023-01-05")).unwrap().amount, -400);
+}
+
+#[test]
+fn it_corrects_the_order_amount_with_the_difference() {
+    let order = order("056d024481a9", 6_174, "2023-01-01");
+
+    let correction = build_correction(Uuid::new_v4(), &order, 6_174, 95, "DEFAULT:1970-01-01", 5_000, date("2023-01-05")).unwrap();
+    assert_eq!(correction.kind, AdjustmentKind::Correction);
+    assert_eq!(correction.amount, -1_174);
+    assert_eq!(correction.fee_rate, 95);
+
+    assert!(build_correction(Uuid::new_v4(), &order, 6_174, 95, "DEFAULT:1970-01-01", 6_174, date("2023-01-05")).is_err());
+    assert!(build_correction(Uuid::new_v4(), &order, 6_174, 95, "DEFAULT:1970-01-01", -1, date("2023-01-05")).is_err());
+}
+
+#[tokio::test]
+async fn it_books_a_redelivered_refund_or_correction_once() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    upsert_merchants(&mut pool.acquire().await.unwrap(), &[merchant("2022-01-01", DisbursementFrequency::Daily, 0)]).await.unwrap();
+    insert_orders(&mut pool.acquire().await.unwrap(), &[order("056d024481a9", 6_174, "2023-01-01")]).await.unwrap();
+
+    let refunds = OrderRefundedHandler::new(pool.clone());
+    let refund = json!({"event_id": Uuid::new_v4(), "order_id": "056d024481a9", "amount": 1_000, "refunded_at": "2023-01-05"});
+    refunds.handle(refund.clone()).await.unwrap();
+    refunds.handle(refund).await.unwrap();
+
+    let corrections = OrderAdjustedHandler::new(pool.clone());
+    let correction = json!({"event_id": Uuid::new_v4(), "order_id": "056d024481a9", "amount": 4_000, "adjusted_at": "2023-01-06"});
+    corrections.handle(correction.clone()).await.unwrap();
+    corrections.handle(correction).await.unwrap();
+
+    let amounts: Vec<i64> = sqlx::query("SELECT amount FROM order_adjustments ORDER BY created_at")
+        .fetch_all(&pool)
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| row.get("amount"))
+        .collect();
+    assert_eq!(amounts, vec![-1_000, -1_174]);
+
+    let entries: i64 = sqlx::query("SELECT COUNT(*) AS count FROM journal_entries").fetch_one(&pool).await.unwrap().get("count");
+    assert_eq!(entries, 2);
+
+    // an event without its id can't be told apart from a redelivery
+    let anonymous = json!({"order_id": "056d024481a9", "amount": 100, "refunded_at": "2023-01-07"});
+    assert!(refunds.handle(anonymous).await.is_err());
+}
@@ -0,0 +1,113 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use async_trait::async_trait;
+use calculator::entities::disbursements::DisbursementStatus;
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::events::publisher::EventPublisher;
+use calculator::jobs::publish_events::{publish_events_handler, PublishEventsJob};
+use calculator::repositories::outbox::insert_outbox_event;
+use calculator::services::disbursement_calculator::{build_disbursement, build_monthly_fee};
+use calculator::services::disbursement_status::change_status;
+use calculator::services::fx::FxRates;
+use calculator::services::outbox::{disbursement_created, disbursement_status_changed, monthly_fee_calculated};
+use calculator::settings::config::Settings;
+use chrono::Utc;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+mod utils;
+
+use utils::{clean_db, date, default_pricing, merchant, order};
+
+#[test]
+fn it_publishes_the_created_disbursements_keyed_by_merchant() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let orders = [order("056d024481a9", 6_174, "2023-01-01"), order("70530cdc7b59", 37_333, "2023-01-01")];
+    let disbursement = build_disbursement(&merchant, &orders, &[], &default_pricing(), &FxRates::default(), date("2023-01-02"))
+        .unwrap()
+        .unwrap();
+
+    let event = disbursement_created(&disbursement, Some((date("2022-12-31"), date("2023-01-01"))), Utc::now());
+
+    assert_eq!((event.topic.as_str(), event.merchant_reference.as_str()), ("disbursement_created", "padberg_group"));
+    assert_eq!(event.payload["event_id"], json!(event.id));
+    assert_eq!(event.payload["merchant_reference"], json!("padberg_group"));
+    assert_eq!(event.payload["reference"], json!(disbursement.reference));
+    assert_eq!(event.payload["disbursed_on"], json!("2023-01-02"));
+    assert_eq!((&event.payload["period_from"], &event.payload["period_to"]), (&json!("2022-12-31"), &json!("2023-01-01")));
+    assert_eq!(
+        (&event.payload["gross_amount"], &event.payload["net_amount"], &event.payload["order_count"]),
+        (&json!(43_507), &json!(disbursement.net_amount), &json!(2))
+    );
+    assert_eq!(event.payload["status"], json!("CALCULATED"));
+}
+
+#[test]
+fn it_publishes_the_calculated_monthly_fees() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 2_900);
+    let monthly_fee = build_monthly_fee(&merchant, &[], &default_pricing(), &FxRates::default(), date("2023-02-01"))
+        .unwrap()
+        .unwrap();
+
+    let event = monthly_fee_calculated(&monthly_fee, Utc::now());
+
+    assert_eq!(event.topic, "monthly_fee_calculated");
+    assert_eq!((&event.payload["month"], &event.payload["amount"], &event.payload["mode"]), (&json!("2023-01-01"), &json!(2_900), &json!("INVOICE")));
+}
+
+#[test]
+fn it_publishes_every_status_change_with_the_disbursement_reference() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let mut disbursement = build_disbursement(&merchant, &[order("056d024481a9", 6_174, "2023-01-01")], &[], &default_pricing(), &FxRates::default(), date("2023-01-02"))
+        .unwrap()
+        .unwrap();
+    let change = change_status(&mut disbursement, DisbursementStatus::Voided, "alice", Some("Duplicated".to_string()), Utc::now()).unwrap();
+
+    let event = disbursement_status_changed(&disbursement.reference, &disbursement.merchant_reference, &change);
+
+    assert_eq!((event.topic.as_str(), event.created_at), ("disbursement_status_changed", change.changed_at));
+    assert_eq!((&event.payload["from"], &event.payload["to"]), (&json!("CALCULATED"), &json!("VOIDED")));
+    assert_eq!((&event.payload["reference"], &event.payload["actor"], &event.payload["note"]), (&json!(disbursement.reference), &json!("alice"), &json!("Duplicated")));
+}
+
+// Records what it publishes, slowly enough for two relays to overlap.
+#[derive(Default)]
+struct RecordingPublisher {
+    published: Mutex<Vec<Value>>,
+}
+
+#[async_trait]
+impl EventPublisher for RecordingPublisher {
+    async fn publish(&self, _topic: &str, payload: Value) -> Result<(), anyhow::Error> {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        self.published.lock().unwrap().push(payload["to"].clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn it_publishes_the_events_in_order_with_two_relays() {
+    dotenvy::from_filename(".env.test").ok();
+    let pool = PgPool::connect(&Settings::from_env().database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let mut disbursement = build_disbursement(&merchant, &[order("056d024481a9", 6_174, "2023-01-01")], &[], &default_pricing(), &FxRates::default(), date("2023-01-02"))
+        .unwrap()
+        .unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    for status in [DisbursementStatus::Approved, DisbursementStatus::Exported, DisbursementStatus::Sent, DisbursementStatus::Settled] {
+        let change = change_status(&mut disbursement, status, "alice", None, Utc::now()).unwrap();
+        insert_outbox_event(&mut conn, &disbursement_status_changed(&disbursement.reference, &disbursement.merchant_reference, &change))
+            .await
+            .unwrap();
+    }
+    drop(conn);
+
+    let publisher = RecordingPublisher::default();
+    let relay = || async {
+        while publish_events_handler(PublishEventsJob { limit: 1 }, &pool, &publisher).await.unwrap() > 0 {}
+    };
+    tokio::join!(relay(), relay());
+
+    assert_eq!(*publisher.published.lock().unwrap(), vec![json!("APPROVED"), json!("EXPORTED"), json!("SENT"), json!("SETTLED")]);
+}
@@ -0,0 +1,124 @@
+use calculator::entities::disbursements::DisbursementStatus;
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::jobs::disbursement_batches::approve_batch_handler;
+use calculator::jobs::disbursement_status::change_disbursement_status_handler;
+use calculator::jobs::export_payouts::{
+    export_payouts_handler, reissue_payout_file_handler, withdraw_payout_file_handler, ExportPayoutsJob, PayoutFileFormat,
+};
+use calculator::jobs::process_disbursements::{process_disbursements_handler, ProcessDisbursementsJob, RunMode, RunOutcome};
+use calculator::repositories::disbursement_batches::find_batches_on;
+use calculator::repositories::disbursements::find_disbursements_by_reference;
+use calculator::repositories::merchants::upsert_merchants;
+use calculator::repositories::orders::insert_orders;
+use calculator::services::calendar::BusinessCalendar;
+use calculator::settings::config::Settings;
+use calculator::settings::disbursements::DisbursementSettings;
+use calculator::settings::payouts::PayoutSettings;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use std::path::PathBuf;
+mod utils;
+
+use utils::{bank_account, clean_db, date, merchant, order};
+
+fn payout_settings() -> PayoutSettings {
+    PayoutSettings {
+        debtor_name: "Company XYZ".to_string(),
+        debtor_iban: "DE89370400440532013000".to_string(),
+        debtor_bic: "COBADEFFXXX".to_string(),
+    }
+}
+
+fn output(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("payouts-{}-{}", std::process::id(), name))
+}
+
+async fn export(pool: &PgPool, date: NaiveDate, name: &str) -> Result<(String, usize), anyhow::Error> {
+    let job = ExportPayoutsJob { date, format: PayoutFileFormat::Xml, output_path: output(name).display().to_string() };
+    let (file, batch) = export_payouts_handler(job, pool, &payout_settings()).await?;
+
+    Ok((file.message_id, batch.instructions.len()))
+}
+
+// A run of the date with its batch approved by two people.
+async fn approved_run(pool: &PgPool, date: NaiveDate) -> Vec<String> {
+    let job = ProcessDisbursementsJob { date, mode: RunMode::Commit };
+    let RunOutcome::Committed(run) = process_disbursements_handler(job, pool, &BusinessCalendar::default(), &DisbursementSettings::default())
+        .await
+        .unwrap()
+    else {
+        panic!("the run was not committed");
+    };
+
+    let batch_id = find_batches_on(&mut pool.acquire().await.unwrap(), date).await.unwrap()[0].id;
+    approve_batch_handler(pool, batch_id, "alice").await.unwrap();
+    approve_batch_handler(pool, batch_id, "bob").await.unwrap();
+
+    run.disbursements.into_iter().map(|disbursement| disbursement.reference).collect()
+}
+
+async fn status(pool: &PgPool, reference: &str) -> DisbursementStatus {
+    find_disbursements_by_reference(&mut pool.acquire().await.unwrap(), &[reference.to_string()]).await.unwrap()[0].status
+}
+
+async fn setup(pool: &PgPool) {
+    let mut padberg = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    padberg.bank_account = Some(bank_account());
+    let mut conn = pool.acquire().await.unwrap();
+    upsert_merchants(&mut conn, &[padberg]).await.unwrap();
+    insert_orders(&mut conn, &[order("order_1", 10_000, "2023-01-01")]).await.unwrap();
+}
+
+#[tokio::test]
+async fn it_exports_each_disbursement_once_in_a_file_with_its_own_message_id() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+    setup(&pool).await;
+
+    let references = approved_run(&pool, date("2023-01-02")).await;
+    assert_eq!(export(&pool, date("2023-01-02"), "first.xml").await.unwrap(), ("PAYOUTS-20230102-001".to_string(), 1));
+
+    let mut conn = pool.acquire().await.unwrap();
+    let exported = find_disbursements_by_reference(&mut conn, &references).await.unwrap();
+    assert_eq!(exported[0].status, DisbursementStatus::Exported);
+    drop(conn);
+
+    // the exported one is not paid a second time
+    assert_eq!(export(&pool, date("2023-01-02"), "second.xml").await.unwrap(), ("PAYOUTS-20230102-002".to_string(), 0));
+
+    // a lost file is written again as it was sent
+    let file = reissue_payout_file_handler(&pool, "PAYOUTS-20230102-001", &output("reissued.xml").display().to_string()).await.unwrap();
+    assert_eq!(file.disbursement_ids, vec![exported[0].id]);
+    assert_eq!(std::fs::read_to_string(output("reissued.xml")).unwrap(), std::fs::read_to_string(output("first.xml")).unwrap());
+    assert!(reissue_payout_file_handler(&pool, "PAYOUTS-20230102-009", &output("unknown.xml").display().to_string()).await.is_err());
+}
+
+#[tokio::test]
+async fn it_only_voids_an_exported_disbursement_once_its_file_is_withdrawn() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+    setup(&pool).await;
+
+    let reference = approved_run(&pool, date("2023-01-02")).await.remove(0);
+    export(&pool, date("2023-01-02"), "withdrawn.xml").await.unwrap();
+
+    // the file may already be at the bank
+    assert!(change_disbursement_status_handler(&pool, &reference, DisbursementStatus::Voided, "alice", None).await.is_err());
+    assert_eq!(status(&pool, &reference).await, DisbursementStatus::Exported);
+
+    let file = withdraw_payout_file_handler(&pool, "PAYOUTS-20230102-001", "alice").await.unwrap();
+    assert_eq!(file.withdrawn_by.as_deref(), Some("alice"));
+    assert_eq!(status(&pool, &reference).await, DisbursementStatus::Approved);
+    assert!(withdraw_payout_file_handler(&pool, "PAYOUTS-20230102-001", "alice").await.is_err());
+    assert!(reissue_payout_file_handler(&pool, "PAYOUTS-20230102-001", &output("withdrawn-again.xml").display().to_string()).await.is_err());
+
+    // approved again, it goes out in the next file, or it's voided
+    assert_eq!(export(&pool, date("2023-01-02"), "replacement.xml").await.unwrap(), ("PAYOUTS-20230102-002".to_string(), 1));
+    withdraw_payout_file_handler(&pool, "PAYOUTS-20230102-002", "alice").await.unwrap();
+    change_disbursement_status_handler(&pool, &reference, DisbursementStatus::Voided, "alice", None).await.unwrap();
+    assert_eq!(export(&pool, date("2023-01-02"), "empty.xml").await.unwrap(), ("PAYOUTS-20230102-003".to_string(), 0));
+}
@@ -0,0 +1,117 @@
+use calculator::entities::disbursements::{Disbursement, DisbursementStatus, LineKind};
+use calculator::entities::merchants::{BankAccount, DisbursementFrequency};
+use calculator::entities::payout_returns::{ReturnNotice, ReturnSource};
+use calculator::services::disbursement_calculator::empty_disbursement;
+use calculator::services::fx::FxRates;
+use calculator::services::ledger::{disbursement_entries, payout_return_entry};
+use calculator::services::payout_return_files::parse_return_file;
+use calculator::services::payout_returns::{bank_details_changed, redisburse_returns, return_payout};
+use chrono::Utc;
+mod utils;
+
+use utils::{bank_account, date, merchant};
+
+fn disbursement(status: DisbursementStatus) -> Disbursement {
+    Disbursement {
+        reference: "A1B2C3D4E5F6".to_string(),
+        net_amount: 13_837,
+        status,
+        ..empty_disbursement(&merchant("2022-01-01", DisbursementFrequency::Daily, 0), date("2023-01-02"))
+    }
+}
+
+fn notice(amount: Option<i64>) -> ReturnNotice {
+    ReturnNotice {
+        reference: "A1B2C3D4E5F6".to_string(),
+        returned_on: date("2023-01-05"),
+        amount,
+        currency: Some("EUR".to_string()),
+        reason_code: Some("AC04".to_string()),
+        reason: Some("Account closed".to_string()),
+        source: ReturnSource::Event,
+    }
+}
+
+#[test]
+fn it_reads_the_rejected_transfers_of_a_pain_002() {
+    let notices = parse_return_file(&std::fs::read_to_string("tests/fixtures/pain.002.xml").unwrap()).unwrap();
+
+    // the accepted transfer is left out
+    assert_eq!(notices.len(), 1);
+    assert_eq!(notices[0].reference, "A1B2C3D4E5F6");
+    assert_eq!((notices[0].returned_on, notices[0].amount, notices[0].currency.as_deref()), (date("2023-01-03"), Some(13_837), Some("EUR")));
+    assert_eq!((notices[0].reason_code.as_deref(), notices[0].reason.as_deref()), (Some("AC04"), Some("Account closed")));
+    assert_eq!(notices[0].source, ReturnSource::Pain002);
+}
+
+#[test]
+fn it_reads_the_returned_credits_of_a_camt_054() {
+    let content = std::fs::read_to_string("tests/fixtures/camt.054.xml").unwrap();
+    let notices = parse_return_file(&content).unwrap();
+
+    // the credit without return information and the debit are left out
+    assert_eq!(notices.len(), 1);
+    assert_eq!((notices[0].reference.as_str(), notices[0].returned_on), ("A1B2C3D4E5F6", date("2023-01-05")));
+    assert_eq!((notices[0].amount, notices[0].reason_code.as_deref()), (Some(13_837), Some("AC04")));
+    assert_eq!(notices[0].source, ReturnSource::Camt054);
+
+    assert!(parse_return_file(&content.replace("camt.054.001.02", "camt.053.001.02")).is_err());
+}
+
+#[test]
+fn it_returns_a_sent_payout_to_the_merchant_balance() {
+    let mut disbursement = disbursement(DisbursementStatus::Exported);
+
+    let (changes, payout_return) = return_payout(&notice(Some(13_837)), &mut disbursement, Some(&bank_account()), Utc::now()).unwrap();
+
+    assert_eq!(disbursement.status, DisbursementStatus::Returned);
+    assert_eq!(
+        changes.iter().map(|change| (change.from, change.to)).collect::<Vec<_>>(),
+        vec![(Some(DisbursementStatus::Exported), DisbursementStatus::Sent), (Some(DisbursementStatus::Sent), DisbursementStatus::Returned)]
+    );
+    assert_eq!(changes[1].note.as_deref(), Some("Returned on 2023-01-05 (AC04 Account closed)"));
+    assert_eq!((payout_return.disbursement_id, payout_return.amount, payout_return.currency.as_str()), (disbursement.id, 13_837, "EUR"));
+    assert_eq!(payout_return.iban.as_deref(), Some("ES9121000418450200051332"));
+    assert_eq!(payout_return.released_on, None);
+
+    // the money is back in our account and owed to the merchant again
+    let entry = payout_return_entry(&payout_return);
+    assert!(entry.is_balanced());
+    assert_eq!(entry.postings.iter().map(|posting| posting.amount).collect::<Vec<_>>(), vec![13_837, -13_837]);
+}
+
+#[test]
+fn it_refuses_the_returns_that_do_not_agree_with_the_disbursement() {
+    assert!(return_payout(&notice(Some(10_000)), &mut disbursement(DisbursementStatus::Settled), None, Utc::now()).is_err());
+    // never sent to the bank
+    assert!(return_payout(&notice(None), &mut disbursement(DisbursementStatus::Approved), None, Utc::now()).is_err());
+
+    let mut settled = disbursement(DisbursementStatus::Settled);
+    let (changes, _) = return_payout(&notice(None), &mut settled, None, Utc::now()).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(settled.status, DisbursementStatus::Returned);
+}
+
+#[test]
+fn it_holds_the_payouts_until_the_bank_details_change() {
+    let (_, payout_return) =
+        return_payout(&notice(None), &mut disbursement(DisbursementStatus::Sent), Some(&bank_account()), Utc::now()).unwrap();
+
+    assert!(!bank_details_changed(&payout_return, Some(&bank_account())));
+    assert!(!bank_details_changed(&payout_return, None));
+    assert!(bank_details_changed(&payout_return, Some(&BankAccount { iban: "DE89370400440532013000".to_string(), ..bank_account() })));
+    assert!(bank_details_changed(&payout_return, Some(&BankAccount { account_holder: "Padberg Group SL".to_string(), ..bank_account() })));
+}
+
+#[test]
+fn it_pays_the_released_return_again_with_the_next_disbursement() {
+    let (_, payout_return) = return_payout(&notice(None), &mut disbursement(DisbursementStatus::Sent), None, Utc::now()).unwrap();
+    let mut next = empty_disbursement(&merchant("2022-01-01", DisbursementFrequency::Daily, 0), date("2023-01-09"));
+
+    redisburse_returns(&mut next, std::slice::from_ref(&payout_return), &FxRates::default()).unwrap();
+
+    assert_eq!(next.lines.len(), 1);
+    assert_eq!((next.lines[0].kind, next.lines[0].payout_return_id), (LineKind::ReturnedPayout, Some(payout_return.id)));
+    assert_eq!((next.gross_amount, next.fee_amount, next.net_amount), (0, 0, 13_837));
+    assert!(disbursement_entries(&next).iter().all(|entry| entry.is_balanced()));
+}
@@ -0,0 +1,131 @@
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::services::disbursement_calculator::build_disbursement;
+use calculator::services::payout_csv::to_payout_csv;
+use calculator::services::currencies::format_amount;
+use calculator::services::payouts::{build_payout_batch, payout_message_id, SEPA_CURRENCY};
+use calculator::services::sepa_credit_transfer::to_pain_001;
+use calculator::settings::payouts::PayoutSettings;
+use calculator::services::fx::{parse_fx_rates_csv, FxRates};
+use std::process::Command;
+mod utils;
+
+use utils::{bank_account, date, default_pricing, merchant, order};
+
+fn payout_settings() -> PayoutSettings {
+    PayoutSettings {
+        debtor_name: "Company XYZ".to_string(),
+        debtor_iban: "DE89370400440532013000".to_string(),
+        debtor_bic: "COBADEFFXXX".to_string(),
+    }
+}
+
+#[test]
+fn it_formats_minor_units_as_decimal_amounts() {
+    assert_eq!(format_amount(43_131, "EUR").unwrap(), "431.31");
+    assert_eq!(format_amount(5, "GBP").unwrap(), "0.05");
+    assert_eq!(format_amount(-1_000, "EUR").unwrap(), "-10.00");
+    assert_eq!(format_amount(43_131, "JPY").unwrap(), "43131");
+    assert!(format_amount(1, "XXX").is_err());
+}
+
+#[test]
+fn it_logs_the_bank_account_with_the_iban_masked() {
+    let debug = format!("{:?}", bank_account());
+
+    assert!(debug.contains(&bank_account().masked_iban()));
+    assert!(!debug.contains(&bank_account().iban));
+}
+
+#[test]
+fn it_exports_a_pain_001_file_valid_against_the_schema() {
+    let mut merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    merchant.bank_account = Some(bank_account());
+    let disbursement = build_disbursement(&merchant, &[order("056d024481a9", 43_507, "2023-01-01")], &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+
+    let batch = build_payout_batch(date("2023-01-02"), std::slice::from_ref(&disbursement), &[merchant]);
+    let message_id = payout_message_id(date("2023-01-02"), 2);
+    let xml = to_pain_001(&batch, &payout_settings(), &message_id, date("2023-01-02").and_hms_opt(7, 30, 0).unwrap()).unwrap();
+
+    assert_eq!(message_id, "PAYOUTS-20230102-002");
+    assert!(xml.contains("<MsgId>PAYOUTS-20230102-002</MsgId>") && xml.contains("<PmtInfId>PAYOUTS-20230102-002-1</PmtInfId>"));
+
+    assert!(xml.contains(&format!("<EndToEndId>{}</EndToEndId>", disbursement.reference)));
+    assert!(xml.contains("<Nm>Padberg Group &amp; Sons</Nm>"));
+
+    let path = std::env::temp_dir().join(format!("pain.001-{}.xml", disbursement.reference));
+    std::fs::write(&path, &xml).unwrap();
+
+    // the schema validation needs xmllint (libxml2), a missing one fails the test rather than skipping the validation
+    let output = Command::new("xmllint")
+        .args(["--noout", "--schema", "tests/fixtures/pain.001.001.03.xsd"])
+        .arg(&path)
+        .output()
+        .expect("xmllint (libxml2) is needed to validate the pain.001 file against its schema");
+
+    std::fs::remove_file(&path).ok();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn it_exports_the_same_batch_as_csv_and_skips_merchants_without_bank_account() {
+    let mut paid = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    paid.bank_account = Some(bank_account());
+    let mut unpaid = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    unpaid.merchant_reference = "deckow_gibson".to_string();
+
+    let paid_disbursement = build_disbursement(&paid, &[order("056d024481a9", 6_174, "2023-01-01")], &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+    let unpaid_disbursement = build_disbursement(&unpaid, &[order("33c80364591c", 29_308, "2023-01-01")], &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+
+    let batch = build_payout_batch(
+        date("2023-01-02"),
+        &[paid_disbursement.clone(), unpaid_disbursement.clone()],
+        &[paid, unpaid],
+    );
+
+    assert_eq!(batch.instructions.len(), 1);
+    assert_eq!(batch.skipped, vec![unpaid_disbursement.reference]);
+
+    let csv = to_payout_csv(&batch).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(lines[0], "reference;merchant_reference;account_holder;iban;bic;amount;currency;execution_date");
+    assert_eq!(
+        lines[1],
+        format!(
+            "{};padberg_group;Padberg Group & Sons;ES9121000418450200051332;CAIXESBBXXX;61.15;EUR;2023-01-02",
+            paid_disbursement.reference
+        )
+    );
+}
+
+#[test]
+fn it_only_exports_the_euro_payouts_as_sepa_transfers() {
+    let mut euros = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    euros.bank_account = Some(bank_account());
+    let mut pounds = euros.clone();
+    pounds.merchant_reference = "deckow_gibson".to_string();
+    pounds.payout_currency = "GBP".to_string();
+
+    let mut pounds_order = order("33c80364591c", 29_308, "2023-01-01");
+    pounds_order.merchant_reference = "deckow_gibson".to_string();
+    pounds_order.currency = "GBP".to_string();
+
+    let euros_disbursement = build_disbursement(&euros, &[order("056d024481a9", 6_174, "2023-01-01")], &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+    // the fee tier of the pounds order is picked by its amount in euros
+    let rates = FxRates::new(parse_fx_rates_csv(&std::fs::read_to_string("tests/fixtures/fx_rates.csv").unwrap()).unwrap());
+    let pounds_disbursement = build_disbursement(&pounds, &[pounds_order], &[], &default_pricing(), &rates, date("2023-01-02")).unwrap().unwrap();
+
+    let batch = build_payout_batch(
+        date("2023-01-02"),
+        &[euros_disbursement.clone(), pounds_disbursement.clone()],
+        &[euros, pounds],
+    );
+
+    assert!(to_pain_001(&batch, &payout_settings(), "PAYOUTS-20230102-001", date("2023-01-02").and_hms_opt(7, 30, 0).unwrap()).is_err());
+    assert!(to_payout_csv(&batch).unwrap().contains(";290.59;GBP;"));
+
+    let sepa_batch = batch.only_in(SEPA_CURRENCY);
+    assert_eq!(sepa_batch.instructions.len(), 1);
+    assert_eq!(sepa_batch.skipped, vec![pounds_disbursement.reference]);
+    assert_eq!(sepa_batch.control_sum(), euros_disbursement.net_amount);
+}
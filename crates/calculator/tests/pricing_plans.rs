@@ -0,0 +1,119 @@
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::entities::pricing_plans::{since_always, PricingPlan, PricingTier, DEFAULT_PRICING_PLAN};
+use calculator::services::disbursement_calculator::{build_disbursement, build_monthly_fee};
+use calculator::services::fx::FxRates;
+use calculator::repositories::pricing_plans::find_pricing_plans;
+use calculator::services::pricing::PricingPlans;
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+mod utils;
+
+use utils::{date, default_plan, merchant, order};
+
+fn gold_plan() -> PricingPlan {
+    PricingPlan {
+        code: "GOLD".to_string(),
+        valid_from: since_always(),
+        tiers: vec![
+            PricingTier { from_amount: 0, fee_rate: 80 },
+            PricingTier { from_amount: 100_000, fee_rate: 60 },
+        ],
+        fixed_fee: 25,
+    }
+}
+
+#[test]
+fn it_charges_the_merchant_pricing_plan_with_its_fixed_fee_per_order() {
+    let mut merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    merchant.pricing_plan = Some("GOLD".to_string());
+    let orders = vec![order("056d024481a9", 6_174, "2023-01-01"), order("70530cdc7b59", 150_000, "2023-01-01")];
+    let pricing = PricingPlans::new(vec![gold_plan(), default_plan()]);
+
+    let disbursement = build_disbursement(&merchant, &orders, &[], &pricing, &FxRates::default(), date("2023-01-02"))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(disbursement.lines[0].fee_rate, 80);
+    assert_eq!(disbursement.lines[0].fixed_fee, 25);
+    assert_eq!(disbursement.lines[0].fee_amount, 49 + 25);
+    assert_eq!(disbursement.lines[1].fee_rate, 60);
+    assert_eq!(disbursement.fee_amount, 49 + 25 + 900 + 25);
+
+    // the fixed fees count for the minimum monthly fee as well
+    let monthly_fee = build_monthly_fee(&merchant, &orders, &pricing, &FxRates::default(), date("2023-02-01"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(monthly_fee.commissions_amount, 49 + 25 + 900 + 25);
+}
+
+#[test]
+fn it_falls_back_to_the_default_tiers() {
+    let pricing = PricingPlans::new(vec![gold_plan(), default_plan()]);
+
+    assert_eq!(pricing.for_order(None, date("2023-01-01")).unwrap().code, DEFAULT_PRICING_PLAN);
+    // a plan we never received is refused instead of priced with the default tiers
+    assert!(pricing.for_order(Some("PLATINUM"), date("2023-01-01")).is_err());
+
+    let default = default_plan();
+    assert_eq!(default.fee_rate(4_999), 100);
+    assert_eq!(default.fee_rate(5_000), 95);
+    assert_eq!(default.fee_rate(30_000), 85);
+
+    // the default tiers are only loaded from the database
+    assert!(PricingPlans::new(vec![gold_plan()]).for_order(None, date("2023-01-01")).is_err());
+}
+
+#[tokio::test]
+async fn it_seeds_the_default_tiers_the_tests_price_with() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+
+    let plans = find_pricing_plans(&mut pool.acquire().await.unwrap()).await.unwrap();
+    assert!(plans.contains(&default_plan()));
+}
+
+#[test]
+fn it_rejects_plans_with_unsorted_tiers_or_invalid_rates() {
+    assert!(gold_plan().validate().is_ok());
+
+    let mut not_from_zero = gold_plan();
+    not_from_zero.tiers.remove(0);
+    assert!(not_from_zero.validate().is_err());
+
+    let mut unsorted = gold_plan();
+    unsorted.tiers.reverse();
+    assert!(unsorted.validate().is_err());
+
+    let mut over_100 = gold_plan();
+    over_100.tiers[1].fee_rate = 10_001;
+    assert!(over_100.validate().is_err());
+}
+
+#[test]
+fn it_prices_each_order_with_the_version_effective_when_it_was_created() {
+    let mut merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    merchant.pricing_plan = Some("GOLD".to_string());
+
+    let mut cheaper_gold = gold_plan();
+    cheaper_gold.valid_from = date("2023-01-02");
+    cheaper_gold.tiers[0].fee_rate = 70;
+    let mut cheaper_default = PricingPlan { valid_from: date("2023-01-02"), ..default_plan() };
+    cheaper_default.tiers[0].fee_rate = 90;
+    let pricing = PricingPlans::new(vec![gold_plan(), cheaper_gold, default_plan(), cheaper_default]);
+
+    let orders = vec![order("056d024481a9", 6_174, "2023-01-01"), order("70530cdc7b59", 6_174, "2023-01-02")];
+    let disbursement = build_disbursement(&merchant, &orders, &[], &pricing, &FxRates::default(), date("2023-01-03"))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(disbursement.lines[0].fee_rate, 80);
+    assert_eq!(disbursement.lines[0].pricing_version, "GOLD:1970-01-01");
+    assert_eq!(disbursement.lines[1].fee_rate, 70);
+    assert_eq!(disbursement.lines[1].pricing_version, "GOLD:2023-01-02");
+
+    // the default tiers are versioned the same way
+    assert_eq!(pricing.for_order(None, date("2023-01-01")).unwrap().fee_rate(4_999), 100);
+    assert_eq!(pricing.for_order(None, date("2023-01-02")).unwrap().fee_rate(4_999), 90);
+    assert_eq!(pricing.for_order(None, date("2023-01-02")).unwrap().version(), "DEFAULT:2023-01-02");
+}
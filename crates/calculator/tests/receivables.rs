@@ -0,0 +1,104 @@
+use calculator::entities::disbursements::LineKind;
+use calculator::entities::ledger::{Account, JournalEntry, Posting};
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::repositories::disbursements::insert_disbursement;
+use calculator::repositories::order_adjustments::insert_order_adjustment;
+use calculator::repositories::orders::insert_orders;
+use calculator::repositories::receivables::{find_open_receivables, insert_receivable};
+use calculator::services::disbursement_calculator::build_disbursement;
+use calculator::services::fx::FxRates;
+use calculator::services::ledger::{adjustment_entry, disbursement_entries, order_entry};
+use calculator::services::order_adjustments::build_refund;
+use calculator::services::receivables::{exceeds_negative_balance_limit, offset_receivables, open_receivable};
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+mod utils;
+
+use utils::{clean_db, date, default_pricing, merchant, order};
+
+#[test]
+fn it_opens_a_receivable_instead_of_a_negative_payout() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let refunded = order("056d024481a9", 6_174, "2023-01-01");
+    let refund = build_refund(Uuid::new_v4(), &refunded, 6_174, 95, "DEFAULT:1970-01-01", None, date("2023-01-03")).unwrap();
+    let mut disbursement = build_disbursement(&merchant, &[], std::slice::from_ref(&refund), &default_pricing(), &FxRates::default(), date("2023-01-04")).unwrap().unwrap();
+    assert_eq!(disbursement.net_amount, -6_115);
+
+    assert!(!exceeds_negative_balance_limit(&disbursement, None));
+    assert!(!exceeds_negative_balance_limit(&disbursement, Some(10_000)));
+    assert!(exceeds_negative_balance_limit(&disbursement, Some(5_000)));
+
+    let receivable = open_receivable(&mut disbursement, true).unwrap();
+
+    assert_eq!((receivable.amount, receivable.opened_on, receivable.flagged_for_collection), (6_115, date("2023-01-04"), true));
+    assert_eq!(disbursement.lines.last().map(|line| (line.kind, line.amount, line.receivable_id)), Some((LineKind::Receivable, 6_115, Some(receivable.id))));
+    assert_eq!((disbursement.gross_amount, disbursement.fee_amount, disbursement.net_amount), (-6_174, -59, 0));
+    assert!(open_receivable(&mut disbursement, false).is_none());
+}
+
+#[test]
+fn it_offsets_the_receivables_against_the_next_disbursement() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let refunded = order("056d024481a9", 6_174, "2022-12-30");
+    let refund = build_refund(Uuid::new_v4(), &refunded, 6_174, 95, "DEFAULT:1970-01-01", None, date("2023-01-03")).unwrap();
+    let mut negative = build_disbursement(&merchant, &[], std::slice::from_ref(&refund), &default_pricing(), &FxRates::default(), date("2023-01-04")).unwrap().unwrap();
+    let receivable = open_receivable(&mut negative, false).unwrap();
+
+    let next_order = order("70530cdc7b59", 37_333, "2023-01-04");
+    let mut next = build_disbursement(&merchant, std::slice::from_ref(&next_order), &[], &default_pricing(), &FxRates::default(), date("2023-01-05")).unwrap().unwrap();
+    offset_receivables(&mut next, std::slice::from_ref(&receivable), &FxRates::default()).unwrap();
+
+    // 37_333 - 317 of fees - 6_115 owed
+    assert_eq!((next.gross_amount, next.fee_amount, next.net_amount), (37_333, 317, 30_901));
+    assert_eq!(next.lines.last().map(|line| (line.kind, line.receivable_id)), Some((LineKind::ReceivableOffset, Some(receivable.id))));
+
+    let mut entries = vec![order_entry(&refunded), adjustment_entry(&refund), order_entry(&next_order)];
+    entries.extend(disbursement_entries(&negative));
+    assert_eq!(balances(&entries)[&Account::MerchantReceivable], 6_115);
+
+    entries.extend(disbursement_entries(&next));
+    assert!(entries.iter().all(|entry| entry.is_balanced()));
+    let balances = balances(&entries);
+    assert_eq!(balances[&Account::MerchantReceivable], 0);
+    // the refunded order was paid out by an earlier disbursement, not booked here
+    assert_eq!(balances[&Account::MerchantPayable], -6_174);
+}
+
+#[tokio::test]
+async fn it_does_not_offset_the_receivables_flagged_for_collection() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+    // the receivable lines reference the receivables inserted after them, checked on commit
+    let mut tx = pool.begin().await.unwrap();
+
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let refunded = order("056d024481a9", 6_174, "2022-12-30");
+    insert_orders(&mut tx, std::slice::from_ref(&refunded)).await.unwrap();
+    let mut receivables = Vec::new();
+    for (day, flagged) in [("2023-01-04", false), ("2023-01-05", true)] {
+        let refund = build_refund(Uuid::new_v4(), &refunded, 6_174, 95, "DEFAULT:1970-01-01", Some(3_000), date("2023-01-03")).unwrap();
+        insert_order_adjustment(&mut tx, &refund).await.unwrap();
+        let mut negative = build_disbursement(&merchant, &[], &[refund], &default_pricing(), &FxRates::default(), date(day)).unwrap().unwrap();
+        let receivable = open_receivable(&mut negative, flagged).unwrap();
+        insert_disbursement(&mut tx, &negative).await.unwrap();
+        insert_receivable(&mut tx, &receivable).await.unwrap();
+        receivables.push(receivable);
+    }
+    tx.commit().await.unwrap();
+
+    // the one flagged is collected by other means, taking it from the next payout as well would collect it twice
+    let open = find_open_receivables(&mut pool.acquire().await.unwrap(), &merchant.merchant_reference, date("2023-01-06")).await.unwrap();
+    assert_eq!(open.iter().map(|receivable| receivable.id).collect::<Vec<_>>(), vec![receivables[0].id]);
+}
+
+fn balances(entries: &[JournalEntry]) -> HashMap<Account, i64> {
+    let mut balances: HashMap<Account, i64> = HashMap::new();
+    for Posting { account, amount, .. } in entries.iter().flat_map(|entry| entry.postings.iter().cloned()) {
+        *balances.entry(account).or_default() += amount;
+    }
+    balances
+}
@@ -0,0 +1,95 @@
+use calculator::entities::disbursements::{Disbursement, DisbursementStatus};
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::entities::reconciliation::ReconciliationStatus;
+use calculator::services::camt_053::parse_camt_053;
+use calculator::services::disbursement_calculator::empty_disbursement;
+use calculator::services::reconciliation::reconcile;
+use calculator::services::reconciliation_csv::to_reconciliation_csv;
+mod utils;
+
+use utils::{date, merchant};
+
+fn statement() -> String {
+    std::fs::read_to_string("tests/fixtures/camt.053.xml").unwrap()
+}
+
+fn disbursement(reference: &str, net_amount: i64) -> Disbursement {
+    Disbursement {
+        reference: reference.to_string(),
+        net_amount,
+        status: DisbursementStatus::Exported,
+        ..empty_disbursement(&merchant("2022-01-01", DisbursementFrequency::Daily, 0), date("2023-01-02"))
+    }
+}
+
+#[test]
+fn it_reads_the_booked_debits_of_the_statement() {
+    let statements = parse_camt_053(&statement()).unwrap();
+
+    assert_eq!(statements.len(), 1);
+    assert_eq!(statements[0].id, "20230102-001");
+    assert_eq!(statements[0].iban.as_deref(), Some("ES7921000813610123456789"));
+    // the batch is split in its transactions, credits and pending entries are left out
+    assert_eq!(
+        statements[0].entries.iter().map(|entry| (entry.reference.as_deref(), entry.bank_reference.as_deref(), entry.amount)).collect::<Vec<_>>(),
+        vec![
+            (Some("A1B2C3D4E5F6"), Some("BANK-0001"), 13_837),
+            (Some("0F1E2D3C4B5A"), Some("BANK-0001"), 10_000),
+            (None, Some("BANK-0002"), 4_510),
+        ]
+    );
+    assert!(statements[0].entries.iter().all(|entry| entry.booked_on == date("2023-01-02") && entry.currency == "EUR"));
+}
+
+#[test]
+fn it_reads_the_status_code_of_the_newer_versions() {
+    let content = r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.08"><BkToCstmrStmt><Stmt><Id>S1</Id>
+        <Ntry><Amt Ccy="JPY">1500</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts><Cd>BOOK</Cd></Sts><BookgDt><DtTm>2023-01-02T10:00:00</DtTm></BookgDt>
+        <NtryDtls><TxDtls><Refs><EndToEndId>A1B2C3D4E5F6</EndToEndId></Refs></TxDtls></NtryDtls></Ntry>
+        </Stmt></BkToCstmrStmt></Document>"#;
+
+    let entries = &parse_camt_053(content).unwrap()[0].entries;
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!((entries[0].amount, entries[0].currency.as_str(), entries[0].booked_on), (1_500, "JPY", date("2023-01-02")));
+    assert!(parse_camt_053(&content.replace("camt.053.001.08", "pain.001.001.03")).is_err());
+}
+
+#[test]
+fn it_matches_the_payouts_by_reference_and_amount() {
+    let statements = parse_camt_053(&statement()).unwrap();
+    let disbursements = [disbursement("A1B2C3D4E5F6", 13_837), disbursement("0F1E2D3C4B5A", 10_050)];
+
+    let report = reconcile(&statements, &disbursements);
+
+    assert_eq!(
+        report.lines.iter().map(|line| line.status).collect::<Vec<_>>(),
+        vec![ReconciliationStatus::Matched, ReconciliationStatus::Mismatched, ReconciliationStatus::Unmatched]
+    );
+    assert_eq!(report.lines[0].disbursement_id, Some(disbursements[0].id));
+    assert_eq!(report.lines[1].reason.as_deref(), Some("Paid 100.00, the disbursement is 100.50"));
+    assert_eq!(report.count(ReconciliationStatus::Matched), 1);
+
+    let csv = to_reconciliation_csv(&report).unwrap();
+    assert!(csv.contains("MATCHED;A1B2C3D4E5F6;BANK-0001;2023-01-02;138.37;EUR;138.37;EUR;\n"));
+    assert!(csv.contains("UNMATCHED;;BANK-0002;2023-01-02;45.10;EUR;;;No end-to-end id\n"));
+
+    // reconciling the same statement again doesn't settle the payout twice
+    let settled = [Disbursement { status: DisbursementStatus::Settled, ..disbursements[0].clone() }];
+    let again = reconcile(&statements, &settled);
+    assert_eq!(again.lines[0].status, ReconciliationStatus::AlreadyMatched);
+    assert_eq!(again.lines[0].reason.as_deref(), Some("Already settled"));
+    assert_eq!(again.count(ReconciliationStatus::Mismatched), 0);
+
+    // the same payout twice in the statements is paid twice
+    let mut twice = statements.clone();
+    let first = twice[0].entries[0].clone();
+    twice[0].entries.push(first);
+    let report = reconcile(&twice, &disbursements);
+    assert_eq!(report.lines[3].status, ReconciliationStatus::Mismatched);
+    assert_eq!(report.lines[3].reason.as_deref(), Some("Paid twice in the statements"));
+
+    // a payout of a disbursement that was never exported is not the one we sent
+    let approved = [Disbursement { status: DisbursementStatus::Approved, ..disbursements[0].clone() }];
+    assert_eq!(reconcile(&statements, &approved).lines[0].reason.as_deref(), Some("The disbursement is APPROVED"));
+}
@@ -0,0 +1,80 @@
+use calculator::entities::disbursements::LineKind;
+use calculator::entities::ledger::{Account, JournalEntry, Posting};
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::entities::reserves::ReservePolicy;
+use calculator::services::disbursement_calculator::{build_disbursement, empty_disbursement};
+use calculator::services::fx::FxRates;
+use calculator::services::ledger::{disbursement_entries, order_entry};
+use calculator::services::reserves::{hold_reserve, parse_reserve_policies_csv, release_reserves};
+use std::collections::HashMap;
+mod utils;
+
+use utils::{date, default_pricing, merchant, order};
+
+fn policy() -> ReservePolicy {
+    ReservePolicy { merchant_reference: "padberg_group".to_string(), reserve_rate: 1_000, hold_days: 90 }
+}
+
+#[test]
+fn it_holds_back_the_reserve_rate_of_the_net_sales() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let orders = [order("056d024481a9", 10_000, "2023-01-01")];
+    let mut disbursement = build_disbursement(&merchant, &orders, &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+
+    let reserve = hold_reserve(&mut disbursement, &policy()).unwrap();
+
+    // 10 % of 10_000 - 95 of fees, rounded half up
+    assert_eq!(reserve.amount, 991);
+    assert_eq!(reserve.release_on, date("2023-04-02"));
+    assert_eq!(reserve.disbursement_id, disbursement.id);
+    assert_eq!(disbursement.lines.last().map(|line| (line.kind, line.amount, line.reserve_id)), Some((LineKind::Reserve, -991, Some(reserve.id))));
+    assert_eq!((disbursement.gross_amount, disbursement.fee_amount, disbursement.net_amount), (10_000, 95, 8_914));
+}
+
+#[test]
+fn it_releases_the_reserves_into_a_later_disbursement() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    let orders = [order("056d024481a9", 10_000, "2023-01-01")];
+    let mut holding = build_disbursement(&merchant, &orders, &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+    let reserve = hold_reserve(&mut holding, &policy()).unwrap();
+
+    // no sales on the release day, the disbursement only pays the reserve back
+    let mut releasing = empty_disbursement(&merchant, reserve.release_on);
+    release_reserves(&mut releasing, std::slice::from_ref(&reserve), &FxRates::default()).unwrap();
+    assert!(hold_reserve(&mut releasing, &policy()).is_none());
+
+    assert_eq!(releasing.lines.len(), 1);
+    assert_eq!(releasing.lines[0].reserve_id, Some(reserve.id));
+    assert_eq!((releasing.gross_amount, releasing.fee_amount, releasing.net_amount), (0, 0, 991));
+
+    let mut entries = vec![order_entry(&orders[0])];
+    entries.extend(disbursement_entries(&holding));
+    let held_balances = balances(&entries);
+    assert_eq!(held_balances[&Account::MerchantReserve], -991);
+    assert_eq!(held_balances[&Account::MerchantPayable], 0);
+
+    entries.extend(disbursement_entries(&releasing));
+    assert!(entries.iter().all(|entry| entry.is_balanced()));
+    let released_balances = balances(&entries);
+    assert_eq!(released_balances[&Account::MerchantReserve], 0);
+    assert_eq!(released_balances[&Account::MerchantPayable], 0);
+    assert_eq!(released_balances[&Account::Cash], 95);
+}
+
+#[test]
+fn it_parses_the_reserve_policies_file() {
+    let policies = parse_reserve_policies_csv("merchant_reference;reserve_percentage;hold_days\npadberg_group;10.00;90\nromaguera;7.5;30\n").unwrap();
+
+    assert_eq!(policies.iter().map(|policy| policy.reserve_rate).collect::<Vec<_>>(), vec![1_000, 750]);
+    assert_eq!(policies[1].hold_days, 30);
+    assert!(parse_reserve_policies_csv("merchant_reference;reserve_percentage;hold_days\npadberg_group;10.001;90\n").is_err());
+    assert!(parse_reserve_policies_csv("merchant_reference;reserve_percentage;hold_days\npadberg_group;10;0\n").is_err());
+}
+
+fn balances(entries: &[JournalEntry]) -> HashMap<Account, i64> {
+    let mut balances: HashMap<Account, i64> = HashMap::new();
+    for Posting { account, amount, .. } in entries.iter().flat_map(|entry| entry.postings.iter().cloned()) {
+        *balances.entry(account).or_default() += amount;
+    }
+    balances
+}
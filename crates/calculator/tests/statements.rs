@@ -0,0 +1,75 @@
+use calculator::entities::ledger::{Account, AccountBalance};
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::entities::statements::StatementBalance;
+use calculator::entities::vat::{VatCharge, VatTreatment};
+use calculator::services::disbursement_calculator::{build_disbursement, build_monthly_fee};
+use calculator::services::fx::FxRates;
+use calculator::services::statement_csv::to_statement_csv;
+use calculator::services::statement_html::to_statement_html;
+use calculator::services::statements::{build_statement, merchant_balances};
+use calculator::services::vat::charge_vat;
+mod utils;
+
+use utils::{date, default_pricing, merchant, order};
+
+fn balance(account: Account, currency: &str, balance: i64) -> AccountBalance {
+    AccountBalance { account, currency: currency.to_string(), balance }
+}
+
+#[test]
+fn it_takes_the_merchant_balance_from_its_accounts() {
+    let balances = merchant_balances(&[
+        balance(Account::MerchantPayable, "EUR", -10_000),
+        balance(Account::MerchantReserve, "EUR", -991),
+        balance(Account::MerchantReceivable, "EUR", 500),
+        // invoiced, paid apart
+        balance(Account::MonthlyFeeReceivable, "EUR", 1_650),
+        balance(Account::MerchantPayable, "GBP", -2_000),
+        balance(Account::MerchantPayable, "USD", 0),
+    ]);
+
+    assert_eq!(
+        balances,
+        vec![
+            StatementBalance { currency: "EUR".to_string(), amount: 10_491 },
+            StatementBalance { currency: "GBP".to_string(), amount: 2_000 },
+        ]
+    );
+}
+
+#[test]
+fn it_renders_every_order_of_the_disbursements_of_the_month() {
+    let merchant = merchant("2022-01-01", DisbursementFrequency::Daily, 2_900);
+    let orders = [order("056d024481a9", 10_000, "2023-01-01"), order("33c080a7e2c9", 4_000, "2023-01-01")];
+    let mut disbursement = build_disbursement(&merchant, &orders, &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+    charge_vat(&mut disbursement, Some(VatCharge { treatment: VatTreatment::Standard, rate: 2_100 }));
+    let monthly_fee = build_monthly_fee(&merchant, &orders, &default_pricing(), &FxRates::default(), date("2023-02-01")).unwrap();
+
+    let statement = build_statement(
+        &merchant,
+        date("2023-01-01"),
+        vec![disbursement.clone()],
+        monthly_fee,
+        &[],
+        &[balance(Account::MerchantPayable, "EUR", -3_000)],
+    );
+
+    let csv = to_statement_csv(&statement).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows[0], "type;disbursement_reference;date;order_id;gross_amount;fee_rate;fee_amount;net_amount;currency");
+    assert_eq!(rows[1], format!("ORDER;{};2023-01-02;056d024481a9;100.00;0.95;0.95;99.05;EUR", disbursement.reference));
+    assert_eq!(rows[2], format!("ORDER;{};2023-01-02;33c080a7e2c9;40.00;1.00;0.40;39.60;EUR", disbursement.reference));
+    // 21 % of 1.35
+    assert_eq!(rows[3], format!("FEE_VAT;{};2023-01-02;;;21.00;0.28;-0.28;EUR", disbursement.reference));
+    assert_eq!(rows[4], format!("DISBURSEMENT;{};2023-01-02;;140.00;;1.63;138.37;EUR", disbursement.reference));
+    assert_eq!(rows[5], "MONTHLY_FEE_INVOICE;;2023-02-01;;;;27.65;;EUR");
+    assert_eq!(rows[6], "CLOSING_BALANCE;;2023-01-31;;;;;30.00;EUR");
+
+    let html = to_statement_html(&statement).unwrap();
+    assert!(html.contains("<h1>Statement padberg_group, January 2023</h1>"));
+    assert!(html.contains(&format!("<h2>Disbursement {} on 2023-01-02</h2>", disbursement.reference)));
+    assert!(html.contains("<td>056d024481a9</td><td class=\"amount\">100.00 EUR</td><td class=\"amount\">0.95 %</td>"));
+    assert!(html.contains("Paid out</th><td class=\"amount\">138.37 EUR"));
+    assert!(html.contains("<strong>Opening balance on 2023-01-01:</strong> 0.00 EUR"));
+    assert!(html.contains("<strong>Closing balance on 2023-01-31:</strong> 30.00 EUR"));
+}
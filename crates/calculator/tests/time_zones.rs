@@ -0,0 +1,76 @@
+use calculator::entities::merchants::DisbursementFrequency;
+use calculator::entities::orders::Order;
+use calculator::entities::pricing_plans::PricingPlan;
+use calculator::services::disbursement_calculator::build_disbursement;
+use calculator::services::fx::FxRates;
+use calculator::services::pricing::PricingPlans;
+use calculator::services::time_zones::{local_date, parse_local_date, start_of_day};
+use chrono::{DateTime, Utc};
+use chrono_tz::{America, Europe};
+mod utils;
+
+use utils::{date, default_plan, merchant, order_at};
+
+fn instant(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+}
+
+#[test]
+fn it_starts_the_merchant_days_at_their_local_midnight_across_dst_transitions() {
+    // madrid moves from CET (+01) to CEST (+02) on 2023-03-26 at 02:00
+    assert_eq!(start_of_day(date("2023-03-26"), Europe::Madrid), instant("2023-03-25T23:00:00Z"));
+    assert_eq!(start_of_day(date("2023-03-27"), Europe::Madrid), instant("2023-03-26T22:00:00Z"));
+    assert_eq!(start_of_day(date("2023-03-27"), chrono_tz::UTC), instant("2023-03-27T00:00:00Z"));
+    // santiago skips midnight on 2022-09-11, the day starts at 01:00 (-03)
+    assert_eq!(start_of_day(date("2022-09-11"), America::Santiago), instant("2022-09-11T04:00:00Z"));
+}
+
+#[test]
+fn it_groups_orders_by_the_local_day_of_the_merchant() {
+    let late_order = order_at("late", 1_000, "2023-03-26T22:30:00Z");
+
+    assert_eq!(late_order.created_on(chrono_tz::UTC), date("2023-03-26"));
+    assert_eq!(late_order.created_on(Europe::Madrid), date("2023-03-27"));
+    assert_eq!(local_date(late_order.created_at, Europe::Madrid), date("2023-03-27"));
+    assert_eq!(local_date(late_order.created_at, America::New_York), date("2023-03-26"));
+    // it's not part of madrid's "yesterday's sales" on the 27th
+    assert!(late_order.created_at >= start_of_day(date("2023-03-27"), Europe::Madrid));
+}
+
+#[test]
+fn it_reads_order_timestamps_and_the_plain_dates_of_older_events() {
+    let with_time: Order = serde_json::from_value(serde_json::json!({
+        "id": "a", "merchant_reference": "padberg_group", "amount": 100, "created_at": "2023-01-05T10:15:00+01:00"
+    }))
+    .unwrap();
+    let date_only: Order = serde_json::from_value(serde_json::json!({
+        "id": "b", "merchant_reference": "padberg_group", "amount": 100, "created_at": "2023-01-05"
+    }))
+    .unwrap();
+
+    assert_eq!(with_time.created_at, instant("2023-01-05T09:15:00Z"));
+    assert_eq!(date_only.created_at, instant("2023-01-05T00:00:00Z"));
+}
+
+#[test]
+fn it_prices_orders_and_dates_adjustments_by_the_local_day_of_the_merchant() {
+    let mut madrid = merchant("2022-01-01", DisbursementFrequency::Daily, 0);
+    madrid.time_zone = Europe::Madrid;
+    let mut cheaper = PricingPlan { valid_from: date("2023-01-02"), ..default_plan() };
+    cheaper.tiers[0].fee_rate = 90;
+    let pricing = PricingPlans::new(vec![default_plan(), cheaper]);
+
+    // 00:30 on the 2nd in madrid, still the 1st in UTC
+    let late_order = order_at("late", 1_000, "2023-01-01T23:30:00Z");
+    let disbursement = build_disbursement(&madrid, &[late_order], &[], &pricing, &FxRates::default(), date("2023-01-03"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(disbursement.lines[0].pricing_version, "DEFAULT:2023-01-02");
+    assert_eq!(disbursement.lines[0].fee_rate, 90);
+
+    // refunds and corrections are dated the same way
+    assert_eq!(parse_local_date("2023-01-01T23:30:00Z", Europe::Madrid).unwrap(), date("2023-01-02"));
+    assert_eq!(parse_local_date("2023-01-01T23:30:00Z", chrono_tz::UTC).unwrap(), date("2023-01-01"));
+    assert_eq!(parse_local_date("2023-01-01", Europe::Madrid).unwrap(), date("2023-01-01"));
+    assert!(parse_local_date("01/01/2023", Europe::Madrid).is_err());
+}
@@ -0,0 +1,96 @@
+// shared by every test crate, not all of them use every helper
+#![allow(dead_code)]
+
+use calculator::entities::merchants::{BankAccount, DisbursementFrequency, Merchant, MonthlyFeeMode};
+use calculator::entities::orders::Order;
+use calculator::entities::pricing_plans::{since_always, PricingPlan, PricingTier, DEFAULT_PRICING_PLAN};
+use calculator::services::pricing::PricingPlans;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+pub fn merchant(live_on: &str, disbursement_frequency: DisbursementFrequency, minimum_monthly_fee: i32) -> Merchant {
+    Merchant {
+        id: Uuid::new_v4(),
+        merchant_reference: "padberg_group".to_string(),
+        live_on: date(live_on),
+        disbursement_frequency,
+        disbursement_weekday: None,
+        disbursement_day_of_month: None,
+        minimum_monthly_fee,
+        bank_account: None,
+        payout_currency: "EUR".to_string(),
+        pricing_plan: None,
+        roll_convention: None,
+        time_zone: chrono_tz::UTC,
+        minimum_payout: None,
+        offboarded_on: None,
+        monthly_fee_mode: MonthlyFeeMode::Invoice,
+        country: None,
+        vat_number: None,
+    }
+}
+
+pub fn order(id: &str, amount: i64, created_at: &str) -> Order {
+    Order {
+        id: id.to_string(),
+        merchant_reference: "padberg_group".to_string(),
+        amount,
+        currency: "EUR".to_string(),
+        created_at: date(created_at).and_hms_opt(0, 0, 0).unwrap().and_utc(),
+    }
+}
+
+pub fn order_in(id: &str, amount: i64, currency: &str, created_at: &str) -> Order {
+    Order { currency: currency.to_string(), ..order(id, amount, created_at) }
+}
+
+// The DEFAULT plan seeded by the migrations, `it_seeds_the_default_tiers_the_tests_price_with` checks they match.
+pub fn default_plan() -> PricingPlan {
+    PricingPlan {
+        code: DEFAULT_PRICING_PLAN.to_string(),
+        valid_from: since_always(),
+        tiers: vec![
+            PricingTier { from_amount: 0, fee_rate: 100 },
+            PricingTier { from_amount: 5_000, fee_rate: 95 },
+            PricingTier { from_amount: 30_000, fee_rate: 85 },
+        ],
+        fixed_fee: 0,
+    }
+}
+
+pub fn default_pricing() -> PricingPlans {
+    PricingPlans::new(vec![default_plan()])
+}
+
+pub fn bank_account() -> BankAccount {
+    BankAccount {
+        iban: "ES9121000418450200051332".to_string(),
+        bic: "CAIXESBBXXX".to_string(),
+        account_holder: "Padberg Group & Sons".to_string(),
+    }
+}
+
+// an order created at a given instant, `created_at` is RFC 3339 (2023-03-26T22:30:00Z)
+pub fn order_at(id: &str, amount: i64, created_at: &str) -> Order {
+    Order {
+        created_at: DateTime::parse_from_rfc3339(created_at).unwrap().with_timezone(&Utc),
+        ..order(id, amount, "1970-01-01")
+    }
+}
+
+// Everything but the pricing plans, their DEFAULT tiers are seeded by the migrations.
+pub async fn clean_db(pool: &PgPool) {
+    sqlx::query(
+        "TRUNCATE TABLE merchants, orders, order_adjustments, disbursements, disbursement_lines, disbursement_status_history, \
+         disbursement_batches, monthly_fees, carried_balances, reserves, reserve_policies, receivables, payout_returns, \
+         journal_entries, event_outbox, fx_rates, vat_rules, invoices, invoice_sequences, payout_files RESTART IDENTITY CASCADE"
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
@@ -0,0 +1,97 @@
+use calculator::entities::ledger::{Account, JournalEntry, Posting};
+use calculator::entities::merchants::{DisbursementFrequency, Merchant};
+use calculator::entities::vat::{VatCharge, VatRule, VatTreatment};
+use calculator::services::disbursement_calculator::{build_disbursement, build_monthly_fee};
+use calculator::services::fx::FxRates;
+use calculator::services::ledger::{disbursement_entries, monthly_fee_entry, order_entry};
+use calculator::services::vat::{charge_monthly_fee_vat, charge_vat, parse_vat_rules_csv, VatRules};
+use std::collections::HashMap;
+mod utils;
+
+use utils::{date, default_pricing, merchant, order};
+
+fn rules() -> VatRules {
+    VatRules::new(
+        vec![
+            VatRule { country: "ES".to_string(), standard_rate: 2_100, eu_member: true },
+            VatRule { country: "DE".to_string(), standard_rate: 1_900, eu_member: true },
+            VatRule { country: "CH".to_string(), standard_rate: 810, eu_member: false },
+        ],
+        Some("ES".to_string()),
+    )
+}
+
+fn merchant_in(country: Option<&str>, vat_number: Option<&str>) -> Merchant {
+    Merchant {
+        country: country.map(str::to_string),
+        vat_number: vat_number.map(str::to_string),
+        ..merchant("2022-01-01", DisbursementFrequency::Daily, 0)
+    }
+}
+
+#[test]
+fn it_picks_the_vat_treatment_by_the_country_of_the_merchant() {
+    let charge = |country, vat_number| rules().charge_for(&merchant_in(country, vat_number)).unwrap();
+
+    assert_eq!(charge(Some("ES"), Some("ESB12345678")), Some(VatCharge { treatment: VatTreatment::Standard, rate: 2_100 }));
+    assert_eq!(charge(None, None), Some(VatCharge { treatment: VatTreatment::Standard, rate: 2_100 }));
+    assert_eq!(charge(Some("DE"), Some("DE123456789")), Some(VatCharge { treatment: VatTreatment::ReverseCharge, rate: 0 }));
+    // not VAT registered, charged the rate of its own country
+    assert_eq!(charge(Some("DE"), None), Some(VatCharge { treatment: VatTreatment::Standard, rate: 1_900 }));
+    assert_eq!(charge(Some("CH"), None), Some(VatCharge { treatment: VatTreatment::OutsideScope, rate: 0 }));
+
+    assert!(rules().charge_for(&merchant_in(Some("FR"), None)).is_err());
+    assert_eq!(VatRules::default().charge_for(&merchant_in(Some("DE"), None)).unwrap(), None);
+}
+
+#[test]
+fn it_takes_the_vat_of_the_commissions_from_the_payout() {
+    let merchant = merchant_in(Some("ES"), None);
+    let orders = [order("056d024481a9", 10_000, "2023-01-01")];
+    let mut disbursement = build_disbursement(&merchant, &orders, &[], &default_pricing(), &FxRates::default(), date("2023-01-02")).unwrap().unwrap();
+
+    charge_vat(&mut disbursement, rules().charge_for(&merchant).unwrap());
+
+    // 21 % of 95 of fees, rounded half up
+    assert_eq!(disbursement.fee_vat_amount, 20);
+    assert_eq!((disbursement.fee_amount, disbursement.fee_gross_amount, disbursement.net_amount), (95, 115, 9_885));
+
+    let mut entries = vec![order_entry(&orders[0])];
+    entries.extend(disbursement_entries(&disbursement));
+    assert!(entries.iter().all(|entry| entry.is_balanced()));
+    let balances = balances(&entries);
+    assert_eq!(balances[&Account::VatPayable], -20);
+    assert_eq!(balances[&Account::CommissionRevenue], -95);
+    assert_eq!(balances[&Account::MerchantPayable], 0);
+}
+
+#[test]
+fn it_charges_the_vat_on_the_monthly_fee() {
+    let merchant = Merchant { minimum_monthly_fee: 2_900, ..merchant_in(Some("DE"), None) };
+    let mut monthly_fee = build_monthly_fee(&merchant, &[], &default_pricing(), &FxRates::default(), date("2023-02-01")).unwrap().unwrap();
+
+    charge_monthly_fee_vat(&mut monthly_fee, rules().charge_for(&merchant).unwrap());
+
+    assert_eq!((monthly_fee.amount, monthly_fee.vat_amount, monthly_fee.gross_amount), (2_900, 551, 3_451));
+    let entry = monthly_fee_entry(&monthly_fee);
+    assert!(entry.is_balanced());
+    assert_eq!(balances(std::slice::from_ref(&entry))[&Account::MonthlyFeeReceivable], 3_451);
+}
+
+#[test]
+fn it_parses_the_vat_rules_file() {
+    let rules = parse_vat_rules_csv("country;standard_rate;eu_member\nes;21.00;true\nCH;8.1;false\n").unwrap();
+
+    assert_eq!(rules[0], VatRule { country: "ES".to_string(), standard_rate: 2_100, eu_member: true });
+    assert_eq!((rules[1].standard_rate, rules[1].eu_member), (810, false));
+    assert!(parse_vat_rules_csv("country;standard_rate;eu_member\nESP;21.00;true\n").is_err());
+    assert!(parse_vat_rules_csv("country;standard_rate;eu_member\nES;21.00;yes\n").is_err());
+}
+
+fn balances(entries: &[JournalEntry]) -> HashMap<Account, i64> {
+    let mut balances: HashMap<Account, i64> = HashMap::new();
+    for Posting { account, amount, .. } in entries.iter().flat_map(|entry| entry.postings.iter().cloned()) {
+        *balances.entry(account).or_default() += amount;
+    }
+    balances
+}
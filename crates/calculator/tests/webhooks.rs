@@ -0,0 +1,245 @@
+use calculator::entities::webhooks::{AttemptOutcome, DeliveryStatus, WebhookDelivery, WebhookEndpoint};
+use calculator::services::webhook_client::WebhookClient;
+use calculator::services::webhooks::{new_endpoint, next_attempt_at, record_attempt, redeliver, signature, verify_signature};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2023, 1, 2, hour, minute, 0).unwrap()
+}
+
+fn endpoint(url: &str) -> WebhookEndpoint {
+    new_endpoint("padberg_group", url, &[], false, at(8, 0)).unwrap()
+}
+
+// The stand-in listens on localhost.
+fn local_endpoint(url: &str) -> WebhookEndpoint {
+    new_endpoint("padberg_group", url, &[], true, at(8, 0)).unwrap()
+}
+
+fn delivery(endpoint: &WebhookEndpoint) -> WebhookDelivery {
+    WebhookDelivery {
+        id: Uuid::new_v4(),
+        endpoint_id: endpoint.id,
+        event_id: Uuid::new_v4(),
+        topic: "disbursement_created".to_string(),
+        merchant_reference: endpoint.merchant_reference.clone(),
+        payload: json!({"reference": "5A1B2C3D4E5F", "net_amount": 43_085, "merchant_reference": "padberg_group"}),
+        status: DeliveryStatus::Pending,
+        attempts: 0,
+        next_attempt_at: Some(at(9, 0)),
+        delivered_at: None,
+        created_at: at(9, 0),
+    }
+}
+
+fn failure(status: Option<u16>) -> AttemptOutcome {
+    AttemptOutcome { response_status: status, error: Some("boom".to_string()), duration_ms: 12 }
+}
+
+struct Request {
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+// A local stand-in for the endpoint of a merchant: answers one request with `status` and hands it over.
+async fn stand_in(status: u16) -> (String, tokio::task::JoinHandle<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/webhooks", listener.local_addr().unwrap());
+
+    let handle = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut received = Vec::new();
+        let mut buffer = [0; 4096];
+        let (head, body_start) = loop {
+            let read = socket.read(&mut buffer).await.unwrap();
+            received.extend_from_slice(&buffer[..read]);
+            if let Some(end) = received.windows(4).position(|window| window == b"\r\n\r\n") {
+                break (String::from_utf8(received[..end].to_vec()).unwrap(), end + 4);
+            }
+        };
+
+        let headers: HashMap<String, String> = head
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(": "))
+            .map(|(name, value)| (name.to_lowercase(), value.to_string()))
+            .collect();
+        let length: usize = headers["content-length"].parse().unwrap();
+        while received.len() < body_start + length {
+            let read = socket.read(&mut buffer).await.unwrap();
+            received.extend_from_slice(&buffer[..read]);
+        }
+
+        let answer = if status == 200 { "ok" } else { "try later" };
+        let response = format!("HTTP/1.1 {} Whatever\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", status, answer.len(), answer);
+        socket.write_all(response.as_bytes()).await.unwrap();
+
+        Request {
+            headers,
+            body: String::from_utf8(received[body_start..body_start + length].to_vec()).unwrap(),
+        }
+    });
+
+    (url, handle)
+}
+
+#[test]
+fn it_signs_the_timestamp_and_the_body_with_hmac_sha256() {
+    let body = r#"{"net_amount":43085}"#;
+    let header = signature("whsec_secret", 1672650000, body);
+
+    assert_eq!(header, "sha256=138ebb4946516150c43d230d4d304d8918da243cdf1c1d97610bf5b61d580306");
+    assert!(verify_signature("whsec_secret", 1672650000, body, &header));
+    assert!(!verify_signature("whsec_secret", 1672650001, body, &header));
+    assert!(!verify_signature("whsec_secret", 1672650000, r#"{"net_amount":43086}"#, &header));
+    assert!(!verify_signature("another_secret", 1672650000, body, &header));
+    assert!(!verify_signature("whsec_secret", 1672650000, body, "sha256=nothex"));
+}
+
+#[test]
+fn it_registers_http_endpoints_for_the_outbox_topics() {
+    let endpoint = endpoint("https://merchant.example/hooks");
+
+    assert_eq!(endpoint.topics, vec!["disbursement_created", "monthly_fee_calculated", "disbursement_status_changed"]);
+    assert!(endpoint.secret.starts_with("whsec_") && endpoint.secret.len() == 70);
+    assert_ne!(endpoint.secret, self::endpoint("https://merchant.example/hooks").secret);
+
+    let only_fees = new_endpoint("padberg_group", "https://merchant.example", &["monthly_fee_calculated".to_string()], false, at(8, 0)).unwrap();
+    assert_eq!(only_fees.topics, vec!["monthly_fee_calculated"]);
+
+    assert!(new_endpoint("padberg_group", "ftp://merchant.example", &[], false, at(8, 0)).is_err());
+    assert!(new_endpoint("padberg_group", "merchant.example/hooks", &[], false, at(8, 0)).is_err());
+    assert!(new_endpoint("padberg_group", "https://merchant.example", &["order_created".to_string()], false, at(8, 0)).is_err());
+}
+
+#[test]
+fn it_refuses_plain_http_and_local_endpoints() {
+    let refused = [
+        "http://merchant.example/hooks",
+        "https://localhost/hooks",
+        "https://api.localhost/hooks",
+        "https://127.0.0.1/hooks",
+        "https://127.8.0.1:8443/hooks",
+        "https://2130706433/hooks",
+        "https://0.0.0.0/hooks",
+        "https://10.1.2.3/hooks",
+        "https://172.16.0.1/hooks",
+        "https://172.31.255.255/hooks",
+        "https://192.168.1.10/hooks",
+        "https://169.254.169.254/latest/meta-data",
+        "https://[::1]/hooks",
+        "https://[fd00::1]/hooks",
+        "https://[fe80::1]/hooks",
+        "https://[::ffff:10.0.0.1]/hooks",
+    ];
+    for url in refused {
+        assert!(new_endpoint("padberg_group", url, &[], false, at(8, 0)).is_err(), "{} was accepted", url);
+        assert!(new_endpoint("padberg_group", url, &[], true, at(8, 0)).is_ok(), "{} was refused in development", url);
+    }
+
+    assert!(new_endpoint("padberg_group", "https://172.32.0.1/hooks", &[], false, at(8, 0)).is_ok());
+    assert!(new_endpoint("padberg_group", "https://[2001:db8::1]/hooks", &[], false, at(8, 0)).is_ok());
+    assert!(new_endpoint("padberg_group", "ftp://localhost/hooks", &[], true, at(8, 0)).is_err());
+}
+
+#[test]
+fn it_retries_with_backoff_until_the_attempts_run_out() {
+    let endpoint = endpoint("https://merchant.example/hooks");
+    let mut delivery = delivery(&endpoint);
+
+    let attempt = record_attempt(&mut delivery, &failure(Some(503)), at(9, 0));
+    assert_eq!((attempt.response_status, attempt.error.as_deref()), (Some(503), Some("boom")));
+    assert_eq!((delivery.status, delivery.attempts, delivery.next_attempt_at), (DeliveryStatus::Pending, 1, Some(at(9, 1))));
+
+    record_attempt(&mut delivery, &failure(None), at(9, 1));
+    assert_eq!(delivery.next_attempt_at, Some(at(9, 6)));
+
+    let mut attempted_at = at(9, 6);
+    while let Some(next) = delivery.next_attempt_at {
+        record_attempt(&mut delivery, &failure(Some(500)), attempted_at);
+        attempted_at = next;
+    }
+
+    assert_eq!((delivery.status, delivery.attempts), (DeliveryStatus::Failed, 8));
+    assert_eq!(next_attempt_at(7, at(9, 0)), Some(at(21, 0)));
+    assert_eq!(next_attempt_at(8, at(9, 0)), None);
+}
+
+#[test]
+fn it_delivers_on_any_2xx_and_redelivers_by_hand() {
+    let endpoint = endpoint("https://merchant.example/hooks");
+    let mut delivery = delivery(&endpoint);
+    record_attempt(&mut delivery, &failure(Some(500)), at(9, 0));
+
+    assert!(redeliver(&mut delivery, at(9, 0)).is_err());
+
+    let success = AttemptOutcome { response_status: Some(204), error: None, duration_ms: 8 };
+    record_attempt(&mut delivery, &success, at(9, 1));
+    assert_eq!((delivery.status, delivery.delivered_at, delivery.next_attempt_at), (DeliveryStatus::Delivered, Some(at(9, 1)), None));
+
+    redeliver(&mut delivery, at(10, 0)).unwrap();
+    assert_eq!((delivery.status, delivery.attempts, delivery.next_attempt_at, delivery.delivered_at), (DeliveryStatus::Pending, 0, Some(at(10, 0)), None));
+}
+
+#[tokio::test]
+async fn it_posts_the_signed_event_to_the_endpoint() {
+    let (url, request) = stand_in(200).await;
+    let endpoint = local_endpoint(&url);
+    let delivery = delivery(&endpoint);
+    let client = WebhookClient::new(std::time::Duration::from_secs(5), true).unwrap();
+
+    let outcome = client.send(&endpoint, &delivery, at(9, 0)).await;
+    let request = request.await.unwrap();
+
+    assert!(outcome.is_success());
+    assert_eq!((outcome.response_status, outcome.error), (Some(200), None));
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&request.body).unwrap(), delivery.payload);
+    assert_eq!(request.headers["content-type"], "application/json");
+    assert_eq!(request.headers["x-webhook-event"], "disbursement_created");
+    assert_eq!(request.headers["x-webhook-delivery"], delivery.id.to_string());
+    assert_eq!(request.headers["x-webhook-timestamp"], at(9, 0).timestamp().to_string());
+    assert!(verify_signature(&endpoint.secret, at(9, 0).timestamp(), &request.body, &request.headers["x-webhook-signature"]));
+}
+
+#[tokio::test]
+async fn it_logs_what_went_wrong_with_an_attempt() {
+    let (url, request) = stand_in(503).await;
+    let endpoint = local_endpoint(&url);
+    let client = WebhookClient::new(std::time::Duration::from_secs(5), true).unwrap();
+
+    let outcome = client.send(&endpoint, &delivery(&endpoint), at(9, 0)).await;
+    request.await.unwrap();
+
+    assert!(!outcome.is_success());
+    assert_eq!((outcome.response_status, outcome.error.as_deref()), (Some(503), Some("try later")));
+
+    // nobody listens there anymore
+    let outcome = client.send(&endpoint, &delivery(&endpoint), at(9, 0) + Duration::minutes(1)).await;
+    assert_eq!(outcome.response_status, None);
+    assert!(outcome.error.is_some());
+}
+
+#[tokio::test]
+async fn it_does_not_send_to_a_host_that_resolves_to_a_local_address() {
+    let (url, request) = stand_in(200).await;
+    let client = WebhookClient::new(std::time::Duration::from_secs(5), false).unwrap();
+
+    // a name, only its resolved addresses tell it's local
+    let endpoint = local_endpoint(&url.replace("127.0.0.1", "localhost"));
+    let outcome = client.send(&endpoint, &delivery(&endpoint), at(9, 0)).await;
+    assert_eq!(outcome.response_status, None);
+    assert!(outcome.error.unwrap().contains("localhost resolves to a local or private network"));
+
+    let endpoint = local_endpoint(&url);
+    let outcome = client.send(&endpoint, &delivery(&endpoint), at(9, 0)).await;
+    assert_eq!(outcome.response_status, None);
+    assert!(outcome.error.unwrap().contains("is on a local or private network"));
+
+    assert!(!request.is_finished());
+    request.abort();
+}
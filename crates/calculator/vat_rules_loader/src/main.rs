@@ -0,0 +1,28 @@
+use anyhow::{anyhow, Result};
+use calculator::repositories::vat::upsert_vat_rules;
+use calculator::services::vat::parse_vat_rules_csv;
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+
+// Usage: vat_rules_loader --file <path>
+// The file is `country;standard_rate;eu_member` with a header, e.g. `ES;21.00;true`
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let path = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["--file", path] => path.to_string(),
+        _ => return Err(anyhow!("Usage: vat_rules_loader --file <path>")),
+    };
+
+    let rules = parse_vat_rules_csv(&std::fs::read_to_string(&path)?)?;
+
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+    upsert_vat_rules(&pool, &rules).await?;
+
+    println!("Loaded {} VAT rules from {}", rules.len(), path);
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use anyhow::{anyhow, Result};
+use calculator::jobs::webhooks::{deliver_webhooks_handler, redeliver_webhook_handler, register_webhook_endpoint_handler, DeliverWebhooksJob};
+use calculator::repositories::webhooks::{find_deliveries, find_delivery_attempts, find_webhook_endpoints, set_webhook_endpoint_active, DeliveryFilter};
+use calculator::services::webhook_client::WebhookClient;
+use calculator::settings::config::Settings;
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+const DEFAULT_LIMIT: i64 = 50;
+const DEFAULT_INTERVAL_SECONDS: u64 = 10;
+const DEFAULT_DELIVERIES: i64 = 50;
+const TIMEOUT_SECONDS: u64 = 10;
+
+// Usage:
+//   webhooks register --merchant <merchant_reference> --url <url> [--topic <topic>]... [--allow-local]
+//   webhooks endpoints [--merchant <merchant_reference>]
+//   webhooks disable --endpoint <id>
+//   webhooks enable --endpoint <id>
+//   webhooks deliver [--once] [--limit <deliveries per run>] [--interval <seconds>] [--allow-local]
+//   webhooks deliveries [--merchant <merchant_reference>] [--status <STATUS>]... [--event <event_id>] [--limit <n>]
+//   webhooks attempts --delivery <id>
+//   webhooks redeliver --delivery <id>
+// The topics are disbursement_created, monthly_fee_calculated and disbursement_status_changed, all of them by default.
+// The delivery statuses are PENDING, DELIVERED and FAILED.
+// --allow-local accepts http and localhost or private network urls, and delivers to them, for development only.
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let mut args = std::env::args().skip(1);
+    let command = args
+        .next()
+        .ok_or_else(|| anyhow!("Usage: webhooks register | endpoints | disable | enable | deliver | deliveries | attempts | redeliver"))?;
+    let options = parse_options(args)?;
+
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+
+    match command.as_str() {
+        "register" => {
+            let merchant_reference = options.filter.merchant_reference.ok_or_else(|| anyhow!("--merchant is required"))?;
+            let url = options.url.ok_or_else(|| anyhow!("--url is required"))?;
+            let endpoint = register_webhook_endpoint_handler(&pool, &merchant_reference, &url, &options.topics, options.allow_local).await?;
+
+            println!("Endpoint {} registered for {} ({})", endpoint.id, endpoint.merchant_reference, endpoint.topics.join(", "));
+            println!("Secret (shown only once): {}", endpoint.secret);
+        }
+        "endpoints" => {
+            let mut conn = pool.acquire().await?;
+            let endpoints = find_webhook_endpoints(&mut conn, options.filter.merchant_reference.as_deref()).await?;
+
+            println!("{}", serde_json::to_string_pretty(&endpoints)?);
+        }
+        "disable" | "enable" => {
+            let endpoint_id = options.endpoint_id.ok_or_else(|| anyhow!("--endpoint is required"))?;
+            let mut conn = pool.acquire().await?;
+            if !set_webhook_endpoint_active(&mut conn, endpoint_id, command == "enable").await? {
+                return Err(anyhow!("Unknown endpoint {}", endpoint_id));
+            }
+
+            println!("Endpoint {} {}d", endpoint_id, command);
+        }
+        "deliver" => {
+            let client = WebhookClient::new(Duration::from_secs(TIMEOUT_SECONDS), options.allow_local)?;
+            let job = DeliverWebhooksJob { limit: options.filter.limit.unwrap_or(DEFAULT_LIMIT) };
+
+            loop {
+                match deliver_webhooks_handler(job.clone(), &pool, &client).await {
+                    Ok(report) => {
+                        if report.sent() > 0 {
+                            println!("Delivered {}, retrying {}, failed {}", report.delivered, report.retrying, report.failed);
+                        }
+                        // a full run means there may be more due
+                        if report.sent() as i64 == job.limit {
+                            continue;
+                        }
+                    }
+                    Err(err) => eprintln!("Error delivering webhooks: {:#}", err),
+                }
+
+                if options.once {
+                    break;
+                }
+                tokio::time::sleep(options.interval).await;
+            }
+        }
+        "deliveries" => {
+            let filter = DeliveryFilter { limit: options.filter.limit.or(Some(DEFAULT_DELIVERIES)), ..options.filter };
+            let mut conn = pool.acquire().await?;
+            let deliveries = find_deliveries(&mut conn, &filter).await?;
+
+            for delivery in &deliveries {
+                println!(
+                    "{} {} {} {} {} attempts={} next={}",
+                    delivery.id,
+                    delivery.merchant_reference,
+                    delivery.topic,
+                    delivery.event_id,
+                    delivery.status,
+                    delivery.attempts,
+                    delivery.next_attempt_at.map(|at| at.to_rfc3339()).unwrap_or_else(|| "-".to_string())
+                );
+            }
+        }
+        "attempts" => {
+            let delivery_id = options.delivery_id.ok_or_else(|| anyhow!("--delivery is required"))?;
+            let mut conn = pool.acquire().await?;
+            let attempts = find_delivery_attempts(&mut conn, delivery_id).await?;
+
+            println!("{}", serde_json::to_string_pretty(&attempts)?);
+        }
+        "redeliver" => {
+            let delivery_id = options.delivery_id.ok_or_else(|| anyhow!("--delivery is required"))?;
+            let delivery = redeliver_webhook_handler(&pool, delivery_id).await?;
+
+            println!("Delivery {} of event {} queued again", delivery.id, delivery.event_id);
+        }
+        other => return Err(anyhow!("Unknown command: {}", other)),
+    }
+
+    Ok(())
+}
+
+struct Options {
+    filter: DeliveryFilter,
+    url: Option<String>,
+    topics: Vec<String>,
+    endpoint_id: Option<Uuid>,
+    delivery_id: Option<Uuid>,
+    once: bool,
+    interval: Duration,
+    allow_local: bool,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options> {
+    let mut options = Options {
+        filter: DeliveryFilter::default(),
+        url: None,
+        topics: Vec::new(),
+        endpoint_id: None,
+        delivery_id: None,
+        once: false,
+        interval: Duration::from_secs(DEFAULT_INTERVAL_SECONDS),
+        allow_local: false,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--merchant" => options.filter.merchant_reference = Some(value()?),
+            "--status" => options.filter.statuses.push(value()?.to_uppercase().parse()?),
+            "--event" => options.filter.event_id = Some(Uuid::parse_str(&value()?)?),
+            "--limit" => options.filter.limit = Some(value()?.parse()?),
+            "--url" => options.url = Some(value()?),
+            "--topic" => options.topics.push(value()?),
+            "--endpoint" => options.endpoint_id = Some(Uuid::parse_str(&value()?)?),
+            "--delivery" => options.delivery_id = Some(Uuid::parse_str(&value()?)?),
+            "--once" => options.once = true,
+            "--interval" => options.interval = Duration::from_secs(value()?.parse()?),
+            "--allow-local" => options.allow_local = true,
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(options)
+}
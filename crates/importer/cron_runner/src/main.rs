@@ -58,7 +58,10 @@ async fn start_scheduler_once() -> Result<(), Box<dyn std::error::Error>> {
 
 async fn run_import_csv_job(settings: Settings) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let file_path = &settings.csv_path;
-    let job = ImportCsvJob { file_path: file_path.to_string() };
+    let job = ImportCsvJob {
+        file_path: file_path.to_string(),
+        pricing_plans_path: settings.pricing_plans_csv_path.clone(),
+    };
     let publisher = KafkaPublisherBuilder::new(&settings.kafka_brokers).build()?;
 
     import_merchants_csv_handler(job, &publisher, settings).await?;
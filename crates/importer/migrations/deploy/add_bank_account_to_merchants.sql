@@ -0,0 +1,11 @@
+-- Deploy importer:add_bank_account_to_merchants to pg
+
+BEGIN;
+
+-- Nullable, not every merchant file has the bank account columns yet
+ALTER TABLE merchants
+    ADD COLUMN iban TEXT,
+    ADD COLUMN bic TEXT,
+    ADD COLUMN account_holder TEXT;
+
+COMMIT;
@@ -0,0 +1,17 @@
+-- Deploy importer:add_disbursement_schedule_to_merchants to pg
+
+BEGIN;
+
+ALTER TABLE merchants DROP CONSTRAINT merchants_disbursement_frequency_check;
+ALTER TABLE merchants ADD CONSTRAINT merchants_disbursement_frequency_check
+    CHECK (disbursement_frequency IN ('DAILY', 'WEEKLY', 'BIWEEKLY', 'MONTHLY'));
+
+-- WEEKLY and BIWEEKLY merchants are disbursed on disbursement_weekday, MONTHLY ones on disbursement_day_of_month,
+-- NULL means the weekday (or the day) of live_on
+ALTER TABLE merchants
+    ADD COLUMN disbursement_weekday TEXT
+        CHECK (disbursement_weekday IN ('MONDAY', 'TUESDAY', 'WEDNESDAY', 'THURSDAY', 'FRIDAY', 'SATURDAY', 'SUNDAY')),
+    ADD COLUMN disbursement_day_of_month SMALLINT
+        CHECK (disbursement_day_of_month BETWEEN 1 AND 31);
+
+COMMIT;
@@ -0,0 +1,21 @@
+-- Deploy importer:add_email_notifications to pg
+
+BEGIN;
+
+-- The send log of the emails to the merchants, an event is emailed until one of them is SENT
+CREATE TABLE email_notifications (
+    id UUID PRIMARY KEY,
+    event_id UUID NOT NULL,
+    merchant_reference TEXT NOT NULL,
+    template TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('SENT', 'FAILED')),
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE UNIQUE INDEX email_notifications_sent_unique ON email_notifications (event_id, template) WHERE status = 'SENT';
+CREATE INDEX email_notifications_merchant_reference_idx ON email_notifications (merchant_reference, created_at);
+
+COMMIT;
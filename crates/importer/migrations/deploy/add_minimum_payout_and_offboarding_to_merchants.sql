@@ -0,0 +1,10 @@
+-- Deploy importer:add_minimum_payout_and_offboarding_to_merchants to pg
+
+BEGIN;
+
+-- In the minor unit of the payout currency, NULL for the global minimum payout of the calculator
+ALTER TABLE merchants
+    ADD COLUMN minimum_payout BIGINT CHECK (minimum_payout >= 0),
+    ADD COLUMN offboarded_on DATE;
+
+COMMIT;
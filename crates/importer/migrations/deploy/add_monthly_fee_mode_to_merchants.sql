@@ -0,0 +1,9 @@
+-- Deploy importer:add_monthly_fee_mode_to_merchants to pg
+
+BEGIN;
+
+-- INVOICE keeps invoicing the minimum monthly fee shortfall, DEDUCT takes it from the next disbursement
+ALTER TABLE merchants
+    ADD COLUMN monthly_fee_mode TEXT NOT NULL DEFAULT 'INVOICE' CHECK (monthly_fee_mode IN ('INVOICE', 'DEDUCT'));
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Deploy importer:add_payout_currency_to_merchants to pg
+
+BEGIN;
+
+-- ISO 4217, the merchants imported before it existed are paid out in euros
+ALTER TABLE merchants
+    ADD COLUMN payout_currency TEXT NOT NULL DEFAULT 'EUR';
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Deploy importer:add_pricing_plan_to_merchants to pg
+
+BEGIN;
+
+-- The code of the plan in the pricing plans file, NULL for the default tiers
+ALTER TABLE merchants
+    ADD COLUMN pricing_plan TEXT;
+
+COMMIT;
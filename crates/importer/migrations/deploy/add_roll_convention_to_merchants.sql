@@ -0,0 +1,9 @@
+-- Deploy importer:add_roll_convention_to_merchants to pg
+
+BEGIN;
+
+-- How the payouts scheduled on a weekend or a bank holiday are moved, NULL for the global convention
+ALTER TABLE merchants
+    ADD COLUMN roll_convention TEXT CHECK (roll_convention IN ('NEXT', 'PREVIOUS'));
+
+COMMIT;
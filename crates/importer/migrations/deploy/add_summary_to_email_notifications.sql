@@ -0,0 +1,9 @@
+-- Deploy importer:add_summary_to_email_notifications to pg
+
+BEGIN;
+
+-- The payout summary emailed, so a FAILED email can be sent again without its event. The emails logged before
+-- have none and can't be resent.
+ALTER TABLE email_notifications ADD COLUMN summary JSONB;
+
+COMMIT;
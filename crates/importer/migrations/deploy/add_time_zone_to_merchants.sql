@@ -0,0 +1,9 @@
+-- Deploy importer:add_time_zone_to_merchants to pg
+
+BEGIN;
+
+-- IANA time zone (Europe/Madrid), the merchants from older files are in UTC
+ALTER TABLE merchants
+    ADD COLUMN time_zone TEXT NOT NULL DEFAULT 'UTC';
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Deploy importer:add_vat_registration_to_merchants to pg
+
+BEGIN;
+
+-- ISO 3166-1 alpha-2 and the VAT number with its country prefix, both optional
+ALTER TABLE merchants
+    ADD COLUMN country TEXT CHECK (country ~ '^[A-Z]{2}$'),
+    ADD COLUMN vat_number TEXT,
+    ADD CONSTRAINT merchants_vat_number_country_check CHECK (vat_number IS NULL OR country IS NOT NULL);
+
+COMMIT;
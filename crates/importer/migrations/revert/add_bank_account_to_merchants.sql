@@ -0,0 +1,10 @@
+-- Revert importer:add_bank_account_to_merchants from pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    DROP COLUMN iban,
+    DROP COLUMN bic,
+    DROP COLUMN account_holder;
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Revert importer:add_email_notifications from pg
+
+BEGIN;
+
+DROP TABLE IF EXISTS email_notifications;
+
+COMMIT;
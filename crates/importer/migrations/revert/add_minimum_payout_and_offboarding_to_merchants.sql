@@ -0,0 +1,9 @@
+-- Revert importer:add_minimum_payout_and_offboarding_to_merchants from pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    DROP COLUMN minimum_payout,
+    DROP COLUMN offboarded_on;
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Revert importer:add_monthly_fee_mode_to_merchants from pg
+
+BEGIN;
+
+ALTER TABLE merchants DROP COLUMN monthly_fee_mode;
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Revert importer:add_payout_currency_to_merchants from pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    DROP COLUMN payout_currency;
+
+COMMIT;
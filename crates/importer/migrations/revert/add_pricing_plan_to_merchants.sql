@@ -0,0 +1,8 @@
+-- Revert importer:add_pricing_plan_to_merchants from pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    DROP COLUMN pricing_plan;
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Revert importer:add_roll_convention_to_merchants from pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    DROP COLUMN roll_convention;
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Revert importer:add_summary_to_email_notifications from pg
+
+BEGIN;
+
+ALTER TABLE email_notifications DROP COLUMN summary;
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Revert importer:add_time_zone_to_merchants from pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    DROP COLUMN time_zone;
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Revert importer:add_vat_registration_to_merchants from pg
+
+BEGIN;
+
+ALTER TABLE merchants
+    DROP COLUMN country,
+    DROP COLUMN vat_number;
+
+COMMIT;
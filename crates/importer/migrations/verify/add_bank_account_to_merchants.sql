@@ -0,0 +1,9 @@
+-- Verify importer:add_bank_account_to_merchants on pg
+
+BEGIN;
+
+SELECT iban, bic, account_holder
+FROM merchants
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,9 @@
+-- Verify importer:add_email_notifications on pg
+
+BEGIN;
+
+SELECT id, event_id, merchant_reference, template, recipient, subject, status, error, created_at
+FROM email_notifications
+WHERE FALSE;
+
+ROLLBACK;
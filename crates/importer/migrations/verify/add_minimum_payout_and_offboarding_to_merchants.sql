@@ -0,0 +1,9 @@
+-- Verify importer:add_minimum_payout_and_offboarding_to_merchants on pg
+
+BEGIN;
+
+SELECT minimum_payout, offboarded_on
+FROM merchants
+WHERE FALSE;
+
+ROLLBACK;
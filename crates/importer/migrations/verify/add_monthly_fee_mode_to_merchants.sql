@@ -0,0 +1,9 @@
+-- Verify importer:add_monthly_fee_mode_to_merchants on pg
+
+BEGIN;
+
+SELECT monthly_fee_mode
+FROM merchants
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,9 @@
+-- Verify importer:add_payout_currency_to_merchants on pg
+
+BEGIN;
+
+SELECT payout_currency
+FROM merchants
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,9 @@
+-- Verify importer:add_pricing_plan_to_merchants on pg
+
+BEGIN;
+
+SELECT pricing_plan
+FROM merchants
+WHERE FALSE;
+
+ROLLBACK;
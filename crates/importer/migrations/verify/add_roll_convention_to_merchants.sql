@@ -0,0 +1,9 @@
+-- Verify importer:add_roll_convention_to_merchants on pg
+
+BEGIN;
+
+SELECT roll_convention
+FROM merchants
+WHERE FALSE;
+
+ROLLBACK;
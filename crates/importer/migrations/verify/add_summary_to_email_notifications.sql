@@ -0,0 +1,9 @@
+-- Verify importer:add_summary_to_email_notifications on pg
+
+BEGIN;
+
+SELECT summary
+FROM email_notifications
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,9 @@
+-- Verify importer:add_time_zone_to_merchants on pg
+
+BEGIN;
+
+SELECT time_zone
+FROM merchants
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,9 @@
+-- Verify importer:add_vat_registration_to_merchants on pg
+
+BEGIN;
+
+SELECT country, vat_number
+FROM merchants
+WHERE FALSE;
+
+ROLLBACK;
@@ -0,0 +1,76 @@
+use anyhow::{anyhow, Result};
+use importer::events::handlers::disbursement_created_handler::DisbursementCreatedHandlerBuilder;
+use importer::events::kafka::consumer::KafkaImporterConsumer;
+use importer::jobs::notify_payout::resend_payout_notifications_handler;
+use importer::repositories::notifications::find_email_notifications;
+use importer::services::email_templates::{load_template, PAYOUT_SUMMARY_TEMPLATE};
+use importer::services::smtp_mailer::SmtpMailerBuilder;
+use importer::settings::config::Settings;
+use importer::settings::notifications::NotificationSettings;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+const DEFAULT_LOG_LIMIT: i64 = 50;
+const DEFAULT_RESEND_LIMIT: i64 = 100;
+
+// Usage:
+//   payout_notifier                                          emails the merchants their disbursement_created events
+//   payout_notifier log [--merchant <merchant_reference>] [--limit <n>]   the send log, latest first
+//   payout_notifier resend [--limit <n>]                     emails again the payouts whose emails all failed
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await?;
+
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    if !matches!(command.as_deref(), None | Some("log") | Some("resend")) {
+        return Err(anyhow!("Unknown command: {}", command.unwrap_or_default()));
+    }
+
+    let mut merchant_reference = None;
+    let mut limit = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--merchant" => merchant_reference = Some(value()?),
+            "--limit" => limit = Some(value()?.parse()?),
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    if command.as_deref() == Some("log") {
+        let notifications = find_email_notifications(&pool, merchant_reference.as_deref(), limit.unwrap_or(DEFAULT_LOG_LIMIT)).await?;
+        println!("{}", serde_json::to_string_pretty(&notifications)?);
+
+        return Ok(());
+    }
+
+    let notification_settings = NotificationSettings::from_env();
+    // a broken template stops the notifier before it consumes anything
+    let template = load_template(&notification_settings.templates_dir, PAYOUT_SUMMARY_TEMPLATE).map_err(|err| anyhow!(err))?;
+    let mailer = SmtpMailerBuilder::new(&notification_settings).build()?;
+
+    if command.as_deref() == Some("resend") {
+        let report = resend_payout_notifications_handler(&pool, &mailer, &template, limit.unwrap_or(DEFAULT_RESEND_LIMIT))
+            .await
+            .map_err(|err| anyhow!(err))?;
+        for failure in &report.failures {
+            eprintln!("{}", failure);
+        }
+        println!("Resent {} payout summaries, {} failed again", report.sent, report.failures.len());
+
+        return Ok(());
+    }
+
+    let consumer = KafkaImporterConsumer::new(
+        Arc::new(DisbursementCreatedHandlerBuilder.build(pool, mailer, template)),
+        &settings.kafka_brokers,
+        "importer-payout-notification-group",
+    )?;
+    consumer.subscribe(&["disbursement_created"])?;
+
+    consumer.run().await
+}
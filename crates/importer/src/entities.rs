@@ -1 +1,3 @@
 pub mod merchants;
+pub mod pricing_plans;
+pub mod notifications;
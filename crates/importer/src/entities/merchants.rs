@@ -1,6 +1,8 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Weekday};
+use chrono_tz::Tz;
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
+use shared::iban::mask_iban;
 use strum_macros::Display;
 
 #[derive(Deserialize)]
@@ -11,14 +13,97 @@ pub struct Merchant {
     pub email: String,
     pub live_on: NaiveDate,
     pub disbursement_frequency: DisbursementFrequency,
+    // only for weekly and biweekly merchants, the weekday of live_on when it's None
+    pub disbursement_weekday: Option<Weekday>,
+    // only for monthly merchants, the day of live_on when it's None
+    pub disbursement_day_of_month: Option<u32>,
+    // in the minor unit of the payout currency
     pub minimum_monthly_fee: i32,
+    pub bank_account: Option<BankAccount>,
+    // ISO 4217
+    pub payout_currency: String,
+    // the code of a plan of the pricing plans file, the default tiers when it's None
+    pub pricing_plan: Option<String>,
+    // NEXT or PREVIOUS business day for the payouts on bank holidays, the calculator's global one when it's None
+    pub roll_convention: Option<RollConvention>,
+    // IANA name, UTC when the file doesn't have it
+    pub time_zone: Tz,
+    // in the minor unit of the payout currency, the calculator's global minimum payout when it's None
+    pub minimum_payout: Option<i64>,
+    pub offboarded_on: Option<NaiveDate>,
+    // INVOICE or DEDUCT, INVOICE when the file doesn't have it
+    pub monthly_fee_mode: MonthlyFeeMode,
+    // ISO 3166-1 alpha-2, the calculator charges the VAT of its home country when it's None
+    pub country: Option<String>,
+    // with the country prefix (ESB12345678), merchants without it are not VAT registered
+    pub vat_number: Option<String>,
 }
 
-#[derive(Deserialize, Serialize, Display)]
+// Debug masks the iban, so a merchant can be logged without leaking its account number.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct BankAccount {
+    pub iban: String,
+    pub bic: String,
+    pub account_holder: String,
+}
+
+impl BankAccount {
+    // Only the country, the check digits and the last four characters are kept: ES91****************1332
+    pub fn masked_iban(&self) -> String {
+        mask_iban(&self.iban)
+    }
+}
+
+impl std::fmt::Debug for BankAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BankAccount")
+            .field("iban", &self.masked_iban())
+            .field("bic", &self.bic)
+            .field("account_holder", &self.account_holder)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Display, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum DisbursementFrequency {
     #[strum(to_string = "DAILY")]
     Daily,
     #[strum(to_string = "WEEKLY")]
     Weekly,
-}
\ No newline at end of file
+    #[strum(to_string = "BIWEEKLY")]
+    Biweekly,
+    #[strum(to_string = "MONTHLY")]
+    Monthly,
+}
+
+// How weekdays are stored and published: MONDAY, TUESDAY...
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MONDAY",
+        Weekday::Tue => "TUESDAY",
+        Weekday::Wed => "WEDNESDAY",
+        Weekday::Thu => "THURSDAY",
+        Weekday::Fri => "FRIDAY",
+        Weekday::Sat => "SATURDAY",
+        Weekday::Sun => "SUNDAY",
+    }
+}
+#[derive(Deserialize, Serialize, Display, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RollConvention {
+    #[strum(to_string = "NEXT")]
+    Next,
+    #[strum(to_string = "PREVIOUS")]
+    Previous,
+}
+
+#[derive(Deserialize, Serialize, Display, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MonthlyFeeMode {
+    #[default]
+    #[strum(to_string = "INVOICE")]
+    Invoice,
+    #[strum(to_string = "DEDUCT")]
+    Deduct,
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use uuid::Uuid;
+
+// What the merchant is told about a disbursement, read from the disbursement_created event of the calculator.
+// Amounts in the minor unit of the currency.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PayoutSummary {
+    pub event_id: Uuid,
+    pub merchant_reference: String,
+    pub reference: String,
+    pub disbursed_on: NaiveDate,
+    // the first and last day of the sales paid, None when the payout has no sales
+    pub period_from: Option<NaiveDate>,
+    pub period_to: Option<NaiveDate>,
+    pub currency: String,
+    pub gross_amount: i64,
+    // the commissions, VAT excluded
+    pub fee_amount: i64,
+    pub fee_vat_amount: i64,
+    pub net_amount: i64,
+}
+
+// A rendered template, ready to be sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+// The send log, one per email the notifier tried to send. An event is only emailed once: a SENT row stops
+// the next attempts of the same event (Kafka delivers at least once).
+#[derive(Serialize, Clone, Debug)]
+pub struct EmailNotification {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub merchant_reference: String,
+    pub template: String,
+    pub recipient: String,
+    pub subject: String,
+    pub status: NotificationStatus,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Display, EnumString, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum NotificationStatus {
+    #[strum(to_string = "SENT")]
+    Sent,
+    #[strum(to_string = "FAILED")]
+    Failed,
+}
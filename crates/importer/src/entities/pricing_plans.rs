@@ -0,0 +1,19 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+// One version of a plan, published inside merchant_upserted, the calculator stores it and charges the merchant
+// orders created from `valid_from` (until the next version) with it.
+// Amounts are in euro cents and rates in basis points (0.95 % == 95).
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PricingPlan {
+    pub code: String,
+    pub valid_from: NaiveDate,
+    pub tiers: Vec<PricingTier>,
+    pub fixed_fee: i64,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PricingTier {
+    pub from_amount: i64,
+    pub fee_rate: i32,
+}
@@ -1,2 +1,3 @@
 pub mod publisher;
-pub mod kafka;
\ No newline at end of file
+pub mod kafka;
+pub mod handlers;
@@ -0,0 +1,2 @@
+pub mod disbursement_created_handler;
+pub mod handler;
@@ -0,0 +1,44 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::PgPool;
+use crate::entities::notifications::PayoutSummary;
+use crate::events::handlers::handler::EventHandler;
+use crate::jobs::notify_payout::notify_payout_handler;
+use crate::services::email_templates::EmailTemplate;
+use crate::services::mailer::Mailer;
+
+// Emails the merchant the summary of every disbursement the calculator creates.
+pub struct DisbursementCreatedHandler<M: Mailer> {
+    pool: PgPool,
+    mailer: M,
+    template: EmailTemplate,
+}
+
+impl<M: Mailer> DisbursementCreatedHandler<M> {
+    pub fn new(pool: PgPool, mailer: M, template: EmailTemplate) -> Self {
+        Self { pool, mailer, template }
+    }
+}
+
+pub struct DisbursementCreatedHandlerBuilder;
+
+impl DisbursementCreatedHandlerBuilder {
+    pub fn build<M: Mailer>(self, pool: PgPool, mailer: M, template: EmailTemplate) -> DisbursementCreatedHandler<M> {
+        DisbursementCreatedHandler::new(pool, mailer, template)
+    }
+}
+
+#[async_trait]
+impl<M: Mailer> EventHandler for DisbursementCreatedHandler<M> {
+    async fn handle(&self, payload: Value) -> Result<()> {
+        let summary: PayoutSummary = serde_json::from_value(payload)?;
+
+        match notify_payout_handler(&summary, &self.pool, &self.mailer, &self.template).await.map_err(|err| anyhow!(err))? {
+            Some(notification) => println!("Emailed payout summary: {} | {}", summary.reference, notification.merchant_reference),
+            None => println!("Payout summary already emailed: {}", summary.reference),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,8 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use anyhow::Result;
+
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    async fn handle(&self, payload: Value) -> Result<()>;
+}
\ No newline at end of file
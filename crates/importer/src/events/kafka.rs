@@ -1 +1,2 @@
 pub mod publisher;
+pub mod consumer;
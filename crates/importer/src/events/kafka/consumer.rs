@@ -0,0 +1,63 @@
+use crate::events::handlers::handler::EventHandler;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::ClientConfig;
+use rdkafka::Message;
+use serde_json::Value;
+use anyhow::Result;
+use tokio_stream::StreamExt;
+use std::sync::Arc;
+
+// Hands every message to the handler. The offsets are committed automatically, a message the handler fails on
+// is only logged, the handler keeps what it needs to retry it (see `payout_notifier resend`).
+pub struct KafkaImporterConsumer {
+    handler: Arc<dyn EventHandler + Send + Sync>,
+    consumer: StreamConsumer,
+}
+
+impl KafkaImporterConsumer {
+    pub fn new(handler: Arc<dyn EventHandler + Send + Sync>, brokers: &str, group_id: &str) -> Result<Self> {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("group.id", group_id)
+            .set("enable.partition.eof", "false")
+            .set("session.timeout.ms", "6000")
+            .set("enable.auto.commit", "true")
+            .set("auto.offset.reset", "earliest")
+            .create()?;
+
+        Ok(Self { handler, consumer })
+    }
+
+    pub fn subscribe(&self, topics: &[&str]) -> Result<()> {
+        self.consumer.subscribe(topics)?;
+        Ok(())
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let mut stream = self.consumer.stream();
+
+        while let Some(message) = stream.next().await {
+            let message = match message {
+                Ok(message) => message,
+                Err(err) => {
+                    eprintln!("Kafka error: {:#}", err);
+                    continue;
+                }
+            };
+            let Some(payload) = message.payload() else {
+                continue;
+            };
+
+            match serde_json::from_slice::<Value>(payload) {
+                Ok(json) => {
+                    if let Err(err) = self.handler.handle(json).await {
+                        eprintln!("Error handling message: {:#}", err);
+                    }
+                }
+                Err(err) => eprintln!("Failed to parse payload: {:#}", err),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1 +1,2 @@
 pub mod import_csv;
+pub mod notify_payout;
@@ -1,14 +1,17 @@
 use apalis::prelude::Job;
-use crate::entities::merchants::Merchant;
+use crate::entities::merchants::{weekday_name, Merchant};
+use crate::entities::pricing_plans::PricingPlan;
 use crate::events::publisher::EventPublisher;
 use crate::repositories::merchants::upsert_merchants;
 use sqlx::PgPool;
 use crate::services::csv_normalizer::read_and_normalize_merchants_from_csv;
+use crate::services::pricing_plans::read_pricing_plans_from_csv;
 use crate::settings::config::Settings;
 
 #[derive(Clone, Debug)]
 pub struct ImportCsvJob {
     pub file_path: String,
+    pub pricing_plans_path: Option<String>,
 }
 
 impl Job for ImportCsvJob {
@@ -17,14 +20,24 @@ impl Job for ImportCsvJob {
 
 pub async fn import_merchants_csv_handler<P: EventPublisher>(job: ImportCsvJob, publisher: &P, settings: Settings) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let merchants = read_and_normalize_merchants_from_csv(&job.file_path)?;
+    let pricing_plans = match &job.pricing_plans_path {
+        Some(path) => read_pricing_plans_from_csv(path)?,
+        None => Vec::new(),
+    };
+
+    // a merchant with an unknown plan fails the whole import, before anything is stored or published
+    for merchant in &merchants {
+        find_pricing_plan_versions(&pricing_plans, merchant)?;
+    }
 
     let pool = PgPool::connect(&settings.database_url).await?;
 
-    let tx = pool.begin().await?;
+    // the merchants are only stored when every one of them was published
+    let mut tx = pool.begin().await?;
 
-    upsert_merchants(&pool, &merchants).await?;
+    upsert_merchants(&mut tx, &merchants).await?;
 
-    publish_merchants(publisher, merchants).await?;
+    publish_merchants(publisher, merchants, &pricing_plans).await?;
 
     tx.commit().await?;
 
@@ -34,6 +47,7 @@ pub async fn import_merchants_csv_handler<P: EventPublisher>(job: ImportCsvJob,
 async fn publish_merchants<P: EventPublisher>(
     publisher: &P,
     merchants: Vec<Merchant>,
+    pricing_plans: &[PricingPlan],
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     for merchant in &merchants {
         publisher.publish("merchant_upserted", serde_json::json!({
@@ -41,8 +55,45 @@ async fn publish_merchants<P: EventPublisher>(
             "merchant_reference": merchant.merchant_reference,
             "live_on": merchant.live_on,
             "disbursement_frequency": merchant.disbursement_frequency,
-            "minimum_monthly_fee": merchant.minimum_monthly_fee
+            // null means the weekday (or the day of month) of live_on
+            "disbursement_weekday": merchant.disbursement_weekday.map(weekday_name),
+            "disbursement_day_of_month": merchant.disbursement_day_of_month,
+            "roll_convention": merchant.roll_convention,
+            "time_zone": merchant.time_zone.name(),
+            "minimum_payout": merchant.minimum_payout,
+            "offboarded_on": merchant.offboarded_on,
+            "monthly_fee_mode": merchant.monthly_fee_mode,
+            "country": merchant.country,
+            "vat_number": merchant.vat_number,
+            "minimum_monthly_fee": merchant.minimum_monthly_fee,
+            // the calculator needs the whole iban to pay the merchant, it's the only consumer of this topic.
+            "bank_account": merchant.bank_account,
+            "payout_currency": merchant.payout_currency,
+            // every version of the plan, so the calculator doesn't need the pricing plans file. empty for the default tiers
+            "pricing_plan_versions": find_pricing_plan_versions(pricing_plans, merchant)?
         })).await?;
+
+        println!(
+            "Published merchant: {} | bank account: {}",
+            merchant.merchant_reference,
+            merchant.bank_account.as_ref().map(|account| account.masked_iban()).unwrap_or_else(|| "none".to_string())
+        );
     }
     Ok(())
 }
+
+fn find_pricing_plan_versions<'a>(
+    pricing_plans: &'a [PricingPlan],
+    merchant: &Merchant,
+) -> Result<Vec<&'a PricingPlan>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(code) = &merchant.pricing_plan else {
+        return Ok(Vec::new());
+    };
+
+    let versions: Vec<&PricingPlan> = pricing_plans.iter().filter(|plan| &plan.code == code).collect();
+    if versions.is_empty() {
+        return Err(format!("Unknown pricing plan {} for merchant {}", code, merchant.merchant_reference).into());
+    }
+
+    Ok(versions)
+}
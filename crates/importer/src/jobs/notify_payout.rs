@@ -0,0 +1,74 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::entities::notifications::{EmailNotification, NotificationStatus, PayoutSummary};
+use crate::repositories::notifications::{find_merchant_email, find_unsent_payout_summaries, insert_email_notification, was_notified};
+use crate::services::email_templates::{payout_summary_values, render, EmailTemplate};
+use crate::services::mailer::Mailer;
+
+// Emails the payout summary to the merchant and logs it, sent or not. None when the event was already emailed.
+// A failed send is logged as FAILED and returned as an error. The consumer doesn't retry it (its offsets are
+// committed whatever the handler returns), `resend_payout_notifications_handler` does.
+pub async fn notify_payout_handler<M: Mailer>(
+    summary: &PayoutSummary,
+    pool: &PgPool,
+    mailer: &M,
+    template: &EmailTemplate,
+) -> Result<Option<EmailNotification>, Box<dyn std::error::Error + Send + Sync>> {
+    if was_notified(pool, summary.event_id, &template.name).await? {
+        return Ok(None);
+    }
+
+    let recipient = find_merchant_email(pool, &summary.merchant_reference)
+        .await?
+        .ok_or_else(|| format!("Unknown merchant {}", summary.merchant_reference))?;
+
+    let email = render(template, &recipient, &payout_summary_values(summary)?)?;
+    let result = mailer.send(&email).await;
+
+    let notification = EmailNotification {
+        id: Uuid::new_v4(),
+        event_id: summary.event_id,
+        merchant_reference: summary.merchant_reference.clone(),
+        template: template.name.clone(),
+        recipient,
+        subject: email.subject,
+        status: if result.is_ok() { NotificationStatus::Sent } else { NotificationStatus::Failed },
+        error: result.as_ref().err().map(|err| format!("{:#}", err)),
+        created_at: Utc::now(),
+    };
+    insert_email_notification(pool, &notification, summary).await?;
+
+    if let Err(err) = result {
+        return Err(format!("Emailing the payout {} to {} failed: {:#}", summary.reference, summary.merchant_reference, err).into());
+    }
+
+    Ok(Some(notification))
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ResendReport {
+    pub sent: usize,
+    // why each of the others failed again, they are left for the next resend
+    pub failures: Vec<String>,
+}
+
+// Emails again the payouts whose emails all FAILED, at most `limit` of them. Every attempt is logged like the
+// first one.
+pub async fn resend_payout_notifications_handler<M: Mailer>(
+    pool: &PgPool,
+    mailer: &M,
+    template: &EmailTemplate,
+    limit: i64,
+) -> Result<ResendReport, Box<dyn std::error::Error + Send + Sync>> {
+    let mut report = ResendReport::default();
+
+    for summary in find_unsent_payout_summaries(pool, &template.name, limit).await? {
+        match notify_payout_handler(&summary, pool, mailer, template).await {
+            Ok(_) => report.sent += 1,
+            Err(err) => report.failures.push(err.to_string()),
+        }
+    }
+
+    Ok(report)
+}
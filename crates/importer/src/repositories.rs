@@ -1 +1,2 @@
 pub mod merchants;
+pub mod notifications;
@@ -1,13 +1,13 @@
-use crate::entities::merchants::Merchant;
-use sqlx::PgPool;
+use crate::entities::merchants::{weekday_name, Merchant};
+use sqlx::PgConnection;
 
-pub async fn upsert_merchants(pool: &PgPool, merchants: &[Merchant]) -> Result<(), sqlx::Error> {
+pub async fn upsert_merchants(conn: &mut PgConnection, merchants: &[Merchant]) -> Result<(), sqlx::Error> {
     if merchants.is_empty() {
         return Ok(());
     }
 
     let mut query_builder = sqlx::QueryBuilder::new(
-        "INSERT INTO merchants (id, merchant_reference, email, live_on, disbursement_frequency, minimum_monthly_fee) "
+        "INSERT INTO merchants (id, merchant_reference, email, live_on, disbursement_frequency, disbursement_weekday, disbursement_day_of_month, minimum_monthly_fee, iban, bic, account_holder, payout_currency, pricing_plan, roll_convention, time_zone, minimum_payout, offboarded_on, monthly_fee_mode, country, vat_number) "
     );
 
     query_builder.push_values(merchants, |mut b, merchant| {
@@ -16,7 +16,21 @@ pub async fn upsert_merchants(pool: &PgPool, merchants: &[Merchant]) -> Result<(
         .push_bind(&merchant.email)
         .push_bind(merchant.live_on)
         .push_bind(merchant.disbursement_frequency.to_string())
-        .push_bind(merchant.minimum_monthly_fee);
+        .push_bind(merchant.disbursement_weekday.map(weekday_name))
+        .push_bind(merchant.disbursement_day_of_month.map(|day| day as i16))
+        .push_bind(merchant.minimum_monthly_fee)
+        .push_bind(merchant.bank_account.as_ref().map(|account| &account.iban))
+        .push_bind(merchant.bank_account.as_ref().map(|account| &account.bic))
+        .push_bind(merchant.bank_account.as_ref().map(|account| &account.account_holder))
+        .push_bind(&merchant.payout_currency)
+        .push_bind(&merchant.pricing_plan)
+        .push_bind(merchant.roll_convention.map(|convention| convention.to_string()))
+        .push_bind(merchant.time_zone.name())
+        .push_bind(merchant.minimum_payout)
+        .push_bind(merchant.offboarded_on)
+        .push_bind(merchant.monthly_fee_mode.to_string())
+        .push_bind(&merchant.country)
+        .push_bind(&merchant.vat_number);
     });
 
     query_builder.push(
@@ -24,11 +38,25 @@ pub async fn upsert_merchants(pool: &PgPool, merchants: &[Merchant]) -> Result<(
          email = EXCLUDED.email, \
          live_on = EXCLUDED.live_on, \
          disbursement_frequency = EXCLUDED.disbursement_frequency, \
-         minimum_monthly_fee = EXCLUDED.minimum_monthly_fee"
+         disbursement_weekday = EXCLUDED.disbursement_weekday, \
+         disbursement_day_of_month = EXCLUDED.disbursement_day_of_month, \
+         minimum_monthly_fee = EXCLUDED.minimum_monthly_fee, \
+         iban = EXCLUDED.iban, \
+         bic = EXCLUDED.bic, \
+         account_holder = EXCLUDED.account_holder, \
+         payout_currency = EXCLUDED.payout_currency, \
+         pricing_plan = EXCLUDED.pricing_plan, \
+         roll_convention = EXCLUDED.roll_convention, \
+         time_zone = EXCLUDED.time_zone, \
+         minimum_payout = EXCLUDED.minimum_payout, \
+         offboarded_on = EXCLUDED.offboarded_on, \
+         monthly_fee_mode = EXCLUDED.monthly_fee_mode, \
+         country = EXCLUDED.country, \
+         vat_number = EXCLUDED.vat_number"
     );
 
     let query = query_builder.build();
-    query.execute(pool).await?;
+    query.execute(conn).await?;
 
     Ok(())
 }
@@ -0,0 +1,103 @@
+use crate::entities::notifications::{EmailNotification, NotificationStatus, PayoutSummary};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+// The email only ever leaves this database in the emails sent to the merchant.
+pub async fn find_merchant_email(pool: &PgPool, merchant_reference: &str) -> Result<Option<String>, sqlx::Error> {
+    let row = sqlx::query("SELECT email FROM merchants WHERE merchant_reference = $1")
+        .bind(merchant_reference)
+        .fetch_optional(pool)
+        .await?;
+
+    row.map(|row| row.try_get("email")).transpose()
+}
+
+pub async fn was_notified(pool: &PgPool, event_id: Uuid, template: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT EXISTS (SELECT 1 FROM email_notifications WHERE event_id = $1 AND template = $2 AND status = 'SENT') AS notified"
+    )
+    .bind(event_id)
+    .bind(template)
+    .fetch_one(pool)
+    .await?;
+
+    row.try_get("notified")
+}
+
+// The summary is kept so a FAILED email can be sent again.
+pub async fn insert_email_notification(pool: &PgPool, notification: &EmailNotification, summary: &PayoutSummary) -> Result<(), sqlx::Error> {
+    let summary = serde_json::to_string(summary).map_err(|err| sqlx::Error::Encode(Box::new(err)))?;
+
+    sqlx::query(
+        "INSERT INTO email_notifications (id, event_id, merchant_reference, template, recipient, subject, status, error, created_at, summary) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::JSONB)"
+    )
+    .bind(notification.id)
+    .bind(notification.event_id)
+    .bind(&notification.merchant_reference)
+    .bind(&notification.template)
+    .bind(&notification.recipient)
+    .bind(&notification.subject)
+    .bind(notification.status.to_string())
+    .bind(&notification.error)
+    .bind(notification.created_at)
+    .bind(summary)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// The payouts whose emails all FAILED, the oldest first. The emails logged without a summary are left out.
+pub async fn find_unsent_payout_summaries(pool: &PgPool, template: &str, limit: i64) -> Result<Vec<PayoutSummary>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT summary::TEXT AS summary FROM ( \
+             SELECT DISTINCT ON (event_id) summary, created_at FROM email_notifications failed \
+             WHERE template = $1 AND status = 'FAILED' AND summary IS NOT NULL \
+             AND NOT EXISTS (SELECT 1 FROM email_notifications sent \
+                             WHERE sent.event_id = failed.event_id AND sent.template = failed.template AND sent.status = 'SENT') \
+             ORDER BY event_id, created_at \
+         ) unsent ORDER BY created_at LIMIT $2"
+    )
+    .bind(template)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let summary: String = row.try_get("summary")?;
+            serde_json::from_str(&summary).map_err(|err| sqlx::Error::Decode(Box::new(err)))
+        })
+        .collect()
+}
+
+// The latest ones first.
+pub async fn find_email_notifications(pool: &PgPool, merchant_reference: Option<&str>, limit: i64) -> Result<Vec<EmailNotification>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, event_id, merchant_reference, template, recipient, subject, status, error, created_at FROM email_notifications \
+         WHERE ($1::TEXT IS NULL OR merchant_reference = $1) ORDER BY created_at DESC LIMIT $2"
+    )
+    .bind(merchant_reference)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let status: String = row.try_get("status")?;
+
+            Ok(EmailNotification {
+                id: row.try_get("id")?,
+                event_id: row.try_get("event_id")?,
+                merchant_reference: row.try_get("merchant_reference")?,
+                template: row.try_get("template")?,
+                recipient: row.try_get("recipient")?,
+                subject: row.try_get("subject")?,
+                status: status.parse::<NotificationStatus>().map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+                error: row.try_get("error")?,
+                created_at: row.try_get("created_at")?,
+            })
+        })
+        .collect()
+}
@@ -1 +1,8 @@
-pub mod csv_normalizer;
\ No newline at end of file
+pub mod csv_normalizer;
+pub mod iban;
+pub mod currencies;
+pub mod pricing_plans;
+pub mod vat_numbers;
+pub mod email_templates;
+pub mod mailer;
+pub mod smtp_mailer;
@@ -1,5 +1,9 @@
-use crate::entities::merchants::{DisbursementFrequency, Merchant};
-use chrono::NaiveDate;
+use crate::entities::merchants::{BankAccount, DisbursementFrequency, Merchant, MonthlyFeeMode, RollConvention};
+use crate::services::currencies::{to_minor_units, DEFAULT_CURRENCY};
+use crate::services::iban::{normalize_bic, normalize_iban};
+use crate::services::vat_numbers::{normalize_country, normalize_vat_number};
+use chrono::{NaiveDate, Weekday};
+use chrono_tz::Tz;
 use csv::StringRecord;
 use std::error::Error;
 use std::fs::File;
@@ -15,6 +19,8 @@ pub fn read_and_normalize_merchants_from_csv(path: &str) -> Result<Vec<Merchant>
     let mut reader = csv::ReaderBuilder::new()
         .delimiter(b';')
         .has_headers(true)
+        // the columns after the minimum_monthly_fee are optional, older files don't have them
+        .flexible(true)
         .from_reader(buf_reader);
 
     let mut merchants = Vec::new();
@@ -31,15 +37,155 @@ fn parse_merchant_record(record: &StringRecord) -> Result<Merchant, Box<dyn Erro
   let frequency = match record[4].to_uppercase().as_str() {
     "DAILY" => DisbursementFrequency::Daily,
     "WEEKLY" => DisbursementFrequency::Weekly,
+    "BIWEEKLY" => DisbursementFrequency::Biweekly,
+    "MONTHLY" => DisbursementFrequency::Monthly,
     other => return Err(format!("Unknown disbursement_frequency: {}", other).into()),
   };
 
+    let payout_currency = parse_payout_currency(record);
+    let (country, vat_number) = parse_vat_registration(record).map_err(|err| format!("Merchant {}: {}", &record[1], err))?;
+    let (disbursement_weekday, disbursement_day_of_month) =
+        parse_disbursement_day(record, frequency).map_err(|err| format!("Merchant {}: {}", &record[1], err))?;
+
     Ok(Merchant {
         id: Uuid::parse_str(&record[0])?,
         merchant_reference: record[1].to_string(),
         email: record[2].to_string(),
         live_on: NaiveDate::parse_from_str(&record[3], "%Y-%m-%d")?,
         disbursement_frequency: frequency,
-        minimum_monthly_fee: (record[5].parse::<f64>()? * 100.0).round() as i32,
+        disbursement_weekday,
+        disbursement_day_of_month,
+        minimum_monthly_fee: to_minor_units(&record[5], &payout_currency)
+            .map_err(|err| format!("Merchant {}: {}", &record[1], err))?,
+        bank_account: parse_bank_account(record)?,
+        payout_currency: payout_currency.clone(),
+        pricing_plan: record.get(10).map(str::trim).filter(|code| !code.is_empty()).map(str::to_string),
+        roll_convention: parse_roll_convention(record)?,
+        time_zone: parse_time_zone(record)?,
+        minimum_payout: parse_minimum_payout(record, &payout_currency)?,
+        offboarded_on: parse_offboarded_on(record)?,
+        monthly_fee_mode: parse_monthly_fee_mode(record)?,
+        country,
+        vat_number,
     })
+}
+
+// After the bank account, EUR when it's missing or empty.
+fn parse_payout_currency(record: &StringRecord) -> String {
+    match record.get(9).map(str::trim) {
+        Some(currency) if !currency.is_empty() => currency.to_uppercase(),
+        _ => DEFAULT_CURRENCY.to_string(),
+    }
+}
+
+// After the pricing plan, a weekday (FRIDAY or FRI) for the weekly and biweekly merchants or a day of month (1 to 31)
+// for the monthly ones. Empty means the weekday or the day of live_on, daily merchants can't have it.
+fn parse_disbursement_day(
+    record: &StringRecord,
+    frequency: DisbursementFrequency,
+) -> Result<(Option<Weekday>, Option<u32>), Box<dyn Error + Send + Sync>> {
+    let value = record.get(11).unwrap_or_default().trim();
+    if value.is_empty() {
+        return Ok((None, None));
+    }
+
+    match frequency {
+        DisbursementFrequency::Daily => Err("daily merchants don't have a disbursement_day".into()),
+        DisbursementFrequency::Weekly | DisbursementFrequency::Biweekly => value
+            .parse::<Weekday>()
+            .map(|weekday| (Some(weekday), None))
+            .map_err(|_| format!("Invalid disbursement_day weekday: {}", value).into()),
+        DisbursementFrequency::Monthly => match value.parse::<u32>() {
+            Ok(day) if (1..=31).contains(&day) => Ok((None, Some(day))),
+            _ => Err(format!("Invalid disbursement_day of month: {}", value).into()),
+        },
+    }
+}
+
+// After the time zone, in the payout currency like the minimum_monthly_fee. Empty for the global minimum payout.
+fn parse_minimum_payout(record: &StringRecord, payout_currency: &str) -> Result<Option<i64>, Box<dyn Error + Send + Sync>> {
+    match record.get(14).map(str::trim) {
+        Some(amount) if !amount.is_empty() => {
+            let minimum_payout = to_minor_units(amount, payout_currency).map_err(|err| format!("Merchant {}: {}", &record[1], err))?;
+            if minimum_payout < 0 {
+                return Err(format!("Merchant {}: negative minimum payout {}", &record[1], amount).into());
+            }
+            Ok(Some(minimum_payout as i64))
+        }
+        _ => Ok(None),
+    }
+}
+
+// After the minimum payout, the date the merchant stops using Company XYZ.
+fn parse_offboarded_on(record: &StringRecord) -> Result<Option<NaiveDate>, Box<dyn Error + Send + Sync>> {
+    match record.get(15).map(str::trim) {
+        Some(date) if !date.is_empty() => Ok(Some(NaiveDate::parse_from_str(date, "%Y-%m-%d")?)),
+        _ => Ok(None),
+    }
+}
+
+// After the offboarding date, how the minimum monthly fee shortfall is collected. INVOICE when it's missing or empty.
+fn parse_monthly_fee_mode(record: &StringRecord) -> Result<MonthlyFeeMode, Box<dyn Error + Send + Sync>> {
+    match record.get(16).map(|value| value.trim().to_uppercase()).as_deref() {
+        None | Some("") | Some("INVOICE") => Ok(MonthlyFeeMode::Invoice),
+        Some("DEDUCT") => Ok(MonthlyFeeMode::Deduct),
+        Some(other) => Err(format!("Merchant {}: unknown monthly_fee_mode: {}", &record[1], other).into()),
+    }
+}
+
+// The last columns, the country of the merchant and its VAT number, both optional.
+// A merchant with a VAT number must have the country it's registered in.
+fn parse_vat_registration(record: &StringRecord) -> Result<(Option<String>, Option<String>), Box<dyn Error + Send + Sync>> {
+    let country = match record.get(17).map(str::trim) {
+        Some(country) if !country.is_empty() => Some(normalize_country(country)?),
+        _ => None,
+    };
+
+    let vat_number = match (record.get(18).map(str::trim), &country) {
+        (Some(vat_number), Some(country)) if !vat_number.is_empty() => Some(normalize_vat_number(vat_number, country)?),
+        (Some(vat_number), None) if !vat_number.is_empty() => return Err("a vat_number needs the country".into()),
+        _ => None,
+    };
+
+    Ok((country, vat_number))
+}
+
+// After the roll convention, an IANA name like Europe/Madrid, UTC when it's missing or empty.
+fn parse_time_zone(record: &StringRecord) -> Result<Tz, Box<dyn Error + Send + Sync>> {
+    match record.get(13).map(str::trim) {
+        Some(name) if !name.is_empty() => name
+            .parse::<Tz>()
+            .map_err(|_| format!("Merchant {}: unknown time_zone: {}", &record[1], name).into()),
+        _ => Ok(chrono_tz::UTC),
+    }
+}
+
+// After the disbursement day, empty for the global convention of the calculator.
+fn parse_roll_convention(record: &StringRecord) -> Result<Option<RollConvention>, Box<dyn Error + Send + Sync>> {
+    match record.get(12).map(|value| value.trim().to_uppercase()).as_deref() {
+        None | Some("") => Ok(None),
+        Some("NEXT") => Ok(Some(RollConvention::Next)),
+        Some("PREVIOUS") => Ok(Some(RollConvention::Previous)),
+        Some(other) => Err(format!("Merchant {}: unknown roll_convention: {}", &record[1], other).into()),
+    }
+}
+
+// iban;bic;account_holder after the minimum_monthly_fee, all of them or none.
+fn parse_bank_account(record: &StringRecord) -> Result<Option<BankAccount>, Box<dyn Error + Send + Sync>> {
+    let iban = record.get(6).unwrap_or_default().trim();
+    let bic = record.get(7).unwrap_or_default().trim();
+    let account_holder = record.get(8).unwrap_or_default().trim();
+
+    if iban.is_empty() && bic.is_empty() && account_holder.is_empty() {
+        return Ok(None);
+    }
+    if iban.is_empty() || bic.is_empty() || account_holder.is_empty() {
+        return Err(format!("Incomplete bank account for merchant {}", &record[1]).into());
+    }
+
+    Ok(Some(BankAccount {
+        iban: normalize_iban(iban).map_err(|err| format!("Merchant {}: {}", &record[1], err))?,
+        bic: normalize_bic(bic).map_err(|err| format!("Merchant {}: {}", &record[1], err))?,
+        account_holder: account_holder.to_string(),
+    }))
 }
\ No newline at end of file
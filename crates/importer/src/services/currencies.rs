@@ -0,0 +1,26 @@
+use std::error::Error;
+
+pub const DEFAULT_CURRENCY: &str = "EUR";
+
+// The decimals of the currencies the calculator can pay out in (see shared::currencies).
+pub fn minor_units(currency: &str) -> Result<u32, Box<dyn Error + Send + Sync>> {
+    shared::currencies::minor_units(currency).ok_or_else(|| format!("Unsupported currency: {}", currency).into())
+}
+
+// "15.0" EUR -> 1500, "1500" JPY -> 1500
+pub fn to_minor_units(value: &str, currency: &str) -> Result<i32, Box<dyn Error + Send + Sync>> {
+    let factor = 10_f64.powi(minor_units(currency)? as i32);
+    Ok((value.parse::<f64>()? * factor).round() as i32)
+}
+
+// 43085 EUR -> "430.85", -1500 JPY -> "-1500"
+pub fn format_amount(amount: i64, currency: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let units = minor_units(currency)?;
+    if units == 0 {
+        return Ok(amount.to_string());
+    }
+
+    let factor = 10_i64.pow(units);
+    let sign = if amount < 0 { "-" } else { "" };
+    Ok(format!("{}{}.{:0width$}", sign, amount.abs() / factor, amount.abs() % factor, width = units as usize))
+}
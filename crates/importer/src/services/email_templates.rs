@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+use crate::entities::notifications::{Email, PayoutSummary};
+use crate::services::currencies::format_amount;
+
+pub const PAYOUT_SUMMARY_TEMPLATE: &str = "payout_summary";
+
+// A plain text template: a "Subject: " line, an empty line and the body, with {{placeholders}} in both.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmailTemplate {
+    pub name: String,
+    pub subject: String,
+    pub body: String,
+}
+
+// <dir>/<name>.txt, read once when the notifier starts so a broken template stops it right away.
+pub fn load_template(dir: &str, name: &str) -> Result<EmailTemplate, Box<dyn Error + Send + Sync>> {
+    let path = Path::new(dir).join(format!("{}.txt", name));
+    let content = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Can't read the template {}: {}", path.display(), err))?;
+
+    parse_template(name, &content)
+}
+
+pub fn parse_template(name: &str, content: &str) -> Result<EmailTemplate, Box<dyn Error + Send + Sync>> {
+    let (first_line, rest) = content.split_once('\n').unwrap_or((content, ""));
+    let Some(subject) = first_line.trim_end().strip_prefix("Subject: ") else {
+        return Err(format!("The template {} must start with a \"Subject: \" line", name).into());
+    };
+    let Some(body) = rest.strip_prefix('\n').or_else(|| rest.strip_prefix("\r\n")) else {
+        return Err(format!("The template {} needs an empty line after the subject", name).into());
+    };
+
+    Ok(EmailTemplate {
+        name: name.to_string(),
+        subject: subject.to_string(),
+        body: body.to_string(),
+    })
+}
+
+// A placeholder without a value is an error instead of an email with "{{typo}}" in it.
+pub fn render(template: &EmailTemplate, to: &str, values: &BTreeMap<&str, String>) -> Result<Email, Box<dyn Error + Send + Sync>> {
+    Ok(Email {
+        to: to.to_string(),
+        subject: render_text(&template.name, &template.subject, values)?,
+        body: render_text(&template.name, &template.body, values)?,
+    })
+}
+
+fn render_text(name: &str, text: &str, values: &BTreeMap<&str, String>) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            return Err(format!("Unclosed placeholder in the template {}", name).into());
+        };
+        let key = rest[start + 2..start + end].trim();
+        let value = values
+            .get(key)
+            .ok_or_else(|| format!("Unknown placeholder {{{{{}}}}} in the template {}", key, name))?;
+
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(value);
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+// The amounts with the decimals of their currency, fees are what the merchant is charged (VAT included).
+pub fn payout_summary_values(summary: &PayoutSummary) -> Result<BTreeMap<&'static str, String>, Box<dyn Error + Send + Sync>> {
+    let period = match (summary.period_from, summary.period_to) {
+        (Some(from), Some(to)) if from == to => from.to_string(),
+        (Some(from), Some(to)) => format!("{} to {}", from, to),
+        _ => "-".to_string(),
+    };
+
+    Ok(BTreeMap::from([
+        ("merchant_reference", summary.merchant_reference.clone()),
+        ("reference", summary.reference.clone()),
+        ("disbursed_on", summary.disbursed_on.to_string()),
+        ("period", period),
+        ("currency", summary.currency.clone()),
+        ("gross", format_amount(summary.gross_amount, &summary.currency)?),
+        ("fees", format_amount(summary.fee_amount + summary.fee_vat_amount, &summary.currency)?),
+        ("fee_vat", format_amount(summary.fee_vat_amount, &summary.currency)?),
+        ("net", format_amount(summary.net_amount, &summary.currency)?),
+    ]))
+}
@@ -0,0 +1,53 @@
+use std::error::Error;
+
+const MIN_IBAN_LENGTH: usize = 15;
+const MAX_IBAN_LENGTH: usize = 34;
+
+// Returns the iban without spaces and in uppercase, the way it's stored and sent to the bank,
+// if its ISO 13616 (mod 97) checksum is right.
+pub fn normalize_iban(value: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let iban: String = value.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+
+    if iban.len() < MIN_IBAN_LENGTH || iban.len() > MAX_IBAN_LENGTH {
+        return Err(format!("Invalid IBAN length: {}", iban.len()).into());
+    }
+    if !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("Invalid IBAN: only letters and digits are allowed".into());
+    }
+    if !iban[..2].chars().all(|c| c.is_ascii_alphabetic()) || !iban[2..4].chars().all(|c| c.is_ascii_digit()) {
+        return Err("Invalid IBAN: it must start with the country code and two check digits".into());
+    }
+
+    // the first four characters are moved to the end and every letter becomes two digits (A = 10 ... Z = 35)
+    let rearranged = format!("{}{}", &iban[4..], &iban[..4]);
+    let remainder = rearranged.chars().fold(0u32, |remainder, c| {
+        let value = c.to_digit(36).expect("checked alphanumeric above");
+        if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        }
+    });
+
+    if remainder != 1 {
+        return Err("Invalid IBAN checksum".into());
+    }
+
+    Ok(iban)
+}
+
+// ISO 9362: 4 letters (bank), 2 letters (country), 2 alphanumeric (location) and an optional 3 alphanumeric branch.
+pub fn normalize_bic(value: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let bic = value.trim().to_uppercase();
+
+    let valid = bic.is_ascii()
+        && (bic.len() == 8 || bic.len() == 11)
+        && bic[..6].chars().all(|c| c.is_ascii_alphabetic())
+        && bic[6..].chars().all(|c| c.is_ascii_alphanumeric());
+
+    if !valid {
+        return Err(format!("Invalid BIC: {}", bic).into());
+    }
+
+    Ok(bic)
+}
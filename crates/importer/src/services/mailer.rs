@@ -0,0 +1,7 @@
+use async_trait::async_trait;
+use crate::entities::notifications::Email;
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<(), anyhow::Error>;
+}
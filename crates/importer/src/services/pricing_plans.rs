@@ -0,0 +1,79 @@
+use crate::entities::pricing_plans::{PricingPlan, PricingTier};
+use chrono::NaiveDate;
+use csv::StringRecord;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+
+// One row per tier: plan_code;from_amount;percentage;fixed_fee;valid_from, e.g. GOLD;1000.0;0.60;0.25;2023-01-01
+// The amounts are in euros like the minimum_monthly_fee, and the fixed_fee is the same in every tier of a version.
+// Without valid_from the version applies to every order (since 1970-01-01).
+pub fn read_pricing_plans_from_csv(path: &str) -> Result<Vec<PricingPlan>, Box<dyn Error + Send + Sync>> {
+    println!("Trying to open pricing plans CSV at path: {}", path);
+
+    let file = File::open(path)?;
+    let buf_reader = BufReader::new(file);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .has_headers(true)
+        // valid_from is optional, older files don't have it
+        .flexible(true)
+        .from_reader(buf_reader);
+
+    let mut plans: Vec<PricingPlan> = Vec::new();
+
+    for result in reader.records() {
+        let record = result?;
+        let code = record[0].trim().to_string();
+        let (tier, fixed_fee, valid_from) =
+            parse_tier_record(&record).map_err(|err| format!("Pricing plan {}: {}", code, err))?;
+
+        match plans.iter_mut().find(|plan| plan.code == code && plan.valid_from == valid_from) {
+            Some(plan) if plan.fixed_fee != fixed_fee => {
+                return Err(format!("Pricing plan {} ({}) has different fixed fees", code, valid_from).into());
+            }
+            Some(plan) => plan.tiers.push(tier),
+            None => plans.push(PricingPlan { code, valid_from, tiers: vec![tier], fixed_fee }),
+        }
+    }
+
+    for plan in &mut plans {
+        plan.tiers.sort_by_key(|tier| tier.from_amount);
+        validate_tiers(plan)?;
+    }
+    plans.sort_by(|a, b| (&a.code, a.valid_from).cmp(&(&b.code, b.valid_from)));
+
+    Ok(plans)
+}
+
+fn parse_tier_record(record: &StringRecord) -> Result<(PricingTier, i64, NaiveDate), Box<dyn Error + Send + Sync>> {
+    let tier = PricingTier {
+        from_amount: (record[1].trim().parse::<f64>()? * 100.0).round() as i64,
+        fee_rate: (record[2].trim().parse::<f64>()? * 100.0).round() as i32,
+    };
+    let fixed_fee = (record.get(3).unwrap_or("0").trim().parse::<f64>()? * 100.0).round() as i64;
+    let valid_from = match record.get(4).map(str::trim) {
+        Some(date) if !date.is_empty() => NaiveDate::parse_from_str(date, "%Y-%m-%d")?,
+        _ => NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date"),
+    };
+
+    Ok((tier, fixed_fee, valid_from))
+}
+
+fn validate_tiers(plan: &PricingPlan) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if plan.code.is_empty() {
+        return Err("A pricing plan needs a code".into());
+    }
+    if plan.tiers.first().map(|tier| tier.from_amount) != Some(0) {
+        return Err(format!("The first tier of the pricing plan {} must start at 0", plan.code).into());
+    }
+    if plan.tiers.windows(2).any(|tiers| tiers[0].from_amount == tiers[1].from_amount) {
+        return Err(format!("The pricing plan {} has two tiers from the same amount", plan.code).into());
+    }
+    if plan.tiers.iter().any(|tier| !(0..=10_000).contains(&tier.fee_rate)) || plan.fixed_fee < 0 {
+        return Err(format!("The pricing plan {} has a negative fee or a rate over 100 %", plan.code).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::entities::notifications::Email;
+use crate::services::mailer::Mailer;
+use crate::settings::notifications::NotificationSettings;
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::time::Duration;
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+pub struct SmtpMailerBuilder {
+    settings: NotificationSettings,
+}
+
+// Same split as the KafkaPublisherBuilder: new can't fail, build is where the settings are checked.
+impl SmtpMailerBuilder {
+    pub fn new(settings: &NotificationSettings) -> Self {
+        Self {
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn build(self) -> Result<SmtpMailer, anyhow::Error> {
+        let settings = self.settings;
+
+        let mut builder = if settings.smtp_tls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.smtp_host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.smtp_host)
+        };
+        builder = builder.port(settings.smtp_port).timeout(Some(SMTP_TIMEOUT));
+
+        if let (Some(username), Some(password)) = (settings.smtp_username, settings.smtp_password) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(SmtpMailer {
+            transport: builder.build(),
+            from: settings.email_from.parse()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), anyhow::Error> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(email.to.parse()?)
+            .subject(&email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body.clone())?;
+
+        self.transport.send(message).await?;
+
+        Ok(())
+    }
+}
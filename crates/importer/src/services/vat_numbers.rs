@@ -0,0 +1,40 @@
+use std::error::Error;
+
+const MIN_VAT_NUMBER_LENGTH: usize = 4;
+const MAX_VAT_NUMBER_LENGTH: usize = 15;
+
+// ISO 3166-1 alpha-2, in uppercase.
+pub fn normalize_country(value: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let country = value.trim().to_uppercase();
+
+    if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("Invalid country: {}", value).into());
+    }
+
+    Ok(country)
+}
+
+// Returns the VAT number without spaces, dots or dashes and in uppercase, the way VIES shows it.
+// It must start with the prefix of the merchant country, EL for Greece.
+// Only the format is checked, not that the number is registered.
+pub fn normalize_vat_number(value: &str, country: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let vat_number: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '.' && *c != '-')
+        .collect::<String>()
+        .to_uppercase();
+
+    if vat_number.len() < MIN_VAT_NUMBER_LENGTH || vat_number.len() > MAX_VAT_NUMBER_LENGTH {
+        return Err(format!("Invalid VAT number length: {}", vat_number.len()).into());
+    }
+    if !vat_number.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("Invalid VAT number: only letters and digits are allowed".into());
+    }
+
+    let prefix = if country == "GR" { "EL" } else { country };
+    if !vat_number.starts_with(prefix) {
+        return Err(format!("Invalid VAT number: it must start with {}", prefix).into());
+    }
+
+    Ok(vat_number)
+}
@@ -2,6 +2,8 @@ use std::env;
 #[derive(Debug, Clone)]
 pub struct Settings {
     pub csv_path: String,
+    // optional, only needed when some merchant has a pricing plan
+    pub pricing_plans_csv_path: Option<String>,
     pub database_url: String,
     pub kafka_brokers: String,
 }
@@ -11,6 +13,8 @@ impl Settings {
         let csv_path = env::var("CSV_PATH")
             .expect("CSV_PATH must be set in .env or .env.test");
 
+        let pricing_plans_csv_path = env::var("PRICING_PLANS_CSV_PATH").ok();
+
         let database_url = env::var("IMPORTER_DATABASE_URL")
             .expect("DATABASE_URL must be set in .env or .env.test");
 
@@ -19,6 +23,7 @@ impl Settings {
 
         Settings {
             csv_path,
+            pricing_plans_csv_path,
             database_url,
             kafka_brokers,
         }
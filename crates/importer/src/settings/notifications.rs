@@ -0,0 +1,43 @@
+use std::env;
+
+// Only needed by the payout notifier.
+#[derive(Debug, Clone)]
+pub struct NotificationSettings {
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    // no authentication when they are missing
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    // STARTTLS, only a local stand-in should go without it
+    pub smtp_tls: bool,
+    pub email_from: String,
+    pub templates_dir: String,
+}
+
+impl NotificationSettings {
+    pub fn from_env() -> Self {
+        let smtp_host = env::var("SMTP_HOST")
+            .expect("SMTP_HOST must be set in .env or .env.test");
+
+        let smtp_tls = env::var("SMTP_TLS").map(|value| value != "false").unwrap_or(true);
+
+        let smtp_port = env::var("SMTP_PORT")
+            .map(|value| value.parse().expect("SMTP_PORT must be a port number"))
+            .unwrap_or(if smtp_tls { 587 } else { 25 });
+
+        let email_from = env::var("EMAIL_FROM")
+            .expect("EMAIL_FROM must be set in .env or .env.test");
+
+        let templates_dir = env::var("EMAIL_TEMPLATES_DIR").unwrap_or_else(|_| "templates".to_string());
+
+        NotificationSettings {
+            smtp_host,
+            smtp_port,
+            smtp_username: env::var("SMTP_USERNAME").ok(),
+            smtp_password: env::var("SMTP_PASSWORD").ok(),
+            smtp_tls,
+            email_from,
+            templates_dir,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use importer::services::iban::{normalize_bic, normalize_iban};
+use rstest::rstest;
+
+#[rstest]
+#[case("ES91 2100 0418 4502 0005 1332", "ES9121000418450200051332")]
+#[case("de89370400440532013000", "DE89370400440532013000")]
+#[case("NL91ABNA0417164300", "NL91ABNA0417164300")]
+fn it_normalizes_ibans_with_a_valid_checksum(#[case] iban: &str, #[case] expected: &str) {
+    assert_eq!(normalize_iban(iban).unwrap(), expected);
+}
+
+#[rstest]
+#[case("ES9121000418450200051333")]
+#[case("ES91")]
+#[case("9121000418450200051332ES")]
+#[case("ES91-2100-0418-4502-0005-1332")]
+fn it_rejects_invalid_ibans(#[case] iban: &str) {
+    assert!(normalize_iban(iban).is_err());
+}
+
+#[test]
+fn it_validates_bics() {
+    assert_eq!(normalize_bic("caixesbbxxx").unwrap(), "CAIXESBBXXX");
+    assert_eq!(normalize_bic("COBADEFF").unwrap(), "COBADEFF");
+    assert!(normalize_bic("COBADEF").is_err());
+    assert!(normalize_bic("C0BADEFFXXX").is_err());
+}
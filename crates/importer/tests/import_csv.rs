@@ -19,7 +19,10 @@ async fn it_imports_csv_and_insert_merchants_and_publish_event() {
     clean_db(&pool).await;
     let mock = MockPublisher::default();
 
-    let job = ImportCsvJob { file_path: settings.csv_path.to_string() };
+    let job = ImportCsvJob {
+        file_path: settings.csv_path.to_string(),
+        pricing_plans_path: settings.pricing_plans_csv_path.clone(),
+    };
     let result = import_merchants_csv_handler(job, &mock, settings).await;
 
     let row = sqlx::query("SELECT COUNT(*) as count FROM merchants")
@@ -33,6 +36,77 @@ async fn it_imports_csv_and_insert_merchants_and_publish_event() {
     assert!(result.is_ok());
     assert_eq!(mock.calls_count().await, 4);
     assert!(mock.was_called_with("merchant_upserted").await);
+
+    let bank_row = sqlx::query("SELECT iban, bic, account_holder FROM merchants WHERE merchant_reference = 'padberg_group'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let iban: Option<String> = bank_row.get("iban");
+    assert_eq!(iban.as_deref(), Some("ES9121000418450200051332"));
+
+    let published = mock.published.lock().await;
+    let padberg = published
+        .iter()
+        .map(|(_, payload)| payload)
+        .find(|payload| payload["merchant_reference"] == "padberg_group")
+        .unwrap();
+    let rosenbaum = published
+        .iter()
+        .map(|(_, payload)| payload)
+        .find(|payload| payload["merchant_reference"] == "rosenbaum_parisian")
+        .unwrap();
+
+    assert_eq!(padberg["bank_account"]["iban"], "ES9121000418450200051332");
+    assert_eq!(padberg["bank_account"]["bic"], "CAIXESBBXXX");
+    assert!(rosenbaum["bank_account"].is_null());
+
+    let romaguera = published
+        .iter()
+        .map(|(_, payload)| payload)
+        .find(|payload| payload["merchant_reference"] == "romaguera_and_sons")
+        .unwrap();
+
+    assert_eq!(padberg["payout_currency"], "EUR");
+    let gold_versions = padberg["pricing_plan_versions"].as_array().unwrap();
+    assert_eq!(gold_versions.len(), 2);
+    assert_eq!(gold_versions[0]["code"], "GOLD");
+    assert_eq!(gold_versions[0]["valid_from"], "1970-01-01");
+    assert_eq!(gold_versions[0]["tiers"][1]["from_amount"], 100_000);
+    assert_eq!(gold_versions[0]["tiers"][1]["fee_rate"], 60);
+    assert_eq!(gold_versions[0]["fixed_fee"], 25);
+    assert_eq!(gold_versions[1]["valid_from"], "2023-06-01");
+    assert!(romaguera["pricing_plan_versions"].as_array().unwrap().is_empty());
+    assert_eq!(romaguera["payout_currency"], "CHF");
+    // files without the column are paid out in euros
+    assert_eq!(rosenbaum["payout_currency"], "EUR");
+    assert!(padberg.get("email").is_none());
+
+    assert_eq!(romaguera["disbursement_frequency"], "MONTHLY");
+    assert_eq!(romaguera["disbursement_day_of_month"], 31);
+    assert!(romaguera["disbursement_weekday"].is_null());
+    assert!(rosenbaum["disbursement_weekday"].is_null());
+    assert_eq!(romaguera["roll_convention"], "PREVIOUS");
+    assert!(padberg["roll_convention"].is_null());
+    assert_eq!(romaguera["time_zone"], "Europe/Zurich");
+    assert_eq!(padberg["time_zone"], "UTC");
+    // in CHF cents
+    assert_eq!(romaguera["minimum_payout"], 500);
+    assert_eq!(romaguera["offboarded_on"], "2023-06-30");
+    assert!(padberg["minimum_payout"].is_null());
+    assert_eq!(romaguera["monthly_fee_mode"], "DEDUCT");
+    assert_eq!(padberg["monthly_fee_mode"], "INVOICE");
+
+    let deckow = published
+        .iter()
+        .map(|(_, payload)| payload)
+        .find(|payload| payload["merchant_reference"] == "deckow_gibson")
+        .unwrap();
+    assert_eq!(deckow["country"], "DE");
+    assert_eq!(deckow["vat_number"], "DE123456789");
+    assert_eq!(romaguera["country"], "CH");
+    assert!(romaguera["vat_number"].is_null());
+    assert!(padberg["country"].is_null());
 }
 #[tokio::test]
 async fn it_imports_csv_and_upsert_existing_merchants_and_publish_event() {
@@ -85,6 +159,7 @@ async fn it_imports_csv_and_upsert_existing_merchants_and_publish_event() {
 
     let job = ImportCsvJob {
         file_path: settings.csv_path.to_string(),
+        pricing_plans_path: settings.pricing_plans_csv_path.clone(),
     };
     let result = import_merchants_csv_handler(job, &mock_publisher, settings).await;
 
@@ -119,3 +194,33 @@ async fn it_imports_csv_and_upsert_existing_merchants_and_publish_event() {
     assert_eq!(mock_publisher.calls_count().await, 4);
     assert!(mock_publisher.was_called_with("merchant_upserted").await);
 }
+
+#[tokio::test]
+async fn it_refuses_an_import_with_an_unknown_pricing_plan_and_stores_nothing() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+
+    clean_db(&pool).await;
+    let mock = MockPublisher::default();
+
+    let merchants = std::fs::read_to_string(&settings.csv_path).unwrap().replace(";GOLD", ";PLATINUM");
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), merchants).unwrap();
+
+    let job = ImportCsvJob {
+        file_path: file.path().to_str().unwrap().to_string(),
+        pricing_plans_path: settings.pricing_plans_csv_path.clone(),
+    };
+    let result = import_merchants_csv_handler(job, &mock, settings).await;
+
+    assert!(result.unwrap_err().to_string().contains("Unknown pricing plan PLATINUM"));
+
+    let row = sqlx::query("SELECT COUNT(*) as count FROM merchants")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let count: i64 = row.get("count");
+    assert_eq!(count, 0);
+    assert_eq!(mock.calls_count().await, 0);
+}
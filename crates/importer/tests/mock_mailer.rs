@@ -0,0 +1,33 @@
+use std::sync::Arc;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use importer::entities::notifications::Email;
+use importer::services::mailer::Mailer;
+use tokio::sync::Mutex;
+
+#[derive(Clone, Default)]
+pub struct MockMailer {
+    pub sent: Arc<Mutex<Vec<Email>>>,
+    // every send fails when set, like an unreachable SMTP server
+    pub failing: bool,
+}
+
+#[async_trait]
+impl Mailer for MockMailer {
+    async fn send(&self, email: &Email) -> Result<(), anyhow::Error> {
+        if self.failing {
+            return Err(anyhow!("Connection refused"));
+        }
+
+        let mut lock = self.sent.lock().await;
+        lock.push(email.clone());
+        Ok(())
+    }
+}
+
+impl MockMailer {
+    pub async fn sent_count(&self) -> usize {
+        let lock = self.sent.lock().await;
+        lock.len()
+    }
+}
@@ -0,0 +1,246 @@
+use chrono::NaiveDate;
+use importer::entities::notifications::{Email, NotificationStatus, PayoutSummary};
+use importer::jobs::notify_payout::{notify_payout_handler, resend_payout_notifications_handler, ResendReport};
+use importer::services::currencies::format_amount;
+use importer::services::email_templates::{load_template, parse_template, payout_summary_values, render, PAYOUT_SUMMARY_TEMPLATE};
+use importer::services::mailer::Mailer;
+use importer::services::smtp_mailer::SmtpMailerBuilder;
+use importer::settings::config::Settings;
+use importer::settings::notifications::NotificationSettings;
+use rstest::rstest;
+use sqlx::{PgPool, Row};
+use std::collections::BTreeMap;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+mod utils;
+mod mock_mailer;
+
+use utils::clean_db;
+use mock_mailer::MockMailer;
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn summary() -> PayoutSummary {
+    PayoutSummary {
+        event_id: Uuid::new_v4(),
+        merchant_reference: "padberg_group".to_string(),
+        reference: "5A1B2C3D4E5F".to_string(),
+        disbursed_on: date("2023-01-09"),
+        period_from: Some(date("2023-01-02")),
+        period_to: Some(date("2023-01-08")),
+        currency: "EUR".to_string(),
+        gross_amount: 43_507,
+        fee_amount: 413,
+        fee_vat_amount: 87,
+        net_amount: 43_007,
+    }
+}
+
+// A local stand-in for the SMTP server: accepts one message and hands over the recipient and the data.
+async fn smtp_stand_in() -> (u16, tokio::task::JoinHandle<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = socket.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let (mut recipient, mut data) = (String::new(), String::new());
+
+        writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            let command = line.to_uppercase();
+            let answer = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                "250 localhost"
+            } else if command.starts_with("RCPT TO:") {
+                recipient = line["RCPT TO:".len()..].to_string();
+                "250 OK"
+            } else if command == "DATA" {
+                writer.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n").await.unwrap();
+                while let Some(line) = lines.next_line().await.unwrap() {
+                    if line == "." {
+                        break;
+                    }
+                    data.push_str(&line);
+                    data.push('\n');
+                }
+                "250 Queued"
+            } else if command == "QUIT" {
+                writer.write_all(b"221 Bye\r\n").await.unwrap();
+                break;
+            } else {
+                "250 OK"
+            };
+            writer.write_all(format!("{}\r\n", answer).as_bytes()).await.unwrap();
+        }
+
+        (recipient, data)
+    });
+
+    (port, handle)
+}
+
+#[rstest]
+#[case(43_085, "EUR", "430.85")]
+#[case(5, "EUR", "0.05")]
+#[case(-1_250, "GBP", "-12.50")]
+#[case(1_500, "JPY", "1500")]
+fn it_formats_the_amounts_with_the_decimals_of_their_currency(#[case] amount: i64, #[case] currency: &str, #[case] expected: &str) {
+    assert_eq!(format_amount(amount, currency).unwrap(), expected);
+}
+
+#[test]
+fn it_renders_the_payout_summary_template_on_disk() {
+    let template = load_template("templates", PAYOUT_SUMMARY_TEMPLATE).unwrap();
+
+    let email = render(&template, "info@padberg-group.com", &payout_summary_values(&summary()).unwrap()).unwrap();
+
+    assert_eq!(email.to, "info@padberg-group.com");
+    assert_eq!(email.subject, "Your payout 5A1B2C3D4E5F of 430.07 EUR");
+    assert!(email.body.contains("Sales period:   2023-01-02 to 2023-01-08"));
+    assert!(email.body.contains("Gross amount:   435.07 EUR"));
+    assert!(email.body.contains("Fees:           5.00 EUR (VAT included: 0.87 EUR)"));
+    assert!(email.body.contains("Net payout:     430.07 EUR"));
+    assert!(!email.body.contains("{{"));
+}
+
+#[test]
+fn it_refuses_broken_templates() {
+    let values = BTreeMap::from([("reference", "5A1B2C3D4E5F".to_string())]);
+
+    assert!(parse_template("no_subject", "Hello\n\n{{reference}}").is_err());
+    assert!(parse_template("no_blank_line", "Subject: Payout\n{{reference}}").is_err());
+
+    let typo = parse_template("typo", "Subject: Payout {{reference}}\n\nNet {{nett}}").unwrap();
+    assert!(render(&typo, "info@padberg-group.com", &values).is_err());
+
+    let unclosed = parse_template("unclosed", "Subject: Payout {{reference\n\nHello").unwrap();
+    assert!(render(&unclosed, "info@padberg-group.com", &values).is_err());
+}
+
+#[test]
+fn it_shows_a_single_day_period_once() {
+    let mut summary = summary();
+    summary.period_from = Some(date("2023-01-08"));
+    let values = payout_summary_values(&summary).unwrap();
+    assert_eq!(values["period"], "2023-01-08");
+
+    summary.period_from = None;
+    summary.period_to = None;
+    assert_eq!(payout_summary_values(&summary).unwrap()["period"], "-");
+}
+
+#[tokio::test]
+async fn it_sends_the_email_through_smtp() {
+    let (port, received) = smtp_stand_in().await;
+    let settings = NotificationSettings {
+        smtp_host: "127.0.0.1".to_string(),
+        smtp_port: port,
+        smtp_username: None,
+        smtp_password: None,
+        smtp_tls: false,
+        email_from: "Company XYZ <payouts@company-xyz.com>".to_string(),
+        templates_dir: "templates".to_string(),
+    };
+    let mailer = SmtpMailerBuilder::new(&settings).build().unwrap();
+    let email = Email {
+        to: "info@padberg-group.com".to_string(),
+        subject: "Your payout 5A1B2C3D4E5F of 430.07 EUR".to_string(),
+        body: "Net payout: 430.07 EUR".to_string(),
+    };
+
+    mailer.send(&email).await.unwrap();
+    let (recipient, data) = received.await.unwrap();
+
+    assert_eq!(recipient, "<info@padberg-group.com>");
+    assert!(data.contains("Subject: Your payout 5A1B2C3D4E5F of 430.07 EUR"));
+    assert!(data.contains("From: \"Company XYZ\" <payouts@company-xyz.com>"));
+    assert!(data.contains("Net payout: 430.07 EUR"));
+}
+
+#[tokio::test]
+async fn it_emails_each_payout_once_and_logs_it() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    sqlx::query(
+        "INSERT INTO merchants (id, merchant_reference, email, live_on, disbursement_frequency, minimum_monthly_fee)
+         VALUES ($1, 'padberg_group', 'info@padberg-group.com', '2022-01-01', 'DAILY', 0)"
+    )
+    .bind(Uuid::new_v4())
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let template = load_template("templates", PAYOUT_SUMMARY_TEMPLATE).unwrap();
+    let summary = summary();
+
+    let failing = MockMailer { failing: true, ..MockMailer::default() };
+    assert!(notify_payout_handler(&summary, &pool, &failing, &template).await.is_err());
+
+    let mailer = MockMailer::default();
+    let notification = notify_payout_handler(&summary, &pool, &mailer, &template).await.unwrap().unwrap();
+    assert_eq!((notification.status, notification.recipient.as_str()), (NotificationStatus::Sent, "info@padberg-group.com"));
+
+    assert!(notify_payout_handler(&summary, &pool, &mailer, &template).await.unwrap().is_none());
+    assert_eq!(mailer.sent_count().await, 1);
+
+    let log = sqlx::query("SELECT status, error FROM email_notifications WHERE event_id = $1 ORDER BY created_at")
+        .bind(summary.event_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    let statuses: Vec<String> = log.iter().map(|row| row.get("status")).collect();
+    let first_error: Option<String> = log[0].get("error");
+    assert_eq!(statuses, vec!["FAILED", "SENT"]);
+    assert_eq!(first_error.as_deref(), Some("Connection refused"));
+
+    let unknown = PayoutSummary { event_id: Uuid::new_v4(), merchant_reference: "unknown".to_string(), ..summary };
+    assert!(notify_payout_handler(&unknown, &pool, &mailer, &template).await.is_err());
+}
+
+#[tokio::test]
+async fn it_resends_the_payouts_whose_emails_failed() {
+    dotenvy::from_filename(".env.test").ok();
+    let settings = Settings::from_env();
+    let pool = PgPool::connect(&settings.database_url).await.unwrap();
+    clean_db(&pool).await;
+
+    sqlx::query(
+        "INSERT INTO merchants (id, merchant_reference, email, live_on, disbursement_frequency, minimum_monthly_fee)
+         VALUES ($1, 'padberg_group', 'info@padberg-group.com', '2022-01-01', 'DAILY', 0)"
+    )
+    .bind(Uuid::new_v4())
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let template = load_template("templates", PAYOUT_SUMMARY_TEMPLATE).unwrap();
+    let failing = MockMailer { failing: true, ..MockMailer::default() };
+    let mailer = MockMailer::default();
+
+    let failed_twice = summary();
+    let already_sent = PayoutSummary { event_id: Uuid::new_v4(), reference: "6B2C3D4E5F6A".to_string(), ..summary() };
+    for _ in 0..2 {
+        assert!(notify_payout_handler(&failed_twice, &pool, &failing, &template).await.is_err());
+    }
+    assert!(notify_payout_handler(&already_sent, &pool, &failing, &template).await.is_err());
+    notify_payout_handler(&already_sent, &pool, &mailer, &template).await.unwrap().unwrap();
+
+    let report = resend_payout_notifications_handler(&pool, &failing, &template, 10).await.unwrap();
+    assert_eq!((report.sent, report.failures.len()), (0, 1));
+
+    let report = resend_payout_notifications_handler(&pool, &mailer, &template, 10).await.unwrap();
+    assert_eq!((report.sent, report.failures.len()), (1, 0));
+    let sent = mailer.sent.lock().await;
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1].subject, "Your payout 5A1B2C3D4E5F of 430.07 EUR");
+    drop(sent);
+
+    assert_eq!(resend_payout_notifications_handler(&pool, &mailer, &template, 10).await.unwrap(), ResendReport::default());
+}
@@ -0,0 +1,39 @@
+use chrono::NaiveDate;
+use importer::entities::pricing_plans::PricingTier;
+use importer::services::pricing_plans::read_pricing_plans_from_csv;
+use tempfile::NamedTempFile;
+
+#[test]
+fn it_reads_the_tiers_of_each_plan_in_cents_and_basis_points() {
+    let plans = read_pricing_plans_from_csv("tests/fixtures/pricing_plans.csv").unwrap();
+
+    assert_eq!(plans.len(), 2);
+    assert_eq!(plans[0].code, "GOLD");
+    assert_eq!(plans[0].valid_from, NaiveDate::from_ymd_opt(1970, 1, 1).unwrap());
+    assert_eq!(plans[0].fixed_fee, 25);
+    assert_eq!(
+        plans[0].tiers,
+        vec![
+            PricingTier { from_amount: 0, fee_rate: 80 },
+            PricingTier { from_amount: 100_000, fee_rate: 60 },
+        ]
+    );
+
+    // a new version of the plan from June
+    assert_eq!(plans[1].code, "GOLD");
+    assert_eq!(plans[1].valid_from, NaiveDate::from_ymd_opt(2023, 6, 1).unwrap());
+    assert_eq!(plans[1].tiers[0].fee_rate, 70);
+    assert_eq!(plans[1].fixed_fee, 20);
+}
+
+#[test]
+fn it_rejects_plans_not_starting_at_zero_or_with_different_fixed_fees() {
+    let file = NamedTempFile::new().unwrap();
+    let path = file.path().to_str().unwrap();
+
+    std::fs::write(path, "plan_code;from_amount;percentage;fixed_fee\nSILVER;50.0;0.90;0.0\n").unwrap();
+    assert!(read_pricing_plans_from_csv(path).is_err());
+
+    std::fs::write(path, "plan_code;from_amount;percentage;fixed_fee\nSILVER;0.0;0.90;0.0\nSILVER;50.0;0.80;0.10\n").unwrap();
+    assert!(read_pricing_plans_from_csv(path).is_err());
+}
@@ -1,7 +1,7 @@
 use sqlx::PgPool;
 
 pub async fn clean_db(pool: &PgPool) {
-  sqlx::query("TRUNCATE TABLE merchants RESTART IDENTITY CASCADE")
+  sqlx::query("TRUNCATE TABLE merchants, email_notifications RESTART IDENTITY CASCADE")
       .execute(pool)
       .await
       .unwrap();
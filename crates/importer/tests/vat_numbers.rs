@@ -0,0 +1,27 @@
+use importer::services::vat_numbers::{normalize_country, normalize_vat_number};
+use rstest::rstest;
+
+#[rstest]
+#[case("DE 123 456 789", "DE", "DE123456789")]
+#[case("esb12345678", "ES", "ESB12345678")]
+#[case("EL-123.456.789", "GR", "EL123456789")]
+#[case("CHE-123.456.789", "CH", "CHE123456789")]
+fn it_normalizes_vat_numbers_with_the_country_prefix(#[case] vat_number: &str, #[case] country: &str, #[case] expected: &str) {
+    assert_eq!(normalize_vat_number(vat_number, country).unwrap(), expected);
+}
+
+#[rstest]
+#[case("123456789", "DE")]
+#[case("FR12345678901", "DE")]
+#[case("DE12345678_9", "DE")]
+#[case("DE", "DE")]
+fn it_rejects_invalid_vat_numbers(#[case] vat_number: &str, #[case] country: &str) {
+    assert!(normalize_vat_number(vat_number, country).is_err());
+}
+
+#[test]
+fn it_normalizes_countries() {
+    assert_eq!(normalize_country(" es ").unwrap(), "ES");
+    assert!(normalize_country("ESP").is_err());
+    assert!(normalize_country("E1").is_err());
+}
@@ -0,0 +1,24 @@
+// ISO 4217 minor units (decimals) of the currencies we support, amounts are stored as integers of that unit.
+// The importer accepts exactly the payout currencies the calculator pays out in.
+const MINOR_UNITS: [(&str, u32); 12] = [
+    ("EUR", 2),
+    ("GBP", 2),
+    ("CHF", 2),
+    ("USD", 2),
+    ("SEK", 2),
+    ("NOK", 2),
+    ("DKK", 2),
+    ("PLN", 2),
+    ("CZK", 2),
+    ("HUF", 2),
+    ("RON", 2),
+    ("JPY", 0),
+];
+
+// None for an unsupported currency.
+pub fn minor_units(currency: &str) -> Option<u32> {
+    MINOR_UNITS
+        .iter()
+        .find(|(code, _)| *code == currency)
+        .map(|(_, units)| *units)
+}
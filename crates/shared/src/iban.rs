@@ -0,0 +1,13 @@
+// Only the country, the check digits and the last four characters are kept: ES91****************1332.
+pub fn mask_iban(iban: &str) -> String {
+    let chars: Vec<char> = iban.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+
+    chars
+        .iter()
+        .enumerate()
+        .map(|(index, c)| if index < 4 || index >= chars.len() - 4 { *c } else { '*' })
+        .collect()
+}
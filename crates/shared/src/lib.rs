@@ -0,0 +1,3 @@
+// What the importer and the calculator must agree on, each one wraps it in its own errors.
+pub mod currencies;
+pub mod iban;
@@ -0,0 +1,15 @@
+use shared::currencies::minor_units;
+
+#[test]
+fn it_supports_the_payout_currencies() {
+    let supported = [
+        ("EUR", 2), ("GBP", 2), ("CHF", 2), ("USD", 2), ("SEK", 2), ("NOK", 2),
+        ("DKK", 2), ("PLN", 2), ("CZK", 2), ("HUF", 2), ("RON", 2), ("JPY", 0),
+    ];
+
+    for (currency, units) in supported {
+        assert_eq!(minor_units(currency), Some(units), "{}", currency);
+    }
+    assert_eq!(minor_units("XXX"), None);
+    assert_eq!(minor_units("eur"), None);
+}
@@ -0,0 +1,8 @@
+use shared::iban::mask_iban;
+
+#[test]
+fn it_masks_the_iban_keeping_the_country_and_the_last_digits() {
+    assert_eq!(mask_iban("ES9121000418450200051332"), "ES91****************1332");
+    assert_eq!(mask_iban("GB29NWBK60161331926819"), "GB29**************6819");
+    assert_eq!(mask_iban("ES91"), "****");
+}